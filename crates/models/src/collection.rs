@@ -2,9 +2,11 @@
 
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::{Id, Timestamp, new_id, now, Temporal, Identifiable};
+use crate::{Id, RemoteId, Timestamp, new_id, now, Temporal, Identifiable};
+use crate::environment::VariableResolver;
+use crate::request::Request;
 
 /// Collection - a container for organizing API requests
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -32,6 +34,15 @@ pub struct Collection {
     /// Authentication configuration for the collection
     pub auth: Option<crate::request::AuthConfig>,
 
+    /// Headers sent with every request in the collection; a request header
+    /// with the same name (case-insensitive) takes precedence
+    #[serde(default)]
+    pub headers: Vec<crate::request::Header>,
+
+    /// Lowercase, de-duplicated tags for filtering
+    #[serde(default)]
+    pub tags: Vec<String>,
+
     /// Sync state for cloud support
     #[serde(default)]
     pub sync_state: SyncState,
@@ -94,6 +105,9 @@ pub struct Folder {
     #[serde(default)]
     pub requests: Vec<Id>,
 
+    /// Authentication for requests in this folder (None inherits)
+    pub auth: Option<crate::request::AuthConfig>,
+
     /// UI-specific state
     #[serde(default)]
     pub ui_state: FolderUiState,
@@ -153,8 +167,8 @@ pub struct SyncState {
     /// Sync status
     pub status: SyncStatus,
 
-    /// Remote collection ID (if synced)
-    pub remote_id: Option<Id>,
+    /// Remote collection ID (if synced), in the server's id scheme
+    pub remote_id: Option<RemoteId>,
 
     /// Version for conflict resolution
     pub version: Option<i64>,
@@ -228,6 +242,82 @@ pub struct FolderUiState {
     pub depth: usize,
 }
 
+/// Structural differences between two versions of a collection
+///
+/// Folders are matched by id at any depth and requests by id regardless of
+/// order or folder.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct CollectionDiff {
+    /// Folders only present on the new side
+    #[serde(default)]
+    pub added_folders: Vec<FolderSummary>,
+
+    /// Folders only present on the old side
+    #[serde(default)]
+    pub removed_folders: Vec<FolderSummary>,
+
+    /// Folders present on both sides under different names
+    #[serde(default)]
+    pub renamed_folders: Vec<FolderRename>,
+
+    #[serde(default)]
+    pub added_requests: Vec<Id>,
+
+    #[serde(default)]
+    pub removed_requests: Vec<Id>,
+
+    /// Variables whose key only exists on the new side
+    #[serde(default)]
+    pub added_variables: Vec<Variable>,
+
+    /// Keys only present on the old side
+    #[serde(default)]
+    pub removed_variables: Vec<String>,
+
+    /// New version of variables whose value or settings changed
+    #[serde(default)]
+    pub changed_variables: Vec<Variable>,
+}
+
+impl CollectionDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A structural problem found by [`Collection::validate_integrity`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum IntegrityError {
+    /// A request id that doesn't match any stored request (`folder_id` is
+    /// None for the collection root)
+    DanglingRequest { request_id: Id, folder_id: Option<Id> },
+
+    /// A request id listed more than once across the root and folders
+    DuplicateRequest { request_id: Id, folder_id: Option<Id> },
+
+    /// A folder whose `parent_id` matches no folder in the tree
+    OrphanedFolder { folder_id: Id, parent_id: Id },
+
+    /// A folder whose `ui_state.depth` doesn't match its nesting level
+    WrongDepth { folder_id: Id, expected: usize, actual: usize },
+}
+
+/// Folder id and name, for listing folders in a [`CollectionDiff`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FolderSummary {
+    pub id: Id,
+    pub name: String,
+}
+
+/// A folder renamed between two versions of a collection
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FolderRename {
+    pub id: Id,
+    pub old_name: String,
+    pub new_name: String,
+}
+
 impl Collection {
     /// Create a new collection
     pub fn new(name: String) -> Self {
@@ -241,6 +331,8 @@ impl Collection {
             requests: Vec::new(),
             variables: Vec::new(),
             auth: None,
+            headers: Vec::new(),
+            tags: Vec::new(),
             sync_state: SyncState::default(),
             ui_state: CollectionUiState::default(),
             created_at: now,
@@ -273,6 +365,12 @@ impl Collection {
         self
     }
 
+    /// Add a header sent with every request in the collection
+    pub fn with_header(mut self, key: String, value: String) -> Self {
+        self.headers.push(crate::request::Header::new(key, value));
+        self
+    }
+
     /// Add a folder to the collection
     pub fn add_folder(&mut self, folder: Folder) {
         self.folders.push(folder);
@@ -296,6 +394,58 @@ impl Collection {
         removed
     }
 
+    /// Reorder the root folders to match `ordered_ids`
+    ///
+    /// Folders not listed keep their relative order after the listed ones.
+    /// Errors without changing anything if an id is unknown or repeated.
+    pub fn reorder_folders(&mut self, ordered_ids: &[Id]) -> Result<(), String> {
+        reorder_by_id(&mut self.folders, ordered_ids)?;
+        self.updated_at = now();
+        Ok(())
+    }
+
+    /// Reorder the children of a folder anywhere in the tree
+    pub fn reorder_child_folders(&mut self, parent_id: Id, ordered_ids: &[Id]) -> Result<(), String> {
+        let parent = self
+            .find_folder_mut(parent_id)
+            .ok_or_else(|| format!("Unknown folder id: {}", parent_id))?;
+        reorder_by_id(&mut parent.children, ordered_ids)?;
+        parent.updated_at = now();
+        self.updated_at = now();
+        Ok(())
+    }
+
+    /// Add a tag, returning false if the collection already has it
+    ///
+    /// Tags are trimmed and lowercased; empty tags are ignored.
+    pub fn add_tag(&mut self, tag: &str) -> bool {
+        let tag = normalize_tag(tag);
+        if tag.is_empty() || self.tags.contains(&tag) {
+            return false;
+        }
+        self.tags.push(tag);
+        self.updated_at = now();
+        true
+    }
+
+    /// Remove a tag (case-insensitive)
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        let tag = normalize_tag(tag);
+        let original_len = self.tags.len();
+        self.tags.retain(|t| t != &tag);
+        let removed = self.tags.len() < original_len;
+        if removed {
+            self.updated_at = now();
+        }
+        removed
+    }
+
+    /// Check whether the collection has a tag (case-insensitive)
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = normalize_tag(tag);
+        self.tags.contains(&tag)
+    }
+
     /// Remove a request by ID
     pub fn remove_request(&mut self, request_id: Id) -> bool {
         let original_len = self.requests.len();
@@ -307,6 +457,44 @@ impl Collection {
         removed
     }
 
+    /// Auth set on a folder or its nearest ancestor that has one
+    pub fn folder_auth(&self, folder_id: Id) -> Option<&crate::request::AuthConfig> {
+        fn search<'a>(
+            folders: &'a [Folder],
+            folder_id: Id,
+            inherited: Option<&'a crate::request::AuthConfig>,
+        ) -> Option<Option<&'a crate::request::AuthConfig>> {
+            for folder in folders {
+                let auth = folder.auth.as_ref().or(inherited);
+                if folder.id == folder_id {
+                    return Some(auth);
+                }
+                if let Some(found) = search(&folder.children, folder_id, auth) {
+                    return Some(found);
+                }
+            }
+            None
+        }
+
+        search(&self.folders, folder_id, None).flatten()
+    }
+
+    /// Folders from the root down to `folder_id`, ending with the folder itself
+    pub fn folder_path(&self, folder_id: Id) -> Option<Vec<&Folder>> {
+        folder_chain(&self.folders, &|folder| folder.id == folder_id)
+    }
+
+    /// Folders from the root down to the one holding `request_id`
+    ///
+    /// Empty for a request at the collection root; `None` if the request
+    /// isn't in the collection.
+    pub fn request_location(&self, request_id: Id) -> Option<Vec<&Folder>> {
+        if self.requests.contains(&request_id) {
+            return Some(Vec::new());
+        }
+        folder_chain(&self.folders, &|folder| folder.requests.contains(&request_id))
+    }
+
     /// Get all request IDs (including those in folders)
     pub fn all_request_ids(&self) -> Vec<Id> {
         let mut ids = self.requests.clone();
@@ -316,6 +504,25 @@ impl Collection {
         ids
     }
 
+    /// Requests in display order: root requests, then each folder depth-first
+    ///
+    /// IDs with no match in `requests` are skipped; see
+    /// [`Collection::missing_requests`].
+    pub fn flatten<'a>(&self, requests: &'a [Request]) -> Vec<&'a Request> {
+        self.all_request_ids()
+            .into_iter()
+            .filter_map(|id| requests.iter().find(|r| r.id == id))
+            .collect()
+    }
+
+    /// Request IDs in the collection tree with no match in `requests`, in display order
+    pub fn missing_requests(&self, requests: &[Request]) -> Vec<Id> {
+        self.all_request_ids()
+            .into_iter()
+            .filter(|id| !requests.iter().any(|r| r.id == *id))
+            .collect()
+    }
+
     /// Find a folder by ID (recursive)
     pub fn find_folder(&self, folder_id: Id) -> Option<&Folder> {
         for folder in &self.folders {
@@ -373,10 +580,10 @@ impl Collection {
     }
 
     /// Mark collection as synced
-    pub fn mark_synced(&mut self, remote_id: Id, version: i64) {
+    pub fn mark_synced(&mut self, remote_id: impl Into<RemoteId>, version: i64) {
         self.sync_state.status = SyncStatus::Synced;
         self.sync_state.last_synced_at = Some(now());
-        self.sync_state.remote_id = Some(remote_id);
+        self.sync_state.remote_id = Some(remote_id.into());
         self.sync_state.version = Some(version);
         self.sync_state.pending_changes = 0;
     }
@@ -394,6 +601,51 @@ impl Collection {
         }
     }
 
+    /// Get the current sync version (0 if never versioned)
+    pub fn current_version(&self) -> i64 {
+        self.sync_state.version.unwrap_or(0)
+    }
+
+    /// Advance the sync version and mark the collection as pending
+    ///
+    /// A collection modified mid-sync goes back to pending too, since the
+    /// sync in flight doesn't carry the new version.
+    pub fn bump_version(&mut self) -> i64 {
+        let version = self.current_version() + 1;
+        self.sync_state.version = Some(version);
+        self.sync_state.status = SyncStatus::Pending;
+        version
+    }
+
+    /// Resolve the URL of every request in this collection, in collection order
+    ///
+    /// URLs that still contain `{{` after resolution are unresolved; check them
+    /// with [`VariableResolver::has_unresolved`]. IDs missing from `requests` are skipped.
+    pub fn resolved_url_preview(&self, requests: &[Request], resolver: &VariableResolver) -> Vec<(Id, String)> {
+        self.all_request_ids()
+            .into_iter()
+            .filter_map(|id| requests.iter().find(|r| r.id == id))
+            .map(|r| (r.id, resolver.resolve(&r.url.raw)))
+            .collect()
+    }
+
+    /// Estimate the storage footprint of this collection in bytes
+    ///
+    /// Sums the serialized JSON size of the collection (including its folders)
+    /// and of every request in `requests` that belongs to it, either by
+    /// `collection_id` or by being listed in the collection tree.
+    pub fn estimated_size_bytes(&self, requests: &[Request]) -> usize {
+        let ids = self.all_request_ids();
+        let own_size = serde_json::to_vec(self).map(|v| v.len()).unwrap_or(0);
+
+        own_size
+            + requests
+                .iter()
+                .filter(|r| r.collection_id == Some(self.id) || ids.contains(&r.id))
+                .map(|r| serde_json::to_vec(r).map(|v| v.len()).unwrap_or(0))
+                .sum::<usize>()
+    }
+
     /// Export to Postman collection format (v2.1)
     pub fn to_postman(&self) -> serde_json::Value {
         serde_json::json!({
@@ -402,6 +654,9 @@ impl Collection {
                 "description": self.description,
                 "schema": self.info.schema,
                 "_postman_id": self.info.postboy_id,
+                "_postboy": {
+                    "tags": self.tags,
+                },
             },
             "item": self.to_postman_items(),
             "variable": self.variables.iter()
@@ -447,6 +702,16 @@ impl Collection {
             collection.info.postboy_id = postman_id.to_string();
         }
 
+        // Tags are a Postboy extension; plain Postman exports have none
+        if let Some(tags) = info.get("_postboy")
+            .and_then(|v| v.get("tags"))
+            .and_then(|v| v.as_array())
+        {
+            for tag in tags.iter().filter_map(|t| t.as_str()) {
+                collection.add_tag(tag);
+            }
+        }
+
         // Parse items
         if let Some(items) = value.get("item").and_then(|v| v.as_array()) {
             for item in items {
@@ -478,6 +743,128 @@ impl Collection {
         dup.updated_at = now();
         dup
     }
+
+    /// Check request references and the folder tree for corruption
+    ///
+    /// Reports request ids missing from `known_request_ids`, request ids
+    /// listed more than once, folders whose `parent_id` points outside the
+    /// tree and folders with the wrong display depth.
+    pub fn validate_integrity(&self, known_request_ids: &HashSet<Id>) -> Vec<IntegrityError> {
+        let mut errors = Vec::new();
+        let mut seen = HashSet::new();
+        let mut check_requests = |requests: &[Id], folder_id: Option<Id>, errors: &mut Vec<IntegrityError>| {
+            for &request_id in requests {
+                if !known_request_ids.contains(&request_id) {
+                    errors.push(IntegrityError::DanglingRequest { request_id, folder_id });
+                } else if !seen.insert(request_id) {
+                    errors.push(IntegrityError::DuplicateRequest { request_id, folder_id });
+                }
+            }
+        };
+        check_requests(&self.requests, None, &mut errors);
+
+        let folder_ids: HashSet<Id> = flatten_folders(&self.folders).iter().map(|f| f.id).collect();
+        let mut pending: Vec<(&Folder, usize)> = self.folders.iter().rev().map(|f| (f, 0)).collect();
+        while let Some((folder, depth)) = pending.pop() {
+            if let Some(parent_id) = folder.parent_id.filter(|id| !folder_ids.contains(id)) {
+                errors.push(IntegrityError::OrphanedFolder { folder_id: folder.id, parent_id });
+            }
+            if folder.ui_state.depth != depth {
+                errors.push(IntegrityError::WrongDepth {
+                    folder_id: folder.id,
+                    expected: depth,
+                    actual: folder.ui_state.depth,
+                });
+            }
+            check_requests(&folder.requests, Some(folder.id), &mut errors);
+            pending.extend(folder.children.iter().rev().map(|child| (child, depth + 1)));
+        }
+
+        errors
+    }
+
+    /// Fix what [`Collection::validate_integrity`] reports, returning the
+    /// problems that were fixed
+    ///
+    /// Dangling request ids and all but the first listing of a duplicate are
+    /// dropped, orphaned folders get the parent they are nested under (None at
+    /// the top level) and depths are recomputed.
+    pub fn repair(&mut self, known_request_ids: &HashSet<Id>) -> Vec<IntegrityError> {
+        let errors = self.validate_integrity(known_request_ids);
+        if errors.is_empty() {
+            return errors;
+        }
+
+        let mut seen = HashSet::new();
+        self.requests.retain(|id| known_request_ids.contains(id) && seen.insert(*id));
+
+        fn repair_folders(folders: &mut [Folder], parent_id: Option<Id>, depth: usize, known: &HashSet<Id>, seen: &mut HashSet<Id>) {
+            for folder in folders {
+                if folder.parent_id.is_some() {
+                    folder.parent_id = parent_id;
+                }
+                folder.ui_state.depth = depth;
+                folder.requests.retain(|id| known.contains(id) && seen.insert(*id));
+                repair_folders(&mut folder.children, Some(folder.id), depth + 1, known, seen);
+            }
+        }
+        repair_folders(&mut self.folders, None, 0, known_request_ids, &mut seen);
+
+        self.updated_at = now();
+        errors
+    }
+
+    /// Compare this collection with a newer version of it
+    pub fn diff(&self, other: &Collection) -> CollectionDiff {
+        let mut diff = CollectionDiff::default();
+
+        let (old_folders, new_folders) = (flatten_folders(&self.folders), flatten_folders(&other.folders));
+        for folder in &new_folders {
+            match old_folders.iter().find(|f| f.id == folder.id) {
+                Some(old) if old.name != folder.name => diff.renamed_folders.push(FolderRename {
+                    id: folder.id,
+                    old_name: old.name.clone(),
+                    new_name: folder.name.clone(),
+                }),
+                Some(_) => {}
+                None => diff.added_folders.push(FolderSummary { id: folder.id, name: folder.name.clone() }),
+            }
+        }
+        for folder in &old_folders {
+            if !new_folders.iter().any(|f| f.id == folder.id) {
+                diff.removed_folders.push(FolderSummary { id: folder.id, name: folder.name.clone() });
+            }
+        }
+
+        let (old_requests, new_requests) = (self.all_request_ids(), other.all_request_ids());
+        for id in &new_requests {
+            if !old_requests.contains(id) && !diff.added_requests.contains(id) {
+                diff.added_requests.push(*id);
+            }
+        }
+        for id in &old_requests {
+            if !new_requests.contains(id) && !diff.removed_requests.contains(id) {
+                diff.removed_requests.push(*id);
+            }
+        }
+
+        let find = |values: &[Variable], key: &str| values.iter().any(|v| v.key == key);
+        for var in &other.variables {
+            match self.variables.iter().find(|v| v.key == var.key) {
+                Some(existing) if existing == var => {}
+                Some(_) => diff.changed_variables.push(var.clone()),
+                None if !find(&diff.added_variables, &var.key) => diff.added_variables.push(var.clone()),
+                None => {}
+            }
+        }
+        for var in &self.variables {
+            if !find(&other.variables, &var.key) && !diff.removed_variables.contains(&var.key) {
+                diff.removed_variables.push(var.key.clone());
+            }
+        }
+
+        diff
+    }
 }
 
 impl Temporal for Collection {
@@ -507,6 +894,7 @@ impl Folder {
             parent_id: None,
             children: Vec::new(),
             requests: Vec::new(),
+            auth: None,
             ui_state: FolderUiState::default(),
             created_at: now,
             updated_at: now,
@@ -724,6 +1112,81 @@ impl Variable {
             initial_value: None,
         })
     }
+
+    /// Check the value parses as the variable's type
+    ///
+    /// Numbers must be finite, booleans `true` or `false`, and JSON
+    /// variables valid JSON. String and secret variables always pass, as do
+    /// values referencing other variables, which can only be checked once
+    /// resolved.
+    pub fn validate(&self) -> Result<(), String> {
+        validate_typed_value(&self.key, &self.value, self.variable_type)
+    }
+}
+
+/// Check `value` parses as `variable_type`, naming `key` in the error
+pub(crate) fn validate_typed_value(key: &str, value: &str, variable_type: VariableType) -> Result<(), String> {
+    if VariableResolver::has_unresolved(value) {
+        return Ok(());
+    }
+    let valid = match variable_type {
+        VariableType::String | VariableType::Secret => true,
+        VariableType::Number => value.trim().parse::<f64>().is_ok_and(f64::is_finite),
+        VariableType::Boolean => matches!(value.trim(), "true" | "false"),
+        VariableType::Json => serde_json::from_str::<serde_json::Value>(value).is_ok(),
+    };
+    if valid {
+        return Ok(());
+    }
+    let expected = match variable_type {
+        VariableType::Number => "a number",
+        VariableType::Boolean => "true or false",
+        _ => "valid JSON",
+    };
+    Err(format!("Variable '{}' should be {}, got '{}'", key, expected, value))
+}
+
+/// Path to the first folder matching `is_target`, searching depth-first
+fn folder_chain<'a>(folders: &'a [Folder], is_target: &dyn Fn(&Folder) -> bool) -> Option<Vec<&'a Folder>> {
+    for folder in folders {
+        if is_target(folder) {
+            return Some(vec![folder]);
+        }
+        if let Some(mut chain) = folder_chain(&folder.children, is_target) {
+            chain.insert(0, folder);
+            return Some(chain);
+        }
+    }
+    None
+}
+
+/// All folders in a tree, parents before their children
+fn flatten_folders(folders: &[Folder]) -> Vec<&Folder> {
+    let mut flat = Vec::new();
+    for folder in folders {
+        flat.push(folder);
+        flat.extend(flatten_folders(&folder.children));
+    }
+    flat
+}
+
+fn reorder_by_id(folders: &mut [Folder], ordered_ids: &[Id]) -> Result<(), String> {
+    for (i, id) in ordered_ids.iter().enumerate() {
+        if ordered_ids[..i].contains(id) {
+            return Err(format!("Folder id listed more than once: {}", id));
+        }
+        if !folders.iter().any(|f| f.id == *id) {
+            return Err(format!("Unknown folder id: {}", id));
+        }
+    }
+
+    folders.sort_by_key(|f| {
+        ordered_ids
+            .iter()
+            .position(|id| *id == f.id)
+            .unwrap_or(ordered_ids.len())
+    });
+    Ok(())
 }
 
 fn variable_type_to_postman(var_type: VariableType) -> &'static str {
@@ -747,6 +1210,11 @@ fn postman_variable_type(s: &str) -> Option<VariableType> {
     }
 }
 
+/// Canonical form of a collection tag
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -841,6 +1309,73 @@ mod tests {
         assert_eq!(collection.sync_state.pending_changes, 2);
     }
 
+    #[test]
+    fn test_bump_version() {
+        let mut collection = Collection::new("My API".to_string());
+        assert_eq!(collection.current_version(), 0);
+
+        assert_eq!(collection.bump_version(), 1);
+        assert_eq!(collection.bump_version(), 2);
+        assert_eq!(collection.current_version(), 2);
+        assert_eq!(collection.sync_state.status, SyncStatus::Pending);
+        assert!(collection.has_pending_changes());
+
+        collection.mark_syncing();
+        assert_eq!(collection.bump_version(), 3);
+        assert_eq!(collection.sync_state.status, SyncStatus::Pending);
+    }
+
+    #[test]
+    fn test_resolved_url_preview() {
+        use crate::request::HttpMethod;
+
+        let resolved = Request::new("List".to_string(), HttpMethod::GET, "{{base_url}}/users".to_string());
+        let unresolved = Request::new("Get".to_string(), HttpMethod::GET, "{{base_url}}/{{missing}}".to_string());
+
+        let mut collection = Collection::new("My API".to_string());
+        collection.add_request(resolved.id);
+        collection.add_request(unresolved.id);
+
+        let mut env = HashMap::new();
+        env.insert("base_url".to_string(), "https://api.example.com".to_string());
+        let resolver = VariableResolver::new().with_environment(env);
+
+        let preview = collection.resolved_url_preview(&[resolved.clone(), unresolved.clone()], &resolver);
+        assert_eq!(preview.len(), 2);
+        assert_eq!(preview[0], (resolved.id, "https://api.example.com/users".to_string()));
+        assert!(!VariableResolver::has_unresolved(&preview[0].1));
+        assert_eq!(preview[1].0, unresolved.id);
+        assert!(VariableResolver::has_unresolved(&preview[1].1));
+    }
+
+    #[test]
+    fn test_estimated_size_bytes() {
+        use crate::request::{HttpMethod, RequestBody};
+
+        let mut small = Request::new("Small".to_string(), HttpMethod::GET, "https://example.com".to_string());
+        let mut large = small.clone();
+        large.id = crate::new_id();
+        large.body = RequestBody::raw("x".repeat(10_000));
+
+        let mut small_collection = Collection::new("Small".to_string());
+        small.collection_id = Some(small_collection.id);
+        small_collection.add_request(small.id);
+
+        let mut large_collection = Collection::new("Large".to_string());
+        large.collection_id = Some(large_collection.id);
+        large_collection.add_request(large.id);
+
+        let requests = [small.clone(), large];
+        let small_size = small_collection.estimated_size_bytes(&requests);
+        let large_size = large_collection.estimated_size_bytes(&requests);
+
+        assert!(small_size > 0);
+        assert!(large_size > small_size + 10_000);
+
+        // Requests of other collections are not counted
+        assert_eq!(small_size, small_collection.estimated_size_bytes(&[small]));
+    }
+
     #[test]
     fn test_enabled_variables_map() {
         let collection = Collection::new("My API".to_string())
@@ -875,4 +1410,269 @@ mod tests {
         assert_eq!(folder.children[0].depth(), 1);
         assert_eq!(folder.children[0].children[0].depth(), 2);
     }
+
+    #[test]
+    fn test_collection_tags() {
+        let mut collection = Collection::new("My API".to_string());
+
+        assert!(collection.add_tag(" Billing "));
+        assert!(!collection.add_tag("BILLING"));
+        assert!(!collection.add_tag("   "));
+        assert!(collection.add_tag("internal"));
+        assert_eq!(collection.tags, vec!["billing", "internal"]);
+        assert!(collection.has_tag("Billing"));
+
+        assert!(collection.remove_tag("INTERNAL"));
+        assert!(!collection.remove_tag("internal"));
+        assert_eq!(collection.tags, vec!["billing"]);
+    }
+
+    #[test]
+    fn test_collection_tags_postman_round_trip() {
+        let mut collection = Collection::new("My API".to_string());
+        collection.add_tag("billing");
+
+        let exported = collection.to_postman();
+        assert_eq!(exported["info"]["_postboy"]["tags"], serde_json::json!(["billing"]));
+
+        let imported = Collection::from_postman(exported).unwrap();
+        assert_eq!(imported.tags, vec!["billing"]);
+
+        // Plain Postman exports and older serialized collections have no tags
+        let plain = Collection::from_postman(serde_json::json!({
+            "info": { "name": "Plain", "schema": "v2.1" },
+            "item": [],
+        }))
+        .unwrap();
+        assert!(plain.tags.is_empty());
+
+        let mut legacy = serde_json::to_value(&collection).unwrap();
+        legacy.as_object_mut().unwrap().remove("tags");
+        let legacy: Collection = serde_json::from_value(legacy).unwrap();
+        assert!(legacy.tags.is_empty());
+    }
+
+    #[test]
+    fn test_folder_auth_inherits_from_ancestors() {
+        let mut collection = Collection::new("My API".to_string());
+        let mut parent = Folder::new("Parent".to_string());
+        parent.auth = Some(crate::request::AuthConfig::Bearer { token: "parent".to_string() });
+        let mut child = Folder::new("Child".to_string());
+        let grandchild = Folder::new("Grandchild".to_string());
+        let grandchild_id = grandchild.id;
+        child.add_child(grandchild);
+        parent.add_child(child);
+        let sibling = Folder::new("Sibling".to_string());
+        let sibling_id = sibling.id;
+        collection.add_folder(parent);
+        collection.add_folder(sibling);
+
+        assert_eq!(
+            collection.folder_auth(grandchild_id),
+            Some(&crate::request::AuthConfig::Bearer { token: "parent".to_string() })
+        );
+        assert_eq!(collection.folder_auth(sibling_id), None);
+        assert_eq!(collection.folder_auth(new_id()), None);
+    }
+
+    #[test]
+    fn test_folder_path_and_request_location() {
+        let root_request = new_id();
+        let nested_request = new_id();
+
+        let mut leaf = Folder::new("Leaf".to_string());
+        leaf.add_request(nested_request);
+        let leaf_id = leaf.id;
+        let mut middle = Folder::new("Middle".to_string());
+        middle.add_child(leaf);
+        let mut top = Folder::new("Top".to_string());
+        top.add_child(middle);
+        let mut collection = Collection::new("Test".to_string());
+        collection.add_folder(Folder::new("Other".to_string()));
+        collection.add_folder(top);
+        collection.add_request(root_request);
+
+        let names = |chain: Vec<&Folder>| chain.iter().map(|f| f.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(collection.folder_path(leaf_id).unwrap()), vec!["Top", "Middle", "Leaf"]);
+        assert_eq!(names(collection.request_location(nested_request).unwrap()), vec!["Top", "Middle", "Leaf"]);
+        assert_eq!(collection.request_location(root_request), Some(Vec::new()));
+        assert_eq!(collection.folder_path(new_id()), None);
+        assert_eq!(collection.request_location(new_id()), None);
+    }
+
+    #[test]
+    fn test_flatten() {
+        let request = |name: &str| Request::new(name.to_string(), crate::request::HttpMethod::GET, "https://example.com".to_string());
+        let (a, b, c, d) = (request("a"), request("b"), request("c"), request("d"));
+        let missing = new_id();
+
+        let mut child = Folder::new("Child".to_string());
+        child.add_request(c.id);
+        let mut folder = Folder::new("Folder".to_string());
+        folder.add_request(b.id);
+        folder.add_request(missing);
+        folder.add_child(child);
+        let mut second = Folder::new("Second".to_string());
+        second.add_request(d.id);
+        let mut collection = Collection::new("Test".to_string());
+        collection.add_folder(folder);
+        collection.add_folder(second);
+        collection.add_request(a.id);
+
+        let requests = vec![d.clone(), c.clone(), b.clone(), a.clone()];
+        let names: Vec<&str> = collection.flatten(&requests).iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "c", "d"]);
+        assert_eq!(collection.missing_requests(&requests), vec![missing]);
+    }
+
+    #[test]
+    fn test_reorder_folders() {
+        let mut collection = Collection::new("My API".to_string());
+        let names = ["Auth", "Users", "Orders"];
+        let ids: Vec<Id> = names
+            .iter()
+            .map(|name| {
+                let folder = Folder::new(name.to_string());
+                let id = folder.id;
+                collection.add_folder(folder);
+                id
+            })
+            .collect();
+        collection.updated_at = 0;
+
+        collection.reorder_folders(&[ids[2], ids[0], ids[1]]).unwrap();
+        let order: Vec<&str> = collection.folders.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(order, vec!["Orders", "Auth", "Users"]);
+        assert!(collection.updated_at > 0);
+
+        // Unlisted folders stay at the end; unknown ids leave the order alone
+        collection.reorder_folders(&[ids[1]]).unwrap();
+        let order: Vec<&str> = collection.folders.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(order, vec!["Users", "Orders", "Auth"]);
+        assert!(collection.reorder_folders(&[ids[0], new_id()]).is_err());
+        assert!(collection.reorder_folders(&[ids[0], ids[0]]).is_err());
+        assert_eq!(collection.folders[0].name, "Users");
+    }
+
+    #[test]
+    fn test_reorder_child_folders() {
+        let mut collection = Collection::new("My API".to_string());
+        let mut parent = Folder::new("Parent".to_string());
+        let first = Folder::new("First".to_string());
+        let second = Folder::new("Second".to_string());
+        let (parent_id, first_id, second_id) = (parent.id, first.id, second.id);
+        parent.add_child(first);
+        parent.add_child(second);
+        collection.add_folder(parent);
+
+        collection.reorder_child_folders(parent_id, &[second_id, first_id]).unwrap();
+        let children = &collection.find_folder(parent_id).unwrap().children;
+        assert_eq!(children[0].id, second_id);
+        assert!(collection.reorder_child_folders(new_id(), &[]).is_err());
+    }
+
+    #[test]
+    fn test_collection_diff() {
+        let mut old = Collection::new("API".to_string())
+            .with_variable("base_url".to_string(), "https://a.example.com".to_string())
+            .with_variable("retired".to_string(), "1".to_string());
+        let mut users = Folder::new("Users".to_string());
+        let mut admin = Folder::new("Admin".to_string());
+        let (shared, moved, dropped) = (new_id(), new_id(), new_id());
+        admin.add_request(moved);
+        users.add_child(admin);
+        old.add_folder(users);
+        let orders = Folder::new("Orders".to_string());
+        old.add_folder(orders.clone());
+        old.requests = vec![shared, dropped];
+
+        let mut new = old.clone();
+        new.folders.retain(|f| f.id != orders.id);
+        new.folders[0].children[0].name = "Administrators".to_string();
+        new.folders[0].children[0].requests.clear();
+        let added_folder = Folder::new("Billing".to_string());
+        new.add_folder(added_folder.clone());
+        let added_request = new_id();
+        // Same request set in a different order and place counts as unchanged
+        new.requests = vec![added_request, moved, shared];
+        new.variables[0].value = "https://b.example.com".to_string();
+        new.variables.remove(1);
+        new.variables.push(Variable::new("token".to_string(), "abc".to_string()));
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added_folders, vec![FolderSummary { id: added_folder.id, name: "Billing".to_string() }]);
+        assert_eq!(diff.removed_folders, vec![FolderSummary { id: orders.id, name: "Orders".to_string() }]);
+        assert_eq!(diff.renamed_folders.len(), 1);
+        assert_eq!(diff.renamed_folders[0].new_name, "Administrators");
+        assert_eq!(diff.added_requests, vec![added_request]);
+        assert_eq!(diff.removed_requests, vec![dropped]);
+        assert_eq!(diff.changed_variables.len(), 1);
+        assert_eq!(diff.changed_variables[0].value, "https://b.example.com");
+        assert_eq!(diff.removed_variables, vec!["retired".to_string()]);
+        assert_eq!(diff.added_variables[0].key, "token");
+
+        assert!(old.diff(&old.clone()).is_empty());
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(serde_json::from_value::<CollectionDiff>(json).unwrap(), diff);
+    }
+
+    #[test]
+    fn test_variable_validate() {
+        let typed = |value: &str, variable_type| Variable {
+            variable_type,
+            ..Variable::new("port".to_string(), value.to_string())
+        };
+
+        assert!(typed("8080", VariableType::Number).validate().is_ok());
+        assert!(typed(" -1.5e3 ", VariableType::Number).validate().is_ok());
+        assert_eq!(
+            typed("eihgt", VariableType::Number).validate(),
+            Err("Variable 'port' should be a number, got 'eihgt'".to_string())
+        );
+        assert!(typed("NaN", VariableType::Number).validate().is_err());
+        assert!(typed("false", VariableType::Boolean).validate().is_ok());
+        assert!(typed("yes", VariableType::Boolean).validate().is_err());
+        assert!(typed("{\"a\": [1]}", VariableType::Json).validate().is_ok());
+        assert!(typed("{a: 1}", VariableType::Json).validate().is_err());
+        assert!(typed("anything", VariableType::String).validate().is_ok());
+        assert!(Variable::secret("token".to_string(), "{not json".to_string()).validate().is_ok());
+        assert!(typed("{{base_port}}", VariableType::Number).validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_and_repair_integrity() {
+        let (kept, dangling) = (new_id(), new_id());
+        let mut collection = Collection::new("API".to_string());
+        collection.requests = vec![kept, dangling];
+        let mut users = Folder::new("Users".to_string());
+        let mut admin = Folder::new("Admin".to_string()).with_parent(new_id());
+        admin.add_request(kept);
+        let admin_id = admin.id;
+        users.add_child(admin);
+        collection.add_folder(users);
+        let known = HashSet::from([kept]);
+
+        let errors = collection.validate_integrity(&known);
+        assert_eq!(
+            errors,
+            vec![
+                IntegrityError::DanglingRequest { request_id: dangling, folder_id: None },
+                IntegrityError::OrphanedFolder {
+                    folder_id: admin_id,
+                    parent_id: collection.folders[0].children[0].parent_id.unwrap(),
+                },
+                IntegrityError::WrongDepth { folder_id: admin_id, expected: 1, actual: 0 },
+                IntegrityError::DuplicateRequest { request_id: kept, folder_id: Some(admin_id) },
+            ]
+        );
+
+        assert_eq!(collection.repair(&known), errors);
+        assert!(collection.validate_integrity(&known).is_empty());
+        assert_eq!(collection.requests, vec![kept]);
+        let admin = collection.find_folder(admin_id).unwrap();
+        assert!(admin.requests.is_empty());
+        assert_eq!(admin.parent_id, Some(collection.folders[0].id));
+        assert_eq!(admin.depth(), 1);
+        assert!(collection.repair(&known).is_empty());
+    }
 }
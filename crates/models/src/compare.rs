@@ -0,0 +1,616 @@
+//! Response comparison
+//!
+//! Compares two responses field by field, e.g. the same request across two
+//! runs. Fields that legitimately vary between runs can be ignored.
+//! [`Response::compare`] lists raw differences for assertions;
+//! [`Response::diff`] groups them into a report of what changed.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::response::{Response, ResponseBody, format_bytes};
+
+/// Headers that change on every response and are left out of
+/// [`Response::diff`] by default
+pub const VOLATILE_HEADERS: &[&str] = &[
+    "age",
+    "date",
+    "etag",
+    "expires",
+    "last-modified",
+    "set-cookie",
+    "x-request-id",
+    "x-runtime",
+];
+
+/// Line pairs compared before a text diff stops looking for common lines
+const MAX_LINE_DIFF_CELLS: usize = 1_000_000;
+
+/// What to leave out when comparing two responses
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompareOptions {
+    /// JSON paths removed from both bodies before diffing, e.g.
+    /// `$.timestamp` or `$.items[*].id`
+    #[serde(default)]
+    pub ignore_json_paths: Vec<String>,
+
+    /// Header names (case-insensitive) left out of the comparison
+    #[serde(default)]
+    pub ignore_headers: Vec<String>,
+}
+
+impl CompareOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn ignore_json_path(mut self, path: impl Into<String>) -> Self {
+        self.ignore_json_paths.push(path.into());
+        self
+    }
+
+    pub fn ignore_header(mut self, name: impl Into<String>) -> Self {
+        self.ignore_headers.push(name.into());
+        self
+    }
+
+    /// Options ignoring [`VOLATILE_HEADERS`]
+    pub fn ignoring_volatile_headers() -> Self {
+        VOLATILE_HEADERS.iter().fold(Self::new(), |options, name| options.ignore_header(*name))
+    }
+}
+
+/// A single difference between two responses
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ResponseDifference {
+    Status { expected: u16, actual: u16 },
+    Header { name: String, expected: Option<String>, actual: Option<String> },
+    /// `path` is a JSON path for JSON bodies and `$` for anything else
+    Body { path: String, expected: Option<Value>, actual: Option<Value> },
+}
+
+impl Response {
+    /// List the differences between this (expected) response and `actual`
+    ///
+    /// Headers are compared by lowercase name, with repeated headers joined.
+    /// Bodies that both parse as JSON are compared structurally after the
+    /// ignored paths are removed; other bodies are compared as text. Bodies
+    /// streamed to disk are compared chunk by chunk and reported by size
+    /// rather than loaded. An empty result means the responses match.
+    pub fn compare(&self, actual: &Response, options: &CompareOptions) -> Vec<ResponseDifference> {
+        let mut differences = Vec::new();
+
+        if self.status_code != actual.status_code {
+            differences.push(ResponseDifference::Status {
+                expected: self.status_code,
+                actual: actual.status_code,
+            });
+        }
+
+        let expected_headers = comparable_headers(self, options);
+        let actual_headers = comparable_headers(actual, options);
+        let mut names: Vec<&String> = expected_headers.iter().chain(&actual_headers).map(|(name, _)| name).collect();
+        names.sort();
+        names.dedup();
+        for name in names {
+            let find = |headers: &[(String, String)]| {
+                headers.iter().find(|(n, _)| n == name).map(|(_, v)| v.clone())
+            };
+            let (expected, actual) = (find(&expected_headers), find(&actual_headers));
+            if expected != actual {
+                differences.push(ResponseDifference::Header {
+                    name: name.clone(),
+                    expected,
+                    actual,
+                });
+            }
+        }
+
+        match (comparable_json(self, options), comparable_json(actual, options)) {
+            (Some(expected), Some(actual)) => diff_json("$".to_string(), Some(&expected), Some(&actual), &mut differences),
+            _ if is_streamed(self) || is_streamed(actual) => {
+                // An unreadable file counts as different
+                if !same_body(self, actual).unwrap_or(false) {
+                    differences.push(ResponseDifference::Body {
+                        path: "$".to_string(),
+                        expected: Some(body_summary(self)),
+                        actual: Some(body_summary(actual)),
+                    });
+                }
+            }
+            _ => {
+                let (expected, actual) = (self.text(), actual.text());
+                if expected != actual {
+                    differences.push(ResponseDifference::Body {
+                        path: "$".to_string(),
+                        expected: Some(Value::String(expected)),
+                        actual: Some(Value::String(actual)),
+                    });
+                }
+            }
+        }
+
+        differences
+    }
+}
+
+/// How a header or JSON value changed between two responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl ChangeKind {
+    fn of<T>(old: &Option<T>, new: &Option<T>) -> Self {
+        match (old, new) {
+            (None, _) => ChangeKind::Added,
+            (_, None) => ChangeKind::Removed,
+            _ => ChangeKind::Changed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeaderChange {
+    /// Lowercase header name
+    pub name: String,
+    pub kind: ChangeKind,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonChange {
+    /// JSON path such as `$.items[0].id`
+    pub path: String,
+    pub kind: ChangeKind,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+/// How the body changed between two responses
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BodyDiff {
+    #[default]
+    Unchanged,
+    /// Both bodies are JSON; one entry per differing path
+    Json { changes: Vec<JsonChange> },
+    /// Line counts for anything else
+    Text { added_lines: usize, removed_lines: usize },
+    /// A body is streamed to disk, so only the fact that they differ is known
+    Streamed,
+}
+
+/// What changed from one response to another, see [`Response::diff`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResponseDiff {
+    /// `(old, new)` when the status code changed
+    pub status: Option<(u16, u16)>,
+    pub headers: Vec<HeaderChange>,
+    pub body: BodyDiff,
+}
+
+impl ResponseDiff {
+    /// True when nothing compared differs
+    pub fn is_empty(&self) -> bool {
+        self.status.is_none() && self.headers.is_empty() && self.body == BodyDiff::Unchanged
+    }
+}
+
+impl Response {
+    /// Report what changed from this response to `other`, ignoring
+    /// [`VOLATILE_HEADERS`]
+    pub fn diff(&self, other: &Response) -> ResponseDiff {
+        self.diff_with(other, &CompareOptions::ignoring_volatile_headers())
+    }
+
+    /// Report what changed from this response to `other`
+    ///
+    /// Bodies that both parse as JSON get a structural diff by key path;
+    /// other bodies get a count of added and removed lines.
+    pub fn diff_with(&self, other: &Response, options: &CompareOptions) -> ResponseDiff {
+        let mut diff = ResponseDiff::default();
+        let mut json_changes = Vec::new();
+
+        for difference in self.compare(other, options) {
+            match difference {
+                ResponseDifference::Status { expected, actual } => diff.status = Some((expected, actual)),
+                ResponseDifference::Header { name, expected, actual } => diff.headers.push(HeaderChange {
+                    name,
+                    kind: ChangeKind::of(&expected, &actual),
+                    old: expected,
+                    new: actual,
+                }),
+                ResponseDifference::Body { path, expected, actual } => json_changes.push(JsonChange {
+                    path,
+                    kind: ChangeKind::of(&expected, &actual),
+                    old: expected,
+                    new: actual,
+                }),
+            }
+        }
+
+        let both_json = comparable_json(self, options).is_some() && comparable_json(other, options).is_some();
+        diff.body = if json_changes.is_empty() {
+            BodyDiff::Unchanged
+        } else if both_json {
+            BodyDiff::Json { changes: json_changes }
+        } else if is_streamed(self) || is_streamed(other) {
+            BodyDiff::Streamed
+        } else {
+            let (added_lines, removed_lines) =
+                line_changes(&self.text(), &other.text());
+            BodyDiff::Text { added_lines, removed_lines }
+        };
+        diff
+    }
+}
+
+fn is_streamed(response: &Response) -> bool {
+    matches!(response.body, ResponseBody::Streamed { .. })
+}
+
+/// Whether two bodies have the same bytes, reading them incrementally
+fn same_body(expected: &Response, actual: &Response) -> std::io::Result<bool> {
+    use std::io::BufRead;
+
+    let mut expected = std::io::BufReader::new(expected.open_body_reader()?);
+    let mut actual = std::io::BufReader::new(actual.open_body_reader()?);
+    loop {
+        let (left, right) = (expected.fill_buf()?, actual.fill_buf()?);
+        if left.is_empty() || right.is_empty() {
+            return Ok(left.is_empty() && right.is_empty());
+        }
+        let len = left.len().min(right.len());
+        if left[..len] != right[..len] {
+            return Ok(false);
+        }
+        expected.consume(len);
+        actual.consume(len);
+    }
+}
+
+/// Body as reported in a difference: the text, or the size if streamed
+fn body_summary(response: &Response) -> Value {
+    match &response.body {
+        ResponseBody::Streamed { size, .. } => Value::String(format!("<{} streamed to disk>", format_bytes(*size))),
+        _ => Value::String(response.text()),
+    }
+}
+
+/// Lines added and removed going from `old` to `new`
+///
+/// Common leading and trailing lines are skipped, then the rest is matched
+/// by longest common subsequence. Past [`MAX_LINE_DIFF_CELLS`] the middle
+/// is counted as wholly replaced.
+fn line_changes(old: &str, new: &str) -> (usize, usize) {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old = &old[prefix..old.len() - suffix];
+    let new = &new[prefix..new.len() - suffix];
+
+    let common = if old.len().saturating_mul(new.len()) > MAX_LINE_DIFF_CELLS {
+        0
+    } else {
+        let mut previous = vec![0usize; new.len() + 1];
+        for old_line in old {
+            let mut current = vec![0usize; new.len() + 1];
+            for (j, new_line) in new.iter().enumerate() {
+                current[j + 1] = if old_line == new_line {
+                    previous[j] + 1
+                } else {
+                    previous[j + 1].max(current[j])
+                };
+            }
+            previous = current;
+        }
+        previous[new.len()]
+    };
+
+    (new.len() - common, old.len() - common)
+}
+
+/// Lowercase header name/value pairs, sorted by name, repeats joined by ", "
+fn comparable_headers(response: &Response, options: &CompareOptions) -> Vec<(String, String)> {
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for header in &response.headers {
+        let name = header.name.to_ascii_lowercase();
+        if options.ignore_headers.iter().any(|i| i.eq_ignore_ascii_case(&name)) {
+            continue;
+        }
+        match headers.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, value)) => {
+                value.push_str(", ");
+                value.push_str(&header.value);
+            }
+            None => headers.push((name, header.value.clone())),
+        }
+    }
+    headers.sort();
+    headers
+}
+
+fn comparable_json(response: &Response, options: &CompareOptions) -> Option<Value> {
+    let mut value = match &response.body {
+        ResponseBody::Json(value) => value.clone(),
+        ResponseBody::Text(text) => serde_json::from_str(text).ok()?,
+        _ => return None,
+    };
+    for path in &options.ignore_json_paths {
+        if let Some(segments) = parse_path(path) {
+            remove_path(&mut value, &segments);
+        }
+    }
+    Some(value)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+    Wildcard,
+}
+
+/// Parse a `$.a.b[0][*]` style path; `None` if it isn't one
+fn parse_path(path: &str) -> Option<Vec<Segment>> {
+    let mut rest = path.trim().strip_prefix('$')?;
+    let mut segments = Vec::new();
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            let key = &after[..end];
+            if key.is_empty() {
+                return None;
+            }
+            segments.push(if key == "*" { Segment::Wildcard } else { Segment::Key(key.to_string()) });
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']')?;
+            let inner = after[..end].trim();
+            let segment = if inner == "*" {
+                Segment::Wildcard
+            } else if let Ok(index) = inner.parse() {
+                Segment::Index(index)
+            } else {
+                Segment::Key(inner.trim_matches(|c| c == '\'' || c == '"').to_string())
+            };
+            segments.push(segment);
+            rest = &after[end + 1..];
+        } else {
+            return None;
+        }
+    }
+
+    Some(segments)
+}
+
+/// Remove everything matched by `segments`; array elements become null so
+/// later indices still line up
+fn remove_path(value: &mut Value, segments: &[Segment]) {
+    let Some((segment, rest)) = segments.split_first() else {
+        return;
+    };
+
+    if rest.is_empty() {
+        match (segment, value) {
+            (Segment::Key(key), Value::Object(map)) => {
+                map.remove(key);
+            }
+            (Segment::Wildcard, Value::Object(map)) => map.clear(),
+            (Segment::Index(index), Value::Array(items)) => {
+                if let Some(item) = items.get_mut(*index) {
+                    *item = Value::Null;
+                }
+            }
+            (Segment::Wildcard, Value::Array(items)) => items.iter_mut().for_each(|item| *item = Value::Null),
+            _ => {}
+        }
+        return;
+    }
+
+    match (segment, value) {
+        (Segment::Key(key), Value::Object(map)) => {
+            if let Some(child) = map.get_mut(key) {
+                remove_path(child, rest);
+            }
+        }
+        (Segment::Index(index), Value::Array(items)) => {
+            if let Some(child) = items.get_mut(*index) {
+                remove_path(child, rest);
+            }
+        }
+        (Segment::Wildcard, Value::Object(map)) => map.values_mut().for_each(|child| remove_path(child, rest)),
+        (Segment::Wildcard, Value::Array(items)) => items.iter_mut().for_each(|child| remove_path(child, rest)),
+        _ => {}
+    }
+}
+
+fn diff_json(path: String, expected: Option<&Value>, actual: Option<&Value>, differences: &mut Vec<ResponseDifference>) {
+    match (expected, actual) {
+        (Some(Value::Object(expected)), Some(Value::Object(actual))) => {
+            for (key, value) in expected {
+                diff_json(format!("{}.{}", path, key), Some(value), actual.get(key), differences);
+            }
+            for (key, value) in actual {
+                if !expected.contains_key(key) {
+                    diff_json(format!("{}.{}", path, key), None, Some(value), differences);
+                }
+            }
+        }
+        (Some(Value::Array(expected)), Some(Value::Array(actual))) => {
+            for index in 0..expected.len().max(actual.len()) {
+                diff_json(format!("{}[{}]", path, index), expected.get(index), actual.get(index), differences);
+            }
+        }
+        (expected, actual) if expected != actual => differences.push(ResponseDifference::Body {
+            path,
+            expected: expected.cloned(),
+            actual: actual.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response::ResponseHeader;
+    use serde_json::json;
+
+    fn response(body: Value, date: &str) -> Response {
+        let mut response = Response::new(200, "OK".to_string());
+        response.headers = vec![
+            ResponseHeader::new("Content-Type".to_string(), "application/json".to_string()),
+            ResponseHeader::new("Date".to_string(), date.to_string()),
+        ];
+        response.body = ResponseBody::Json(body);
+        response
+    }
+
+    #[test]
+    fn test_compare_ignoring_dynamic_fields() {
+        let first = response(json!({"id": 1, "timestamp": "2024-01-01T00:00:00Z"}), "Mon, 01 Jan 2024 00:00:00 GMT");
+        let second = response(json!({"id": 1, "timestamp": "2024-01-02T00:00:00Z"}), "Tue, 02 Jan 2024 00:00:00 GMT");
+
+        let differences = first.compare(&second, &CompareOptions::new());
+        assert_eq!(differences.len(), 2);
+        assert!(matches!(&differences[0], ResponseDifference::Header { name, .. } if name == "date"));
+        assert!(matches!(&differences[1], ResponseDifference::Body { path, .. } if path == "$.timestamp"));
+
+        let options = CompareOptions::new().ignore_json_path("$.timestamp").ignore_header("date");
+        assert!(first.compare(&second, &options).is_empty());
+
+        let mut changed = second.clone();
+        changed.body = ResponseBody::Json(json!({"id": 2, "timestamp": "2024-01-03T00:00:00Z"}));
+        let differences = first.compare(&changed, &options);
+        assert_eq!(
+            differences,
+            vec![ResponseDifference::Body {
+                path: "$.id".to_string(),
+                expected: Some(json!(1)),
+                actual: Some(json!(2)),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_compare_wildcard_paths_and_text_bodies() {
+        let first = response(json!({"items": [{"id": "a", "name": "x"}, {"id": "b", "name": "y"}]}), "");
+        let second = response(json!({"items": [{"id": "c", "name": "x"}, {"id": "d", "name": "y"}]}), "");
+        assert!(first.compare(&second, &CompareOptions::new().ignore_json_path("$.items[*].id")).is_empty());
+        assert_eq!(first.compare(&second, &CompareOptions::new()).len(), 2);
+
+        let mut text = Response::new(500, "Error".to_string());
+        text.body = ResponseBody::Text("boom".to_string());
+        let differences = first.compare(&text, &CompareOptions::new().ignore_header("date").ignore_header("content-type"));
+        assert_eq!(differences.len(), 2);
+        assert_eq!(differences[0], ResponseDifference::Status { expected: 200, actual: 500 });
+
+        assert_eq!(parse_path("timestamp"), None);
+        assert_eq!(
+            parse_path("$.a['b'][0]"),
+            Some(vec![Segment::Key("a".to_string()), Segment::Key("b".to_string()), Segment::Index(0)])
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = response(json!({"id": 1, "name": "a", "tags": ["x"]}), "Mon, 01 Jan 2024 00:00:00 GMT");
+        let mut new = response(json!({"id": 1, "name": "b", "owner": "me"}), "Tue, 02 Jan 2024 00:00:00 GMT");
+        new.status_code = 201;
+        new.headers.push(ResponseHeader::new("Location".to_string(), "/items/1".to_string()));
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.status, Some((200, 201)));
+        assert_eq!(
+            diff.headers,
+            vec![HeaderChange {
+                name: "location".to_string(),
+                kind: ChangeKind::Added,
+                old: None,
+                new: Some("/items/1".to_string()),
+            }]
+        );
+        let BodyDiff::Json { changes } = &diff.body else {
+            panic!("expected a JSON body diff, got {:?}", diff.body);
+        };
+        let changes: Vec<(&str, ChangeKind)> = changes.iter().map(|c| (c.path.as_str(), c.kind)).collect();
+        assert_eq!(
+            changes,
+            vec![("$.name", ChangeKind::Changed), ("$.tags", ChangeKind::Removed), ("$.owner", ChangeKind::Added)]
+        );
+
+        // Date only shows up once volatile headers aren't ignored
+        let diff = old.diff_with(&new, &CompareOptions::new());
+        assert!(diff.headers.iter().any(|h| h.name == "date" && h.kind == ChangeKind::Changed));
+
+        assert!(old.diff(&old.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_compare_streamed_bodies() {
+        let dir = std::env::temp_dir().join(format!("postboy-compare-{}", crate::new_id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let streamed = |name: &str, data: &[u8]| {
+            let path = dir.join(name);
+            std::fs::write(&path, data).unwrap();
+            let mut response = Response::new(200, "OK".to_string());
+            response.body = ResponseBody::Streamed { path, size: data.len() as u64, content_type: None };
+            response
+        };
+        let large = vec![b'x'; 100_000];
+        let mut changed = large.clone();
+        changed[99_999] = b'y';
+
+        let (old, same, new) = (streamed("old", &large), streamed("same", &large), streamed("new", &changed));
+        assert!(old.compare(&same, &CompareOptions::new()).is_empty());
+        let differences = old.compare(&new, &CompareOptions::new());
+        assert_eq!(
+            differences,
+            vec![ResponseDifference::Body {
+                path: "$".to_string(),
+                expected: Some(json!("<97.66KB streamed to disk>")),
+                actual: Some(json!("<97.66KB streamed to disk>")),
+            }]
+        );
+        assert_eq!(old.diff(&new).body, BodyDiff::Streamed);
+
+        // Streamed against in-memory compares the bytes too
+        let mut inline = Response::new(200, "OK".to_string());
+        inline.body = ResponseBody::Binary(large);
+        assert!(old.compare(&inline, &CompareOptions::new()).is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(old.compare(&same, &CompareOptions::new()).len(), 1);
+    }
+
+    #[test]
+    fn test_diff_text_bodies() {
+        let mut old = Response::new(200, "OK".to_string());
+        old.body = ResponseBody::Text("one\ntwo\nthree\nfour".to_string());
+        let mut new = old.clone();
+        new.body = ResponseBody::Text("one\n2\nthree\nfour\nfive".to_string());
+
+        assert_eq!(
+            old.diff(&new).body,
+            BodyDiff::Text {
+                added_lines: 2,
+                removed_lines: 1,
+            }
+        );
+        assert_eq!(line_changes("a\nb\nc", "c\na"), (1, 2));
+    }
+}
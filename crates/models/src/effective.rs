@@ -0,0 +1,216 @@
+//! Effective request computation
+//!
+//! A saved request only holds what the user set on it; auth, headers,
+//! variables and transport settings may come from its folder, collection,
+//! environment or the user's settings. This module folds all of that into
+//! the one structure the transport layer sends.
+
+use crate::collection::{Collection, Folder};
+use crate::environment::{Environment, Globals, VariableResolver};
+use crate::request::{AuthConfig, AuthError, Header, HttpMethod, Param, Request, RequestBody};
+use crate::user::UserSettings;
+
+/// How many nested `{{var}}` levels are resolved before giving up
+const MAX_RESOLVE_DEPTH: usize = 5;
+
+/// A request with everything inherited and resolved, ready to send
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectiveRequest {
+    pub method: HttpMethod,
+
+    /// URL with variables resolved
+    pub url: String,
+
+    /// Enabled collection and request headers plus auth headers, resolved
+    pub headers: Vec<Header>,
+
+    /// Enabled query params plus auth params, resolved
+    pub query_params: Vec<Param>,
+
+    /// Body with variables resolved in its text and form values
+    pub body: RequestBody,
+
+    /// Auth that applies after inheritance (None when there is none)
+    pub auth: Option<AuthConfig>,
+
+    /// Set when the auth scheme couldn't be turned into headers or params
+    pub auth_error: Option<AuthError>,
+
+    pub timeout_secs: u32,
+    pub follow_redirects: bool,
+    pub max_redirects: u32,
+    pub validate_ssl: bool,
+}
+
+/// Fold inheritance and context into the request that will be sent
+///
+/// - Auth: the request's own auth, else the folder's (or its nearest
+///   ancestor's in `collection`), else the collection's. An explicit
+///   `Noauth` on the request stops inheritance.
+/// - Headers: enabled collection headers not overridden by a request header
+///   of the same name, then the request's enabled headers, then auth headers.
+/// - Variables: environment over collection variables over globals.
+/// - Timeout, redirects and max redirects: request overrides, else `settings`.
+pub fn compute_effective_request(
+    req: &Request,
+    folder: Option<&Folder>,
+    collection: &Collection,
+    env: Option<&Environment>,
+    globals: &Globals,
+    settings: &UserSettings,
+) -> EffectiveRequest {
+    let resolver = VariableResolver::new()
+        .with_environment(env.map(Environment::to_map).unwrap_or_default())
+        .with_collection(collection.enabled_variables_map())
+        .with_globals(globals.to_map());
+    let resolve = |input: &str| resolver.resolve_recursive(input, MAX_RESOLVE_DEPTH);
+
+    let request_headers = req.enabled_headers();
+    let mut headers: Vec<Header> = collection
+        .headers
+        .iter()
+        .filter(|h| h.enabled && !request_headers.iter().any(|r| r.key.eq_ignore_ascii_case(&h.key)))
+        .chain(request_headers.iter().copied())
+        .map(|h| Header::new(resolve(&h.key), resolve(&h.value)))
+        .collect();
+    let mut query_params: Vec<Param> = req
+        .enabled_query_params()
+        .into_iter()
+        .map(|p| {
+            let mut param = p.clone();
+            param.key = resolve(&p.key);
+            param.value = resolve(&p.value);
+            param
+        })
+        .collect();
+
+    let folder_auth = folder.and_then(|folder| collection.folder_auth(folder.id).or(folder.auth.as_ref()));
+    let auth = req
+        .resolved_auth(folder_auth, collection.auth.as_ref())
+        .filter(|auth| !matches!(auth, AuthConfig::Noauth))
+    .map(|auth| resolve_auth(auth, &resolve));
+    let auth_error = auth
+        .as_ref()
+        .and_then(|auth| auth.apply(&mut headers, &mut query_params).err());
+
+    EffectiveRequest {
+        method: req.method.clone(),
+        url: resolve(&req.url.raw),
+        headers,
+        query_params,
+        body: resolve_body(&req.body, &resolve),
+        auth,
+        auth_error,
+        timeout_secs: req.effective_timeout(settings),
+        follow_redirects: req.effective_follow_redirects(settings),
+        max_redirects: req.effective_max_redirects(settings),
+        validate_ssl: settings.validate_ssl,
+    }
+}
+
+/// Resolve variables in every string field of an auth config
+fn resolve_auth(auth: &AuthConfig, resolve: &impl Fn(&str) -> String) -> AuthConfig {
+    fn walk(value: &mut serde_json::Value, resolve: &impl Fn(&str) -> String) {
+        match value {
+            serde_json::Value::String(s) => *s = resolve(s),
+            serde_json::Value::Array(items) => items.iter_mut().for_each(|item| walk(item, resolve)),
+            serde_json::Value::Object(fields) => fields.values_mut().for_each(|field| walk(field, resolve)),
+            _ => {}
+        }
+    }
+
+    let Ok(mut value) = serde_json::to_value(auth) else {
+        return auth.clone();
+    };
+    walk(&mut value, resolve);
+    serde_json::from_value(value).unwrap_or_else(|_| auth.clone())
+}
+
+fn resolve_body(body: &RequestBody, resolve: &impl Fn(&str) -> String) -> RequestBody {
+    let mut body = body.clone();
+    match &mut body {
+        RequestBody::Json { raw } | RequestBody::Raw { raw, .. } => *raw = resolve(raw),
+        RequestBody::FormData { formdata: fields } | RequestBody::UrlEncoded { urlencoded: fields } => {
+            fields.retain(|field| field.enabled);
+            for field in fields {
+                field.key = resolve(&field.key);
+                field.value = resolve(&field.value);
+            }
+        }
+        RequestBody::None | RequestBody::Binary => {}
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_effective_request_inherits_from_collection() {
+        let mut collection = Collection::new("API".to_string())
+            .with_auth(AuthConfig::Bearer { token: "{{token}}".to_string() })
+            .with_header("X-Client".to_string(), "postboy".to_string())
+            .with_header("Accept".to_string(), "text/plain".to_string());
+        collection.variables.push(crate::collection::Variable::new("token".to_string(), "collection-token".to_string()));
+        let request = Request::new("Users".to_string(), HttpMethod::GET, "{{base_url}}/users".to_string())
+            .with_header("accept".to_string(), "application/json".to_string());
+        let mut env = Environment::new("Staging".to_string());
+        env.add_variable("base_url".to_string(), "https://staging.example.com".to_string());
+
+        let effective = compute_effective_request(
+            &request,
+            None,
+            &collection,
+            Some(&env),
+            &Globals::new(),
+            &UserSettings::default(),
+        );
+
+        assert_eq!(effective.url, "https://staging.example.com/users");
+        let headers: Vec<(&str, &str)> = effective.headers.iter().map(|h| (h.key.as_str(), h.value.as_str())).collect();
+        assert_eq!(
+            headers,
+            vec![
+                ("X-Client", "postboy"),
+                ("accept", "application/json"),
+                ("Authorization", "Bearer collection-token"),
+            ]
+        );
+        assert_eq!(effective.auth_error, None);
+        assert_eq!(effective.timeout_secs, UserSettings::default().default_request_timeout_secs);
+        assert_eq!(effective.max_redirects, UserSettings::default().max_redirects);
+        assert!(effective.validate_ssl);
+    }
+
+    #[test]
+    fn test_compute_effective_request_auth_precedence() {
+        let collection = Collection::new("API".to_string()).with_auth(AuthConfig::Bearer { token: "collection".to_string() });
+        let mut folder = Folder::new("Admin".to_string());
+        folder.auth = Some(AuthConfig::Bearer { token: "folder".to_string() });
+        let mut request = Request::new("Users".to_string(), HttpMethod::GET, "https://example.com".to_string());
+        let effective = |request: &Request, folder: Option<&Folder>| {
+            compute_effective_request(request, folder, &collection, None, &Globals::new(), &UserSettings::default()).auth
+        };
+
+        assert_eq!(effective(&request, Some(&folder)), folder.auth);
+        assert_eq!(effective(&request, None), collection.auth);
+
+        request.auth = Some(AuthConfig::Noauth);
+        assert_eq!(effective(&request, Some(&folder)), None);
+    }
+
+    #[test]
+    fn test_compute_effective_request_max_redirects() {
+        let collection = Collection::new("API".to_string());
+        let mut request = Request::new("Users".to_string(), HttpMethod::GET, "https://example.com".to_string());
+        let settings = UserSettings { max_redirects: 3, ..UserSettings::default() };
+        let effective = |request: &Request| {
+            compute_effective_request(request, None, &collection, None, &Globals::new(), &settings).max_redirects
+        };
+
+        assert_eq!(effective(&request), 3);
+        request.max_redirects = Some(0);
+        assert_eq!(effective(&request), 0);
+    }
+}
@@ -93,6 +93,97 @@ impl Environment {
             .collect()
     }
 
+    /// Merge variables from another environment
+    ///
+    /// Returns the number of variables added or overwritten.
+    pub fn merge(&mut self, other: &Environment, strategy: MergeStrategy) -> usize {
+        let changed = merge_variables(&mut self.values, &other.values, strategy);
+        if changed > 0 {
+            self.updated_at = now();
+        }
+        changed
+    }
+
+    /// Check the environment against a schema
+    ///
+    /// Returns one message per problem: required keys that are missing or
+    /// disabled, values not matching their pattern, and invalid patterns.
+    /// An empty result means the environment is valid.
+    pub fn validate_against(&self, schema: &EnvSchema) -> Vec<String> {
+        let mut problems: Vec<String> = schema
+            .required
+            .iter()
+            .filter(|key| self.get(key).is_none())
+            .map(|key| format!("Missing required variable '{}'", key))
+            .collect();
+
+        let mut patterns: Vec<_> = schema.patterns.iter().collect();
+        patterns.sort();
+        for (key, pattern) in patterns {
+            let Some(value) = self.get(key) else {
+                continue;
+            };
+            match regex::Regex::new(pattern) {
+                Ok(re) if re.is_match(&value) => {}
+                Ok(_) => problems.push(format!("Variable '{}' does not match pattern '{}'", key, pattern)),
+                Err(e) => problems.push(format!("Invalid pattern for '{}': {}", key, e)),
+            }
+        }
+
+        problems
+    }
+
+    /// Changes that turn this environment into `other`
+    ///
+    /// Variables are matched by key. Only the name and variables are
+    /// compared; ids, timestamps and the active flag are ignored.
+    pub fn diff(&self, other: &Environment) -> EnvDiff {
+        let find = |values: &[Variable], key: &str| values.iter().find(|v| v.key == key).cloned();
+
+        let mut diff = EnvDiff {
+            name: (self.name != other.name).then(|| other.name.clone()),
+            ..Default::default()
+        };
+
+        for var in &other.values {
+            match find(&self.values, &var.key) {
+                Some(existing) if existing == *var => {}
+                Some(_) => diff.changed.push(var.clone()),
+                None if find(&diff.added, &var.key).is_none() => diff.added.push(var.clone()),
+                None => {}
+            }
+        }
+        for var in &self.values {
+            if find(&other.values, &var.key).is_none() && !diff.removed.contains(&var.key) {
+                diff.removed.push(var.key.clone());
+            }
+        }
+
+        diff
+    }
+
+    /// Apply a diff produced by [`Environment::diff`]
+    ///
+    /// Changed or added variables whose key is already present replace it, so
+    /// a patch can be applied to an environment that has since moved on.
+    pub fn apply_env_patch(&mut self, patch: &EnvDiff) {
+        if patch.is_empty() {
+            return;
+        }
+
+        if let Some(name) = &patch.name {
+            self.name = name.clone();
+        }
+        self.values.retain(|v| !patch.removed.contains(&v.key));
+        for var in patch.changed.iter().chain(&patch.added) {
+            match self.values.iter_mut().find(|v| v.key == var.key) {
+                Some(existing) => *existing = var.clone(),
+                None => self.values.push(var.clone()),
+            }
+        }
+        self.updated_at = now();
+    }
+
     /// Create a duplicate of this environment
     pub fn duplicate(&self) -> Self {
         let mut dup = self.clone();
@@ -121,6 +212,157 @@ impl Identifiable for Environment {
     }
 }
 
+/// Expectations a team places on an environment's variables
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct EnvSchema {
+    /// Keys that must be present and enabled
+    #[serde(default)]
+    pub required: Vec<String>,
+
+    /// Regex each key's value must match, e.g. `base_url` => `^https?://`
+    #[serde(default)]
+    pub patterns: HashMap<String, String>,
+}
+
+/// Key-level differences between two versions of an environment
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct EnvDiff {
+    /// New name, if it changed
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Variables whose key only exists on the new side
+    #[serde(default)]
+    pub added: Vec<Variable>,
+
+    /// Keys only present on the old side
+    #[serde(default)]
+    pub removed: Vec<String>,
+
+    /// New version of variables whose value or settings changed
+    #[serde(default)]
+    pub changed: Vec<Variable>,
+}
+
+impl EnvDiff {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// How to handle keys present on both sides of a variable merge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MergeStrategy {
+    /// Keep the existing value; only add new keys
+    KeepExisting,
+    /// Replace existing values with incoming ones
+    Overwrite,
+}
+
+/// Merge `incoming` variables into `target`, returning how many changed
+fn merge_variables(target: &mut Vec<Variable>, incoming: &[Variable], strategy: MergeStrategy) -> usize {
+    let mut changed = 0;
+    for var in incoming {
+        match target.iter_mut().find(|v| v.key == var.key) {
+            Some(existing) => {
+                if strategy == MergeStrategy::Overwrite && existing != var {
+                    *existing = var.clone();
+                    changed += 1;
+                }
+            }
+            None => {
+                target.push(var.clone());
+                changed += 1;
+            }
+        }
+    }
+    changed
+}
+
+/// Set of environments with at most one active at a time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct EnvironmentSet {
+    #[serde(default)]
+    pub environments: Vec<Environment>,
+}
+
+impl EnvironmentSet {
+    pub fn new() -> Self {
+        Self {
+            environments: Vec::new(),
+        }
+    }
+
+    pub fn with_environments(mut self, environments: Vec<Environment>) -> Self {
+        self.environments = environments;
+        self
+    }
+
+    /// Add an environment to the set
+    ///
+    /// If the new environment is active, all others are deactivated.
+    pub fn add(&mut self, environment: Environment) {
+        let id = environment.id;
+        let is_active = environment.is_active;
+        self.environments.push(environment);
+        if is_active {
+            self.set_active(id);
+        }
+    }
+
+    /// Remove an environment by ID
+    pub fn remove(&mut self, id: Id) -> Option<Environment> {
+        let index = self.environments.iter().position(|e| e.id == id)?;
+        Some(self.environments.remove(index))
+    }
+
+    /// Get an environment by ID
+    pub fn get(&self, id: Id) -> Option<&Environment> {
+        self.environments.iter().find(|e| e.id == id)
+    }
+
+    /// Activate an environment, clearing the flag on all others
+    ///
+    /// Returns false (and changes nothing) if the ID is unknown.
+    pub fn set_active(&mut self, id: Id) -> bool {
+        if self.get(id).is_none() {
+            return false;
+        }
+
+        let now = now();
+        for env in &mut self.environments {
+            let should_be_active = env.id == id;
+            if env.is_active != should_be_active {
+                env.is_active = should_be_active;
+                env.updated_at = now;
+            }
+        }
+        true
+    }
+
+    /// Deactivate all environments
+    pub fn clear_active(&mut self) {
+        let now = now();
+        for env in self.environments.iter_mut().filter(|e| e.is_active) {
+            env.is_active = false;
+            env.updated_at = now;
+        }
+    }
+
+    /// Get the currently active environment
+    pub fn active(&self) -> Option<&Environment> {
+        self.environments.iter().find(|e| e.is_active)
+    }
+
+    pub fn len(&self) -> usize {
+        self.environments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.environments.is_empty()
+    }
+}
+
 /// Environment variable
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Variable {
@@ -204,6 +446,20 @@ impl Variable {
             self.value.clone()
         }
     }
+
+    /// Check the value parses as the variable's type
+    ///
+    /// Same rules as the collection `Variable::validate`; normal, secret,
+    /// system and env variables always pass.
+    pub fn validate(&self) -> Result<(), String> {
+        let variable_type = match self.variable_type {
+            VariableType::Number => crate::collection::VariableType::Number,
+            VariableType::Boolean => crate::collection::VariableType::Boolean,
+            VariableType::Json => crate::collection::VariableType::Json,
+            VariableType::Normal | VariableType::Secret | VariableType::System | VariableType::Env => return Ok(()),
+        };
+        crate::collection::validate_typed_value(&self.key, &self.value, variable_type)
+    }
 }
 
 /// Variable type for categorization and UI handling
@@ -218,6 +474,12 @@ pub enum VariableType {
     System,
     /// Environment-specific variable
     Env,
+    /// Number variable
+    Number,
+    /// Boolean variable
+    Boolean,
+    /// JSON variable
+    Json,
 }
 
 impl Default for VariableType {
@@ -269,6 +531,29 @@ impl Globals {
         self.updated_at = now();
     }
 
+    /// Set a global variable only if the key doesn't exist yet
+    ///
+    /// Returns true if the variable was added.
+    pub fn set_if_absent(&mut self, key: String, value: String) -> bool {
+        if self.values.iter().any(|v| v.key == key) {
+            return false;
+        }
+        self.values.push(Variable::new(key, value));
+        self.updated_at = now();
+        true
+    }
+
+    /// Merge variables from another set of globals
+    ///
+    /// Returns the number of variables added or overwritten.
+    pub fn merge(&mut self, other: &Globals, strategy: MergeStrategy) -> usize {
+        let changed = merge_variables(&mut self.values, &other.values, strategy);
+        if changed > 0 {
+            self.updated_at = now();
+        }
+        changed
+    }
+
     /// Remove a global variable
     pub fn unset(&mut self, key: &str) -> bool {
         let original_len = self.values.len();
@@ -293,8 +578,12 @@ impl Default for Globals {
 }
 
 /// Variable resolver for substituting {{variable}} patterns
+///
+/// Scopes are checked in Postman's order: environment, then collection,
+/// then globals, then system variables.
 pub struct VariableResolver {
     environment: HashMap<String, String>,
+    collection: HashMap<String, String>,
     globals: HashMap<String, String>,
     /// Additional system variables
     system: HashMap<String, String>,
@@ -304,6 +593,7 @@ impl VariableResolver {
     pub fn new() -> Self {
         Self {
             environment: HashMap::new(),
+            collection: HashMap::new(),
             globals: HashMap::new(),
             system: Self::init_system_vars(),
         }
@@ -314,6 +604,11 @@ impl VariableResolver {
         self
     }
 
+    pub fn with_collection(mut self, vars: HashMap<String, String>) -> Self {
+        self.collection = vars;
+        self
+    }
+
     pub fn with_globals(mut self, vars: HashMap<String, String>) -> Self {
         self.globals = vars;
         self
@@ -346,9 +641,10 @@ impl VariableResolver {
         re.replace_all(input, |caps: &regex::Captures| {
             let key = &caps[1];
 
-            // Priority: environment > globals > system
+            // Priority: environment > collection > globals > system
             self.environment
                 .get(key)
+                .or_else(|| self.collection.get(key))
                 .or_else(|| self.globals.get(key))
                 .or_else(|| self.system.get(key))
                 .cloned()
@@ -371,6 +667,11 @@ impl VariableResolver {
         result
     }
 
+    /// Check whether a string still contains unresolved {{variable}} patterns
+    pub fn has_unresolved(input: &str) -> bool {
+        input.contains("{{")
+    }
+
     /// Update system variables (for dynamic values like timestamp)
     pub fn refresh_system_vars(&mut self) {
         self.system = Self::init_system_vars();
@@ -387,6 +688,16 @@ impl Default for VariableResolver {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_variable_validate() {
+        let port = Variable::new("port".to_string(), "eihgt".to_string()).with_type(VariableType::Number);
+        assert_eq!(port.validate(), Err("Variable 'port' should be a number, got 'eihgt'".to_string()));
+        assert!(Variable::new("port".to_string(), "8080".to_string()).with_type(VariableType::Number).validate().is_ok());
+        assert!(Variable::new("debug".to_string(), "maybe".to_string()).with_type(VariableType::Boolean).validate().is_err());
+        assert!(Variable::new("body".to_string(), "[1, 2]".to_string()).with_type(VariableType::Json).validate().is_ok());
+        assert!(Variable::new("name".to_string(), "eihgt".to_string()).validate().is_ok());
+    }
+
     #[test]
     fn test_environment_creation() {
         let env = Environment::new("Production".to_string());
@@ -464,16 +775,25 @@ mod tests {
         let mut env_vars = HashMap::new();
         env_vars.insert("key".to_string(), "env_value".to_string());
 
+        let mut collection_vars = HashMap::new();
+        collection_vars.insert("key".to_string(), "collection_value".to_string());
+        collection_vars.insert("shared".to_string(), "collection_value".to_string());
+
         let mut global_vars = HashMap::new();
         global_vars.insert("key".to_string(), "global_value".to_string());
+        global_vars.insert("shared".to_string(), "global_value".to_string());
+        global_vars.insert("global_only".to_string(), "global_value".to_string());
 
         let resolver = VariableResolver::new()
             .with_environment(env_vars)
+            .with_collection(collection_vars)
             .with_globals(global_vars);
 
-        // Environment should have priority over globals
+        // Environment over collection over globals
         let result = resolver.resolve("{{key}}");
         assert_eq!(result, "env_value");
+        assert_eq!(resolver.resolve("{{shared}}"), "collection_value");
+        assert_eq!(resolver.resolve("{{global_only}}"), "global_value");
     }
 
     #[test]
@@ -490,6 +810,49 @@ mod tests {
         assert_eq!(globals.get("api_key"), None);
     }
 
+    #[test]
+    fn test_globals_merge() {
+        let mut globals = Globals::new();
+        globals.set("host".to_string(), "localhost".to_string());
+        globals.updated_at = 0;
+
+        let mut incoming = Globals::new();
+        incoming.set("host".to_string(), "example.com".to_string());
+        incoming.set("port".to_string(), "8080".to_string());
+
+        assert_eq!(globals.merge(&incoming, MergeStrategy::KeepExisting), 1);
+        assert_eq!(globals.get("host"), Some("localhost".to_string()));
+        assert_eq!(globals.get("port"), Some("8080".to_string()));
+        assert!(globals.updated_at > 0);
+
+        assert_eq!(globals.merge(&incoming, MergeStrategy::Overwrite), 1);
+        assert_eq!(globals.get("host"), Some("example.com".to_string()));
+
+        assert!(!globals.set_if_absent("port".to_string(), "9090".to_string()));
+        assert!(globals.set_if_absent("scheme".to_string(), "https".to_string()));
+        assert_eq!(globals.get("port"), Some("8080".to_string()));
+    }
+
+    #[test]
+    fn test_environment_set_single_active() {
+        let a = Environment::new("A".to_string()).with_active(true);
+        let b = Environment::new("B".to_string());
+        let (a_id, b_id) = (a.id, b.id);
+
+        let mut set = EnvironmentSet::new();
+        set.add(a);
+        set.add(b);
+        assert_eq!(set.active().map(|e| e.id), Some(a_id));
+
+        assert!(set.set_active(b_id));
+        assert_eq!(set.active().map(|e| e.id), Some(b_id));
+        assert!(!set.get(a_id).unwrap().is_active);
+        assert_eq!(set.environments.iter().filter(|e| e.is_active).count(), 1);
+
+        assert!(!set.set_active(new_id()));
+        assert_eq!(set.active().map(|e| e.id), Some(b_id));
+    }
+
     #[test]
     fn test_environment_duplicate() {
         let original = Environment::new("Production".to_string())
@@ -505,4 +868,61 @@ mod tests {
         assert!(!copy.is_active);
         assert_eq!(copy.values.len(), 1);
     }
+
+    #[test]
+    fn test_validate_against_schema() {
+        let mut env = Environment::new("Staging".to_string());
+        env.add_variable("base_url".to_string(), "staging.example.com".to_string());
+        env.add_variable("api_key".to_string(), "secret".to_string());
+        env.values[1].enabled = false;
+
+        let schema = EnvSchema {
+            required: vec!["base_url".to_string(), "api_key".to_string()],
+            patterns: HashMap::from([
+                ("base_url".to_string(), "^https?://".to_string()),
+                ("timeout".to_string(), "^[0-9]+$".to_string()),
+            ]),
+        };
+
+        let problems = env.validate_against(&schema);
+        assert_eq!(problems, vec![
+            "Missing required variable 'api_key'".to_string(),
+            "Variable 'base_url' does not match pattern '^https?://'".to_string(),
+        ]);
+
+        env.set("base_url".to_string(), "https://staging.example.com".to_string());
+        env.values[1].enabled = true;
+        assert!(env.validate_against(&schema).is_empty());
+
+        let broken = EnvSchema {
+            patterns: HashMap::from([("base_url".to_string(), "(".to_string())]),
+            ..Default::default()
+        };
+        assert_eq!(env.validate_against(&broken).len(), 1);
+    }
+
+    #[test]
+    fn test_environment_diff_and_patch() {
+        let mut local = Environment::new("Staging".to_string());
+        local.add_variable("base_url".to_string(), "https://staging.example.com".to_string());
+        local.add_variable("legacy".to_string(), "1".to_string());
+
+        let mut remote = local.clone();
+        remote.set("base_url".to_string(), "https://staging2.example.com".to_string());
+        remote.add_variable("api_key".to_string(), "secret".to_string());
+        remote.unset("legacy");
+        remote.name = "Staging 2".to_string();
+
+        let diff = local.diff(&remote);
+        assert_eq!(diff.name.as_deref(), Some("Staging 2"));
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].value, "https://staging2.example.com");
+        assert_eq!(diff.added.iter().map(|v| v.key.as_str()).collect::<Vec<_>>(), vec!["api_key"]);
+        assert_eq!(diff.removed, vec!["legacy".to_string()]);
+
+        local.apply_env_patch(&diff);
+        assert_eq!(local.name, "Staging 2");
+        assert_eq!(local.to_map(), remote.to_map());
+        assert!(local.diff(&remote).is_empty());
+    }
 }
@@ -0,0 +1,483 @@
+//! HAR 1.2 import and export of request/response exchanges
+//!
+//! Works on plain `serde_json::Value`s in the shape browsers and performance
+//! tools use, rather than a full typed HAR model.
+
+use base64::Engine;
+use serde_json::{Value, json};
+
+use crate::collection::{Collection, Folder};
+use crate::request::{FormField, Header, Param, Request, RequestBody, Url};
+use crate::response::{ContentCategory, Response, ResponseBody, ResponseHeader};
+use crate::{Timestamp, to_datetime};
+
+const HAR_VERSION: &str = "1.2";
+const HTTP_VERSION: &str = "HTTP/1.1";
+
+impl Response {
+    /// Build a HAR `entry` for this response and the request that produced it
+    ///
+    /// The whole duration is reported as `wait`, since sends don't record
+    /// finer-grained timings. Binary bodies are base64-encoded.
+    pub fn to_har_entry(&self, request: &Request, started_at: Timestamp) -> Value {
+        json!({
+            "startedDateTime": iso_timestamp(started_at),
+            "time": self.duration_ms,
+            "request": har_request(request),
+            "response": {
+                "status": self.status_code,
+                "statusText": self.status_text,
+                "httpVersion": HTTP_VERSION,
+                "cookies": self.cookies.iter().map(|c| json!({
+                    "name": c.name,
+                    "value": c.value,
+                    "path": c.path,
+                    "domain": c.domain,
+                    "expires": c.expires.map(iso_timestamp),
+                    "httpOnly": c.http_only,
+                    "secure": c.secure,
+                })).collect::<Vec<_>>(),
+                "headers": self.headers.iter().map(har_response_header).collect::<Vec<_>>(),
+                "content": self.har_content(),
+                "redirectURL": self.get_header("location").cloned().unwrap_or_default(),
+                "headersSize": -1,
+                "bodySize": self.size,
+            },
+            "cache": {},
+            "timings": {
+                "send": 0,
+                "wait": self.duration_ms,
+                "receive": 0,
+            },
+        })
+    }
+
+    fn har_content(&self) -> Value {
+        let mime_type = self.content_type().unwrap_or_default();
+        let binary = self.content_type_category() == ContentCategory::Binary;
+
+        let mut content = json!({
+            "size": self.body.len(),
+            "mimeType": mime_type,
+        });
+        match &self.body {
+            ResponseBody::Empty => {}
+            // Too large to inline; `text` is optional in HAR
+            ResponseBody::Streamed { .. } => content["comment"] = json!("Body streamed to disk, not included"),
+            ResponseBody::Binary(data) => {
+                content["text"] = json!(base64::engine::general_purpose::STANDARD.encode(data));
+                content["encoding"] = json!("base64");
+            }
+            ResponseBody::Text(text) if binary => {
+                content["text"] = json!(base64::engine::general_purpose::STANDARD.encode(text));
+                content["encoding"] = json!("base64");
+            }
+            ResponseBody::Text(text) => content["text"] = json!(text),
+            ResponseBody::Json(value) => content["text"] = json!(value.to_string()),
+        }
+        content
+    }
+}
+
+impl Collection {
+    /// Wrap a session of sends in a HAR `log`
+    ///
+    /// Each send's start time is taken as its receive time minus its duration.
+    /// With `redact` set, requests are written from [`Request::redacted`] so
+    /// the file can be shared.
+    pub fn to_har(&self, sends: &[(Request, Response)], redact: bool) -> Value {
+        let entries: Vec<Value> = sends
+            .iter()
+            .map(|(request, response)| {
+                let started_at = response.received_at - response.duration_ms as Timestamp;
+                if redact {
+                    response.to_har_entry(&request.redacted(&[]), started_at)
+                } else {
+                    response.to_har_entry(request, started_at)
+                }
+            })
+            .collect();
+
+        json!({
+            "log": {
+                "version": HAR_VERSION,
+                "creator": {
+                    "name": "postboy",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+                "comment": self.name,
+                "entries": entries,
+            }
+        })
+    }
+}
+
+impl Request {
+    /// Build a request from a HAR `entry`
+    ///
+    /// Reads the method, URL, headers, query string and `postData`. HTTP/2
+    /// pseudo-headers are dropped. A query in the URL is moved, decoded, into
+    /// `query_params` so it isn't sent twice; `queryString` is only used when
+    /// the URL has none. `data:` URLs and WebSocket entries can't be replayed
+    /// and are rejected.
+    pub fn from_har_entry(entry: &Value) -> Result<Request, String> {
+        let har = entry.get("request").ok_or("HAR entry has no request")?;
+        let url = har.get("url").and_then(Value::as_str).ok_or("HAR request has no url")?;
+        if !is_replayable(entry) {
+            return Err(format!("HAR entry for {} can't be replayed", url));
+        }
+
+        let method = har
+            .get("method")
+            .and_then(Value::as_str)
+            .ok_or("HAR request has no method")?
+            .parse()?;
+        let mut full = url::Url::parse(url).map_err(|e| e.to_string())?;
+        let query: Vec<Param> = full.query_pairs().map(|(name, value)| Param::new(name.into(), value.into())).collect();
+        full.set_query(None);
+        let parsed = Url::parse(full.to_string())?;
+        let name = format!("{} {}", method, parsed.path.as_deref().unwrap_or("/"));
+
+        let mut request = Request::new(name, method, parsed.raw.clone());
+        request.url = parsed;
+        request.headers = name_value_pairs(har.get("headers"))
+            .filter(|(name, _)| !name.starts_with(':'))
+            .map(|(name, value)| Header::new(name, value))
+            .collect();
+        request.query_params = if query.is_empty() {
+            name_value_pairs(har.get("queryString"))
+                .map(|(name, value)| Param::new(name, value))
+                .collect()
+        } else {
+            query
+        };
+        if let Some(post_data) = har.get("postData") {
+            request.body = har_post_data(post_data);
+        }
+
+        Ok(request)
+    }
+}
+
+impl Collection {
+    /// Build a collection from a HAR `log`
+    ///
+    /// Entries are grouped into one folder per page, named after the page
+    /// title; entries without a page go at the root. Entries that can't be
+    /// replayed or are malformed are skipped; use
+    /// [`Collection::from_har_with_warnings`] to learn which.
+    pub fn from_har(har: &Value) -> Result<(Collection, Vec<Request>), String> {
+        Self::from_har_with_warnings(har).map(|(collection, requests, _)| (collection, requests))
+    }
+
+    /// [`Collection::from_har`], also returning one warning per malformed
+    /// entry that was skipped
+    pub fn from_har_with_warnings(har: &Value) -> Result<(Collection, Vec<Request>, Vec<String>), String> {
+        let log = har.get("log").ok_or("HAR file has no log")?;
+        let entries = log.get("entries").and_then(Value::as_array).ok_or("HAR log has no entries")?;
+        let pages: Vec<(&str, String)> = log
+            .get("pages")
+            .and_then(Value::as_array)
+            .map(|pages| {
+                pages
+                    .iter()
+                    .filter_map(|page| {
+                        let id = page.get("id")?.as_str()?;
+                        let title = page.get("title").and_then(Value::as_str).unwrap_or(id);
+                        Some((id, title.to_string()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let name = pages.first().map_or("HAR import", |(_, title)| title.as_str());
+        let mut collection = Collection::new(name.to_string());
+        let mut folders: Vec<(&str, Folder)> = Vec::new();
+        let mut requests = Vec::new();
+        let mut warnings = Vec::new();
+
+        for (index, entry) in entries.iter().enumerate().filter(|(_, e)| is_replayable(e)) {
+            let mut request = match Request::from_har_entry(entry) {
+                Ok(request) => request.with_collection(collection.id),
+                Err(e) => {
+                    warnings.push(format!("Skipped entry {}: {}", index, e));
+                    continue;
+                }
+            };
+
+            let page = entry.get("pageref").and_then(Value::as_str);
+            match page.and_then(|page| pages.iter().find(|(id, _)| *id == page)) {
+                Some((page_id, title)) => {
+                    let index = match folders.iter().position(|(id, _)| id == page_id) {
+                        Some(index) => index,
+                        None => {
+                            folders.push((page_id, Folder::new(title.clone())));
+                            folders.len() - 1
+                        }
+                    };
+                    let folder = &mut folders[index].1;
+                    request.folder_id = Some(folder.id);
+                    folder.add_request(request.id);
+                }
+                None => collection.add_request(request.id),
+            }
+            requests.push(request);
+        }
+
+        for (_, folder) in folders {
+            collection.add_folder(folder);
+        }
+        Ok((collection, requests, warnings))
+    }
+}
+
+/// Whether a HAR entry is an HTTP exchange that can be sent again
+fn is_replayable(entry: &Value) -> bool {
+    let url = entry["request"]["url"].as_str().unwrap_or_default().to_ascii_lowercase();
+    let websocket = entry["_resourceType"].as_str() == Some("websocket") || entry.get("_webSocketMessages").is_some();
+    !(url.starts_with("data:") || url.starts_with("ws:") || url.starts_with("wss:") || websocket)
+}
+
+/// `[{"name", "value"}]` pairs, skipping malformed items
+fn name_value_pairs(value: Option<&Value>) -> impl Iterator<Item = (String, String)> + '_ {
+    value
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|pair| {
+            let name = pair.get("name")?.as_str()?;
+            let value = pair.get("value").and_then(Value::as_str).unwrap_or_default();
+            Some((name.to_string(), value.to_string()))
+        })
+}
+
+fn har_post_data(post_data: &Value) -> RequestBody {
+    let mime_type = post_data["mimeType"].as_str().unwrap_or_default();
+    let text = post_data["text"].as_str().unwrap_or_default();
+
+    match ContentCategory::from_content_type(mime_type) {
+        ContentCategory::Form if mime_type.to_ascii_lowercase().starts_with("application/x-www-form-urlencoded") => {
+            let mut fields: Vec<FormField> = name_value_pairs(post_data.get("params"))
+                .map(|(name, value)| FormField::new(name, value))
+                .collect();
+            if fields.is_empty() {
+                fields = url::form_urlencoded::parse(text.as_bytes())
+                    .map(|(name, value)| FormField::new(name.into(), value.into()))
+                    .collect();
+            }
+            RequestBody::url_encoded(fields)
+        }
+        ContentCategory::Json => RequestBody::json(text.to_string()),
+        _ if text.is_empty() => RequestBody::none(),
+        _ => RequestBody::raw(text.to_string()),
+    }
+}
+
+fn har_request(request: &Request) -> Value {
+    let headers: Vec<Value> = request.enabled_headers().into_iter().map(har_header).collect();
+    let query: Vec<Value> = request
+        .enabled_query_params()
+        .into_iter()
+        .map(|p| json!({ "name": p.key, "value": p.value }))
+        .collect();
+    let body = request.body.to_bytes();
+
+    let mut har = json!({
+        "method": request.method.as_str(),
+        "url": request.url.raw,
+        "httpVersion": HTTP_VERSION,
+        "cookies": [],
+        "headers": headers,
+        "queryString": query,
+        "headersSize": -1,
+        "bodySize": body.len(),
+    });
+
+    if request.has_body() {
+        let mime_type = request
+            .enabled_headers()
+            .into_iter()
+            .find(|h| h.key.eq_ignore_ascii_case("content-type"))
+            .map(|h| h.value.clone())
+            .unwrap_or_default();
+        let mut post_data = json!({
+            "mimeType": mime_type,
+            "text": String::from_utf8_lossy(&body),
+        });
+        if let RequestBody::FormData { formdata } = &request.body {
+            post_data["params"] = formdata
+                .iter()
+                .filter(|f| f.enabled)
+                .map(|f| json!({ "name": f.key, "value": f.value }))
+                .collect();
+        }
+        har["postData"] = post_data;
+    }
+
+    har
+}
+
+fn har_header(header: &Header) -> Value {
+    json!({ "name": header.key, "value": header.value })
+}
+
+fn har_response_header(header: &ResponseHeader) -> Value {
+    json!({ "name": header.name, "value": header.value })
+}
+
+/// Format a millisecond timestamp as ISO 8601 UTC, as HAR requires
+fn iso_timestamp(ms: Timestamp) -> String {
+    to_datetime(ms).to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::HttpMethod;
+
+    fn exchange() -> (Request, Response) {
+        let mut request = Request::new("Create".to_string(), HttpMethod::POST, "https://api.example.com/users?x=1".to_string())
+            .with_header("Content-Type".to_string(), "application/json".to_string())
+            .with_query_param("x".to_string(), "1".to_string());
+        request.body = RequestBody::json(r#"{"name":"Ada"}"#.to_string());
+
+        let mut response = Response::new(201, "Created".to_string());
+        response.headers.push(ResponseHeader::new("Content-Type".to_string(), "application/json".to_string()));
+        response.body = ResponseBody::Text(r#"{"id":1}"#.to_string());
+        response.duration_ms = 120;
+        response.size = 8;
+        response.received_at = 1_700_000_000_120;
+        (request, response)
+    }
+
+    #[test]
+    fn test_to_har_entry() {
+        let (request, response) = exchange();
+        let entry = response.to_har_entry(&request, 1_700_000_000_000);
+
+        assert_eq!(entry["startedDateTime"], "2023-11-14T22:13:20.000Z");
+        assert_eq!(entry["time"], 120);
+        assert_eq!(entry["timings"]["wait"], 120);
+        assert_eq!(entry["request"]["method"], "POST");
+        assert_eq!(entry["request"]["queryString"][0]["name"], "x");
+        assert_eq!(entry["request"]["postData"]["text"], r#"{"name":"Ada"}"#);
+        assert_eq!(entry["response"]["status"], 201);
+        assert_eq!(entry["response"]["headers"][0]["name"], "Content-Type");
+        assert_eq!(entry["response"]["content"]["text"], r#"{"id":1}"#);
+        assert!(entry["response"]["content"].get("encoding").is_none());
+    }
+
+    #[test]
+    fn test_binary_body_is_base64() {
+        let (request, mut response) = exchange();
+        response.headers[0].value = "image/png".to_string();
+        response.body = ResponseBody::Binary(vec![0x89, b'P', b'N', b'G']);
+
+        let content = &response.to_har_entry(&request, 0)["response"]["content"];
+        assert_eq!(content["encoding"], "base64");
+        assert_eq!(content["text"], "iVBORw==");
+        assert_eq!(content["mimeType"], "image/png");
+    }
+
+    #[test]
+    fn test_streamed_body_is_not_inlined() {
+        let (request, mut response) = exchange();
+        response.body = ResponseBody::Streamed {
+            path: std::env::temp_dir().join(format!("postboy-missing-{}", crate::new_id())),
+            size: 64 * 1024 * 1024,
+            content_type: None,
+        };
+
+        let content = &response.to_har_entry(&request, 0)["response"]["content"];
+        assert!(content.get("text").is_none());
+        assert_eq!(content["size"], 64 * 1024 * 1024);
+        assert!(content["comment"].is_string());
+    }
+
+    #[test]
+    fn test_from_har_entry() {
+        let entry = json!({
+            "request": {
+                "method": "POST",
+                "url": "https://api.example.com/login?next=%2Fhome",
+                "headers": [
+                    {"name": ":authority", "value": "api.example.com"},
+                    {"name": "Content-Type", "value": "application/x-www-form-urlencoded"}
+                ],
+                "queryString": [{"name": "next", "value": "%2Fhome"}],
+                "postData": {
+                    "mimeType": "application/x-www-form-urlencoded",
+                    "text": "user=ada&pass=x&full%20name=Ada+L"
+                }
+            }
+        });
+
+        let request = Request::from_har_entry(&entry).unwrap();
+        assert_eq!(request.method, HttpMethod::POST);
+        assert_eq!(request.name, "POST /login");
+        assert_eq!(request.url.host.as_deref(), Some("api.example.com"));
+        assert_eq!(request.headers.len(), 1);
+        // The query moves out of the URL, decoded, so it's sent once
+        assert_eq!(request.url.raw, "https://api.example.com/login");
+        assert_eq!(request.query_params, vec![Param::new("next".to_string(), "/home".to_string())]);
+        let RequestBody::UrlEncoded { urlencoded } = &request.body else {
+            panic!("expected a urlencoded body");
+        };
+        assert_eq!(urlencoded[1].key, "pass");
+        assert_eq!((urlencoded[2].key.as_str(), urlencoded[2].value.as_str()), ("full name", "Ada L"));
+
+        let raw = json!({"request": {
+            "method": "PUT",
+            "url": "https://api.example.com/notes/1",
+            "postData": {"mimeType": "text/plain", "text": "hello"}
+        }});
+        assert_eq!(Request::from_har_entry(&raw).unwrap().body, RequestBody::raw("hello".to_string()));
+
+        let data_uri = json!({"request": {"method": "GET", "url": "data:image/png;base64,AAAA"}});
+        assert!(Request::from_har_entry(&data_uri).is_err());
+    }
+
+    #[test]
+    fn test_collection_from_har() {
+        let (request, response) = exchange();
+        let mut entry = response.to_har_entry(&request, 0);
+        entry["pageref"] = json!("page_1");
+        let socket = json!({
+            "pageref": "page_1",
+            "_resourceType": "websocket",
+            "request": {"method": "GET", "url": "wss://api.example.com/live"}
+        });
+        let broken = json!({"request": {"method": "GET"}});
+        let har = json!({"log": {
+            "pages": [{"id": "page_1", "title": "Dashboard"}],
+            "entries": [entry, socket, broken]
+        }});
+
+        let (collection, requests, warnings) = Collection::from_har_with_warnings(&har).unwrap();
+        assert_eq!(warnings, vec!["Skipped entry 2: HAR request has no url".to_string()]);
+        assert_eq!(Collection::from_har(&har).unwrap().1.len(), requests.len());
+        assert_eq!(collection.name, "Dashboard");
+        assert_eq!(collection.folders.len(), 1);
+        assert_eq!(collection.folders[0].name, "Dashboard");
+        assert_eq!(requests.len(), 1);
+        assert_eq!(collection.folders[0].requests, vec![requests[0].id]);
+        assert_eq!(requests[0].folder_id, Some(collection.folders[0].id));
+        assert_eq!(requests[0].body, RequestBody::json(r#"{"name":"Ada"}"#.to_string()));
+    }
+
+    #[test]
+    fn test_collection_to_har() {
+        let collection = Collection::new("Session".to_string());
+        let har = collection.to_har(&[exchange(), exchange()], false);
+
+        assert_eq!(har["log"]["version"], "1.2");
+        assert_eq!(har["log"]["entries"].as_array().unwrap().len(), 2);
+        assert_eq!(har["log"]["entries"][0]["startedDateTime"], "2023-11-14T22:13:20.000Z");
+
+        let (mut request, response) = exchange();
+        request.headers.push(Header::new("Authorization".to_string(), "Bearer s3cret".to_string()));
+        let send = [(request, response)];
+        assert!(collection.to_har(&send, false).to_string().contains("s3cret"));
+        assert!(!collection.to_har(&send, true).to_string().contains("s3cret"));
+    }
+}
@@ -0,0 +1,764 @@
+//! Insomnia v4 export import and export
+//!
+//! An Insomnia export is a flat list of `resources` linked by `parentId`: a
+//! `workspace`, `request_group`s (folders), `request`s and `environment`s.
+//! Insomnia templates reference variables as `{{ _.name }}` where Postboy
+//! uses `{{name}}`; templates are converted in both directions.
+
+use std::collections::HashSet;
+
+use serde_json::{Map, Value, json};
+
+use crate::collection::{Collection, Folder, Variable, VariableType};
+use crate::environment::{self, Environment};
+use crate::request::{
+    ApiKeyLocation, AuthConfig, FileField, FormField, Header, HttpMethod, OAuth2Config, Param, Request, RequestBody,
+};
+use crate::response::BodyLanguage;
+use crate::{Id, now, to_datetime};
+
+const EXPORT_FORMAT: u64 = 4;
+const EXPORT_SOURCE: &str = "postboy";
+const BASE_ENVIRONMENT_NAME: &str = "Base Environment";
+
+impl Collection {
+    /// Build a collection and its requests from an Insomnia v4 export
+    ///
+    /// The first workspace names the collection and its base environment
+    /// becomes the collection variables. Each sub-environment of the base
+    /// becomes an [`Environment`] holding the base values overlaid with its
+    /// own. Folders and requests keep their nesting and `metaSortKey` order.
+    /// Auth types with no Postboy equivalent (NTLM, netrc, ASAP, API keys
+    /// sent as cookies) import as `Noauth`.
+    pub fn from_insomnia(export: &Value) -> Result<(Collection, Vec<Request>, Vec<Environment>), String> {
+        if export.get("_type").and_then(Value::as_str) != Some("export") {
+            return Err("Not an Insomnia export".to_string());
+        }
+        match export.get("__export_format").and_then(Value::as_u64) {
+            Some(EXPORT_FORMAT) => {}
+            Some(format) => return Err(format!("Unsupported Insomnia export format {}", format)),
+            None => return Err("Insomnia export has no format version".to_string()),
+        }
+        let resources = export
+            .get("resources")
+            .and_then(Value::as_array)
+            .ok_or("Insomnia export has no resources")?;
+
+        let workspace = resources.iter().find(|r| resource_type(r) == Some("workspace"));
+        let workspace_id = workspace.and_then(|w| w.get("_id")).and_then(Value::as_str);
+        let name = workspace
+            .and_then(|w| w.get("name"))
+            .and_then(Value::as_str)
+            .unwrap_or("Insomnia import");
+        let mut collection = Collection::new(name.to_string());
+        collection.description = workspace.and_then(|w| text_field(w, "description"));
+
+        let environments: Vec<&Value> = resources.iter().filter(|r| resource_type(r) == Some("environment")).collect();
+        let base_environment = environments
+            .iter()
+            .find(|env| workspace_id.is_some() && env.get("parentId").and_then(Value::as_str) == workspace_id)
+            .or(environments.first());
+        let base_data = base_environment.and_then(|env| env.get("data")).and_then(Value::as_object);
+        if let Some(data) = base_data {
+            collection.variables = data
+                .iter()
+                .map(|(key, value)| match value {
+                    Value::String(value) => Variable::new(key.clone(), from_template(value)),
+                    other => Variable::json(key.clone(), other.to_string()),
+                })
+                .collect();
+        }
+
+        let base_id = base_environment.and_then(|env| env.get("_id")).and_then(Value::as_str);
+        let environments: Vec<Environment> = environments
+            .iter()
+            .filter(|env| base_id.is_some() && env.get("parentId").and_then(Value::as_str) == base_id)
+            .map(|env| {
+                let mut data = base_data.cloned().unwrap_or_default();
+                if let Some(overrides) = env.get("data").and_then(Value::as_object) {
+                    data.extend(overrides.clone());
+                }
+                let name = env.get("name").and_then(Value::as_str).unwrap_or("Insomnia environment");
+                let values = data
+                    .iter()
+                    .map(|(key, value)| {
+                        let value = match value {
+                            Value::String(value) => from_template(value),
+                            other => other.to_string(),
+                        };
+                        environment::Variable::new(key.clone(), value)
+                    })
+                    .collect();
+                Environment::new(name.to_string()).with_values(values)
+            })
+            .collect();
+
+        let group_ids: HashSet<&str> = resources
+            .iter()
+            .filter(|r| resource_type(r) == Some("request_group"))
+            .filter_map(|r| r.get("_id").and_then(Value::as_str))
+            .collect();
+        let mut requests = Vec::new();
+        for resource in children(resources, None, &group_ids) {
+            if resource_type(resource) == Some("request_group") {
+                let folder = folder_from_insomnia(resource, resources, &group_ids, collection.id, &mut requests)?;
+                collection.add_folder(folder);
+            } else {
+                let request = request_from_insomnia(resource)?.with_collection(collection.id);
+                collection.add_request(request.id);
+                requests.push(request);
+            }
+        }
+
+        Ok((collection, requests, environments))
+    }
+
+    /// Export the collection as an Insomnia v4 export
+    ///
+    /// Enabled collection variables go in the workspace's base environment.
+    /// Request ids with no match in `requests` are skipped.
+    pub fn to_insomnia(&self, requests: &[Request]) -> Value {
+        let workspace_id = insomnia_id("wrk", self.id);
+        let data: Map<String, Value> = self
+            .variables
+            .iter()
+            .filter(|v| v.enabled)
+            .map(|v| {
+                let value = match v.variable_type {
+                    VariableType::Json => {
+                        serde_json::from_str(&v.value).unwrap_or_else(|_| Value::String(to_template(&v.value)))
+                    }
+                    _ => Value::String(to_template(&v.value)),
+                };
+                (v.key.clone(), value)
+            })
+            .collect();
+
+        let mut resources = vec![
+            json!({
+                "_id": workspace_id,
+                "_type": "workspace",
+                "parentId": null,
+                "name": self.name,
+                "description": self.description.as_deref().unwrap_or_default(),
+                "scope": "collection",
+            }),
+            json!({
+                "_id": insomnia_id("env", self.id),
+                "_type": "environment",
+                "parentId": workspace_id,
+                "name": BASE_ENVIRONMENT_NAME,
+                "data": data,
+            }),
+        ];
+        push_resources(&mut resources, &workspace_id, &self.requests, &self.folders, requests);
+
+        json!({
+            "_type": "export",
+            "__export_format": EXPORT_FORMAT,
+            "__export_date": to_datetime(now()).to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            "__export_source": EXPORT_SOURCE,
+            "resources": resources,
+        })
+    }
+}
+
+fn resource_type(resource: &Value) -> Option<&str> {
+    resource.get("_type").and_then(Value::as_str)
+}
+
+/// A non-empty string field with templates converted
+fn text_field(resource: &Value, name: &str) -> Option<String> {
+    resource
+        .get(name)
+        .and_then(Value::as_str)
+        .filter(|s| !s.is_empty())
+        .map(from_template)
+}
+
+/// Folders and requests under `parent` (the root when `None`, meaning any
+/// parent that isn't a folder), in `metaSortKey` order
+fn children<'a>(resources: &'a [Value], parent: Option<&str>, group_ids: &HashSet<&str>) -> Vec<&'a Value> {
+    let mut children: Vec<&Value> = resources
+        .iter()
+        .filter(|r| matches!(resource_type(r), Some("request_group" | "request")))
+        .filter(|r| {
+            let parent_id = r.get("parentId").and_then(Value::as_str);
+            match parent {
+                Some(parent) => parent_id == Some(parent),
+                None => !parent_id.is_some_and(|id| group_ids.contains(id)),
+            }
+        })
+        .collect();
+    let sort_key = |r: &Value| r.get("metaSortKey").and_then(Value::as_f64).unwrap_or(0.0);
+    children.sort_by(|a, b| sort_key(a).total_cmp(&sort_key(b)));
+    children
+}
+
+fn folder_from_insomnia(
+    group: &Value,
+    resources: &[Value],
+    group_ids: &HashSet<&str>,
+    collection_id: Id,
+    requests: &mut Vec<Request>,
+) -> Result<Folder, String> {
+    let name = group.get("name").and_then(Value::as_str).unwrap_or("Folder");
+    let mut folder = Folder::new(name.to_string());
+    folder.description = text_field(group, "description");
+    folder.auth = group.get("authentication").and_then(auth_from_insomnia);
+
+    let group_id = group.get("_id").and_then(Value::as_str).unwrap_or_default();
+    for resource in children(resources, Some(group_id), group_ids) {
+        if resource_type(resource) == Some("request_group") {
+            let mut child = folder_from_insomnia(resource, resources, group_ids, collection_id, requests)?;
+            child.parent_id = Some(folder.id);
+            folder.add_child(child);
+        } else {
+            let mut request = request_from_insomnia(resource)?.with_collection(collection_id);
+            request.folder_id = Some(folder.id);
+            folder.add_request(request.id);
+            requests.push(request);
+        }
+    }
+
+    Ok(folder)
+}
+
+fn request_from_insomnia(resource: &Value) -> Result<Request, String> {
+    let name = resource.get("name").and_then(Value::as_str).unwrap_or("Request");
+    let method = match resource.get("method").and_then(Value::as_str) {
+        Some(method) => method.parse()?,
+        None => HttpMethod::GET,
+    };
+    let url = resource.get("url").and_then(Value::as_str).unwrap_or_default();
+
+    let mut request = Request::new(name.to_string(), method, from_template(url));
+    request.description = text_field(resource, "description");
+    request.headers = name_value_pairs(resource.get("headers"))
+        .map(|(name, value, enabled)| Header { key: name, value, enabled })
+        .collect();
+    request.query_params = name_value_pairs(resource.get("parameters"))
+        .map(|(name, value, enabled)| Param { enabled, ..Param::new(name, value) })
+        .collect();
+    if let Some(body) = resource.get("body") {
+        request.body = body_from_insomnia(body);
+    }
+    request.auth = resource.get("authentication").and_then(auth_from_insomnia);
+
+    Ok(request)
+}
+
+/// `[{"name", "value", "disabled"}]` as (name, value, enabled), skipping
+/// malformed items
+fn name_value_pairs(value: Option<&Value>) -> impl Iterator<Item = (String, String, bool)> + '_ {
+    value
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|pair| {
+            let name = pair.get("name")?.as_str()?;
+            let value = pair.get("value").and_then(Value::as_str).unwrap_or_default();
+            let disabled = pair.get("disabled").and_then(Value::as_bool).unwrap_or(false);
+            Some((from_template(name), from_template(value), !disabled))
+        })
+}
+
+fn body_from_insomnia(body: &Value) -> RequestBody {
+    let mime_type = body.get("mimeType").and_then(Value::as_str).unwrap_or_default();
+    let essence = mime_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    let text = body.get("text").and_then(Value::as_str).map(from_template).unwrap_or_default();
+
+    match essence.as_str() {
+        "multipart/form-data" => RequestBody::form_data(form_fields(body)),
+        "application/x-www-form-urlencoded" => RequestBody::url_encoded(form_fields(body)),
+        "application/octet-stream" => RequestBody::binary(),
+        "" if text.is_empty() => RequestBody::none(),
+        _ => match BodyLanguage::from_content_type(&essence) {
+            Some(BodyLanguage::Json) => RequestBody::json(text),
+            Some(language) => RequestBody::raw_with_language(text, language.as_str().to_string()),
+            None => RequestBody::raw(text),
+        },
+    }
+}
+
+fn form_fields(body: &Value) -> Vec<FormField> {
+    body.get("params")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|param| {
+            let (name, value, enabled) = name_value_pairs(Some(&json!([param]))).next()?;
+            let file = (param.get("type").and_then(Value::as_str) == Some("file")).then(|| {
+                let path = param.get("fileName").and_then(Value::as_str).unwrap_or_default();
+                FileField {
+                    name: path.rsplit(['/', '\\']).next().unwrap_or_default().to_string(),
+                    content_type: None,
+                    path: Some(path.to_string()).filter(|p| !p.is_empty()),
+                }
+            });
+            Some(FormField { key: name, value, enabled, file })
+        })
+        .collect()
+}
+
+/// `None` when the request inherits (an empty object or `inherit`)
+fn auth_from_insomnia(auth: &Value) -> Option<AuthConfig> {
+    let auth_type = auth.get("type").and_then(Value::as_str)?;
+    if auth_type == "inherit" {
+        return None;
+    }
+    if auth.get("disabled").and_then(Value::as_bool) == Some(true) {
+        return Some(AuthConfig::Noauth);
+    }
+
+    let field = |name: &str| auth.get(name).and_then(Value::as_str).map(from_template).unwrap_or_default();
+    let optional = |name: &str| text_field(auth, name);
+
+    let config = match auth_type {
+        "basic" => AuthConfig::Basic {
+            username: field("username"),
+            password: field("password"),
+        },
+        "digest" => AuthConfig::Digest {
+            username: field("username"),
+            password: field("password"),
+        },
+        "bearer" => AuthConfig::Bearer { token: field("token") },
+        "apikey" => {
+            let add_to = match auth.get("addTo").and_then(Value::as_str) {
+                None | Some("header") => ApiKeyLocation::Header,
+                Some("queryParams") => ApiKeyLocation::Query,
+                Some(_) => return Some(AuthConfig::Noauth),
+            };
+            AuthConfig::ApiKey {
+                key: field("key"),
+                value: field("value"),
+                add_to,
+            }
+        }
+        "oauth1" => AuthConfig::OAuth1 {
+            consumer_key: field("consumerKey"),
+            consumer_secret: field("consumerSecret"),
+            token: field("tokenKey"),
+            token_secret: field("tokenSecret"),
+            signature_method: field("signatureMethod"),
+            timestamp: optional("timestamp"),
+            nonce: optional("nonce"),
+            version: optional("version"),
+            realm: optional("realm"),
+        },
+        "oauth2" => AuthConfig::OAuth2 {
+            config: OAuth2Config {
+                client_id: field("clientId"),
+                client_secret: field("clientSecret"),
+                scope: field("scope"),
+                redirect_url: field("redirectUrl"),
+                auth_url: field("authorizationUrl"),
+                access_token_url: field("accessTokenUrl"),
+                grant_type: field("grantType"),
+                username: optional("username"),
+                password: optional("password"),
+            },
+        },
+        "iam" => AuthConfig::Awsv4 {
+            access_key: field("accessKeyId"),
+            secret_key: field("secretAccessKey"),
+            region: field("region"),
+            service: field("service"),
+        },
+        "hawk" => AuthConfig::Hawk {
+            auth_id: field("id"),
+            auth_key: field("key"),
+            algorithm: field("algorithm"),
+            user: String::new(),
+            nonce: None,
+            ext: optional("ext"),
+            mac: None,
+            timestamp: None,
+        },
+        _ => AuthConfig::Noauth,
+    };
+    Some(config)
+}
+
+/// Requests then folders under `parent_id`, numbered in order
+fn push_resources(resources: &mut Vec<Value>, parent_id: &str, request_ids: &[Id], folders: &[Folder], requests: &[Request]) {
+    let mut sort_key = 0;
+    for request in request_ids.iter().filter_map(|id| requests.iter().find(|r| r.id == *id)) {
+        resources.push(request_to_insomnia(request, parent_id, sort_key));
+        sort_key += 1;
+    }
+    for folder in folders {
+        let folder_id = insomnia_id("fld", folder.id);
+        resources.push(json!({
+            "_id": folder_id,
+            "_type": "request_group",
+            "parentId": parent_id,
+            "metaSortKey": sort_key,
+            "name": folder.name,
+            "description": folder.description.as_deref().unwrap_or_default(),
+            "environment": {},
+            "authentication": auth_to_insomnia(folder.auth.as_ref()),
+        }));
+        sort_key += 1;
+        push_resources(resources, &folder_id, &folder.requests, &folder.children, requests);
+    }
+}
+
+fn request_to_insomnia(request: &Request, parent_id: &str, sort_key: usize) -> Value {
+    let pairs = |pairs: Vec<(&String, &String, bool)>| -> Vec<Value> {
+        pairs
+            .into_iter()
+            .map(|(name, value, enabled)| {
+                json!({ "name": to_template(name), "value": to_template(value), "disabled": !enabled })
+            })
+            .collect()
+    };
+
+    json!({
+        "_id": insomnia_id("req", request.id),
+        "_type": "request",
+        "parentId": parent_id,
+        "metaSortKey": sort_key,
+        "name": request.name,
+        "description": request.description.as_deref().unwrap_or_default(),
+        "method": request.method.as_str(),
+        "url": to_template(&request.url.raw),
+        "body": body_to_insomnia(&request.body),
+        "headers": pairs(request.headers.iter().map(|h| (&h.key, &h.value, h.enabled)).collect()),
+        "parameters": pairs(request.query_params.iter().map(|p| (&p.key, &p.value, p.enabled)).collect()),
+        "authentication": auth_to_insomnia(request.auth.as_ref()),
+    })
+}
+
+fn body_to_insomnia(body: &RequestBody) -> Value {
+    let params = |fields: &[FormField]| -> Vec<Value> {
+        fields
+            .iter()
+            .map(|f| {
+                let mut param = json!({
+                    "name": to_template(&f.key),
+                    "value": to_template(&f.value),
+                    "disabled": !f.enabled,
+                });
+                if let Some(file) = &f.file {
+                    param["type"] = json!("file");
+                    param["fileName"] = json!(file.path.as_deref().unwrap_or(&file.name));
+                }
+                param
+            })
+            .collect()
+    };
+
+    match body {
+        RequestBody::None => json!({}),
+        RequestBody::Json { raw } => json!({ "mimeType": "application/json", "text": to_template(raw) }),
+        RequestBody::Raw { raw, language, .. } => {
+            let mime_type = match language.as_deref().and_then(BodyLanguage::from_hint) {
+                Some(BodyLanguage::Json) => "application/json",
+                Some(BodyLanguage::Xml) => "application/xml",
+                Some(BodyLanguage::Html) => "text/html",
+                Some(BodyLanguage::JavaScript) => "application/javascript",
+                Some(BodyLanguage::Text) | None => "text/plain",
+            };
+            json!({ "mimeType": mime_type, "text": to_template(raw) })
+        }
+        RequestBody::FormData { formdata } => json!({ "mimeType": "multipart/form-data", "params": params(formdata) }),
+        RequestBody::UrlEncoded { urlencoded } => {
+            json!({ "mimeType": "application/x-www-form-urlencoded", "params": params(urlencoded) })
+        }
+        RequestBody::Binary => json!({ "mimeType": "application/octet-stream" }),
+    }
+}
+
+/// `{}` (inherit) for `None`; schemes Insomnia lacks export as `none`
+fn auth_to_insomnia(auth: Option<&AuthConfig>) -> Value {
+    let Some(auth) = auth else {
+        return json!({});
+    };
+    let t = |text: &str| to_template(text);
+    let optional = |text: &Option<String>| text.as_deref().map(t).unwrap_or_default();
+
+    match auth {
+        AuthConfig::Basic { username, password } => {
+            json!({ "type": "basic", "username": t(username), "password": t(password) })
+        }
+        AuthConfig::Digest { username, password } => {
+            json!({ "type": "digest", "username": t(username), "password": t(password) })
+        }
+        AuthConfig::Bearer { token } => json!({ "type": "bearer", "token": t(token), "prefix": "" }),
+        AuthConfig::ApiKey { key, value, add_to } => json!({
+            "type": "apikey",
+            "key": t(key),
+            "value": t(value),
+            "addTo": match add_to {
+                ApiKeyLocation::Header => "header",
+                ApiKeyLocation::Query => "queryParams",
+            },
+        }),
+        AuthConfig::OAuth1 {
+            consumer_key,
+            consumer_secret,
+            token,
+            token_secret,
+            signature_method,
+            timestamp,
+            nonce,
+            version,
+            realm,
+        } => json!({
+            "type": "oauth1",
+            "consumerKey": t(consumer_key),
+            "consumerSecret": t(consumer_secret),
+            "tokenKey": t(token),
+            "tokenSecret": t(token_secret),
+            "signatureMethod": signature_method,
+            "timestamp": optional(timestamp),
+            "nonce": optional(nonce),
+            "version": optional(version),
+            "realm": optional(realm),
+        }),
+        AuthConfig::OAuth2 { config } => json!({
+            "type": "oauth2",
+            "grantType": config.grant_type,
+            "clientId": t(&config.client_id),
+            "clientSecret": t(&config.client_secret),
+            "scope": t(&config.scope),
+            "redirectUrl": t(&config.redirect_url),
+            "authorizationUrl": t(&config.auth_url),
+            "accessTokenUrl": t(&config.access_token_url),
+            "username": optional(&config.username),
+            "password": optional(&config.password),
+        }),
+        AuthConfig::Awsv4 { access_key, secret_key, region, service } => json!({
+            "type": "iam",
+            "accessKeyId": t(access_key),
+            "secretAccessKey": t(secret_key),
+            "region": t(region),
+            "service": t(service),
+        }),
+        AuthConfig::Hawk { auth_id, auth_key, algorithm, ext, .. } => json!({
+            "type": "hawk",
+            "id": t(auth_id),
+            "key": t(auth_key),
+            "algorithm": algorithm,
+            "ext": optional(ext),
+        }),
+        AuthConfig::Noauth | AuthConfig::BearerCustom { .. } => json!({ "type": "none" }),
+    }
+}
+
+fn insomnia_id(prefix: &str, id: Id) -> String {
+    format!("{}_{}", prefix, id.simple())
+}
+
+/// Rewrite the inside of every `{{ ... }}` that `rewrite` accepts
+fn map_template_variables(text: &str, rewrite: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let inner = &rest[start + 2..start + 2 + len];
+        out.push_str(&rest[..start]);
+        out.push_str("{{");
+        out.push_str(&rewrite(inner).unwrap_or_else(|| inner.to_string()));
+        out.push_str("}}");
+        rest = &rest[start + 2 + len + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `{{ _.name }}` or `{{ _['name'] }}` to `{{name}}`
+fn from_template(text: &str) -> String {
+    map_template_variables(text, |inner| {
+        let inner = inner.trim();
+        if let Some(name) = inner.strip_prefix("_.") {
+            return Some(name.to_string());
+        }
+        let quoted = inner.strip_prefix("_[")?.strip_suffix(']')?;
+        let name = quoted
+            .strip_prefix('\'')
+            .and_then(|q| q.strip_suffix('\''))
+            .or_else(|| quoted.strip_prefix('"').and_then(|q| q.strip_suffix('"')))?;
+        Some(name.to_string())
+    })
+}
+
+/// `{{name}}` to `{{ _.name }}`, or `{{ _['name'] }}` for names that aren't
+/// identifiers; dynamic `{{$...}}` variables are left alone
+fn to_template(text: &str) -> String {
+    map_template_variables(text, |inner| {
+        let name = inner.trim();
+        if name.is_empty() || name.starts_with('$') || name.starts_with("_.") || name.starts_with("_[") {
+            return None;
+        }
+        if name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            Some(format!(" _.{} ", name))
+        } else {
+            Some(format!(" _['{}'] ", name.replace('\'', "\\'")))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_insomnia() {
+        let export = json!({
+            "_type": "export",
+            "__export_format": 4,
+            "resources": [
+                { "_id": "wrk_1", "_type": "workspace", "parentId": null, "name": "Shop", "description": "" },
+                { "_id": "env_1", "_type": "environment", "parentId": "wrk_1", "name": "Base Environment",
+                  "data": { "base_url": "https://shop.example.com", "retries": 3 } },
+                { "_id": "env_2", "_type": "environment", "parentId": "env_1", "name": "Staging",
+                  "data": { "base_url": "https://staging.{{ _.domain }}", "debug": true } },
+                { "_id": "req_2", "_type": "request", "parentId": "fld_1", "metaSortKey": -1,
+                  "name": "Login", "method": "POST", "url": "{{ _.base_url }}/login",
+                  "body": { "mimeType": "application/x-www-form-urlencoded",
+                            "params": [{ "name": "user", "value": "{{ _['user-name'] }}" }] },
+                  "headers": [], "parameters": [],
+                  "authentication": { "type": "ntlm", "username": "a", "password": "b" } },
+                { "_id": "fld_1", "_type": "request_group", "parentId": "wrk_1", "metaSortKey": -2, "name": "Auth" },
+                { "_id": "req_1", "_type": "request", "parentId": "fld_1", "metaSortKey": -5,
+                  "name": "Status", "method": "GET", "url": "{{ _.base_url }}/status",
+                  "body": {},
+                  "headers": [{ "name": "Accept", "value": "application/json" },
+                              { "name": "X-Debug", "value": "1", "disabled": true }],
+                  "parameters": [{ "name": "verbose", "value": "true" }],
+                  "authentication": { "type": "apikey", "key": "X-Key", "value": "{{ _.key }}", "addTo": "queryParams" } },
+                { "_id": "req_3", "_type": "request", "parentId": "wrk_1", "metaSortKey": -1,
+                  "name": "Health", "method": "GET", "url": "https://shop.example.com/health", "authentication": {} }
+            ]
+        });
+
+        let (collection, requests, environments) = Collection::from_insomnia(&export).unwrap();
+        assert_eq!(collection.name, "Shop");
+        assert_eq!(collection.description, None);
+        assert_eq!(collection.get_variable("base_url").unwrap().value, "https://shop.example.com");
+        assert_eq!(collection.variables.iter().find(|v| v.key == "retries").unwrap().variable_type, VariableType::Json);
+
+        assert_eq!(environments.len(), 1);
+        assert_eq!(environments[0].name, "Staging");
+        let values: Vec<(&str, &str)> = environments[0].values.iter().map(|v| (v.key.as_str(), v.value.as_str())).collect();
+        assert_eq!(
+            values,
+            vec![("base_url", "https://staging.{{domain}}"), ("debug", "true"), ("retries", "3")]
+        );
+
+        assert_eq!(collection.folders.len(), 1);
+        let folder = &collection.folders[0];
+        assert_eq!(folder.name, "Auth");
+        let names: Vec<&str> = folder
+            .requests
+            .iter()
+            .map(|id| requests.iter().find(|r| r.id == *id).unwrap().name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Status", "Login"]);
+        assert_eq!(collection.requests.len(), 1);
+
+        let status = requests.iter().find(|r| r.name == "Status").unwrap();
+        assert_eq!(status.url.raw, "{{base_url}}/status");
+        assert_eq!(status.folder_id, Some(folder.id));
+        assert!(!status.headers[1].enabled);
+        assert_eq!(status.query_params[0].key, "verbose");
+        assert_eq!(status.body, RequestBody::None);
+        assert_eq!(
+            status.auth,
+            Some(AuthConfig::ApiKey {
+                key: "X-Key".to_string(),
+                value: "{{key}}".to_string(),
+                add_to: ApiKeyLocation::Query,
+            })
+        );
+
+        let login = requests.iter().find(|r| r.name == "Login").unwrap();
+        assert_eq!(login.auth, Some(AuthConfig::Noauth));
+        assert_eq!(
+            login.body,
+            RequestBody::url_encoded(vec![FormField::new("user".to_string(), "{{user-name}}".to_string())])
+        );
+
+        let health = requests.iter().find(|r| r.name == "Health").unwrap();
+        assert_eq!(health.auth, None);
+
+        assert!(Collection::from_insomnia(&json!({ "_type": "export", "__export_format": 3 })).is_err());
+        assert!(Collection::from_insomnia(&json!({ "info": {} })).is_err());
+    }
+
+    #[test]
+    fn test_insomnia_round_trip() {
+        let mut collection = Collection::new("API".to_string())
+            .with_description("Internal API".to_string())
+            .with_variable("base_url".to_string(), "https://api.example.com".to_string());
+        collection.variables.push(Variable::json("limits".to_string(), "{\"max\":10}".to_string()));
+
+        let mut create = Request::new("Create".to_string(), HttpMethod::POST, "{{base_url}}/items".to_string())
+            .with_header("Content-Type".to_string(), "application/json".to_string());
+        create.body = RequestBody::json("{\"name\": \"{{name}}\"}".to_string());
+        create.auth = Some(AuthConfig::Bearer { token: "{{token}}".to_string() });
+        create.query_params.push(Param::new("dry_run".to_string(), "1".to_string()));
+        let mut upload = Request::new("Upload".to_string(), HttpMethod::PUT, "{{base_url}}/files".to_string());
+        let mut file = FormField::new("file".to_string(), String::new());
+        file.file = Some(FileField {
+            name: "a.txt".to_string(),
+            content_type: None,
+            path: Some("/tmp/a.txt".to_string()),
+        });
+        upload.body = RequestBody::form_data(vec![file]);
+        let ping = Request::new("Ping".to_string(), HttpMethod::GET, "{{base_url}}/ping".to_string());
+
+        let mut items = Folder::new("Items".to_string());
+        items.auth = Some(AuthConfig::Basic {
+            username: "admin".to_string(),
+            password: "{{password}}".to_string(),
+        });
+        items.add_request(create.id);
+        let mut files = Folder::new("Files".to_string());
+        files.add_request(upload.id);
+        items.add_child(files);
+        collection.add_folder(items);
+        collection.add_request(ping.id);
+
+        let export = collection.to_insomnia(&[create.clone(), upload.clone(), ping.clone()]);
+        assert!(export.to_string().contains("{{ _.base_url }}/items"));
+
+        let (imported, requests, environments) = Collection::from_insomnia(&export).unwrap();
+        assert!(environments.is_empty());
+        assert_eq!(imported.name, collection.name);
+        assert_eq!(imported.description, collection.description);
+        let variables: Vec<(&str, &str)> = imported.variables.iter().map(|v| (v.key.as_str(), v.value.as_str())).collect();
+        assert_eq!(variables, vec![("base_url", "https://api.example.com"), ("limits", "{\"max\":10}")]);
+
+        let find = |name: &str| requests.iter().find(|r| r.name == name).unwrap();
+        assert_eq!(imported.requests, vec![find("Ping").id]);
+        let items = &imported.folders[0];
+        assert_eq!(items.auth, collection.folders[0].auth);
+        assert_eq!(items.requests, vec![find("Create").id]);
+        assert_eq!(items.children[0].name, "Files");
+        assert_eq!(items.children[0].parent_id, Some(items.id));
+        assert_eq!(items.children[0].requests, vec![find("Upload").id]);
+
+        for original in [&create, &upload, &ping] {
+            let imported = find(&original.name);
+            assert_eq!(imported.method, original.method);
+            assert_eq!(imported.url.raw, original.url.raw);
+            assert_eq!(imported.headers, original.headers);
+            assert_eq!(imported.query_params, original.query_params);
+            assert_eq!(imported.body, original.body);
+            assert_eq!(imported.auth, original.auth);
+        }
+    }
+
+    #[test]
+    fn test_template_conversion() {
+        assert_eq!(to_template("{{host}}/{{api-version}}/{{$guid}}"), "{{ _.host }}/{{ _['api-version'] }}/{{$guid}}");
+        assert_eq!(from_template("{{ _.host }}/{{ _['api-version'] }}/{{$guid}}"), "{{host}}/{{api-version}}/{{$guid}}");
+        assert_eq!(from_template("{% now 'iso-8601' %} {{ unclosed"), "{% now 'iso-8601' %} {{ unclosed");
+    }
+}
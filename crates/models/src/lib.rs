@@ -9,6 +9,16 @@ pub mod response;
 pub mod environment;
 pub mod user;
 pub mod sync;
+pub mod mock;
+pub mod runner;
+pub mod oauth;
+pub mod validation;
+pub mod compare;
+pub mod effective;
+pub mod websocket;
+pub mod har;
+pub mod markdown;
+pub mod insomnia;
 
 pub use collection::*;
 pub use request::*;
@@ -16,6 +26,13 @@ pub use response::*;
 pub use environment::*;
 pub use user::*;
 pub use sync::*;
+pub use mock::*;
+pub use runner::*;
+pub use oauth::*;
+pub use validation::*;
+pub use compare::*;
+pub use effective::*;
+pub use websocket::*;
 
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -55,13 +72,176 @@ pub fn now() -> Timestamp {
     Utc::now().timestamp_millis()
 }
 
+/// Convert a timestamp to a UTC date-time
+///
+/// Timestamps outside chrono's range map to the Unix epoch.
+pub fn to_datetime(ts: Timestamp) -> chrono::DateTime<chrono::Utc> {
+    chrono::DateTime::from_timestamp_millis(ts).unwrap_or_default()
+}
+
+/// Convert a date-time to a timestamp, dropping sub-millisecond precision
+pub fn from_datetime<Tz: chrono::TimeZone>(dt: chrono::DateTime<Tz>) -> Timestamp {
+    dt.timestamp_millis()
+}
+
+/// Format a timestamp in UTC with a chrono `strftime` format string
+///
+/// Fails if `fmt` has an unknown or incomplete specifier such as `%Q`.
+pub fn format_timestamp(ts: Timestamp, fmt: &str) -> Result<String> {
+    use chrono::format::{Item, StrftimeItems};
+    use std::fmt::Write;
+
+    let items: Vec<Item> = StrftimeItems::new(fmt).collect();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        return Err(Error::InvalidData(format!("format string {:?}", fmt)));
+    }
+    let mut out = String::new();
+    write!(out, "{}", to_datetime(ts).format_with_items(items.into_iter()))
+        .map_err(|_| Error::InvalidData(format!("format string {:?}", fmt)))?;
+    Ok(out)
+}
+
+/// Identifier type usable for entities on a sync backend
+///
+/// Local ids are always `Id`; remote servers may use their own scheme.
+pub trait EntityId:
+    Clone + Eq + std::hash::Hash + std::fmt::Debug + std::fmt::Display + Serialize + serde::de::DeserializeOwned
+{
+}
+
+impl EntityId for Uuid {}
+impl EntityId for String {}
+impl EntityId for i64 {}
+impl EntityId for RemoteId {}
+
+/// Id of an item on a sync backend
+///
+/// Serialized as the bare value, so remote ids stored as UUID strings before
+/// this type existed still deserialize (as `Uuid`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RemoteId {
+    Uuid(Uuid),
+    Int(i64),
+    String(String),
+}
+
+impl std::fmt::Display for RemoteId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RemoteId::Uuid(id) => write!(f, "{}", id),
+            RemoteId::Int(id) => write!(f, "{}", id),
+            RemoteId::String(id) => f.write_str(id),
+        }
+    }
+}
+
+impl From<Uuid> for RemoteId {
+    fn from(id: Uuid) -> Self {
+        RemoteId::Uuid(id)
+    }
+}
+
+impl From<i64> for RemoteId {
+    fn from(id: i64) -> Self {
+        RemoteId::Int(id)
+    }
+}
+
+impl From<String> for RemoteId {
+    fn from(id: String) -> Self {
+        RemoteId::String(id)
+    }
+}
+
+impl From<&str> for RemoteId {
+    fn from(id: &str) -> Self {
+        RemoteId::String(id.to_string())
+    }
+}
+
 /// Trait for entities that can be created and updated
 pub trait Temporal {
     fn created_at(&self) -> Timestamp;
     fn updated_at(&self) -> Timestamp;
+
+    /// Milliseconds since creation
+    fn age_ms(&self, now: Timestamp) -> i64 {
+        now - self.created_at()
+    }
+
+    /// Milliseconds since the last update
+    fn idle_ms(&self, now: Timestamp) -> i64 {
+        now - self.updated_at()
+    }
+
+    /// Whether the entity has changed since it was created
+    fn was_updated(&self) -> bool {
+        self.updated_at() > self.created_at()
+    }
 }
 
 /// Trait for entities with unique identifier
 pub trait Identifiable {
     fn id(&self) -> Id;
+
+    /// First 8 hex digits of the id, for logs and lists
+    fn short_id(&self) -> String {
+        short_id(self.id())
+    }
+}
+
+/// First 8 hex digits of an id, like a short git hash
+///
+/// Prefixes aren't unique; use the full id to look anything up.
+pub fn short_id(id: Id) -> String {
+    let mut buf = Uuid::encode_buffer();
+    id.simple().encode_lower(&mut buf)[..8].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_id() {
+        let id = Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap();
+        assert_eq!(short_id(id), "67e55044");
+        assert_eq!(short_id(Uuid::nil()), "00000000");
+
+        let request = request::Request::new("Get".to_string(), request::HttpMethod::GET, "https://example.com".to_string());
+        assert_eq!(request.short_id(), short_id(request.id));
+    }
+
+    #[test]
+    fn test_entity_ids_round_trip() {
+        fn round_trip<T: EntityId>(id: T) {
+            let json = serde_json::to_string(&id).unwrap();
+            assert_eq!(serde_json::from_str::<T>(&json).unwrap(), id);
+        }
+
+        round_trip(new_id());
+        round_trip("req_8f2a".to_string());
+        round_trip(42_i64);
+        round_trip(RemoteId::from("req_8f2a"));
+        round_trip(RemoteId::from(42));
+    }
+
+    #[test]
+    fn test_timestamp_conversions() {
+        use chrono::TimeZone;
+
+        let ts = now();
+        assert_eq!(from_datetime(to_datetime(ts)), ts);
+
+        let fixed = chrono::Utc.with_ymd_and_hms(2024, 2, 29, 13, 45, 10).unwrap();
+        assert_eq!(from_datetime(fixed), 1_709_214_310_000);
+        assert_eq!(to_datetime(1_709_214_310_000), fixed);
+        assert_eq!(format_timestamp(1_709_214_310_123, "%Y-%m-%d %H:%M:%S%.3f").unwrap(), "2024-02-29 13:45:10.123");
+        assert!(format_timestamp(0, "%Q").is_err());
+        assert!(format_timestamp(0, "%Y-%").is_err());
+
+        let offset = chrono::FixedOffset::east_opt(2 * 3600).unwrap().with_ymd_and_hms(2024, 2, 29, 15, 45, 10).unwrap();
+        assert_eq!(from_datetime(offset), 1_709_214_310_000);
+    }
 }
@@ -0,0 +1,255 @@
+//! Markdown documentation for collections
+//!
+//! Renders a collection as a single readable document for sharing with
+//! people who don't use Postboy. Output only depends on its input, so
+//! re-exporting an unchanged collection gives an identical file.
+
+use std::fmt::Write;
+
+use crate::collection::{Collection, Folder, VariableType};
+use crate::request::{REDACTED, Request, RequestBody};
+use crate::Id;
+
+/// Deepest Markdown heading level
+const MAX_HEADING_LEVEL: usize = 6;
+
+impl Collection {
+    /// Render the collection as Markdown documentation
+    ///
+    /// Variables come first as a table sorted by key, with secret values
+    /// masked. Root requests follow, then each folder as a section with its
+    /// requests and subfolders, in collection order. Requests are rendered
+    /// from [`Request::redacted`], so credentials in headers, the URL, form
+    /// fields and auth are masked too. Request ids with no match in
+    /// `requests` are skipped.
+    pub fn to_markdown(&self, requests: &[Request]) -> String {
+        let mut out = String::new();
+
+        heading(&mut out, 1, &self.name);
+        paragraph(&mut out, self.description.as_deref());
+
+        if !self.variables.is_empty() {
+            heading(&mut out, 2, "Variables");
+            let mut variables: Vec<_> = self.variables.iter().collect();
+            variables.sort_by(|a, b| a.key.cmp(&b.key));
+            let rows: Vec<[String; 2]> = variables
+                .iter()
+                .map(|v| {
+                    let value = if v.variable_type == VariableType::Secret && !v.value.is_empty() {
+                        REDACTED.to_string()
+                    } else {
+                        v.value.clone()
+                    };
+                    [v.key.clone(), value]
+                })
+                .collect();
+            table(&mut out, ["Key", "Value"], &rows);
+        }
+
+        render_requests(&mut out, 2, &self.requests, requests);
+        for folder in &self.folders {
+            render_folder(&mut out, 2, folder, requests);
+        }
+
+        out.truncate(out.trim_end().len());
+        out.push('\n');
+        out
+    }
+}
+
+fn render_folder(out: &mut String, level: usize, folder: &Folder, requests: &[Request]) {
+    heading(out, level, &folder.name);
+    paragraph(out, folder.description.as_deref());
+    render_requests(out, level + 1, &folder.requests, requests);
+    for child in &folder.children {
+        render_folder(out, level + 1, child, requests);
+    }
+}
+
+fn render_requests(out: &mut String, level: usize, ids: &[Id], requests: &[Request]) {
+    for request in ids.iter().filter_map(|id| requests.iter().find(|r| r.id == *id)) {
+        let request = request.redacted(&[]);
+        heading(out, level, &request.name);
+        let _ = writeln!(out, "`{} {}`\n", request.method, request.url.raw);
+        paragraph(out, request.description.as_deref());
+
+        let headers: Vec<[String; 2]> = request
+            .enabled_headers()
+            .iter()
+            .map(|h| [h.key.clone(), h.value.clone()])
+            .collect();
+        if !headers.is_empty() {
+            out.push_str("**Headers**\n\n");
+            table(out, ["Key", "Value"], &headers);
+        }
+
+        match &request.body {
+            RequestBody::FormData { formdata: fields } | RequestBody::UrlEncoded { urlencoded: fields } => {
+                let rows: Vec<[String; 2]> = fields
+                    .iter()
+                    .filter(|f| f.enabled)
+                    .map(|f| [f.key.clone(), f.value.clone()])
+                    .collect();
+                if !rows.is_empty() {
+                    out.push_str("**Body**\n\n");
+                    table(out, ["Key", "Value"], &rows);
+                }
+            }
+            body => {
+                if let Some(raw) = body.get_raw().filter(|raw| !raw.trim().is_empty()) {
+                    let language = body.detected_language().map_or("", |l| l.as_str());
+                    out.push_str("**Body**\n\n");
+                    code_block(out, language, raw);
+                }
+            }
+        }
+    }
+}
+
+fn heading(out: &mut String, level: usize, text: &str) {
+    let _ = writeln!(out, "{} {}\n", "#".repeat(level.min(MAX_HEADING_LEVEL)), text.trim());
+}
+
+fn paragraph(out: &mut String, text: Option<&str>) {
+    if let Some(text) = text.map(str::trim).filter(|t| !t.is_empty()) {
+        let _ = writeln!(out, "{}\n", text);
+    }
+}
+
+fn table(out: &mut String, columns: [&str; 2], rows: &[[String; 2]]) {
+    let _ = writeln!(out, "| {} | {} |", columns[0], columns[1]);
+    out.push_str("| --- | --- |\n");
+    for [key, value] in rows {
+        let _ = writeln!(out, "| {} | {} |", table_cell(key), table_cell(value));
+    }
+    out.push('\n');
+}
+
+/// Escape pipes and flatten newlines so a value stays in one cell
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+/// Fence long enough not to be closed by backticks inside `code`
+fn code_block(out: &mut String, language: &str, code: &str) {
+    let mut fence = "```".to_string();
+    while code.contains(&fence) {
+        fence.push('`');
+    }
+    let _ = writeln!(out, "{}{}\n{}\n{}\n", fence, language, code.trim_end(), fence);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collection::Variable;
+    use crate::request::HttpMethod;
+
+    #[test]
+    fn test_to_markdown() {
+        let mut collection = Collection::new("Shop API".to_string())
+            .with_description("Public shop endpoints.".to_string())
+            .with_variable("base_url".to_string(), "https://shop.example.com".to_string());
+        collection.variables.insert(0, Variable::secret("api_key".to_string(), "abc".to_string()));
+
+        let mut create = Request::new("Create order".to_string(), HttpMethod::POST, "{{base_url}}/orders".to_string())
+            .with_header("Content-Type".to_string(), "application/json".to_string())
+            .with_header("X-Note".to_string(), "a|b".to_string());
+        create.description = Some("Places an order.".to_string());
+        create.body = RequestBody::json("{\"sku\": \"A1\"}".to_string());
+        let health = Request::new("Health".to_string(), HttpMethod::GET, "{{base_url}}/health".to_string());
+
+        let mut orders = Folder::new("Orders".to_string());
+        orders.add_request(create.id);
+        collection.add_folder(orders);
+        collection.add_request(health.id);
+        collection.add_request(crate::new_id());
+
+        let markdown = collection.to_markdown(&[create.clone(), health.clone()]);
+        assert_eq!(
+            markdown,
+            "# Shop API\n\
+             \n\
+             Public shop endpoints.\n\
+             \n\
+             ## Variables\n\
+             \n\
+             | Key | Value |\n\
+             | --- | --- |\n\
+             | api_key | •••••••• |\n\
+             | base_url | https://shop.example.com |\n\
+             \n\
+             ## Health\n\
+             \n\
+             `GET {{base_url}}/health`\n\
+             \n\
+             ## Orders\n\
+             \n\
+             ### Create order\n\
+             \n\
+             `POST {{base_url}}/orders`\n\
+             \n\
+             Places an order.\n\
+             \n\
+             **Headers**\n\
+             \n\
+             | Key | Value |\n\
+             | --- | --- |\n\
+             | Content-Type | application/json |\n\
+             | X-Note | a\\|b |\n\
+             \n\
+             **Body**\n\
+             \n\
+             ```json\n\
+             {\"sku\": \"A1\"}\n\
+             ```\n"
+        );
+
+        // Deterministic for the same input
+        assert_eq!(collection.to_markdown(&[health, create]), markdown);
+    }
+
+    #[test]
+    fn test_sensitive_headers_are_masked() {
+        let mut collection = Collection::new("API".to_string());
+        let request = Request::new("Me".to_string(), HttpMethod::GET, "https://example.com/me".to_string())
+            .with_header("Authorization".to_string(), "Bearer abc123".to_string())
+            .with_header("Cookie".to_string(), "session=xyz".to_string())
+            .with_header("Accept".to_string(), "application/json".to_string());
+        collection.add_request(request.id);
+
+        let markdown = collection.to_markdown(&[request]);
+        assert!(markdown.contains(&format!("| Authorization | {} |", REDACTED)));
+        assert!(markdown.contains(&format!("| Cookie | {} |", REDACTED)));
+        assert!(markdown.contains("| Accept | application/json |"));
+        assert!(!markdown.contains("abc123"));
+        assert!(!markdown.contains("xyz"));
+    }
+
+    #[test]
+    fn test_secrets_in_url_and_form_are_masked() {
+        let mut collection = Collection::new("API".to_string());
+        let mut request = Request::new("Login".to_string(), HttpMethod::POST, "https://example.com/login?api_key=k3y".to_string());
+        request.body = RequestBody::UrlEncoded {
+            urlencoded: vec![
+                crate::request::FormField::new("username".to_string(), "ada".to_string()),
+                crate::request::FormField::new("password".to_string(), "hunter2".to_string()),
+            ],
+        };
+        collection.add_request(request.id);
+
+        let markdown = collection.to_markdown(&[request]);
+        assert!(markdown.contains(&format!("`POST https://example.com/login?api_key={}`", REDACTED)));
+        assert!(markdown.contains(&format!("| password | {} |", REDACTED)));
+        assert!(markdown.contains("| username | ada |"));
+        assert!(!markdown.contains("k3y"));
+        assert!(!markdown.contains("hunter2"));
+    }
+
+    #[test]
+    fn test_code_block_fence_outgrows_content() {
+        let mut out = String::new();
+        code_block(&mut out, "", "````\ncode\n````");
+        assert!(out.starts_with("`````\n"));
+    }
+}
@@ -0,0 +1,189 @@
+//! Mock-server routes derived from saved request examples
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::collection::Collection;
+use crate::request::{HttpMethod, Request};
+use crate::response::Response;
+use crate::Id;
+
+/// One route a mock server can answer: method + path pattern + canned response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MockRoute {
+    pub method: HttpMethod,
+
+    /// Path as written in the request, e.g. `/users/{{id}}`
+    pub path: String,
+
+    /// Matchable form of `path`
+    pub segments: Vec<PathSegment>,
+
+    /// Response from the request's preferred example
+    pub response: Response,
+
+    /// Request the route was derived from
+    pub request_id: Id,
+}
+
+/// A single `/`-separated piece of a route path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PathSegment {
+    Literal(String),
+    /// `{{name}}` or `:name`, matching any single segment
+    Variable(String),
+}
+
+impl MockRoute {
+    /// Match an incoming request, returning the captured path variables
+    pub fn matches(&self, method: HttpMethod, path: &str) -> Option<HashMap<String, String>> {
+        if method != self.method {
+            return None;
+        }
+
+        let parts = split_path(path);
+        if parts.len() != self.segments.len() {
+            return None;
+        }
+
+        let mut captures = HashMap::new();
+        for (segment, part) in self.segments.iter().zip(parts) {
+            match segment {
+                PathSegment::Literal(literal) if literal == part => {}
+                PathSegment::Literal(_) => return None,
+                PathSegment::Variable(name) => {
+                    captures.insert(name.clone(), part.to_string());
+                }
+            }
+        }
+        Some(captures)
+    }
+
+    /// Number of variable segments; routes with fewer are more specific
+    fn variable_count(&self) -> usize {
+        self.segments
+            .iter()
+            .filter(|s| matches!(s, PathSegment::Variable(_)))
+            .count()
+    }
+}
+
+impl Collection {
+    /// Build mock routes for the collection's requests that have examples
+    ///
+    /// Each request contributes one route using its preferred example. Routes
+    /// are ordered most specific first, so the first match wins.
+    pub fn mock_routes(&self, requests: &[Request]) -> Vec<MockRoute> {
+        let ids = self.all_request_ids();
+
+        let mut routes: Vec<MockRoute> = requests
+            .iter()
+            .filter(|r| r.collection_id == Some(self.id) || ids.contains(&r.id))
+            .filter_map(|request| {
+                let example = request.preferred_example()?;
+                let path = url_path(&request.url.raw);
+                Some(MockRoute {
+                    method: request.method.clone(),
+                    segments: split_path(&path).into_iter().map(parse_segment).collect(),
+                    path,
+                    response: example.response.clone(),
+                    request_id: request.id,
+                })
+            })
+            .collect();
+
+        routes.sort_by_key(MockRoute::variable_count);
+        routes
+    }
+}
+
+/// Path portion of a raw URL, without scheme, host, query or fragment
+///
+/// A leading `{{base_url}}`-style variable stands in for the host.
+fn url_path(raw: &str) -> String {
+    let end = raw.find(['?', '#']).unwrap_or(raw.len());
+    let url = &raw[..end];
+
+    let path = if let Some((_, rest)) = url.split_once("://") {
+        rest.find('/').map_or("", |i| &rest[i..])
+    } else if url.starts_with("{{") {
+        url.find("}}").map_or("", |i| &url[i + 2..])
+    } else {
+        url
+    };
+
+    format!("/{}", path.trim_matches('/'))
+}
+
+fn split_path(path: &str) -> Vec<&str> {
+    path.split('/').filter(|s| !s.is_empty()).collect()
+}
+
+fn parse_segment(segment: &str) -> PathSegment {
+    if let Some(name) = segment.strip_prefix("{{").and_then(|s| s.strip_suffix("}}")) {
+        PathSegment::Variable(name.to_string())
+    } else if let Some(name) = segment.strip_prefix(':') {
+        PathSegment::Variable(name.to_string())
+    } else {
+        PathSegment::Literal(segment.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::RequestExample;
+
+    fn with_example(mut request: Request, name: &str, status: u16) -> Request {
+        let example = RequestExample::new(name.to_string(), &request, Response::new(status, String::new()));
+        request.add_example(example);
+        request
+    }
+
+    #[test]
+    fn test_mock_routes() {
+        let collection = Collection::new("API".to_string());
+        let by_id = Request::new("User".to_string(), HttpMethod::GET, "{{base_url}}/users/{{id}}?expand=1".to_string())
+            .with_collection(collection.id);
+        let by_id = with_example(with_example(by_id, "Missing", 404), "Found", 200);
+        let me = Request::new("Me".to_string(), HttpMethod::GET, "https://api.example.com/users/me".to_string())
+            .with_collection(collection.id);
+        let me = with_example(me, "Me", 200);
+        let no_examples = Request::new("Create".to_string(), HttpMethod::POST, "{{base_url}}/users".to_string())
+            .with_collection(collection.id);
+        let elsewhere = with_example(
+            Request::new("Other".to_string(), HttpMethod::GET, "/other".to_string()),
+            "Other",
+            200,
+        );
+
+        let routes = collection.mock_routes(&[by_id.clone(), me, no_examples, elsewhere]);
+        assert_eq!(routes.len(), 2);
+
+        // Literal route sorts ahead of the variable one
+        assert_eq!(routes[0].path, "/users/me");
+        assert_eq!(routes[1].path, "/users/{{id}}");
+        assert_eq!(
+            routes[1].segments,
+            vec![PathSegment::Literal("users".to_string()), PathSegment::Variable("id".to_string())]
+        );
+        assert_eq!(routes[1].response.status_code, 200);
+        assert_eq!(routes[1].request_id, by_id.id);
+
+        let captures = routes[1].matches(HttpMethod::GET, "/users/42").unwrap();
+        assert_eq!(captures.get("id").map(String::as_str), Some("42"));
+        assert!(routes[1].matches(HttpMethod::DELETE, "/users/42").is_none());
+        assert!(routes[1].matches(HttpMethod::GET, "/users/42/posts").is_none());
+
+        let first = routes.iter().find(|r| r.matches(HttpMethod::GET, "/users/me").is_some()).unwrap();
+        assert_eq!(first.path, "/users/me");
+    }
+
+    #[test]
+    fn test_url_path() {
+        assert_eq!(url_path("https://api.example.com/v1/users/?page=2"), "/v1/users");
+        assert_eq!(url_path("https://api.example.com"), "/");
+        assert_eq!(url_path("{{base_url}}/users/:id"), "/users/:id");
+        assert_eq!(parse_segment(":id"), PathSegment::Variable("id".to_string()));
+    }
+}
@@ -0,0 +1,243 @@
+//! OAuth 2.0 token acquisition
+//!
+//! Supports the `client_credentials` and `password` grants. Tokens are kept
+//! in a [`TokenCache`] until shortly before they expire so repeated sends
+//! reuse them instead of re-authenticating.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::request::{AuthError, OAuth2Config};
+use crate::Timestamp;
+
+/// Tokens are treated as expired this long before their real expiry
+const EXPIRY_MARGIN_MS: i64 = 30_000;
+
+/// Token endpoint response (RFC 6749 section 5.1)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    #[serde(default = "default_token_type")]
+    pub token_type: String,
+    /// Lifetime in seconds, if the server gave one
+    #[serde(default)]
+    pub expires_in: Option<u64>,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+}
+
+fn default_token_type() -> String {
+    "Bearer".to_string()
+}
+
+/// A token together with when it stops being usable
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedToken {
+    pub token: TokenResponse,
+    /// None for tokens without an `expires_in`, which are kept until evicted
+    pub expires_at: Option<Timestamp>,
+}
+
+impl CachedToken {
+    pub fn new(token: TokenResponse, fetched_at: Timestamp) -> Self {
+        let expires_at = token
+            .expires_in
+            .map(|secs| fetched_at + secs as i64 * 1000 - EXPIRY_MARGIN_MS);
+        Self { token, expires_at }
+    }
+
+    pub fn is_valid_at(&self, at: Timestamp) -> bool {
+        self.expires_at.is_none_or(|expires_at| at < expires_at)
+    }
+}
+
+/// Tokens shared across sends, keyed by endpoint, client, grant and scope
+#[derive(Debug, Default)]
+pub struct TokenCache {
+    tokens: Mutex<HashMap<String, CachedToken>>,
+}
+
+impl TokenCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get a still-valid token for `config`
+    pub fn get(&self, config: &OAuth2Config, at: Timestamp) -> Option<TokenResponse> {
+        let tokens = self.tokens.lock().unwrap_or_else(|e| e.into_inner());
+        tokens
+            .get(&cache_key(config))
+            .filter(|cached| cached.is_valid_at(at))
+            .map(|cached| cached.token.clone())
+    }
+
+    pub fn insert(&self, config: &OAuth2Config, token: TokenResponse, fetched_at: Timestamp) {
+        let mut tokens = self.tokens.lock().unwrap_or_else(|e| e.into_inner());
+        tokens.insert(cache_key(config), CachedToken::new(token, fetched_at));
+    }
+
+    /// Drop the token for `config`, e.g. after the server rejected it
+    pub fn invalidate(&self, config: &OAuth2Config) {
+        let mut tokens = self.tokens.lock().unwrap_or_else(|e| e.into_inner());
+        tokens.remove(&cache_key(config));
+    }
+}
+
+fn cache_key(config: &OAuth2Config) -> String {
+    format!(
+        "{}\n{}\n{}\n{}\n{}",
+        config.access_token_url,
+        config.client_id,
+        config.grant_type,
+        config.scope,
+        config.username.as_deref().unwrap_or(""),
+    )
+}
+
+impl OAuth2Config {
+    /// Form fields for the token request
+    ///
+    /// Client credentials go in the body (`client_secret_post`).
+    pub fn token_request_form(&self) -> Result<Vec<(&'static str, String)>, AuthError> {
+        let mut form = vec![
+            ("grant_type", self.grant_type.clone()),
+            ("client_id", self.client_id.clone()),
+            ("client_secret", self.client_secret.clone()),
+        ];
+
+        match self.grant_type.as_str() {
+            "client_credentials" => {}
+            "password" => {
+                let Some(username) = self.username.as_ref().filter(|u| !u.is_empty()) else {
+                    return Err(AuthError::MissingField("username"));
+                };
+                let Some(password) = &self.password else {
+                    return Err(AuthError::MissingField("password"));
+                };
+                form.push(("username", username.clone()));
+                form.push(("password", password.clone()));
+            }
+            other => return Err(AuthError::UnsupportedAuthType(format!("{} grant", other))),
+        }
+
+        if !self.scope.is_empty() {
+            form.push(("scope", self.scope.clone()));
+        }
+        Ok(form)
+    }
+
+    /// Request a new token from `access_token_url`
+    #[cfg(feature = "oauth")]
+    pub async fn fetch_token(&self) -> Result<TokenResponse, AuthError> {
+        let form = self.token_request_form()?;
+
+        let response = reqwest::Client::new()
+            .post(&self.access_token_url)
+            .header("Accept", "application/json")
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| AuthError::TokenFetchFailed(e.to_string()))?;
+
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| AuthError::TokenFetchFailed(e.to_string()))?;
+        if !status.is_success() {
+            return Err(AuthError::TokenFetchFailed(format!("HTTP {}: {}", status.as_u16(), body)));
+        }
+
+        serde_json::from_str(&body).map_err(|e| AuthError::TokenFetchFailed(e.to_string()))
+    }
+
+    /// Return the cached token if still valid, otherwise fetch and cache one
+    #[cfg(feature = "oauth")]
+    pub async fn token(&self, cache: &TokenCache) -> Result<TokenResponse, AuthError> {
+        if let Some(token) = cache.get(self, crate::now()) {
+            return Ok(token);
+        }
+
+        let fetched_at = crate::now();
+        let token = self.fetch_token().await?;
+        cache.insert(self, token.clone(), fetched_at);
+        Ok(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(grant_type: &str) -> OAuth2Config {
+        OAuth2Config {
+            client_id: "client".to_string(),
+            client_secret: "secret".to_string(),
+            scope: "read".to_string(),
+            redirect_url: String::new(),
+            auth_url: String::new(),
+            access_token_url: "https://auth.example.com/token".to_string(),
+            grant_type: grant_type.to_string(),
+            username: None,
+            password: None,
+        }
+    }
+
+    #[test]
+    fn test_token_request_form() {
+        let form = config("client_credentials").token_request_form().unwrap();
+        assert!(form.contains(&("grant_type", "client_credentials".to_string())));
+        assert!(form.contains(&("scope", "read".to_string())));
+
+        let mut password = config("password");
+        assert_eq!(password.token_request_form(), Err(AuthError::MissingField("username")));
+        password.username = Some("ada".to_string());
+        password.password = Some("hunter2".to_string());
+        assert!(password.token_request_form().unwrap().contains(&("username", "ada".to_string())));
+
+        assert!(matches!(
+            config("authorization_code").token_request_form(),
+            Err(AuthError::UnsupportedAuthType(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_token_response() {
+        let token: TokenResponse = serde_json::from_str(
+            r#"{"access_token":"abc","token_type":"bearer","expires_in":3600,"refresh_token":"r1","scope":"read"}"#,
+        )
+        .unwrap();
+        assert_eq!(token.access_token, "abc");
+        assert_eq!(token.expires_in, Some(3600));
+        assert_eq!(token.refresh_token.as_deref(), Some("r1"));
+
+        let minimal: TokenResponse = serde_json::from_str(r#"{"access_token":"abc"}"#).unwrap();
+        assert_eq!(minimal.token_type, "Bearer");
+        assert_eq!(minimal.expires_in, None);
+    }
+
+    #[test]
+    fn test_token_cache_expiry() {
+        let cache = TokenCache::new();
+        let config = config("client_credentials");
+        let token = TokenResponse {
+            access_token: "abc".to_string(),
+            token_type: "Bearer".to_string(),
+            expires_in: Some(60),
+            refresh_token: None,
+        };
+
+        cache.insert(&config, token.clone(), 0);
+        assert_eq!(cache.get(&config, 1_000), Some(token.clone()));
+        // Expired, with the safety margin applied
+        assert_eq!(cache.get(&config, 60_000 - EXPIRY_MARGIN_MS), None);
+
+        let mut other_scope = config.clone();
+        other_scope.scope = "write".to_string();
+        assert_eq!(cache.get(&other_scope, 1_000), None);
+
+        cache.invalidate(&config);
+        assert_eq!(cache.get(&config, 1_000), None);
+    }
+}
@@ -5,10 +5,15 @@ use uuid::Uuid;
 use std::collections::HashMap;
 
 use crate::{Id, Timestamp, new_id, now, Temporal, Identifiable};
+use crate::environment::VariableResolver;
+use crate::response::BodyLanguage;
+use crate::user::UserSettings;
 
 /// HTTP request method
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "UPPERCASE")]
+///
+/// Serializes as the bare method string (`"GET"`, `"PURGE"`), as Postman does.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
 pub enum HttpMethod {
     GET,
     POST,
@@ -17,10 +22,15 @@ pub enum HttpMethod {
     PATCH,
     HEAD,
     OPTIONS,
+    TRACE,
+    CONNECT,
+    /// Nonstandard verb such as `PURGE`, always uppercase
+    Custom(String),
 }
 
 impl HttpMethod {
-    pub const ALL: [HttpMethod; 7] = [
+    /// Standard methods offered in the method picker
+    pub const ALL: [HttpMethod; 9] = [
         HttpMethod::GET,
         HttpMethod::POST,
         HttpMethod::PUT,
@@ -28,9 +38,11 @@ impl HttpMethod {
         HttpMethod::PATCH,
         HttpMethod::HEAD,
         HttpMethod::OPTIONS,
+        HttpMethod::TRACE,
+        HttpMethod::CONNECT,
     ];
 
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             HttpMethod::GET => "GET",
             HttpMethod::POST => "POST",
@@ -39,10 +51,30 @@ impl HttpMethod {
             HttpMethod::PATCH => "PATCH",
             HttpMethod::HEAD => "HEAD",
             HttpMethod::OPTIONS => "OPTIONS",
+            HttpMethod::TRACE => "TRACE",
+            HttpMethod::CONNECT => "CONNECT",
+            HttpMethod::Custom(method) => method,
+        }
+    }
+}
+
+impl From<HttpMethod> for String {
+    fn from(method: HttpMethod) -> Self {
+        match method {
+            HttpMethod::Custom(method) => method,
+            method => method.as_str().to_string(),
         }
     }
 }
 
+impl TryFrom<String> for HttpMethod {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
 impl std::fmt::Display for HttpMethod {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.as_str())
@@ -61,13 +93,24 @@ impl std::str::FromStr for HttpMethod {
             "PATCH" => Ok(HttpMethod::PATCH),
             "HEAD" => Ok(HttpMethod::HEAD),
             "OPTIONS" => Ok(HttpMethod::OPTIONS),
+            "TRACE" => Ok(HttpMethod::TRACE),
+            "CONNECT" => Ok(HttpMethod::CONNECT),
+            // Any other RFC 9110 token is accepted as a custom method
+            method if !method.is_empty() && method.bytes().all(is_token_byte) => {
+                Ok(HttpMethod::Custom(method.to_string()))
+            }
             _ => Err(format!("Invalid HTTP method: {}", s)),
         }
     }
 }
 
+fn is_token_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
+
 /// HTTP header
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "KeyValueRepr")]
 pub struct Header {
     pub key: String,
     pub value: String,
@@ -94,6 +137,7 @@ impl Header {
 
 /// Query parameter
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "KeyValueRepr")]
 pub struct Param {
     pub key: String,
     pub value: String,
@@ -112,6 +156,46 @@ impl Param {
     }
 }
 
+/// Wire format shared by headers and params
+///
+/// Accepts both our `enabled` flag and Postman's `disabled` flag.
+#[derive(Deserialize)]
+struct KeyValueRepr {
+    key: String,
+    #[serde(default)]
+    value: String,
+    enabled: Option<bool>,
+    disabled: Option<bool>,
+    description: Option<String>,
+}
+
+impl KeyValueRepr {
+    fn enabled(&self) -> bool {
+        self.enabled.unwrap_or(!self.disabled.unwrap_or(false))
+    }
+}
+
+impl From<KeyValueRepr> for Header {
+    fn from(repr: KeyValueRepr) -> Self {
+        Self {
+            enabled: repr.enabled(),
+            key: repr.key,
+            value: repr.value,
+        }
+    }
+}
+
+impl From<KeyValueRepr> for Param {
+    fn from(repr: KeyValueRepr) -> Self {
+        Self {
+            enabled: repr.enabled(),
+            key: repr.key,
+            value: repr.value,
+            description: repr.description,
+        }
+    }
+}
+
 /// Form data field
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FormField {
@@ -169,15 +253,72 @@ impl FileField {
     }
 }
 
+/// Start of every generated multipart boundary
+const MULTIPART_BOUNDARY_PREFIX: &str = "----PostboyFormBoundary";
+
+/// `Content-Disposition` (and for files `Content-Type`) lines of a part
+fn multipart_part_headers(field: &FormField) -> String {
+    let name = escape_multipart_name(&field.key);
+    match &field.file {
+        Some(file) => format!(
+            "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: {}\r\n",
+            name,
+            escape_multipart_name(&file.name),
+            file.content_type
+                .as_deref()
+                .unwrap_or_else(|| content_type_for_file_name(&file.name))
+                .replace(['\r', '\n'], "")
+        ),
+        None => format!("Content-Disposition: form-data; name=\"{}\"\r\n", name),
+    }
+}
+
+/// Percent-encode `"`, CR and LF in a multipart name or filename, as
+/// browsers do
+fn escape_multipart_name(name: &str) -> String {
+    name.replace('"', "%22").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Best-guess content type from a file name's extension
+fn content_type_for_file_name(name: &str) -> &'static str {
+    let extension = name.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase()).unwrap_or_default();
+    match extension.as_str() {
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "txt" => "text/plain",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        _ => "application/octet-stream",
+    }
+}
+
+fn contains_bytes(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|window| window == needle)
+}
+
 /// Request body types
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "mode", rename_all = "lowercase")]
 pub enum RequestBody {
+    #[default]
     None,
     Json { raw: String },
     FormData { formdata: Vec<FormField> },
     UrlEncoded { urlencoded: Vec<FormField> },
-    Raw { raw: String, language: Option<String> },
+    Raw {
+        raw: String,
+        language: Option<String>,
+        /// Character set used to encode `raw` (UTF-8 if unset)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        charset: Option<String>,
+    },
     Binary,
 }
 
@@ -202,6 +343,7 @@ impl RequestBody {
         Self::Raw {
             raw,
             language: None,
+            charset: None,
         }
     }
 
@@ -209,6 +351,15 @@ impl RequestBody {
         Self::Raw {
             raw,
             language: Some(language),
+            charset: None,
+        }
+    }
+
+    pub fn raw_with_charset(raw: String, charset: String) -> Self {
+        Self::Raw {
+            raw,
+            language: None,
+            charset: Some(charset),
         }
     }
 
@@ -216,6 +367,55 @@ impl RequestBody {
         Self::Binary
     }
 
+    /// Language to highlight the body as, for bodies edited as text
+    ///
+    /// Raw bodies use their `language` hint, falling back to sniffing the
+    /// text. Form, binary and empty bodies return `None`.
+    pub fn detected_language(&self) -> Option<BodyLanguage> {
+        match self {
+            RequestBody::Json { .. } => Some(BodyLanguage::Json),
+            RequestBody::Raw { raw, language, .. } => Some(
+                language
+                    .as_deref()
+                    .and_then(BodyLanguage::from_hint)
+                    .unwrap_or_else(|| BodyLanguage::sniff(raw)),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Copy of the body with the values at the given JSON pointers masked
+    ///
+    /// Only applies to `Json` bodies that parse; pointers that match nothing
+    /// are ignored and anything else is returned unchanged. The masked body
+    /// is re-serialized, so object keys come out sorted.
+    pub fn redact_json_paths(&self, paths: &[&str]) -> RequestBody {
+        let RequestBody::Json { raw } = self else {
+            return self.clone();
+        };
+        let Ok(mut value) = serde_json::from_str::<serde_json::Value>(raw) else {
+            return self.clone();
+        };
+
+        let mut masked = false;
+        for path in paths {
+            if let Some(target) = value.pointer_mut(path) {
+                *target = serde_json::Value::String(REDACTED.to_string());
+                masked = true;
+            }
+        }
+        if !masked {
+            return self.clone();
+        }
+
+        let raw = if raw.contains('\n') {
+            serde_json::to_string_pretty(&value)
+        } else {
+            serde_json::to_string(&value)
+        };
+        RequestBody::Json { raw: raw.unwrap_or_default() }
+    }
+
     pub fn mode(&self) -> BodyMode {
         match self {
             RequestBody::None => BodyMode::None,
@@ -238,6 +438,115 @@ impl RequestBody {
         }
     }
 
+    /// Encode the body for sending
+    ///
+    /// Raw bodies use their charset (any WHATWG encoding label, e.g.
+    /// `iso-8859-1`), falling back to UTF-8 for unknown or missing labels.
+    /// Form data and binary bodies are built from files at send time and
+    /// encode to nothing here; see [`to_multipart_bytes`](Self::to_multipart_bytes).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            RequestBody::None | RequestBody::FormData { .. } | RequestBody::Binary => Vec::new(),
+            RequestBody::Json { raw } => raw.as_bytes().to_vec(),
+            RequestBody::UrlEncoded { urlencoded } => url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(urlencoded.iter().filter(|f| f.enabled).map(|f| (&f.key, &f.value)))
+                .finish()
+                .into_bytes(),
+            RequestBody::Raw { raw, charset, .. } => {
+                let encoding = charset
+                    .as_deref()
+                    .and_then(|label| encoding_rs::Encoding::for_label(label.as_bytes()))
+                    .unwrap_or(encoding_rs::UTF_8);
+                let (bytes, _, _) = encoding.encode(raw);
+                bytes.into_owned()
+            }
+        }
+    }
+
+    /// Encode a form-data body as `multipart/form-data`
+    ///
+    /// Returns the body and the boundary to put in the `Content-Type`
+    /// header. Disabled fields are skipped; file fields are read from
+    /// `FileField.path`. Errors for other body types and unreadable files.
+    pub fn to_multipart_bytes(&self) -> Result<(Vec<u8>, String), String> {
+        self.to_multipart_bytes_with(&HashMap::new())
+    }
+
+    /// Like [`to_multipart_bytes`](Self::to_multipart_bytes), with file
+    /// contents supplied by field key; keys not in `files` are read from
+    /// their path
+    pub fn to_multipart_bytes_with(&self, files: &HashMap<String, Vec<u8>>) -> Result<(Vec<u8>, String), String> {
+        let RequestBody::FormData { formdata } = self else {
+            return Err("Only form-data bodies can be encoded as multipart".to_string());
+        };
+
+        let mut parts: Vec<(String, Vec<u8>)> = Vec::new();
+        for field in formdata.iter().filter(|f| f.enabled) {
+            let content = match &field.file {
+                Some(file) => match (files.get(&field.key), &file.path) {
+                    (Some(content), _) => content.clone(),
+                    (None, Some(path)) => std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?,
+                    (None, None) => return Err(format!("No file selected for form field '{}'", field.key)),
+                },
+                None => field.value.as_bytes().to_vec(),
+            };
+            parts.push((multipart_part_headers(field), content));
+        }
+
+        // Regenerate until the delimiter can't be mistaken for content
+        let boundary = loop {
+            let candidate = format!("{}{}", MULTIPART_BOUNDARY_PREFIX, new_id().simple());
+            let delimiter = format!("--{}", candidate);
+            if !parts.iter().any(|(_, content)| contains_bytes(content, delimiter.as_bytes())) {
+                break candidate;
+            }
+        };
+
+        let mut body = Vec::new();
+        for (headers, content) in &parts {
+            body.extend_from_slice(format!("--{}\r\n{}\r\n", boundary, headers).as_bytes());
+            body.extend_from_slice(content);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+
+        Ok((body, boundary))
+    }
+
+    /// Size in bytes the body will have when sent, without building it
+    ///
+    /// Multipart bodies are estimated from part headers, boundaries and the
+    /// size of each file on disk (0 for files that can't be found). Binary
+    /// bodies have no content here and count as 0.
+    pub fn estimated_len(&self) -> u64 {
+        match self {
+            RequestBody::None | RequestBody::Binary => 0,
+            RequestBody::FormData { formdata } => {
+                // A boundary is the prefix plus a 32-digit hex id
+                let delimiter = 2 + MULTIPART_BOUNDARY_PREFIX.len() as u64 + 32;
+                let parts: u64 = formdata
+                    .iter()
+                    .filter(|f| f.enabled)
+                    .map(|field| {
+                        let content = match &field.file {
+                            Some(file) => file
+                                .path
+                                .as_ref()
+                                .and_then(|path| std::fs::metadata(path).ok())
+                                .map_or(0, |metadata| metadata.len()),
+                            None => field.value.len() as u64,
+                        };
+                        // delimiter CRLF headers CRLF content CRLF
+                        delimiter + 2 + multipart_part_headers(field).len() as u64 + 2 + content + 2
+                    })
+                    .sum();
+                // closing delimiter "--" CRLF
+                parts + delimiter + 4
+            }
+            _ => self.to_bytes().len() as u64,
+        }
+    }
+
     pub fn get_json(&self) -> Option<&serde_json::Value> {
         match self {
             RequestBody::Json { raw } => serde_json::from_str(raw).ok(),
@@ -335,6 +644,193 @@ pub enum AuthConfig {
     },
 }
 
+/// Errors from auth operations: applying an `AuthConfig` to a request,
+/// signing, and fetching tokens
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AuthError {
+    /// A field the scheme needs is empty or unset
+    #[error("Missing required auth field: {0}")]
+    MissingField(&'static str),
+
+    /// The scheme or one of its modes (e.g. an OAuth 2.0 grant) isn't supported
+    #[error("Not supported: {0}")]
+    UnsupportedAuthType(String),
+
+    /// The scheme is recognized but Postboy can't apply it yet
+    #[error("{0} auth is not implemented yet")]
+    NotImplemented(&'static str),
+
+    #[error("Signing failed: {0}")]
+    SignatureError(String),
+
+    #[error("Token request failed: {0}")]
+    TokenFetchFailed(String),
+
+    /// Fields are present but can't be used as given
+    #[error("Invalid auth configuration: {0}")]
+    InvalidConfig(String),
+}
+
+impl AuthConfig {
+    /// Add the headers or query params this auth sends
+    ///
+    /// Any existing header or param with the same name (case-insensitive for
+    /// headers) is replaced. Schemes that need signing or a token exchange
+    /// return `AuthError::NotImplemented`; incomplete configs return
+    /// `MissingField` or `InvalidConfig`. On error both lists are untouched.
+    pub fn apply(&self, headers: &mut Vec<Header>, query: &mut Vec<Param>) -> Result<(), AuthError> {
+        use base64::Engine;
+
+        match self {
+            AuthConfig::Noauth => {}
+            AuthConfig::Bearer { token } => {
+                if token.is_empty() {
+                    return Err(AuthError::MissingField("token"));
+                }
+                set_header(headers, "Authorization", format!("Bearer {}", token));
+            }
+            AuthConfig::Basic { username, password } => {
+                // RFC 7617: the user-id can't contain a colon
+                if username.contains(':') {
+                    return Err(AuthError::InvalidConfig("Basic auth username can't contain ':'".to_string()));
+                }
+                let credentials = base64::engine::general_purpose::STANDARD
+                    .encode(format!("{}:{}", username, password));
+                set_header(headers, "Authorization", format!("Basic {}", credentials));
+            }
+            AuthConfig::ApiKey { key, .. } if key.is_empty() => return Err(AuthError::MissingField("key")),
+            AuthConfig::ApiKey { key, value, add_to } => match add_to {
+                ApiKeyLocation::Header => set_header(headers, key, value.clone()),
+                ApiKeyLocation::Query => {
+                    query.retain(|p| p.key != *key);
+                    query.push(Param::new(key.clone(), value.clone()));
+                }
+            },
+            AuthConfig::Digest { .. } => return Err(AuthError::NotImplemented("Digest")),
+            AuthConfig::OAuth1 { .. } => return Err(AuthError::NotImplemented("OAuth 1.0")),
+            AuthConfig::OAuth2 { .. } => return Err(AuthError::NotImplemented("OAuth 2.0")),
+            AuthConfig::Awsv4 { .. } => return Err(AuthError::NotImplemented("AWS Signature v4")),
+            AuthConfig::Hawk { .. } => return Err(AuthError::NotImplemented("Hawk")),
+            AuthConfig::BearerCustom { .. } => return Err(AuthError::NotImplemented("Custom bearer")),
+        }
+        Ok(())
+    }
+}
+
+/// Replacement for masked values in [`Request::redacted`]
+pub const REDACTED: &str = "••••••••";
+
+/// Headers whose values [`Request::redacted`] masks
+const SENSITIVE_HEADERS: [&str; 4] = ["authorization", "proxy-authorization", "cookie", "x-api-key"];
+
+/// Substrings of header and param names that suggest a secret value
+const SECRET_NAME_PATTERNS: [&str; 9] = [
+    "token", "secret", "password", "passwd", "apikey", "api_key", "api-key", "credential", "session",
+];
+
+/// Whether a header or param with this name should be masked when shared
+pub(crate) fn is_sensitive_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SENSITIVE_HEADERS.contains(&name.as_str()) || SECRET_NAME_PATTERNS.iter().any(|pattern| name.contains(pattern))
+}
+
+/// `AuthConfig` fields (at any depth) that [`Request::redacted`] masks
+const SECRET_AUTH_FIELDS: [&str; 11] = [
+    "token", "password", "value", "consumer_secret", "token_secret", "secret_key", "auth_key",
+    "client_secret", "access_token", "refresh_token", "mac",
+];
+
+/// Fields left out of [`Request::to_compact`]
+const COMPACT_SKIPPED_FIELDS: [&str; 7] = [
+    "id", "collection_id", "folder_id", "examples", "ui_state", "created_at", "updated_at",
+];
+
+/// Mask non-empty string values of [`SECRET_AUTH_FIELDS`] anywhere in `value`
+fn mask_secret_fields(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(fields) => {
+            for (key, field) in fields {
+                match field {
+                    serde_json::Value::String(s) if !s.is_empty() && SECRET_AUTH_FIELDS.contains(&key.as_str()) => {
+                        *s = REDACTED.to_string();
+                    }
+                    _ => mask_secret_fields(field),
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(mask_secret_fields),
+        _ => {}
+    }
+}
+
+/// Mask the values of secret-looking keys in a `key=value&...` query string
+fn redact_query(query: &str) -> String {
+    query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) if !value.is_empty() && is_sensitive_name(key) => format!("{}={}", key, REDACTED),
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Escape the characters that would change how a query string splits
+///
+/// Everything else is left as typed, so `{{variables}}` still resolve.
+fn encode_query_component(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '%' | '&' | '=' | '#' | '+' | ' ' => encoded.push_str(&format!("%{:02X}", c as u32)),
+            c => encoded.push(c),
+        }
+    }
+    encoded
+}
+
+/// A raw URL with [`redact_query`] applied to its query string
+fn redact_url_query(raw: &str) -> String {
+    let (rest, fragment) = match raw.split_once('#') {
+        Some((rest, fragment)) => (rest, Some(fragment)),
+        None => (raw, None),
+    };
+    let mut url = match rest.split_once('?') {
+        Some((base, query)) => format!("{}?{}", base, redact_query(query)),
+        None => rest.to_string(),
+    };
+    if let Some(fragment) = fragment {
+        url.push('#');
+        url.push_str(fragment);
+    }
+    url
+}
+
+/// Usual casing of a header name, e.g. `x-api-key` -> `X-Api-Key`
+fn canonical_header_name(name: &str) -> String {
+    const SPECIAL: [&str; 5] = ["DNT", "ETag", "TE", "WWW-Authenticate", "X-XSS-Protection"];
+    if let Some(special) = SPECIAL.iter().find(|s| s.eq_ignore_ascii_case(name)) {
+        return special.to_string();
+    }
+
+    name.split('-')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Replace any header named `key` (case-insensitive) with a single new one
+fn set_header(headers: &mut Vec<Header>, key: &str, value: String) {
+    headers.retain(|h| !h.key.eq_ignore_ascii_case(key));
+    headers.push(Header::new(key.to_string(), value));
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ApiKeyLocation {
@@ -357,6 +853,12 @@ pub struct OAuth2Config {
     pub auth_url: String,
     pub access_token_url: String,
     pub grant_type: String,
+
+    /// Resource owner credentials for the `password` grant
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
 }
 
 /// Script configuration for request hooks
@@ -371,9 +873,15 @@ pub struct ScriptConfig {
 }
 
 /// HTTP request model
+///
+/// The derived serde impls are wrapped (see `remote = "Self"`) so Postman's
+/// `url.query` array can be lifted into `query_params` before deserializing.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(remote = "Self")]
 pub struct Request {
+    #[serde(default = "new_id")]
     pub id: Id,
+    #[serde(default)]
     pub name: String,
     pub description: Option<String>,
 
@@ -385,11 +893,18 @@ pub struct Request {
     pub url: Url,
 
     /// HTTP headers
-    #[serde(default)]
+    #[serde(default, alias = "header")]
     pub headers: Vec<Header>,
 
-    /// Query parameters
+    /// Keep headers exactly where the user placed them
+    ///
+    /// Needed for order-sensitive APIs and signatures (e.g. SigV4). Respected by
+    /// `normalize_headers`, which then only dedupes in place.
     #[serde(default)]
+    pub preserve_header_order: bool,
+
+    /// Query parameters
+    #[serde(default, alias = "query")]
     pub query_params: Vec<Param>,
 
     /// Request body
@@ -397,22 +912,43 @@ pub struct Request {
     pub body: RequestBody,
 
     /// Authentication configuration
+    #[serde(default, deserialize_with = "deserialize_auth")]
     pub auth: Option<AuthConfig>,
 
     /// Script hooks
     #[serde(default)]
     pub script: ScriptConfig,
 
+    /// Timeout override in seconds (None inherits the user setting)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u32>,
+
+    /// Redirect-following override (None inherits the user setting)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub follow_redirects: Option<bool>,
+
+    /// Maximum redirects to follow (None inherits the user setting)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_redirects: Option<u32>,
+
     /// Parent collection ID
     pub collection_id: Option<Id>,
 
     /// Parent folder ID
     pub folder_id: Option<Id>,
 
+    /// Saved request/response pairs for documentation and mocking
+    ///
+    /// Not part of what gets sent.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<RequestExample>,
+
     /// Creation timestamp
+    #[serde(default = "now")]
     pub created_at: Timestamp,
 
     /// Last update timestamp
+    #[serde(default = "now")]
     pub updated_at: Timestamp,
 
     /// UI-specific state
@@ -420,8 +956,85 @@ pub struct Request {
     pub ui_state: RequestUiState,
 }
 
+impl Serialize for Request {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Request::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Request {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut value = serde_json::Value::deserialize(deserializer)?;
+        lift_postman_query(&mut value);
+        Request::deserialize(value).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Move a Postman `url.query` array to the request's `query` key
+///
+/// Postman repeats the query in `url.raw`; it's stripped there since query
+/// params are appended to the URL when sending. Requests that already have
+/// params of their own are left alone.
+fn lift_postman_query(value: &mut serde_json::Value) {
+    let Some(request) = value.as_object_mut() else {
+        return;
+    };
+    if request.contains_key("query_params") || request.contains_key("query") {
+        return;
+    }
+    let Some(url) = request.get_mut("url").and_then(|url| url.as_object_mut()) else {
+        return;
+    };
+    if !url.get("query").is_some_and(|query| query.is_array()) {
+        return;
+    }
+
+    let query = url.remove("query").unwrap_or_default();
+    if let Some(raw) = url.get_mut("raw") {
+        if let Some(text) = raw.as_str() {
+            let (base, rest) = text.split_once('?').unwrap_or((text, ""));
+            let hash = rest.find('#').map(|i| &rest[i..]).unwrap_or("");
+            *raw = format!("{}{}", base, hash).into();
+        }
+    }
+    request.insert("query".to_string(), query);
+}
+
+/// Accept both our auth format and Postman's `{"type": "bearer", "bearer": [{"key", "value"}]}`
+///
+/// Unrecognized auth is dropped rather than failing the whole request.
+fn deserialize_auth<'de, D>(deserializer: D) -> Result<Option<AuthConfig>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    let Some(value) = value else {
+        return Ok(None);
+    };
+
+    if let Ok(auth) = serde_json::from_value(value.clone()) {
+        return Ok(Some(auth));
+    }
+
+    let auth_type = value.get("type").and_then(|t| t.as_str()).unwrap_or_default();
+    let Some(pairs) = value.get(auth_type).and_then(|p| p.as_array()) else {
+        return Ok(None);
+    };
+
+    let mut flattened = serde_json::Map::new();
+    flattened.insert("type".to_string(), auth_type.into());
+    for pair in pairs {
+        if let (Some(key), Some(value)) = (pair.get("key").and_then(|k| k.as_str()), pair.get("value")) {
+            flattened.insert(key.to_string(), value.clone());
+        }
+    }
+
+    Ok(serde_json::from_value(serde_json::Value::Object(flattened)).ok())
+}
+
 /// URL representation that preserves the raw string
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "UrlRepr")]
 pub struct Url {
     /// Raw URL string (may contain variables)
     pub raw: String,
@@ -439,6 +1052,43 @@ pub struct Url {
     pub hash: Option<String>,
 }
 
+/// Wire formats accepted for a URL
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum UrlRepr {
+    /// Plain string
+    Raw(String),
+    /// Our own format
+    Fields {
+        raw: String,
+        protocol: Option<String>,
+        host: Option<String>,
+        port: Option<u16>,
+        path: Option<String>,
+        query: Option<String>,
+        hash: Option<String>,
+    },
+    /// Postman URL object, whose host and path are arrays
+    Postman { raw: String },
+}
+
+impl From<UrlRepr> for Url {
+    fn from(repr: UrlRepr) -> Self {
+        match repr {
+            UrlRepr::Raw(raw) | UrlRepr::Postman { raw } => Url::new(raw),
+            UrlRepr::Fields { raw, protocol, host, port, path, query, hash } => Self {
+                raw,
+                protocol,
+                host,
+                port,
+                path,
+                query,
+                hash,
+            },
+        }
+    }
+}
+
 impl Url {
     pub fn new(raw: String) -> Self {
         Self {
@@ -478,6 +1128,70 @@ pub struct RequestUiState {
     pub scroll_position: Option<f32>,
 }
 
+/// A named request/response pair saved on a request (Postman's "examples")
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequestExample {
+    #[serde(default = "new_id")]
+    pub id: Id,
+    pub name: String,
+
+    /// The request as it was when the example was captured
+    pub request: ExampleRequest,
+
+    /// The response shown for this example
+    pub response: crate::response::Response,
+
+    /// Preferred example when a single response is needed (e.g. mocking)
+    #[serde(default)]
+    pub is_default: bool,
+
+    #[serde(default = "now")]
+    pub created_at: Timestamp,
+    #[serde(default = "now")]
+    pub updated_at: Timestamp,
+}
+
+/// Request half of an example: only what determines the exchange
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExampleRequest {
+    pub method: HttpMethod,
+    pub url: Url,
+    #[serde(default)]
+    pub headers: Vec<Header>,
+    #[serde(default)]
+    pub query_params: Vec<Param>,
+    #[serde(default)]
+    pub body: RequestBody,
+}
+
+impl From<&Request> for ExampleRequest {
+    fn from(request: &Request) -> Self {
+        Self {
+            method: request.method.clone(),
+            url: request.url.clone(),
+            headers: request.headers.clone(),
+            query_params: request.query_params.clone(),
+            body: request.body.clone(),
+        }
+    }
+}
+
+impl RequestExample {
+    /// Capture an example from a request and the response it produced
+    pub fn new(name: String, request: &Request, response: crate::response::Response) -> Self {
+        let now = now();
+        Self {
+            id: new_id(),
+            name,
+            request: ExampleRequest::from(request),
+            response,
+            is_default: false,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
 impl Request {
     pub fn new(name: String, method: HttpMethod, url: String) -> Self {
         let now = now();
@@ -488,12 +1202,17 @@ impl Request {
             method,
             url: Url::new(url),
             headers: Vec::new(),
+            preserve_header_order: false,
             query_params: Vec::new(),
             body: RequestBody::none(),
             auth: None,
             script: ScriptConfig::default(),
+            timeout_secs: None,
+            follow_redirects: None,
+            max_redirects: None,
             collection_id: None,
             folder_id: None,
+            examples: Vec::new(),
             created_at: now,
             updated_at: now,
             ui_state: RequestUiState::default(),
@@ -560,6 +1279,166 @@ impl Request {
         self.query_params.iter().filter(|p| p.enabled).collect()
     }
 
+    /// Key identifying this request in the response cache
+    ///
+    /// Covers the method, the URL with `resolver` applied and a hash of the
+    /// resolved body, so requests that only differ in body or environment
+    /// don't share an entry.
+    pub fn cache_key(&self, resolver: &VariableResolver) -> String {
+        use std::hash::{Hash, Hasher};
+
+        let body = serde_json::to_string(&self.body).unwrap_or_default();
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        resolver.resolve(&body).hash(&mut hasher);
+
+        format!("{} {} {:016x}", self.method.as_str(), resolver.resolve(&self.url.raw), hasher.finish())
+    }
+
+    /// Cache key that also covers the request headers a response `Vary`s on
+    ///
+    /// Header names are matched case-insensitively and their order in `vary`
+    /// doesn't matter. `*` varies on every enabled header. Header values are
+    /// resolved like the URL.
+    pub fn cache_key_with_vary(&self, resolver: &VariableResolver, vary: &[String]) -> String {
+        let mut names: Vec<String> = if vary.iter().any(|v| v.trim() == "*") {
+            self.enabled_headers().iter().map(|h| h.key.to_ascii_lowercase()).collect()
+        } else {
+            vary.iter().map(|v| v.trim().to_ascii_lowercase()).filter(|v| !v.is_empty()).collect()
+        };
+        names.sort();
+        names.dedup();
+
+        let mut key = self.cache_key(resolver);
+        for name in names {
+            let values: Vec<String> = self
+                .enabled_headers()
+                .into_iter()
+                .filter(|h| h.key.eq_ignore_ascii_case(&name))
+                .map(|h| resolver.resolve(&h.value))
+                .collect();
+            key.push_str(&format!("\n{}: {}", name, values.join(", ")));
+        }
+        key
+    }
+
+    /// Collapse enabled headers whose names match case-insensitively
+    ///
+    /// The last enabled value wins, kept at the position where the name first
+    /// appeared. Disabled headers are left alone unless they exactly duplicate
+    /// an enabled one. Names are then canonicalized (`content-type` becomes
+    /// `Content-Type`), except with `preserve_header_order` set, where headers
+    /// are only deduped in place and keep their casing.
+    pub fn normalize_headers(&mut self) {
+        let mut normalized: Vec<Header> = Vec::with_capacity(self.headers.len());
+
+        for header in self.headers.drain(..) {
+            let existing = normalized
+                .iter_mut()
+                .find(|h| h.enabled && header.enabled && h.key.eq_ignore_ascii_case(&header.key));
+
+            match existing {
+                Some(existing) => existing.value = header.value,
+                None => normalized.push(header),
+            }
+        }
+
+        let enabled: Vec<(String, String)> = normalized
+            .iter()
+            .filter(|h| h.enabled)
+            .map(|h| (h.key.to_ascii_lowercase(), h.value.clone()))
+            .collect();
+        normalized.retain(|h| {
+            h.enabled || !enabled.iter().any(|(key, value)| h.key.eq_ignore_ascii_case(key) && h.value == *value)
+        });
+
+        if !self.preserve_header_order {
+            for header in &mut normalized {
+                header.key = canonical_header_name(&header.key);
+            }
+        }
+
+        self.headers = normalized;
+    }
+
+    /// Sort headers by name, case-insensitively, for stable diffs
+    ///
+    /// The sort is stable, so repeated headers keep their relative order.
+    /// Does nothing with `preserve_header_order` set.
+    pub fn sort_headers(&mut self) {
+        if !self.preserve_header_order {
+            self.headers.sort_by_cached_key(|h| h.key.to_ascii_lowercase());
+        }
+    }
+
+    /// Sort query params by key, case-insensitively, for stable diffs
+    ///
+    /// The sort is stable, so repeated keys (`?id=1&id=2`) keep their
+    /// relative order. When `url.raw` carries a query string it is rebuilt
+    /// from the enabled params so the URL sent matches them; the fragment is
+    /// kept.
+    pub fn sort_query_params(&mut self) {
+        self.query_params.sort_by_cached_key(|p| p.key.to_ascii_lowercase());
+        if self.query_params.is_empty() {
+            return;
+        }
+
+        let (rest, fragment) = match self.url.raw.split_once('#') {
+            Some((rest, fragment)) => (rest, Some(fragment)),
+            None => (self.url.raw.as_str(), None),
+        };
+        let Some((base, _)) = rest.split_once('?') else {
+            return;
+        };
+        let query = self
+            .enabled_query_params()
+            .iter()
+            .map(|p| format!("{}={}", encode_query_component(&p.key), encode_query_component(&p.value)))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let mut raw = base.to_string();
+        if !query.is_empty() {
+            raw.push('?');
+            raw.push_str(&query);
+        }
+        if let Some(fragment) = fragment {
+            raw.push('#');
+            raw.push_str(fragment);
+        }
+        self.url.raw = raw;
+        if self.url.query.is_some() {
+            self.url.query = Some(query);
+        }
+    }
+
+    /// Auth to send with this request, following the inheritance chain
+    ///
+    /// The request's own auth wins, then the nearest folder's, then the
+    /// collection's. An explicit `Noauth` anywhere along the way stops
+    /// inheritance and resolves to `Noauth`.
+    pub fn resolved_auth<'a>(
+        &'a self,
+        folder_auth: Option<&'a AuthConfig>,
+        collection_auth: Option<&'a AuthConfig>,
+    ) -> Option<&'a AuthConfig> {
+        self.auth.as_ref().or(folder_auth).or(collection_auth)
+    }
+
+    /// Timeout to use for this request, falling back to the user setting
+    pub fn effective_timeout(&self, settings: &UserSettings) -> u32 {
+        self.timeout_secs.unwrap_or(settings.default_request_timeout_secs)
+    }
+
+    /// Whether to follow redirects, falling back to the user setting
+    pub fn effective_follow_redirects(&self, settings: &UserSettings) -> bool {
+        self.follow_redirects.unwrap_or(settings.follow_redirects)
+    }
+
+    /// Most redirects to follow, falling back to the user setting
+    pub fn effective_max_redirects(&self, settings: &UserSettings) -> u32 {
+        self.max_redirects.unwrap_or(settings.max_redirects)
+    }
+
     /// Check if request has a body
     pub fn has_body(&self) -> bool {
         !matches!(self.body, RequestBody::None)
@@ -574,6 +1453,372 @@ impl Request {
         dup.updated_at = now();
         dup
     }
+
+    /// Encode the request as a single URL-safe base64 token for sharing
+    ///
+    /// Encodes the [`Request::redacted`] copy, so credentials stay out of
+    /// the token. Ids, placement, examples, UI state, timestamps and unset
+    /// fields are dropped to keep it short; [`Request::from_compact`]
+    /// restores them as defaults, with a fresh id.
+    pub fn to_compact(&self) -> String {
+        use base64::Engine;
+
+        let mut value = serde_json::to_value(self.redacted(&[])).unwrap_or_default();
+        if let Some(fields) = value.as_object_mut() {
+            fields.retain(|key, value| !value.is_null() && !COMPACT_SKIPPED_FIELDS.contains(&key.as_str()));
+        }
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(value.to_string())
+    }
+
+    /// Decode a token produced by [`Request::to_compact`]
+    ///
+    /// Timestamps are set to now.
+    pub fn from_compact(s: &str) -> Result<Self, String> {
+        use base64::Engine;
+
+        let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(s.trim().trim_end_matches('='))
+            .map_err(|e| format!("Invalid compact request: {}", e))?;
+        serde_json::from_slice(&bytes).map_err(|e| format!("Invalid compact request: {}", e))
+    }
+
+    /// Copy of the request that is safe to share
+    ///
+    /// Masks credential headers, headers, query params and form fields whose
+    /// names look secret (`X-Auth-Token`, `api_key`, ...) including params
+    /// written into the URL, secret auth fields and the given JSON pointers
+    /// in the body (see [`RequestBody::redact_json_paths`]).
+    pub fn redacted(&self, body_paths: &[&str]) -> Request {
+        let mut request = self.clone();
+
+        for header in &mut request.headers {
+            if is_sensitive_name(&header.key) && !header.value.is_empty() {
+                header.value = REDACTED.to_string();
+            }
+        }
+        for param in &mut request.query_params {
+            if is_sensitive_name(&param.key) && !param.value.is_empty() {
+                param.value = REDACTED.to_string();
+            }
+        }
+        request.url.raw = redact_url_query(&request.url.raw);
+        request.url.query = request.url.query.as_deref().map(redact_query);
+        request.body = request.body.redact_json_paths(body_paths);
+        if let RequestBody::FormData { formdata: fields } | RequestBody::UrlEncoded { urlencoded: fields } = &mut request.body {
+            for field in fields {
+                if is_sensitive_name(&field.key) && !field.value.is_empty() {
+                    field.value = REDACTED.to_string();
+                }
+            }
+        }
+        request.auth = request.auth.as_ref().map(|auth| {
+            let mut value = serde_json::to_value(auth).unwrap_or_default();
+            mask_secret_fields(&mut value);
+            serde_json::from_value(value).unwrap_or(AuthConfig::Noauth)
+        });
+
+        request
+    }
+
+    /// Save an example, replacing any existing example with the same name
+    pub fn add_example(&mut self, example: RequestExample) {
+        match self.examples.iter_mut().find(|e| e.name == example.name) {
+            Some(existing) => {
+                let created_at = existing.created_at;
+                *existing = example;
+                existing.created_at = created_at;
+                existing.updated_at = now();
+            }
+            None => self.examples.push(example),
+        }
+        self.updated_at = now();
+    }
+
+    /// Remove an example by name
+    pub fn remove_example(&mut self, name: &str) -> bool {
+        let original_len = self.examples.len();
+        self.examples.retain(|e| e.name != name);
+        let removed = self.examples.len() < original_len;
+        if removed {
+            self.updated_at = now();
+        }
+        removed
+    }
+
+    /// Find an example by name
+    pub fn example(&self, name: &str) -> Option<&RequestExample> {
+        self.examples.iter().find(|e| e.name == name)
+    }
+
+    /// The example to use when only one is wanted
+    ///
+    /// Prefers the one marked default, then the first 2xx, then the first.
+    pub fn preferred_example(&self) -> Option<&RequestExample> {
+        self.examples
+            .iter()
+            .find(|e| e.is_default)
+            .or_else(|| self.examples.iter().find(|e| (200..300).contains(&e.response.status_code)))
+            .or_else(|| self.examples.first())
+    }
+
+    /// One-line label for lists, e.g. `GET api.example.com/users`
+    ///
+    /// The URL is cut down to host and path; the scheme, query and fragment
+    /// are dropped. Long paths are elided in the middle, and `{{var}}`
+    /// placeholders are never cut in half.
+    pub fn summary_line(&self) -> String {
+        format!("{} {}", self.method.as_str(), shorten_url(&self.url.raw, SUMMARY_URL_MAX_CHARS))
+    }
+}
+
+/// Maximum length of the URL part of `Request::summary_line`
+const SUMMARY_URL_MAX_CHARS: usize = 60;
+
+fn shorten_url(raw: &str, max_chars: usize) -> String {
+    let end = raw.find(['?', '#']).unwrap_or(raw.len());
+    let url = &raw[..end];
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest).trim_end_matches('/');
+
+    if url.chars().count() <= max_chars {
+        return url.to_string();
+    }
+
+    // Keep the host and the last path segment
+    if let Some((host, path)) = url.split_once('/') {
+        let last = path.rsplit('/').next().unwrap_or(path);
+        let elided = format!("{}/…/{}", host, last);
+        if elided.chars().count() <= max_chars {
+            return elided;
+        }
+    }
+
+    let mut truncated: String = url.chars().take(max_chars.saturating_sub(1)).collect();
+    if let Some(open) = truncated.rfind("{{") {
+        if truncated[open..].find("}}").is_none() {
+            truncated.truncate(open);
+        }
+    }
+    truncated.push('…');
+    truncated
+}
+
+impl Request {
+    /// Parse a `curl` command line (e.g. "Copy as cURL" from browser devtools)
+    ///
+    /// Understands the method, headers, data, cookies (`-b`), `--compressed`,
+    /// basic auth (`-u`) and `--url`. Unknown options are skipped. Short
+    /// options may carry their value attached (`-XPOST`), and `-d @file`
+    /// reads the data from `file` as curl would.
+    pub fn from_curl(command: &str) -> Result<Self, String> {
+        let args = split_shell_words(command)?;
+        let mut args = args.into_iter().peekable();
+
+        if args.peek().map(|a| a == "curl") == Some(true) {
+            args.next();
+        }
+
+        let mut method: Option<HttpMethod> = None;
+        let mut url: Option<String> = None;
+        let mut headers: Vec<Header> = Vec::new();
+        let mut data: Vec<String> = Vec::new();
+        let mut cookies: Vec<String> = Vec::new();
+        let mut compressed = false;
+        let mut basic_auth: Option<(String, String)> = None;
+
+        while let Some(arg) = args.next() {
+            // Support `--opt value`, `--opt=value`, `-o value` and `-ovalue`
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((f, v)) if arg.starts_with("--") => (f.to_string(), Some(v.to_string())),
+                _ if !arg.starts_with("--")
+                    && arg.len() > 2
+                    && arg.get(..2).is_some_and(|f| {
+                        CURL_SHORT_VALUE_FLAGS.contains(&f) || CURL_IGNORED_VALUE_FLAGS.contains(&f)
+                    }) =>
+                {
+                    (arg[..2].to_string(), Some(arg[2..].to_string()))
+                }
+                _ => (arg.clone(), None),
+            };
+            let mut value = |name: &str| {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("Missing value for {}", name))
+            };
+
+            match flag.as_str() {
+                "-X" | "--request" => method = Some(value(&flag)?.parse()?),
+                "-H" | "--header" => {
+                    let header = value(&flag)?;
+                    if let Some((key, val)) = header.split_once(':') {
+                        headers.push(Header::new(key.trim().to_string(), val.trim().to_string()));
+                    }
+                }
+                "-d" | "--data" | "--data-binary" | "--data-ascii" => {
+                    let value = value(&flag)?;
+                    data.push(match value.strip_prefix('@') {
+                        Some(path) => read_curl_data_file(path, flag == "--data-binary")?,
+                        None => value,
+                    });
+                }
+                "--data-raw" | "--data-urlencode" => data.push(value(&flag)?),
+                "-b" | "--cookie" => cookies.push(value(&flag)?),
+                "-u" | "--user" => {
+                    let credentials = value(&flag)?;
+                    let (user, pass) = credentials.split_once(':').unwrap_or((credentials.as_str(), ""));
+                    basic_auth = Some((user.to_string(), pass.to_string()));
+                }
+                "-A" | "--user-agent" => headers.push(Header::new("User-Agent".to_string(), value(&flag)?)),
+                "-e" | "--referer" => headers.push(Header::new("Referer".to_string(), value(&flag)?)),
+                "-I" | "--head" => method = Some(HttpMethod::HEAD),
+                "--compressed" => compressed = true,
+                "--url" => url = Some(value(&flag)?),
+                // Options whose value we don't use but must not mistake for the URL
+                other if CURL_IGNORED_VALUE_FLAGS.contains(&other) => {
+                    value(&flag)?;
+                }
+                other if other.starts_with('-') && other.len() > 1 => {
+                    // Unknown flag, skip it
+                }
+                _ => {
+                    if url.is_none() {
+                        url = Some(arg);
+                    }
+                }
+            }
+        }
+
+        let url = url.ok_or("No URL found in curl command")?;
+
+        if !cookies.is_empty() {
+            // A value without `=` names a cookie file, which we can't read here
+            let cookie = cookies
+                .into_iter()
+                .filter(|c| c.contains('='))
+                .collect::<Vec<_>>()
+                .join("; ");
+            if !cookie.is_empty() {
+                headers.push(Header::new("Cookie".to_string(), cookie));
+            }
+        }
+
+        if compressed && !headers.iter().any(|h| h.key.eq_ignore_ascii_case("accept-encoding")) {
+            headers.push(Header::new("Accept-Encoding".to_string(), "gzip, deflate, br".to_string()));
+        }
+
+        let method = method.unwrap_or(if data.is_empty() { HttpMethod::GET } else { HttpMethod::POST });
+        let mut request = Request::new(url.clone(), method, url);
+        request.headers = headers;
+
+        if !data.is_empty() {
+            let raw = data.join("&");
+            let is_json = request.headers.iter().any(|h| {
+                h.key.eq_ignore_ascii_case("content-type") && h.value.to_ascii_lowercase().contains("json")
+            });
+            request.body = if is_json {
+                RequestBody::json(raw)
+            } else {
+                RequestBody::raw(raw)
+            };
+        }
+
+        if let Some((username, password)) = basic_auth {
+            request.auth = Some(AuthConfig::Basic { username, password });
+        }
+
+        Ok(request)
+    }
+}
+
+/// Short curl options that [`Request::from_curl`] reads a value for
+const CURL_SHORT_VALUE_FLAGS: [&str; 7] = ["-X", "-H", "-d", "-b", "-u", "-A", "-e"];
+
+/// Data for `-d @path`; curl drops line breaks unless it's `--data-binary`
+fn read_curl_data_file(path: &str, binary: bool) -> Result<String, String> {
+    if path == "-" {
+        return Err("Reading curl data from stdin is not supported".to_string());
+    }
+    let data = std::fs::read_to_string(path).map_err(|e| format!("Failed to read data file {}: {}", path, e))?;
+    Ok(if binary { data } else { data.replace(['\r', '\n'], "") })
+}
+
+/// curl options that take a value [`Request::from_curl`] doesn't use
+const CURL_IGNORED_VALUE_FLAGS: [&str; 40] = [
+    "-o", "--output", "-m", "--max-time", "--connect-timeout", "--retry", "--retry-delay",
+    "--retry-max-time", "-w", "--write-out", "-x", "--proxy", "-U", "--proxy-user", "--noproxy",
+    "--cacert", "--capath", "--cert", "-E", "--key", "--cert-type", "--key-type", "--pass",
+    "-F", "--form", "--form-string", "-T", "--upload-file", "--resolve", "--connect-to",
+    "-c", "--cookie-jar", "-D", "--dump-header", "-r", "--range", "--max-redirs", "--limit-rate",
+    "--interface", "--unix-socket",
+];
+
+/// Split a shell command line into words, honoring quotes and line continuations
+fn split_shell_words(input: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(ch) => current.push(ch),
+                        None => return Err("Unterminated single quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(ch @ ('"' | '\\' | '$' | '`')) => current.push(ch),
+                            Some(ch) => {
+                                current.push('\\');
+                                current.push(ch);
+                            }
+                            None => return Err("Unterminated double quote".to_string()),
+                        },
+                        Some(ch) => current.push(ch),
+                        None => return Err("Unterminated double quote".to_string()),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                // Line continuation
+                Some('\n') => {}
+                Some('\r') => {
+                    chars.next_if_eq(&'\n');
+                }
+                Some(ch) => {
+                    in_word = true;
+                    current.push(ch);
+                }
+                None => {}
+            },
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
+
+    if in_word {
+        words.push(current);
+    }
+
+    Ok(words)
 }
 
 impl Temporal for Request {
@@ -664,10 +1909,268 @@ impl RequestBuilder {
     }
 }
 
+/// Filter for [`find_requests`]; every condition that is set must match
+///
+/// Substring matches are case-insensitive.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestQuery {
+    pub method: Option<HttpMethod>,
+    pub url_contains: Option<String>,
+    pub name_contains: Option<String>,
+
+    /// Name of an enabled header the request must send
+    pub has_header: Option<String>,
+
+    /// Text in a raw/JSON body or in a form field value
+    pub body_contains: Option<String>,
+}
+
+impl RequestQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn method(mut self, method: HttpMethod) -> Self {
+        self.method = Some(method);
+        self
+    }
+
+    pub fn url_contains(mut self, text: impl Into<String>) -> Self {
+        self.url_contains = Some(text.into());
+        self
+    }
+
+    pub fn name_contains(mut self, text: impl Into<String>) -> Self {
+        self.name_contains = Some(text.into());
+        self
+    }
+
+    pub fn has_header(mut self, name: impl Into<String>) -> Self {
+        self.has_header = Some(name.into());
+        self
+    }
+
+    pub fn body_contains(mut self, text: impl Into<String>) -> Self {
+        self.body_contains = Some(text.into());
+        self
+    }
+
+    /// Whether `request` meets every condition
+    pub fn matches(&self, request: &Request) -> bool {
+        let contains = |haystack: &str, needle: &str| haystack.to_lowercase().contains(&needle.to_lowercase());
+
+        if let Some(method) = &self.method {
+            if request.method != *method {
+                return false;
+            }
+        }
+        if let Some(text) = &self.url_contains {
+            if !contains(&request.url.raw, text) {
+                return false;
+            }
+        }
+        if let Some(text) = &self.name_contains {
+            if !contains(&request.name, text) {
+                return false;
+            }
+        }
+        if let Some(name) = &self.has_header {
+            if !request.enabled_headers().iter().any(|h| h.key.eq_ignore_ascii_case(name)) {
+                return false;
+            }
+        }
+        if let Some(text) = &self.body_contains {
+            let found = match &request.body {
+                RequestBody::FormData { formdata: fields } | RequestBody::UrlEncoded { urlencoded: fields } => {
+                    fields.iter().any(|f| f.enabled && (contains(&f.key, text) || contains(&f.value, text)))
+                }
+                body => body.get_raw().is_some_and(|raw| contains(raw, text)),
+            };
+            if !found {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Requests matching `query`, in their original order
+pub fn find_requests<'a>(requests: &'a [Request], query: &RequestQuery) -> Vec<&'a Request> {
+    requests.iter().filter(|request| query.matches(request)).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_temporal_defaults() {
+        let mut request = Request::new("Users".to_string(), HttpMethod::GET, "https://example.com".to_string());
+        request.created_at -= 1_000;
+        request.updated_at = request.created_at;
+        assert!(!request.was_updated());
+        assert_eq!(request.age_ms(request.created_at + 250), 250);
+
+        request.add_example(RequestExample::new(
+            "Empty".to_string(),
+            &request,
+            crate::response::Response::new(204, "No Content".to_string()),
+        ));
+        assert!(request.was_updated());
+        assert_eq!(request.idle_ms(request.updated_at + 5), 5);
+        assert!(request.age_ms(request.updated_at) >= 1_000);
+    }
+
+    #[test]
+    fn test_sort_headers_and_query_params() {
+        let mut request = Request::new("Sort".to_string(), HttpMethod::GET, "https://example.com".to_string())
+            .with_header("X-Trace".to_string(), "1".to_string())
+            .with_header("accept".to_string(), "text/html".to_string())
+            .with_header("Authorization".to_string(), "Bearer a".to_string())
+            .with_header("Accept".to_string(), "application/json".to_string());
+        request.query_params = vec![
+            Param::new("page".to_string(), "2".to_string()),
+            Param::new("id".to_string(), "9".to_string()),
+            Param::new("ID".to_string(), "1".to_string()),
+        ];
+
+        request.sort_headers();
+        request.sort_query_params();
+        let headers: Vec<(&str, &str)> = request.headers.iter().map(|h| (h.key.as_str(), h.value.as_str())).collect();
+        assert_eq!(
+            headers,
+            vec![
+                ("accept", "text/html"),
+                ("Accept", "application/json"),
+                ("Authorization", "Bearer a"),
+                ("X-Trace", "1"),
+            ]
+        );
+        let params: Vec<(&str, &str)> = request.query_params.iter().map(|p| (p.key.as_str(), p.value.as_str())).collect();
+        assert_eq!(params, vec![("id", "9"), ("ID", "1"), ("page", "2")]);
+
+        let mut ordered = Request::new("Signed".to_string(), HttpMethod::GET, "https://example.com".to_string())
+            .with_header("X-Amz-Date".to_string(), "now".to_string())
+            .with_header("Host".to_string(), "example.com".to_string());
+        ordered.preserve_header_order = true;
+        ordered.sort_headers();
+        assert_eq!(ordered.headers[0].key, "X-Amz-Date");
+    }
+
+    #[test]
+    fn test_sort_query_params_rebuilds_raw_url() {
+        let mut request = Request::new(
+            "Search".to_string(),
+            HttpMethod::GET,
+            "https://example.com/search?q=a%20b&page=2#results".to_string(),
+        );
+        request.query_params = vec![
+            Param::new("q".to_string(), "a b".to_string()),
+            Param::new("page".to_string(), "2".to_string()),
+            Param::new("token".to_string(), "{{token}}".to_string()),
+        ];
+        request.query_params[2].enabled = false;
+        request.sort_query_params();
+        assert_eq!(request.url.raw, "https://example.com/search?page=2&q=a%20b#results");
+
+        // Without a query in the URL the params are appended at send time
+        let mut appended = Request::new("Search".to_string(), HttpMethod::GET, "https://example.com/search".to_string())
+            .with_query_param("q".to_string(), "x".to_string())
+            .with_query_param("a".to_string(), "{{a}}".to_string());
+        appended.sort_query_params();
+        assert_eq!(appended.url.raw, "https://example.com/search");
+    }
+
+    #[test]
+    fn test_to_multipart_bytes() {
+        let mut file = FormField::new("avatar".to_string(), String::new());
+        file.file = Some(FileField::new("me.png".to_string()));
+        let mut disabled = FormField::new("skip".to_string(), "x".to_string());
+        disabled.enabled = false;
+        let body = RequestBody::form_data(vec![
+            FormField::new("na\"me".to_string(), "Ada".to_string()),
+            disabled,
+            file,
+        ]);
+
+        let files = HashMap::from([("avatar".to_string(), vec![0x89, b'P', b'N', b'G'])]);
+        let (bytes, boundary) = body.to_multipart_bytes_with(&files).unwrap();
+        let mut expected = format!(
+            "--{b}\r\nContent-Disposition: form-data; name=\"na%22me\"\r\n\r\nAda\r\n\
+             --{b}\r\nContent-Disposition: form-data; name=\"avatar\"; filename=\"me.png\"\r\n\
+             Content-Type: image/png\r\n\r\n",
+            b = boundary
+        )
+        .into_bytes();
+        expected.extend_from_slice(&[0x89, b'P', b'N', b'G']);
+        expected.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+        assert_eq!(bytes, expected);
+
+        // CR/LF in a content type can't inject extra part headers
+        let mut crafted = FormField::new("avatar".to_string(), String::new());
+        crafted.file = Some(FileField::new("me.png".to_string()).with_content_type("image/png\r\nX-Injected: 1".to_string()));
+        let (bytes, _) = RequestBody::form_data(vec![crafted]).to_multipart_bytes_with(&files).unwrap();
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(text.contains("Content-Type: image/pngX-Injected: 1\r\n\r\n"));
+
+        // Without supplied contents the file needs a path
+        assert!(body.to_multipart_bytes().is_err());
+        assert!(RequestBody::json("{}".to_string()).to_multipart_bytes().is_err());
+    }
+
+    #[test]
+    fn test_estimated_len() {
+        assert_eq!(RequestBody::none().estimated_len(), 0);
+        assert_eq!(RequestBody::binary().estimated_len(), 0);
+        assert_eq!(RequestBody::json("{\"a\": 1}".to_string()).estimated_len(), 8);
+        assert_eq!(RequestBody::raw_with_charset("é".to_string(), "iso-8859-1".to_string()).estimated_len(), 1);
+        let form = RequestBody::url_encoded(vec![
+            FormField::new("q".to_string(), "a b".to_string()),
+            FormField::new("n".to_string(), "1".to_string()),
+        ]);
+        assert_eq!(form.estimated_len(), "q=a+b&n=1".len() as u64);
+    }
+
+    #[test]
+    fn test_to_multipart_bytes_reads_file_path() {
+        let path = std::env::temp_dir().join(format!("postboy-upload-{}.txt", new_id()));
+        std::fs::write(&path, "file contents").unwrap();
+        let mut field = FormField::new("doc".to_string(), String::new());
+        field.file = Some(FileField::new("notes.txt".to_string()).with_path(path.to_string_lossy().to_string()));
+
+        let body = RequestBody::form_data(vec![field, FormField::new("note".to_string(), "hi".to_string())]);
+        let (bytes, _) = body.to_multipart_bytes().unwrap();
+        assert_eq!(body.estimated_len(), bytes.len() as u64);
+        let text = String::from_utf8(bytes).unwrap();
+        assert!(text.contains("Content-Type: text/plain\r\n\r\nfile contents\r\n"));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_find_requests() {
+        let requests = vec![
+            Request::new("Create admin".to_string(), HttpMethod::POST, "https://example.com/admin/users".to_string())
+                .with_header("Content-Type".to_string(), "application/json".to_string()),
+            Request::new("List admins".to_string(), HttpMethod::GET, "https://example.com/ADMIN/users".to_string()),
+            Request::new("Create order".to_string(), HttpMethod::POST, "https://example.com/orders".to_string()),
+        ];
+
+        let admin_posts = find_requests(&requests, &RequestQuery::new().method(HttpMethod::POST).url_contains("/admin"));
+        assert_eq!(admin_posts.len(), 1);
+        assert_eq!(admin_posts[0].name, "Create admin");
+
+        assert_eq!(find_requests(&requests, &RequestQuery::new().url_contains("/admin")).len(), 2);
+        assert_eq!(find_requests(&requests, &RequestQuery::new().has_header("content-type")).len(), 1);
+        assert_eq!(find_requests(&requests, &RequestQuery::new().name_contains("create")).len(), 2);
+        assert_eq!(find_requests(&requests, &RequestQuery::new()).len(), 3);
+
+        let mut with_body = requests.clone();
+        with_body[2].body = RequestBody::json(r#"{"sku":"ABC-1"}"#.to_string());
+        let matched = find_requests(&with_body, &RequestQuery::new().body_contains("abc-1"));
+        assert_eq!(matched.iter().map(|r| r.id).collect::<Vec<_>>(), vec![with_body[2].id]);
+    }
+
     #[test]
     fn test_request_creation() {
         let request = Request::new(
@@ -702,7 +2205,25 @@ mod tests {
         assert_eq!(HttpMethod::from_str("GET"), Ok(HttpMethod::GET));
         assert_eq!(HttpMethod::from_str("get"), Ok(HttpMethod::GET));
         assert_eq!(HttpMethod::from_str("POST"), Ok(HttpMethod::POST));
-        assert!(HttpMethod::from_str("INVALID").is_err());
+        assert_eq!(HttpMethod::from_str("trace"), Ok(HttpMethod::TRACE));
+        assert_eq!(HttpMethod::from_str("CONNECT"), Ok(HttpMethod::CONNECT));
+        assert_eq!(HttpMethod::from_str("purge"), Ok(HttpMethod::Custom("PURGE".to_string())));
+        assert!(HttpMethod::from_str("NOT VALID").is_err());
+        assert!(HttpMethod::from_str("").is_err());
+    }
+
+    #[test]
+    fn test_custom_method_serde_roundtrip() {
+        let method = HttpMethod::Custom("PURGE".to_string());
+        let json = serde_json::to_string(&method).unwrap();
+        assert_eq!(json, "\"PURGE\"");
+        assert_eq!(serde_json::from_str::<HttpMethod>(&json).unwrap(), method);
+        assert_eq!(serde_json::to_string(&HttpMethod::TRACE).unwrap(), "\"TRACE\"");
+        assert!(serde_json::from_str::<HttpMethod>("\"BAD METHOD\"").is_err());
+
+        let request = Request::new("Purge".to_string(), method.clone(), "https://cdn.example.com/a".to_string());
+        let parsed: Request = serde_json::from_str(&serde_json::to_string(&request).unwrap()).unwrap();
+        assert_eq!(parsed.method, method);
     }
 
     #[test]
@@ -723,6 +2244,323 @@ mod tests {
         assert_eq!(enabled[0].key, "Accept");
     }
 
+    #[test]
+    fn test_normalize_headers_preserves_order() {
+        let mut request = Request::new(
+            "Test".to_string(),
+            HttpMethod::GET,
+            "https://example.com".to_string(),
+        )
+        .with_header("X-Amz-Date".to_string(), "1".to_string())
+        .with_header("Host".to_string(), "example.com".to_string())
+        .with_header("x-amz-date".to_string(), "2".to_string());
+
+        let mut canonical = request.clone();
+        canonical.headers[0].key = "x-amz-date".to_string();
+        canonical.normalize_headers();
+        let keys: Vec<&str> = canonical.headers.iter().map(|h| h.key.as_str()).collect();
+        assert_eq!(keys, vec!["X-Amz-Date", "Host"]);
+        assert_eq!(canonical.headers[0].value, "2");
+
+        request.headers[0].key = "X-AMZ-Date".to_string();
+
+        request.preserve_header_order = true;
+        request.normalize_headers();
+        let keys: Vec<&str> = request.headers.iter().map(|h| h.key.as_str()).collect();
+        assert_eq!(keys, vec!["X-AMZ-Date", "Host"]);
+        assert_eq!(request.headers[0].value, "2");
+    }
+
+    #[test]
+    fn test_normalize_mixed_case_duplicates() {
+        let mut request = Request::new(
+            "Test".to_string(),
+            HttpMethod::POST,
+            "https://example.com".to_string(),
+        )
+        .with_header("content-type".to_string(), "text/plain".to_string())
+        .with_header("x-api-key".to_string(), "abc".to_string())
+        .with_header("Content-Type".to_string(), "application/json".to_string())
+        .with_header("CONTENT-TYPE".to_string(), "application/json".to_string())
+        .with_header("etag".to_string(), "\"v1\"".to_string());
+        request.headers.push(Header { key: "Content-Type".to_string(), value: "application/json".to_string(), enabled: false });
+        request.headers.push(Header { key: "X-Debug".to_string(), value: "1".to_string(), enabled: false });
+
+        request.normalize_headers();
+
+        let headers: Vec<(&str, &str, bool)> = request
+            .headers
+            .iter()
+            .map(|h| (h.key.as_str(), h.value.as_str(), h.enabled))
+            .collect();
+        assert_eq!(headers, vec![
+            ("Content-Type", "application/json", true),
+            ("X-Api-Key", "abc", true),
+            ("ETag", "\"v1\"", true),
+            ("X-Debug", "1", false),
+        ]);
+    }
+
+    #[test]
+    fn test_from_curl_browser_style() {
+        let command = r#"curl 'https://api.example.com/users?page=2' \
+  -H 'accept: application/json' \
+  -H 'user-agent: Mozilla/5.0' \
+  -b 'session=abc123; theme=dark' \
+  --compressed \
+  --sec-fetch-unknown"#;
+
+        let request = Request::from_curl(command).unwrap();
+        assert_eq!(request.method, HttpMethod::GET);
+        assert_eq!(request.url.raw, "https://api.example.com/users?page=2");
+
+        let header = |name: &str| {
+            request.headers.iter()
+                .find(|h| h.key.eq_ignore_ascii_case(name))
+                .map(|h| h.value.as_str())
+        };
+        assert_eq!(header("accept"), Some("application/json"));
+        assert_eq!(header("user-agent"), Some("Mozilla/5.0"));
+        assert_eq!(header("Cookie"), Some("session=abc123; theme=dark"));
+        assert_eq!(header("Accept-Encoding"), Some("gzip, deflate, br"));
+        assert_eq!(request.headers.len(), 4);
+    }
+
+    #[test]
+    fn test_from_curl_data_and_url_option() {
+        let request = Request::from_curl(
+            r#"curl --url https://example.com/login -H "Content-Type: application/json" --data-raw '{"user":"a"}'"#,
+        )
+        .unwrap();
+
+        assert_eq!(request.method, HttpMethod::POST);
+        assert_eq!(request.url.raw, "https://example.com/login");
+        assert_eq!(request.body, RequestBody::json(r#"{"user":"a"}"#.to_string()));
+    }
+
+    #[test]
+    fn test_from_curl_attached_short_values() {
+        let request = Request::from_curl("curl -XPUT -H'Accept: text/plain' -uada:pw -o/dev/null https://x").unwrap();
+        assert_eq!(request.method, HttpMethod::PUT);
+        assert_eq!(request.url.raw, "https://x");
+        assert_eq!(request.headers[0].value, "text/plain");
+        assert_eq!(
+            request.auth,
+            Some(AuthConfig::Basic { username: "ada".to_string(), password: "pw".to_string() })
+        );
+    }
+
+    #[test]
+    fn test_from_curl_data_from_file() {
+        let dir = std::env::temp_dir().join(format!("postboy-curl-{}", crate::new_id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("body.json");
+        std::fs::write(&file, "{\"user\":\n\"a\"}\n").unwrap();
+
+        let command = format!("curl -H 'Content-Type: application/json' -d @{} https://x", file.display());
+        let request = Request::from_curl(&command).unwrap();
+        assert_eq!(request.method, HttpMethod::POST);
+        assert_eq!(request.body, RequestBody::json(r#"{"user":"a"}"#.to_string()));
+
+        let command = format!("curl --data-binary @{} https://x", file.display());
+        assert_eq!(Request::from_curl(&command).unwrap().body.get_raw(), Some("{\"user\":\n\"a\"}\n"));
+        let command = format!("curl --data-raw @{} https://x", file.display());
+        assert_eq!(Request::from_curl(&command).unwrap().body.get_raw(), Some(format!("@{}", file.display()).as_str()));
+
+        std::fs::remove_dir_all(dir).unwrap();
+        assert!(Request::from_curl(&format!("curl -d @{} https://x", file.display())).is_err());
+        assert!(Request::from_curl("curl -d @- https://x").is_err());
+    }
+
+    #[test]
+    fn test_from_curl_skips_values_of_unused_flags() {
+        let request = Request::from_curl("curl -F file=@a.txt https://x").unwrap();
+        assert_eq!(request.url.raw, "https://x");
+
+        let request = Request::from_curl(
+            "curl --resolve example.com:443:127.0.0.1 -c jar.txt --form-string a=b https://example.com/upload",
+        )
+        .unwrap();
+        assert_eq!(request.url.raw, "https://example.com/upload");
+    }
+
+    #[test]
+    fn test_deserialize_postman_request() {
+        let json = serde_json::json!({
+            "name": "Create user",
+            "method": "POST",
+            "header": [
+                {"key": "Content-Type", "value": "application/json"},
+                {"key": "X-Debug", "value": "1", "disabled": true}
+            ],
+            "url": {
+                "raw": "{{base_url}}/users?notify=true",
+                "host": ["{{base_url}}"],
+                "path": ["users"],
+                "query": [
+                    {"key": "notify", "value": "true"},
+                    {"key": "debug", "value": "1", "disabled": true, "description": "Verbose logs"}
+                ]
+            },
+            "body": {
+                "mode": "raw",
+                "raw": "{\"name\":\"a\"}",
+                "options": {"raw": {"language": "json"}}
+            },
+            "auth": {
+                "type": "bearer",
+                "bearer": [{"key": "token", "value": "abc", "type": "string"}]
+            }
+        });
+
+        let request: Request = serde_json::from_value(json).unwrap();
+        assert_eq!(request.name, "Create user");
+        assert_eq!(request.method, HttpMethod::POST);
+        assert_eq!(request.url.raw, "{{base_url}}/users");
+        assert_eq!(request.query_params.len(), 2);
+        assert_eq!((request.query_params[0].key.as_str(), request.query_params[0].value.as_str()), ("notify", "true"));
+        assert!(request.query_params[0].enabled);
+        assert!(!request.query_params[1].enabled);
+        assert_eq!(request.query_params[1].description.as_deref(), Some("Verbose logs"));
+        assert_eq!(request.headers.len(), 2);
+        assert!(request.headers[0].enabled);
+        assert!(!request.headers[1].enabled);
+        assert_eq!(request.body.get_raw(), Some("{\"name\":\"a\"}"));
+        assert_eq!(request.auth, Some(AuthConfig::Bearer { token: "abc".to_string() }));
+
+        // Our own format still round-trips
+        let round_trip: Request = serde_json::from_str(&serde_json::to_string(&request).unwrap()).unwrap();
+        assert_eq!(round_trip, request);
+    }
+
+    #[test]
+    fn test_raw_body_charset_encoding() {
+        let latin1 = RequestBody::raw_with_charset("café".to_string(), "ISO-8859-1".to_string());
+        assert_eq!(latin1.to_bytes(), vec![b'c', b'a', b'f', 0xE9]);
+
+        // UTF-8 by default
+        assert_eq!(RequestBody::raw("café".to_string()).to_bytes(), "café".as_bytes());
+
+        // Charset survives serialization and is optional when deserializing
+        let json = serde_json::to_string(&latin1).unwrap();
+        assert_eq!(serde_json::from_str::<RequestBody>(&json).unwrap(), latin1);
+        let legacy: RequestBody = serde_json::from_str(r#"{"mode":"raw","raw":"x","language":null}"#).unwrap();
+        assert_eq!(legacy, RequestBody::raw("x".to_string()));
+    }
+
+    #[test]
+    fn test_request_body_detected_language() {
+        let hinted = RequestBody::raw_with_language("{}".to_string(), "xml".to_string());
+        assert_eq!(hinted.detected_language(), Some(BodyLanguage::Xml));
+        assert_eq!(RequestBody::raw("[1, 2]".to_string()).detected_language(), Some(BodyLanguage::Json));
+        assert_eq!(RequestBody::raw("<html></html>".to_string()).detected_language(), Some(BodyLanguage::Html));
+        assert_eq!(RequestBody::json("{}".to_string()).detected_language(), Some(BodyLanguage::Json));
+        assert_eq!(RequestBody::binary().detected_language(), None);
+    }
+
+    #[test]
+    fn test_redact_json_paths() {
+        let body = RequestBody::json(r#"{"user":"ada","password":"hunter2","nested":{"token":"t"}}"#.to_string());
+        let RequestBody::Json { raw } = body.redact_json_paths(&["/password", "/nested/token", "/missing"]) else {
+            panic!("expected a JSON body");
+        };
+        let value: serde_json::Value = serde_json::from_str(&raw).unwrap();
+        assert_eq!(value["password"], REDACTED);
+        assert_eq!(value["nested"]["token"], REDACTED);
+        assert_eq!(value["user"], "ada");
+
+        let raw = RequestBody::raw("password=hunter2".to_string());
+        assert_eq!(raw.redact_json_paths(&["/password"]), raw);
+        let untouched = RequestBody::json("{\n  \"b\": 1,\n  \"a\": 2\n}".to_string());
+        assert_eq!(untouched.redact_json_paths(&["/password"]), untouched);
+    }
+
+    #[test]
+    fn test_redacted_request() {
+        let mut request = Request::new("Login".to_string(), HttpMethod::POST, "https://example.com/login".to_string())
+            .with_header("Authorization".to_string(), "Bearer abc".to_string())
+            .with_header("Accept".to_string(), "application/json".to_string());
+        request.body = RequestBody::json(r#"{"password":"hunter2"}"#.to_string());
+        request.auth = Some(AuthConfig::Basic {
+            username: "ada".to_string(),
+            password: "hunter2".to_string(),
+        });
+
+        let mut request = request.with_header("X-Auth-Token".to_string(), "t0k3n".to_string());
+        request.query_params = vec![
+            Param::new("access_token".to_string(), "abc".to_string()),
+            Param::new("page".to_string(), "2".to_string()),
+        ];
+
+        let redacted = request.redacted(&["/password"]);
+        assert_eq!(redacted.headers[0].value, REDACTED);
+        assert_eq!(redacted.headers[1].value, "application/json");
+        assert_eq!(redacted.headers[2].value, REDACTED);
+        assert_eq!(redacted.query_params[0].value, REDACTED);
+        assert_eq!(redacted.query_params[1].value, "2");
+        assert_eq!(request.headers[2].value, "t0k3n");
+        assert_eq!(redacted.url.raw, "https://example.com/login");
+        assert!(!redacted.body.get_raw().unwrap().contains("hunter2"));
+        assert_eq!(
+            redacted.auth,
+            Some(AuthConfig::Basic {
+                username: "ada".to_string(),
+                password: REDACTED.to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_redacted_masks_url_query() {
+        let mut request = Request::new(
+            "Callback".to_string(),
+            HttpMethod::GET,
+            "https://example.com/cb?access_token=abc&page=2&api_key=&x#top".to_string(),
+        );
+        request.url = Url::parse(request.url.raw.clone()).unwrap();
+
+        let redacted = request.redacted(&[]);
+        assert_eq!(redacted.url.raw, format!("https://example.com/cb?access_token={}&page=2&api_key=&x#top", REDACTED));
+        assert_eq!(redacted.url.query, Some(format!("access_token={}&page=2&api_key=&x", REDACTED)));
+        assert_eq!(redacted.url.hash.as_deref(), Some("top"));
+        assert!(request.url.raw.contains("access_token=abc"));
+    }
+
+    #[test]
+    fn test_compact_round_trip() {
+        let mut request = Request::new(
+            "Create user".to_string(),
+            HttpMethod::POST,
+            "{{base_url}}/users?notify=true".to_string(),
+        )
+        .with_header("Content-Type".to_string(), "application/json".to_string());
+        request.body = RequestBody::json(r#"{"name":"Ada"}"#.to_string());
+        request.auth = Some(AuthConfig::Basic { username: "ada".to_string(), password: "hunter2".to_string() });
+        request.collection_id = Some(crate::new_id());
+
+        let compact = request.to_compact();
+        assert!(compact.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        let json = base64::Engine::decode(&base64::engine::general_purpose::URL_SAFE_NO_PAD, &compact).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        for skipped in ["ui_state", "\"id\"", "collection_id", "examples", "hunter2"] {
+            assert!(!json.contains(skipped), "{} in {}", skipped, json);
+        }
+
+        let decoded = Request::from_compact(&compact).unwrap();
+        assert_ne!(decoded.id, request.id);
+        assert_eq!(
+            Request {
+                id: request.id,
+                collection_id: request.collection_id,
+                created_at: request.created_at,
+                updated_at: request.updated_at,
+                ..decoded
+            },
+            request.redacted(&[])
+        );
+        assert!(Request::from_compact("not*base64").is_err());
+    }
+
     #[test]
     fn test_request_duplicate() {
         let original = Request::new(
@@ -737,4 +2575,229 @@ mod tests {
         assert_eq!(copy.method, original.method);
         assert_eq!(copy.url.raw, original.url.raw);
     }
+
+    #[test]
+    fn test_timeout_and_redirect_overrides() {
+        let settings = UserSettings::default();
+        let mut request = Request::new(
+            "Report".to_string(),
+            HttpMethod::GET,
+            "https://example.com/report".to_string(),
+        );
+
+        assert_eq!(request.effective_timeout(&settings), settings.default_request_timeout_secs);
+        assert!(request.effective_follow_redirects(&settings));
+
+        // Inherited values are not serialized, so older requests still parse
+        let json = serde_json::to_value(&request).unwrap();
+        assert!(json.get("timeout_secs").is_none());
+        assert!(json.get("follow_redirects").is_none());
+
+        request.timeout_secs = Some(300);
+        request.follow_redirects = Some(false);
+        request.max_redirects = Some(2);
+        assert_eq!(request.effective_timeout(&settings), 300);
+        assert!(!request.effective_follow_redirects(&settings));
+
+        let parsed: Request = serde_json::from_str(&serde_json::to_string(&request).unwrap()).unwrap();
+        assert_eq!(parsed.timeout_secs, Some(300));
+        assert_eq!(parsed.follow_redirects, Some(false));
+        assert_eq!(parsed.max_redirects, Some(2));
+    }
+
+    #[test]
+    fn test_cache_key_with_vary() {
+        let json = Request::new("Users".to_string(), HttpMethod::GET, "https://example.com/users".to_string())
+            .with_header("Accept".to_string(), "application/json".to_string())
+            .with_header("X-Trace".to_string(), "1".to_string());
+        let xml = Request::new("Users".to_string(), HttpMethod::GET, "https://example.com/users".to_string())
+            .with_header("accept".to_string(), "application/xml".to_string())
+            .with_header("X-Trace".to_string(), "2".to_string());
+
+        let resolver = VariableResolver::new();
+        assert_eq!(json.cache_key(&resolver), xml.cache_key(&resolver));
+
+        let vary = vec!["Accept".to_string()];
+        assert_ne!(json.cache_key_with_vary(&resolver, &vary), xml.cache_key_with_vary(&resolver, &vary));
+        assert_eq!(
+            json.cache_key_with_vary(&resolver, &["accept".to_string(), " ACCEPT ".to_string()]),
+            json.cache_key_with_vary(&resolver, &vary),
+        );
+
+        // Headers not listed in Vary don't affect the key
+        let mut other_trace = json.clone();
+        other_trace.headers[1].value = "3".to_string();
+        assert_eq!(json.cache_key_with_vary(&resolver, &vary), other_trace.cache_key_with_vary(&resolver, &vary));
+        assert_ne!(
+            json.cache_key_with_vary(&resolver, &["*".to_string()]),
+            other_trace.cache_key_with_vary(&resolver, &["*".to_string()]),
+        );
+    }
+
+    #[test]
+    fn test_cache_key_covers_body_and_environment() {
+        let create = |body: &str| {
+            Request::new("Create".to_string(), HttpMethod::POST, "{{base}}/users".to_string())
+                .with_body(RequestBody::json(body.to_string()))
+        };
+        let env = |base: &str| {
+            VariableResolver::new().with_environment(HashMap::from([("base".to_string(), base.to_string())]))
+        };
+        let staging = env("https://staging.example.com");
+        let prod = env("https://example.com");
+
+        assert_eq!(create(r#"{"name":"a"}"#).cache_key(&staging), create(r#"{"name":"a"}"#).cache_key(&staging));
+        assert_ne!(create(r#"{"name":"a"}"#).cache_key(&staging), create(r#"{"name":"b"}"#).cache_key(&staging));
+        assert_ne!(create(r#"{"name":"a"}"#).cache_key(&staging), create(r#"{"name":"a"}"#).cache_key(&prod));
+        assert!(create("{}").cache_key(&prod).starts_with("POST https://example.com/users "));
+    }
+
+    #[test]
+    fn test_auth_apply() {
+        let mut headers = vec![Header::new("authorization".to_string(), "stale".to_string())];
+        let mut query = Vec::new();
+
+        AuthConfig::Basic {
+            username: "Aladdin".to_string(),
+            password: "open sesame".to_string(),
+        }
+        .apply(&mut headers, &mut query)
+        .unwrap();
+        assert_eq!(headers, vec![Header::new(
+            "Authorization".to_string(),
+            "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==".to_string(),
+        )]);
+
+        AuthConfig::Bearer { token: "abc".to_string() }.apply(&mut headers, &mut query).unwrap();
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[0].value, "Bearer abc");
+
+        let mut api_key = AuthConfig::ApiKey {
+            key: "X-Api-Key".to_string(),
+            value: "secret".to_string(),
+            add_to: ApiKeyLocation::Header,
+        };
+        api_key.apply(&mut headers, &mut query).unwrap();
+        assert_eq!(headers[1].key, "X-Api-Key");
+        assert!(query.is_empty());
+
+        if let AuthConfig::ApiKey { add_to, .. } = &mut api_key {
+            *add_to = ApiKeyLocation::Query;
+        }
+        let mut headers = Vec::new();
+        api_key.apply(&mut headers, &mut query).unwrap();
+        assert!(headers.is_empty());
+        assert_eq!(query, vec![Param::new("X-Api-Key".to_string(), "secret".to_string())]);
+
+        let digest = AuthConfig::Digest {
+            username: "user".to_string(),
+            password: "pass".to_string(),
+        };
+        assert_eq!(digest.apply(&mut headers, &mut query), Err(AuthError::NotImplemented("Digest")));
+        assert!(headers.is_empty());
+
+        let empty_bearer = AuthConfig::Bearer { token: String::new() };
+        assert_eq!(empty_bearer.apply(&mut headers, &mut query), Err(AuthError::MissingField("token")));
+        let colon = AuthConfig::Basic {
+            username: "a:b".to_string(),
+            password: "pass".to_string(),
+        };
+        assert!(matches!(colon.apply(&mut headers, &mut query), Err(AuthError::InvalidConfig(_))));
+        assert!(headers.is_empty());
+    }
+
+    #[test]
+    fn test_resolved_auth() {
+        let folder = AuthConfig::Bearer { token: "folder".to_string() };
+        let collection = AuthConfig::Basic {
+            username: "user".to_string(),
+            password: "pass".to_string(),
+        };
+        let mut request = Request::new("Users".to_string(), HttpMethod::GET, "https://example.com".to_string());
+
+        assert_eq!(request.resolved_auth(Some(&folder), Some(&collection)), Some(&folder));
+        assert_eq!(request.resolved_auth(None, Some(&collection)), Some(&collection));
+        assert_eq!(request.resolved_auth(None, None), None);
+
+        request.auth = Some(AuthConfig::Noauth);
+        assert_eq!(request.resolved_auth(Some(&folder), Some(&collection)), Some(&AuthConfig::Noauth));
+
+        request.auth = Some(AuthConfig::Bearer { token: "own".to_string() });
+        assert_eq!(request.resolved_auth(Some(&folder), Some(&collection)), request.auth.as_ref());
+    }
+
+    #[test]
+    fn test_summary_line() {
+        let request = Request::new(
+            "Users".to_string(),
+            HttpMethod::GET,
+            "https://api.example.com/v1/users?page=2&per_page=100&sort=created_at&order=desc#top".to_string(),
+        );
+        assert_eq!(request.summary_line(), "GET api.example.com/v1/users");
+
+        let templated = Request::new(
+            "Orders".to_string(),
+            HttpMethod::POST,
+            "{{base_url}}/orders/{{order_id}}/items?expand=true".to_string(),
+        );
+        assert_eq!(templated.summary_line(), "POST {{base_url}}/orders/{{order_id}}/items");
+
+        let deep = Request::new(
+            "Deep".to_string(),
+            HttpMethod::DELETE,
+            "https://api.example.com/organizations/acme/projects/website/environments/staging/secrets".to_string(),
+        );
+        assert_eq!(deep.summary_line(), "DELETE api.example.com/…/secrets");
+
+        let long_var = format!("{{{{base_url}}}}/{}{{{{resource_identifier}}}}", "a".repeat(40));
+        assert_eq!(shorten_url(&long_var, 60), format!("{{{{base_url}}}}/{}…", "a".repeat(40)));
+    }
+
+    #[test]
+    fn test_request_examples() {
+        let mut request = Request::new(
+            "Users".to_string(),
+            HttpMethod::GET,
+            "https://example.com/users".to_string(),
+        )
+        .with_header("Accept".to_string(), "application/json".to_string());
+
+        request.add_example(RequestExample::new(
+            "Success".to_string(),
+            &request,
+            crate::response::Response::new(200, "OK".to_string()),
+        ));
+        request.add_example(RequestExample::new(
+            "Not found".to_string(),
+            &request,
+            crate::response::Response::new(404, "Not Found".to_string()),
+        ));
+        request.add_example(RequestExample::new(
+            "Success".to_string(),
+            &request,
+            crate::response::Response::new(201, "Created".to_string()),
+        ));
+
+        assert_eq!(request.examples.len(), 2);
+        let success = request.example("Success").unwrap();
+        assert_eq!(success.response.status_code, 201);
+        assert_eq!(success.request.headers[0].key, "Accept");
+
+        let json = serde_json::to_string(&request).unwrap();
+        let parsed: Request = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.examples, request.examples);
+
+        assert_eq!(request.preferred_example().unwrap().name, "Success");
+        request.examples[1].is_default = true;
+        assert_eq!(request.preferred_example().unwrap().name, "Not found");
+
+        assert!(request.remove_example("Not found"));
+        assert!(!request.remove_example("Not found"));
+        assert!(request.example("Not found").is_none());
+
+        // Requests without examples serialize as before
+        request.remove_example("Success");
+        let json = serde_json::to_value(&request).unwrap();
+        assert!(json.get("examples").is_none());
+    }
 }
@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::Duration;
 
+use crate::request::{REDACTED, is_sensitive_name};
 use crate::{Timestamp, now};
 
 /// HTTP response from a request
@@ -18,6 +19,10 @@ pub struct Response {
     /// Response headers
     pub headers: Vec<ResponseHeader>,
 
+    /// Trailer headers sent after a chunked body (e.g. gRPC-web `grpc-status`)
+    #[serde(default)]
+    pub trailers: Vec<ResponseHeader>,
+
     /// Response body
     pub body: ResponseBody,
 
@@ -50,6 +55,7 @@ impl Response {
             status_code,
             status_text,
             headers: Vec::new(),
+            trailers: Vec::new(),
             body: ResponseBody::Empty,
             duration_ms: 0,
             size: 0,
@@ -66,6 +72,7 @@ impl Response {
             status_code: 0,
             status_text: "Error".to_string(),
             headers: Vec::new(),
+            trailers: Vec::new(),
             body: ResponseBody::Text(message),
             duration_ms: 0,
             size: 0,
@@ -109,6 +116,37 @@ impl Response {
             .map(String::from)
     }
 
+    /// Classify the body by its content-type header
+    pub fn content_type_category(&self) -> ContentCategory {
+        self.content_type()
+            .map(|ct| ContentCategory::from_content_type(&ct))
+            .unwrap_or(ContentCategory::Unknown)
+    }
+
+    /// Language to highlight the body as
+    ///
+    /// A JSON, XML, HTML or JavaScript content type wins; otherwise the body
+    /// is sniffed. Binary bodies are plain text.
+    pub fn detected_language(&self) -> BodyLanguage {
+        let text = match &self.body {
+            ResponseBody::Json(_) => return BodyLanguage::Json,
+            ResponseBody::Binary(_) => return BodyLanguage::Text,
+            ResponseBody::Empty => return BodyLanguage::Text,
+            ResponseBody::Streamed { content_type, .. } => {
+                return content_type
+                    .as_deref()
+                    .or(self.content_type().as_deref())
+                    .and_then(BodyLanguage::from_content_type)
+                    .unwrap_or(BodyLanguage::Text);
+            }
+            ResponseBody::Text(text) => text,
+        };
+
+        self.content_type()
+            .and_then(|ct| BodyLanguage::from_content_type(&ct))
+            .unwrap_or_else(|| BodyLanguage::sniff(text))
+    }
+
     /// Get a header value by name (case-insensitive)
     pub fn get_header(&self, name: &str) -> Option<&String> {
         self.headers
@@ -117,6 +155,22 @@ impl Response {
             .map(|h| &h.value)
     }
 
+    /// Headers followed by trailers, in the order they were received
+    pub fn all_headers(&self) -> impl Iterator<Item = &ResponseHeader> {
+        self.headers.iter().chain(&self.trailers)
+    }
+
+    /// Header names listed in `Vary`, for keying the response cache
+    pub fn vary_headers(&self) -> Vec<String> {
+        self.headers
+            .iter()
+            .filter(|h| h.name.eq_ignore_ascii_case("vary"))
+            .flat_map(|h| h.value.split(','))
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect()
+    }
+
     /// Parse response body as JSON
     pub fn json(&self) -> Result<serde_json::Value, JsonError> {
         match &self.body {
@@ -129,34 +183,291 @@ impl Response {
         }
     }
 
+    /// Whether the body is XML, by content type or sniffing
+    pub fn is_xml(&self) -> bool {
+        self.detected_language() == BodyLanguage::Xml
+    }
+
+    /// Parse response body as XML
+    #[cfg(feature = "xml")]
+    pub fn xml(&self) -> Result<XmlValue, String> {
+        let text = match &self.body {
+            ResponseBody::Empty => return Err("Response body is empty".to_string()),
+            ResponseBody::Json(_) => return Err("Response is not XML".to_string()),
+            _ => self.read_text().map_err(|e| format!("Failed to read body: {}", e))?,
+        };
+        let document = roxmltree::Document::parse(&text).map_err(|e| format!("Failed to parse XML: {}", e))?;
+        Ok(XmlValue::from_node(document.root_element()))
+    }
+
+    /// Look up an element's text or an attribute in the XML body
+    ///
+    /// The path is slash-separated element names starting at the root
+    /// element, e.g. `Envelope/Body/GetUserResponse/name`, optionally ending
+    /// in `@attr` to read an attribute (a path with `@attr` anywhere else
+    /// matches nothing). Names ignore namespace prefixes and
+    /// the first matching child is followed at each step. `Ok(None)` means
+    /// the body parsed but nothing matched.
+    #[cfg(feature = "xml")]
+    pub fn xml_path(&self, path: &str) -> Result<Option<String>, String> {
+        let root = self.xml()?;
+        let mut segments = path.trim_start_matches('/').split('/');
+        if segments.next() != Some(root.name.as_str()) {
+            return Ok(None);
+        }
+
+        let mut element = &root;
+        let mut segments = segments.peekable();
+        while let Some(segment) = segments.next() {
+            if let Some(attribute) = segment.strip_prefix('@') {
+                if segments.peek().is_some() {
+                    return Ok(None);
+                }
+                return Ok(element.attribute(attribute).map(String::from));
+            }
+            match element.child(segment) {
+                Some(child) => element = child,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(element.text.clone()))
+    }
+
+    /// Decode a binary body as the Protobuf message `message` into JSON
+    ///
+    /// `message` is a full name like `shop.v1.Order`, or a name relative to
+    /// the descriptor's package. Fields are named as in the Protobuf JSON
+    /// mapping (lowerCamelCase) and fields left at their default are omitted.
+    #[cfg(feature = "protobuf")]
+    pub fn decode_protobuf(
+        &self,
+        descriptor: &prost_reflect::FileDescriptor,
+        message: &str,
+    ) -> Result<serde_json::Value, String> {
+        if matches!(self.body, ResponseBody::Text(_) | ResponseBody::Json(_)) {
+            return Err("Response body is not binary".to_string());
+        }
+
+        let pool = descriptor.parent_pool();
+        let message_descriptor = pool
+            .get_message_by_name(message)
+            .or_else(|| pool.get_message_by_name(&format!("{}.{}", descriptor.package_name(), message)))
+            .ok_or_else(|| format!("Message type '{}' not found in {}", message, descriptor.name()))?;
+        let data = self.read_bytes().map_err(|e| format!("Failed to read body: {}", e))?;
+        let decoded = prost_reflect::DynamicMessage::decode(message_descriptor.clone(), data.as_slice()).map_err(|e| {
+            match protobuf_error_field(&message_descriptor, &data) {
+                Some(field) => format!("Failed to decode {} at field '{}': {}", message, field, e),
+                None => format!("Failed to decode {}: {}", message, e),
+            }
+        })?;
+        serde_json::to_value(&decoded).map_err(|e| e.to_string())
+    }
+
     /// Get response body as text
+    ///
+    /// Streamed bodies aren't loaded and give an empty string; use
+    /// [`read_text`](Response::read_text) for those.
     pub fn text(&self) -> String {
         match &self.body {
             ResponseBody::Text(text) => text.clone(),
             ResponseBody::Json(value) => value.to_string(),
-            ResponseBody::Empty => String::new(),
+            ResponseBody::Empty | ResponseBody::Streamed { .. } => String::new(),
+            ResponseBody::Binary(data) => String::from_utf8_lossy(data).to_string(),
+        }
+    }
+
+    /// Response body as text, reading streamed bodies into memory
+    ///
+    /// Only fails when a streamed file can't be read. Prefer
+    /// [`open_body_reader`](Response::open_body_reader) for large bodies.
+    pub fn read_text(&self) -> std::io::Result<String> {
+        match &self.body {
+            ResponseBody::Streamed { .. } => Ok(String::from_utf8_lossy(&self.read_bytes()?).to_string()),
+            _ => Ok(self.text()),
+        }
+    }
+
+    /// Body as 2-space indented JSON, or the raw text if it isn't JSON
+    ///
+    /// Text bodies are only reformatted when they parse as JSON and the
+    /// content type is JSON or missing. Key order and number formatting are
+    /// kept as received. Streamed bodies aren't loaded and give an empty
+    /// string; read those with [`open_body_reader`](Response::open_body_reader).
+    pub fn pretty_body(&self) -> String {
+        self.formatted_body(true)
+    }
+
+    /// Body as JSON with all insignificant whitespace removed, or the raw
+    /// text if it isn't JSON
+    pub fn minified_body(&self) -> String {
+        self.formatted_body(false)
+    }
+
+    fn formatted_body(&self, pretty: bool) -> String {
+        match &self.body {
+            ResponseBody::Json(value) if pretty => {
+                serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+            }
+            ResponseBody::Json(value) => value.to_string(),
+            ResponseBody::Text(text)
+                if matches!(self.content_type_category(), ContentCategory::Json | ContentCategory::Unknown)
+                    && serde_json::from_str::<serde::de::IgnoredAny>(text).is_ok() =>
+            {
+                reformat_json(text, pretty)
+            }
+            ResponseBody::Text(text) => text.clone(),
+            ResponseBody::Empty | ResponseBody::Streamed { .. } => String::new(),
             ResponseBody::Binary(data) => String::from_utf8_lossy(data).to_string(),
         }
     }
 
     /// Get response body as bytes
+    ///
+    /// Streamed bodies give no bytes, as for [`text`](Response::text); use
+    /// [`read_bytes`](Response::read_bytes) for those.
     pub fn bytes(&self) -> Vec<u8> {
         match &self.body {
             ResponseBody::Text(text) => text.as_bytes().to_vec(),
             ResponseBody::Json(value) => value.to_string().as_bytes().to_vec(),
-            ResponseBody::Empty => Vec::new(),
+            ResponseBody::Empty | ResponseBody::Streamed { .. } => Vec::new(),
             ResponseBody::Binary(data) => data.clone(),
         }
     }
 
+    /// Response body as bytes, reading streamed bodies into memory
+    ///
+    /// Only fails when a streamed file can't be read.
+    pub fn read_bytes(&self) -> std::io::Result<Vec<u8>> {
+        match &self.body {
+            ResponseBody::Streamed { path, .. } => std::fs::read(path),
+            _ => Ok(self.bytes()),
+        }
+    }
+
+    /// Copy of the response that is safe to share
+    ///
+    /// Masks `Set-Cookie`, headers and trailers echoing credentials back
+    /// (`Authorization`, `X-Auth-Token`, ...) and cookie values, using the
+    /// same rules as [`Request::redacted`](crate::request::Request::redacted).
+    /// The body is left as is.
+    pub fn redacted(&self) -> Response {
+        let mut response = self.clone();
+        for header in response.headers.iter_mut().chain(response.trailers.iter_mut()) {
+            let sensitive = header.name.eq_ignore_ascii_case("set-cookie") || is_sensitive_name(&header.name);
+            if sensitive && !header.value.is_empty() {
+                header.value = REDACTED.to_string();
+            }
+        }
+        for cookie in &mut response.cookies {
+            if !cookie.value.is_empty() {
+                cookie.value = REDACTED.to_string();
+            }
+        }
+        response
+    }
+
+    /// Read the body incrementally, whether it's held in memory or streamed
+    /// to disk
+    pub fn open_body_reader(&self) -> std::io::Result<Box<dyn std::io::Read>> {
+        match &self.body {
+            ResponseBody::Streamed { path, .. } => Ok(Box::new(std::fs::File::open(path)?)),
+            _ => Ok(Box::new(std::io::Cursor::new(self.bytes()))),
+        }
+    }
+
+    /// Write the body to `path` and return the file written
+    ///
+    /// When `path` is a directory the file name comes from the
+    /// `Content-Disposition` header, else `response` plus an extension
+    /// inferred from the content type. Existing files in the directory are
+    /// kept; the name gets a ` (1)`, ` (2)`, ... suffix instead. Bodies are
+    /// written as [`bytes`] returns them, so JSON is written serialized.
+    ///
+    /// [`bytes`]: Response::bytes
+    pub fn save_body(&self, path: &std::path::Path) -> std::io::Result<std::path::PathBuf> {
+        let path = if path.is_dir() {
+            let name = self
+                .get_header("content-disposition")
+                .and_then(|value| content_disposition_filename(value))
+                .unwrap_or_else(|| {
+                    let extension = self
+                        .content_type()
+                        .and_then(|ct| extension_for_content_type(&ct))
+                        .unwrap_or(match self.body {
+                            ResponseBody::Binary(_) | ResponseBody::Streamed { .. } => "bin",
+                            ResponseBody::Json(_) => "json",
+                            _ => "txt",
+                        });
+                    format!("response.{}", extension)
+                });
+            unused_file_path(path, &name)
+        } else {
+            path.to_path_buf()
+        };
+
+        match &self.body {
+            ResponseBody::Streamed { path: source, .. } => {
+                std::fs::copy(source, &path)?;
+            }
+            _ => std::fs::write(&path, self.bytes())?,
+        }
+        Ok(path)
+    }
+
+    /// Split a batch response into its sub-responses
+    ///
+    /// Understands `multipart/mixed` bodies (each part optionally an embedded
+    /// `HTTP/1.1 ...` response) and JSON batches: an array of
+    /// `{"status", "headers", "body"}` objects, or such an array under a
+    /// `responses` key. Returns `None` for anything else.
+    pub fn split_batch(&self) -> Option<Vec<Response>> {
+        let content_type = self.get_header("content-type").map(String::as_str).unwrap_or("");
+        let mime = content_type.split(';').next().unwrap_or("").trim();
+
+        let parts = if mime.eq_ignore_ascii_case("multipart/mixed") {
+            let boundary = content_type
+                .split(';')
+                .skip(1)
+                .filter_map(|param| param.trim().split_once('='))
+                .find(|(name, _)| name.trim().eq_ignore_ascii_case("boundary"))
+                .map(|(_, value)| value.trim().trim_matches('"').to_string())?;
+            split_multipart(&self.read_text().ok()?, &boundary)
+        } else {
+            let value = self.json().ok()?;
+            let items = match &value {
+                serde_json::Value::Array(items) => items,
+                serde_json::Value::Object(map) => map.get("responses")?.as_array()?,
+                _ => return None,
+            };
+            items.iter().map(json_batch_item).collect::<Option<Vec<_>>>()?
+        };
+
+        if parts.is_empty() {
+            return None;
+        }
+
+        Some(
+            parts
+                .into_iter()
+                .map(|mut part| {
+                    part.received_at = self.received_at;
+                    part
+                })
+                .collect(),
+        )
+    }
+
     /// Get formatted duration string
     pub fn duration_str(&self) -> String {
         format_duration(self.duration_ms)
     }
 
-    /// Get formatted size string
+    /// Get formatted size string (the file size for streamed bodies)
     pub fn size_str(&self) -> String {
-        format_bytes(self.size)
+        match &self.body {
+            ResponseBody::Streamed { size, .. } => format_bytes(*size),
+            _ => format_bytes(self.size),
+        }
     }
 
     /// Add a test result
@@ -186,6 +497,189 @@ impl Response {
     }
 }
 
+/// Build a sub-response from a `{"status", "headers", "body"}` batch item
+fn json_batch_item(item: &serde_json::Value) -> Option<Response> {
+    let item = item.as_object()?;
+    let status = item.get("status")?.as_u64()?;
+    let status_text = item
+        .get("statusText")
+        .or_else(|| item.get("status_text"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+
+    let mut response = Response::new(u16::try_from(status).ok()?, status_text.to_string());
+    if let Some(headers) = item.get("headers").and_then(|v| v.as_object()) {
+        response.headers = headers
+            .iter()
+            .map(|(name, value)| {
+                let value = value.as_str().map(String::from).unwrap_or_else(|| value.to_string());
+                ResponseHeader::new(name.clone(), value)
+            })
+            .collect();
+    }
+    response.body = match item.get("body") {
+        None | Some(serde_json::Value::Null) => ResponseBody::Empty,
+        Some(serde_json::Value::String(text)) => ResponseBody::Text(text.clone()),
+        Some(value) => ResponseBody::Json(value.clone()),
+    };
+    response.size = response.body.len() as u64;
+    Some(response)
+}
+
+/// Split a `multipart/mixed` body into one response per part
+fn split_multipart(body: &str, boundary: &str) -> Vec<Response> {
+    let delimiter = format!("--{}", boundary);
+    let mut responses = Vec::new();
+
+    // The first chunk is the preamble
+    for chunk in body.split(delimiter.as_str()).skip(1) {
+        if chunk.starts_with("--") {
+            break;
+        }
+        let chunk = chunk.trim_start_matches(['\r', '\n']);
+        let chunk = chunk
+            .strip_suffix("\r\n")
+            .or_else(|| chunk.strip_suffix('\n'))
+            .unwrap_or(chunk);
+
+        let (part_headers, content) = split_head(chunk);
+        let mut response = if content.starts_with("HTTP/") {
+            parse_http_message(content)
+        } else {
+            let mut response = Response::new(200, "OK".to_string());
+            response.headers = parse_header_lines(part_headers);
+            response.body = text_body(content, &response);
+            response
+        };
+        response.size = response.body.len() as u64;
+        responses.push(response);
+    }
+
+    responses
+}
+
+/// Parse an embedded `HTTP/1.1 200 OK` response message
+fn parse_http_message(message: &str) -> Response {
+    let (head, content) = split_head(message);
+    let mut lines = head.lines();
+    let status_line = lines.next().unwrap_or("");
+    let mut status = status_line.splitn(3, ' ').skip(1);
+    let status_code = status.next().and_then(|code| code.parse().ok()).unwrap_or(0);
+    let status_text = status.next().unwrap_or("").trim().to_string();
+
+    let mut response = Response::new(status_code, status_text);
+    response.headers = parse_header_lines(&lines.collect::<Vec<_>>().join("\n"));
+    response.body = text_body(content, &response);
+    response
+}
+
+/// Split a message into its header block and body at the first blank line
+fn split_head(message: &str) -> (&str, &str) {
+    let crlf = message.find("\r\n\r\n").map(|i| (i, 4));
+    let lf = message.find("\n\n").map(|i| (i, 2));
+    match (crlf, lf) {
+        (Some(a), Some(b)) => {
+            let (i, len) = if a.0 <= b.0 { a } else { b };
+            (&message[..i], &message[i + len..])
+        }
+        (Some((i, len)), None) | (None, Some((i, len))) => (&message[..i], &message[i + len..]),
+        (None, None) => (message, ""),
+    }
+}
+
+fn parse_header_lines(head: &str) -> Vec<ResponseHeader> {
+    head.lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| ResponseHeader::new(name.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+/// Body for a text part, parsed as JSON when the part says it is JSON
+fn text_body(content: &str, response: &Response) -> ResponseBody {
+    if content.is_empty() {
+        return ResponseBody::Empty;
+    }
+    if response.content_type_category() == ContentCategory::Json {
+        if let Ok(value) = serde_json::from_str(content) {
+            return ResponseBody::Json(value);
+        }
+    }
+    ResponseBody::Text(content.to_string())
+}
+
+/// Re-indent valid JSON text without reordering keys
+///
+/// With `pretty` unset all whitespace outside strings is dropped.
+fn reformat_json(text: &str, pretty: bool) -> String {
+    fn newline(out: &mut String, depth: usize) {
+        out.push('\n');
+        out.push_str(&"  ".repeat(depth));
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            '{' | '[' => {
+                out.push(c);
+                while chars.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+                    chars.next();
+                }
+                match chars.peek() {
+                    Some(&close @ ('}' | ']')) => {
+                        out.push(close);
+                        chars.next();
+                    }
+                    _ => {
+                        depth += 1;
+                        if pretty {
+                            newline(&mut out, depth);
+                        }
+                    }
+                }
+            }
+            '}' | ']' => {
+                depth = depth.saturating_sub(1);
+                if pretty {
+                    newline(&mut out, depth);
+                }
+                out.push(c);
+            }
+            ',' => {
+                out.push(c);
+                if pretty {
+                    newline(&mut out, depth);
+                }
+            }
+            ':' => out.push_str(if pretty { ": " } else { ":" }),
+            c if c.is_ascii_whitespace() => {}
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
 /// Response header
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ResponseHeader {
@@ -199,14 +693,27 @@ impl ResponseHeader {
     }
 }
 
+/// Body size above which senders should write the body to a file and use
+/// [`ResponseBody::Streamed`] rather than buffer it
+pub const STREAM_THRESHOLD_BYTES: u64 = 32 * 1024 * 1024;
+
 /// Response body types
+///
+/// Adjacently tagged: `Text`, `Json` and `Binary` wrap non-object values,
+/// which an internal tag can't serialize.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(tag = "type", rename_all = "lowercase")]
+#[serde(tag = "type", content = "value", rename_all = "lowercase")]
 pub enum ResponseBody {
     Empty,
     Text(String),
     Json(serde_json::Value),
     Binary(Vec<u8>),
+    /// Body spilled to a file on disk instead of held in memory
+    Streamed {
+        path: std::path::PathBuf,
+        size: u64,
+        content_type: Option<String>,
+    },
 }
 
 impl ResponseBody {
@@ -220,10 +727,270 @@ impl ResponseBody {
             ResponseBody::Text(s) => s.len(),
             ResponseBody::Json(v) => v.to_string().len(),
             ResponseBody::Binary(b) => b.len(),
+            ResponseBody::Streamed { size, .. } => *size as usize,
+        }
+    }
+}
+
+/// Broad category of a response body, derived from its content type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentCategory {
+    Json,
+    Xml,
+    Html,
+    Text,
+    Form,
+    Binary,
+    Unknown,
+}
+
+impl ContentCategory {
+    /// Classify a content-type value (parameters such as `charset` are ignored)
+    pub fn from_content_type(content_type: &str) -> Self {
+        let mime = content_type
+            .split(';')
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_ascii_lowercase();
+
+        let Some((kind, subtype)) = mime.split_once('/') else {
+            return ContentCategory::Unknown;
+        };
+
+        match (kind, subtype) {
+            (_, "json") | (_, "x-ndjson") => ContentCategory::Json,
+            (_, s) if s.ends_with("+json") => ContentCategory::Json,
+            ("text", "html") | ("application", "xhtml+xml") => ContentCategory::Html,
+            (_, "xml") => ContentCategory::Xml,
+            (_, s) if s.ends_with("+xml") => ContentCategory::Xml,
+            ("application", "x-www-form-urlencoded") | ("multipart", "form-data") => ContentCategory::Form,
+            ("text", _) => ContentCategory::Text,
+            ("application", "javascript") | ("application", "ecmascript") => ContentCategory::Text,
+            ("image", _) | ("audio", _) | ("video", _) | ("font", _) => ContentCategory::Binary,
+            ("application", "octet-stream") | ("application", "pdf") | ("application", "zip")
+            | ("application", "gzip") | ("application", "wasm") => ContentCategory::Binary,
+            _ => ContentCategory::Unknown,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentCategory::Json => "json",
+            ContentCategory::Xml => "xml",
+            ContentCategory::Html => "html",
+            ContentCategory::Text => "text",
+            ContentCategory::Form => "form",
+            ContentCategory::Binary => "binary",
+            ContentCategory::Unknown => "unknown",
+        }
+    }
+}
+
+/// Syntax-highlighting language for a request or response body
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BodyLanguage {
+    Json,
+    Xml,
+    Html,
+    JavaScript,
+    Text,
+}
+
+impl BodyLanguage {
+    /// Language implied by a content type, if it names a highlightable one
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        match ContentCategory::from_content_type(content_type) {
+            ContentCategory::Json => Some(BodyLanguage::Json),
+            ContentCategory::Xml => Some(BodyLanguage::Xml),
+            ContentCategory::Html => Some(BodyLanguage::Html),
+            _ if content_type.to_ascii_lowercase().contains("javascript") => Some(BodyLanguage::JavaScript),
+            _ => None,
+        }
+    }
+
+    /// Parse a Postman raw-body `language` hint (`json`, `xml`, `html`, ...)
+    pub fn from_hint(hint: &str) -> Option<Self> {
+        match hint.trim().to_ascii_lowercase().as_str() {
+            "json" => Some(BodyLanguage::Json),
+            "xml" => Some(BodyLanguage::Xml),
+            "html" => Some(BodyLanguage::Html),
+            "javascript" | "js" => Some(BodyLanguage::JavaScript),
+            "text" => Some(BodyLanguage::Text),
+            _ => None,
+        }
+    }
+
+    /// Guess the language from the start of the text
+    pub fn sniff(text: &str) -> Self {
+        let text = text.trim_start_matches('\u{feff}').trim_start();
+        if text.starts_with('{') || text.starts_with('[') {
+            return BodyLanguage::Json;
+        }
+        if !text.starts_with('<') {
+            return BodyLanguage::Text;
+        }
+
+        let head: String = text.chars().take(64).collect::<String>().to_ascii_lowercase();
+        if head.starts_with("<?xml") {
+            BodyLanguage::Xml
+        } else if ["<!doctype html", "<html", "<head", "<body"].iter().any(|tag| head.starts_with(tag)) {
+            BodyLanguage::Html
+        } else {
+            BodyLanguage::Xml
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BodyLanguage::Json => "json",
+            BodyLanguage::Xml => "xml",
+            BodyLanguage::Html => "html",
+            BodyLanguage::JavaScript => "javascript",
+            BodyLanguage::Text => "text",
+        }
+    }
+}
+
+/// File name from a `Content-Disposition` header, preferring the RFC 5987
+/// `filename*` form; directory parts are dropped so the name can't escape
+/// the target directory
+fn content_disposition_filename(value: &str) -> Option<String> {
+    let mut plain = None;
+    let mut extended = None;
+    for param in split_header_params(value).into_iter().skip(1) {
+        let Some((key, val)) = param.split_once('=') else {
+            continue;
+        };
+        let val = val.trim();
+        match key.trim().to_ascii_lowercase().as_str() {
+            "filename*" => {
+                // charset'language'percent-encoded
+                let encoded = val.splitn(3, '\'').nth(2).unwrap_or(val);
+                extended = Some(percent_decode(encoded));
+            }
+            "filename" => plain = Some(unquote(val)),
+            _ => {}
+        }
+    }
+
+    let name = extended.or(plain)?;
+    let name = name.rsplit(['/', '\\']).next().unwrap_or_default().trim();
+    (!name.is_empty() && name != "." && name != "..").then(|| name.to_string())
+}
+
+/// Split a header value on `;`, leaving separators inside quoted-strings alone
+fn split_header_params(value: &str) -> Vec<&str> {
+    let mut params = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                params.push(&value[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    params.push(&value[start..]);
+    params
+}
+
+/// Value of a header parameter, with quotes and backslash escapes removed
+/// from a quoted-string
+fn unquote(value: &str) -> String {
+    let Some(inner) = value.strip_prefix('"') else {
+        return value.to_string();
+    };
+    let inner = inner.strip_suffix('"').unwrap_or(inner);
+    let mut unquoted = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unquoted.extend(chars.next()),
+            c => unquoted.push(c),
+        }
+    }
+    unquoted
+}
+
+/// `dir/name`, or `dir/stem (n).ext` with the first `n` that isn't taken
+fn unused_file_path(dir: &std::path::Path, name: &str) -> std::path::PathBuf {
+    let candidate = dir.join(name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+        _ => (name, String::new()),
+    };
+    let mut n = 1;
+    loop {
+        let candidate = dir.join(format!("{} ({}){}", stem, n, extension));
+        if !candidate.exists() {
+            return candidate;
         }
+        n += 1;
     }
 }
 
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| text.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match hex {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// File extension (without the dot) for common content types
+fn extension_for_content_type(content_type: &str) -> Option<&'static str> {
+    let content_type = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    let extension = match content_type.as_str() {
+        "application/json" => "json",
+        "application/xml" | "text/xml" => "xml",
+        "text/html" => "html",
+        "text/plain" => "txt",
+        "text/csv" => "csv",
+        "text/css" => "css",
+        "application/javascript" | "text/javascript" => "js",
+        "application/pdf" => "pdf",
+        "application/zip" => "zip",
+        "application/gzip" => "gz",
+        "application/octet-stream" => "bin",
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/svg+xml" => "svg",
+        ct if ct.ends_with("+json") => "json",
+        ct if ct.ends_with("+xml") => "xml",
+        _ => return None,
+    };
+    Some(extension)
+}
+
 /// Cookie received in response
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Cookie {
@@ -244,6 +1011,105 @@ pub enum SameSite {
     None,
 }
 
+/// Prefix curl and wget put on the domain of HttpOnly cookies
+const NETSCAPE_HTTP_ONLY_PREFIX: &str = "#HttpOnly_";
+
+/// Cookies kept across requests
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CookieJar {
+    pub cookies: Vec<Cookie>,
+}
+
+impl CookieJar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a cookie, replacing one with the same name, domain and path
+    pub fn insert(&mut self, cookie: Cookie) {
+        match self
+            .cookies
+            .iter_mut()
+            .find(|c| c.name == cookie.name && c.domain == cookie.domain && c.path == cookie.path)
+        {
+            Some(existing) => *existing = cookie,
+            None => self.cookies.push(cookie),
+        }
+    }
+
+    /// Parse a Netscape `cookies.txt` file as written by curl and wget
+    ///
+    /// Each line is `domain include_subdomains path secure expiry name value`,
+    /// tab-separated, with expiry in Unix seconds (0 for a session cookie).
+    /// A `#HttpOnly_` domain prefix marks an HttpOnly cookie; other `#`
+    /// lines are comments. Malformed lines are skipped.
+    pub fn from_netscape(text: &str) -> Self {
+        let mut jar = Self::new();
+
+        for line in text.lines() {
+            let line = line.trim_end_matches('\r');
+            let (line, http_only) = match line.strip_prefix(NETSCAPE_HTTP_ONLY_PREFIX) {
+                Some(rest) => (rest, true),
+                None if line.starts_with('#') || line.trim().is_empty() => continue,
+                None => (line, false),
+            };
+
+            let fields: Vec<&str> = line.splitn(7, '\t').collect();
+            let [domain, include_subdomains, path, secure, expiry, name, value] = fields[..] else {
+                continue;
+            };
+            let Ok(expiry) = expiry.trim().parse::<i64>() else {
+                continue;
+            };
+
+            // The subdomain flag is carried by a leading dot on the domain
+            let domain = if include_subdomains.eq_ignore_ascii_case("TRUE") && !domain.starts_with('.') {
+                format!(".{}", domain)
+            } else {
+                domain.to_string()
+            };
+            jar.insert(Cookie {
+                name: name.to_string(),
+                value: value.to_string(),
+                domain: Some(domain),
+                path: Some(path.to_string()).filter(|p| !p.is_empty()),
+                expires: (expiry > 0).then(|| expiry.saturating_mul(1000)),
+                http_only,
+                secure: secure.eq_ignore_ascii_case("TRUE"),
+                same_site: None,
+            });
+        }
+
+        jar
+    }
+
+    /// Write the jar as a Netscape `cookies.txt` file
+    ///
+    /// Expiry is rounded down to whole seconds; session cookies get 0. Cookies
+    /// without a domain can't be represented and are left out.
+    pub fn to_netscape(&self) -> String {
+        let mut out = String::from("# Netscape HTTP Cookie File\n");
+        for cookie in &self.cookies {
+            let Some(domain) = cookie.domain.as_deref() else {
+                continue;
+            };
+            let flag = |value: bool| if value { "TRUE" } else { "FALSE" };
+            out.push_str(&format!(
+                "{}{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                if cookie.http_only { NETSCAPE_HTTP_ONLY_PREFIX } else { "" },
+                domain,
+                flag(domain.starts_with('.')),
+                cookie.path.as_deref().unwrap_or("/"),
+                flag(cookie.secure),
+                cookie.expires.map_or(0, |ms| ms.div_euclid(1000)),
+                cookie.name,
+                cookie.value,
+            ));
+        }
+        out
+    }
+}
+
 /// Test result from post-response script
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TestResult {
@@ -277,6 +1143,38 @@ impl TestResult {
             duration_ms: None,
         }
     }
+
+    /// Pass if `actual_ms` is strictly under `budget_ms`
+    pub fn assert_duration_under(name: String, actual_ms: u64, budget_ms: u64) -> Self {
+        if actual_ms < budget_ms {
+            Self::passed(name)
+        } else {
+            let message = format!(
+                "Response took {}, budget is under {}",
+                format_duration(actual_ms),
+                format_duration(budget_ms)
+            );
+            Self::failed(name, message)
+        }
+    }
+
+    /// Pass if the status code matches
+    pub fn assert_status(name: String, actual: u16, expected: u16) -> Self {
+        if actual == expected {
+            Self::passed(name)
+        } else {
+            Self::failed(name, format!("Expected status {}, got {}", expected, actual))
+        }
+    }
+
+    /// Pass if `response` has the header (case-insensitive)
+    pub fn assert_header_present(name: String, response: &Response, header: &str) -> Self {
+        if response.get_header(header).is_some() {
+            Self::passed(name)
+        } else {
+            Self::failed(name, format!("Expected header '{}' in response", header))
+        }
+    }
 }
 
 /// Response error
@@ -287,6 +1185,109 @@ pub struct ResponseError {
     pub stack: Option<String>,
 }
 
+/// Element of a parsed XML body
+///
+/// Names are local names; namespace prefixes are dropped.
+#[cfg(feature = "xml")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct XmlValue {
+    pub name: String,
+    /// Attributes in document order
+    pub attributes: Vec<(String, String)>,
+    /// Text directly inside this element, trimmed
+    pub text: String,
+    pub children: Vec<XmlValue>,
+}
+
+#[cfg(feature = "xml")]
+impl XmlValue {
+    fn from_node(node: roxmltree::Node) -> Self {
+        let text: String = node
+            .children()
+            .filter(|child| child.is_text())
+            .filter_map(|child| child.text())
+            .collect();
+        Self {
+            name: node.tag_name().name().to_string(),
+            attributes: node
+                .attributes()
+                .map(|attr| (attr.name().to_string(), attr.value().to_string()))
+                .collect(),
+            text: text.trim().to_string(),
+            children: node.children().filter(|child| child.is_element()).map(Self::from_node).collect(),
+        }
+    }
+
+    /// First child element with this name
+    pub fn child(&self, name: &str) -> Option<&XmlValue> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    /// Value of an attribute
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Dotted path to the first field in `data` that fails to decode
+///
+/// Walks the top-level wire format and decodes each field on its own,
+/// descending into nested messages. Groups aren't supported.
+#[cfg(feature = "protobuf")]
+fn protobuf_error_field(message: &prost_reflect::MessageDescriptor, mut data: &[u8]) -> Option<String> {
+    use prost_reflect::prost::encoding::{decode_key, decode_varint, WireType};
+
+    while !data.is_empty() {
+        let start = data;
+        let (number, wire_type) = decode_key(&mut data).ok()?;
+        let field = message.get_field(number);
+        let name = field
+            .as_ref()
+            .map(|f| f.name().to_string())
+            .unwrap_or_else(|| number.to_string());
+
+        let mut rest = data;
+        let payload = match wire_type {
+            WireType::Varint => decode_varint(&mut rest).ok().map(|_| &data[..0]),
+            WireType::SixtyFourBit => take(&mut rest, 8),
+            WireType::ThirtyTwoBit => take(&mut rest, 4),
+            WireType::LengthDelimited => decode_varint(&mut rest).ok().and_then(|len| take(&mut rest, len as usize)),
+            WireType::StartGroup | WireType::EndGroup => return None,
+        };
+        // Truncated
+        let Some(payload) = payload else {
+            return Some(name);
+        };
+
+        let encoded = &start[..start.len() - rest.len()];
+        if prost_reflect::DynamicMessage::decode(message.clone(), encoded).is_err() {
+            let nested = match field.map(|f| f.kind()) {
+                Some(prost_reflect::Kind::Message(inner)) if wire_type == WireType::LengthDelimited => {
+                    protobuf_error_field(&inner, payload)
+                }
+                _ => None,
+            };
+            return Some(match nested {
+                Some(nested) => format!("{}.{}", name, nested),
+                None => name,
+            });
+        }
+        data = rest;
+    }
+    None
+}
+
+/// Split `n` bytes off the front of `data`
+#[cfg(feature = "protobuf")]
+fn take<'a>(data: &mut &'a [u8], n: usize) -> Option<&'a [u8]> {
+    let taken = data.get(..n)?;
+    *data = &data[n..];
+    Some(taken)
+}
+
 /// JSON parsing error
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 pub enum JsonError {
@@ -331,6 +1332,151 @@ pub fn format_bytes(bytes: u64) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_redacted_response() {
+        let mut response = Response::new(200, "OK".to_string());
+        response.headers = vec![
+            ResponseHeader::new("Set-Cookie".to_string(), "sid=abc; HttpOnly".to_string()),
+            ResponseHeader::new("X-Auth-Token".to_string(), "t0k3n".to_string()),
+            ResponseHeader::new("Content-Type".to_string(), "application/json".to_string()),
+        ];
+        response.cookies.push(Cookie {
+            name: "sid".to_string(),
+            value: "abc".to_string(),
+            domain: None,
+            path: None,
+            expires: None,
+            http_only: true,
+            secure: false,
+            same_site: None,
+        });
+
+        let redacted = response.redacted();
+        let values: Vec<&str> = redacted.headers.iter().map(|h| h.value.as_str()).collect();
+        assert_eq!(values, vec![REDACTED, REDACTED, "application/json"]);
+        assert_eq!(redacted.cookies[0].value, REDACTED);
+        assert_eq!(response.headers[0].value, "sid=abc; HttpOnly");
+    }
+
+    #[test]
+    fn test_streamed_body() {
+        let dir = std::env::temp_dir().join(format!("postboy-streamed-{}", crate::new_id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("body.bin");
+        std::fs::write(&file, b"large body").unwrap();
+
+        let mut response = Response::new(200, "OK".to_string());
+        response.body = ResponseBody::Streamed {
+            path: file.clone(),
+            size: 10,
+            content_type: Some("application/octet-stream".to_string()),
+        };
+        assert_eq!(response.body.len(), 10);
+        assert_eq!(response.size_str(), format_bytes(10));
+        assert_eq!(response.detected_language(), BodyLanguage::Text);
+
+        let mut read = String::new();
+        response.open_body_reader().unwrap().read_to_string(&mut read).unwrap();
+        assert_eq!(read, "large body");
+        assert_eq!(response.read_text().unwrap(), "large body");
+        assert_eq!(response.read_bytes().unwrap(), b"large body");
+        assert_eq!(response.text(), "");
+        assert_eq!(response.pretty_body(), "");
+
+        let saved = response.save_body(&dir.join("copy.bin")).unwrap();
+        assert_eq!(std::fs::read(saved).unwrap(), b"large body");
+
+        let inline = Response::new(200, "OK".to_string());
+        let mut read = Vec::new();
+        inline.open_body_reader().unwrap().read_to_end(&mut read).unwrap();
+        assert!(read.is_empty());
+
+        std::fs::remove_dir_all(dir).unwrap();
+        // A missing file is an error, not an empty body
+        assert!(response.read_text().is_err());
+        assert!(response.read_bytes().is_err());
+    }
+
+    #[test]
+    fn test_save_body() {
+        let dir = std::env::temp_dir().join(format!("postboy-save-body-{}", crate::new_id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut pdf = Response::new(200, "OK".to_string());
+        pdf.headers.push(ResponseHeader::new("Content-Type".to_string(), "application/pdf".to_string()));
+        pdf.body = ResponseBody::Binary(vec![0x25, 0x50, 0x44, 0x46]);
+        let path = pdf.save_body(&dir).unwrap();
+        assert_eq!(path, dir.join("response.pdf"));
+        assert_eq!(std::fs::read(&path).unwrap(), vec![0x25, 0x50, 0x44, 0x46]);
+
+        pdf.headers.push(ResponseHeader::new(
+            "Content-Disposition".to_string(),
+            "attachment; filename=\"../report.pdf\"; filename*=UTF-8''r%C3%A9sum%C3%A9.pdf".to_string(),
+        ));
+        assert_eq!(pdf.save_body(&dir).unwrap(), dir.join("résumé.pdf"));
+
+        // Saving again into the directory doesn't overwrite earlier files
+        assert_eq!(pdf.save_body(&dir).unwrap(), dir.join("résumé (1).pdf"));
+        assert_eq!(pdf.save_body(&dir).unwrap(), dir.join("résumé (2).pdf"));
+        assert!(dir.join("résumé.pdf").exists());
+
+        let mut json = Response::new(200, "OK".to_string());
+        json.body = ResponseBody::Json(serde_json::json!({"ok": true}));
+        let path = json.save_body(&dir).unwrap();
+        assert_eq!(path, dir.join("response.json"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"ok\":true}");
+
+        let explicit = dir.join("out.txt");
+        assert_eq!(json.save_body(&explicit).unwrap(), explicit);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_content_disposition_filename() {
+        assert_eq!(content_disposition_filename("attachment; filename=\"a b.txt\"").as_deref(), Some("a b.txt"));
+        assert_eq!(content_disposition_filename("attachment; filename=../../etc/passwd").as_deref(), Some("passwd"));
+        assert_eq!(content_disposition_filename("attachment; filename=\"..\""), None);
+        assert_eq!(content_disposition_filename("inline"), None);
+        assert_eq!(
+            content_disposition_filename("attachment; filename=\"a;b.txt\"; size=3").as_deref(),
+            Some("a;b.txt")
+        );
+        assert_eq!(
+            content_disposition_filename(r#"attachment; filename="say \"hi\".txt""#).as_deref(),
+            Some("say \"hi\".txt")
+        );
+    }
+
+    #[test]
+    fn test_cookie_jar_netscape() {
+        let text = "# Netscape HTTP Cookie File\n\
+                    # https://curl.se/docs/http-cookies.html\n\
+                    \n\
+                    .example.com\tTRUE\t/\tTRUE\t1893456000\tsession\tabc\tdef\n\
+                    #HttpOnly_api.example.com\tFALSE\t/v1\tFALSE\t0\tcsrf\txyz\n\
+                    not a cookie line\n\
+                    example.org\tTRUE\t/\tFALSE\tsoon\tbad\tvalue\n";
+
+        let jar = CookieJar::from_netscape(text);
+        assert_eq!(jar.cookies.len(), 2);
+        let session = &jar.cookies[0];
+        assert_eq!(session.domain.as_deref(), Some(".example.com"));
+        assert_eq!(session.value, "abc\tdef");
+        assert_eq!(session.expires, Some(1_893_456_000_000));
+        assert!(session.secure && !session.http_only);
+        let csrf = &jar.cookies[1];
+        assert_eq!(csrf.domain.as_deref(), Some("api.example.com"));
+        assert_eq!(csrf.path.as_deref(), Some("/v1"));
+        assert_eq!(csrf.expires, None);
+        assert!(csrf.http_only && !csrf.secure);
+
+        let exported = jar.to_netscape();
+        assert!(exported.contains("#HttpOnly_api.example.com\tFALSE\t/v1\tFALSE\t0\tcsrf\txyz\n"));
+        assert_eq!(CookieJar::from_netscape(&exported), jar);
+    }
 
     #[test]
     fn test_response_creation() {
@@ -371,6 +1517,145 @@ mod tests {
         assert_eq!(parsed, json_value);
     }
 
+    #[test]
+    fn test_response_trailers() {
+        let mut response = Response::new(200, "OK".to_string());
+        response.headers.push(ResponseHeader::new("Content-Type".to_string(), "application/grpc-web".to_string()));
+        response.trailers.push(ResponseHeader::new("grpc-status".to_string(), "0".to_string()));
+
+        let names: Vec<&str> = response.all_headers().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, vec!["Content-Type", "grpc-status"]);
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(serde_json::from_value::<Response>(json.clone()).unwrap(), response);
+
+        // Responses saved before trailers existed still load
+        let mut legacy = json;
+        legacy.as_object_mut().unwrap().remove("trailers");
+        assert!(serde_json::from_value::<Response>(legacy).unwrap().trailers.is_empty());
+    }
+
+    #[test]
+    fn test_detected_language() {
+        let mut response = Response::new(200, "OK".to_string());
+        response.body = ResponseBody::Text("  {\"id\": 1}".to_string());
+        assert_eq!(response.detected_language(), BodyLanguage::Json);
+
+        response.body = ResponseBody::Text("<?xml version=\"1.0\"?><a/>".to_string());
+        assert_eq!(response.detected_language(), BodyLanguage::Xml);
+        response.body = ResponseBody::Text("<!DOCTYPE html><html></html>".to_string());
+        assert_eq!(response.detected_language(), BodyLanguage::Html);
+        response.body = ResponseBody::Text("hello".to_string());
+        assert_eq!(response.detected_language(), BodyLanguage::Text);
+
+        // The content type takes precedence over sniffing
+        response.headers.push(ResponseHeader::new("Content-Type".to_string(), "text/html; charset=utf-8".to_string()));
+        response.body = ResponseBody::Text("[not json]".to_string());
+        assert_eq!(response.detected_language(), BodyLanguage::Html);
+    }
+
+    #[test]
+    fn test_content_type_category() {
+        let mut response = Response::new(200, "OK".to_string());
+        assert_eq!(response.content_type_category(), ContentCategory::Unknown);
+
+        response.headers.push(ResponseHeader::new(
+            "Content-Type".to_string(),
+            "application/vnd.api+json; charset=utf-8".to_string(),
+        ));
+        assert_eq!(response.content_type_category(), ContentCategory::Json);
+
+        assert_eq!(ContentCategory::from_content_type("image/png"), ContentCategory::Binary);
+        assert_eq!(ContentCategory::from_content_type("application/soap+xml"), ContentCategory::Xml);
+        assert_eq!(ContentCategory::from_content_type("text/html"), ContentCategory::Html);
+        assert_eq!(ContentCategory::from_content_type("text/plain"), ContentCategory::Text);
+        assert_eq!(ContentCategory::from_content_type("multipart/form-data; boundary=x"), ContentCategory::Form);
+    }
+
+    #[test]
+    fn test_split_json_batch() {
+        let mut response = Response::new(207, "Multi-Status".to_string());
+        response.headers.push(ResponseHeader::new("Content-Type".to_string(), "application/json".to_string()));
+        response.body = ResponseBody::Json(serde_json::json!([
+            { "status": 201, "headers": { "Location": "/users/1" }, "body": { "id": 1 } },
+            { "status": 409, "body": "duplicate email" },
+        ]));
+
+        let parts = response.split_batch().unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].status_code, 201);
+        assert_eq!(parts[0].get_header("location").map(String::as_str), Some("/users/1"));
+        assert_eq!(parts[0].json().unwrap(), serde_json::json!({ "id": 1 }));
+        assert_eq!(parts[1].status_code, 409);
+        assert_eq!(parts[1].text(), "duplicate email");
+        assert_eq!(parts[1].received_at, response.received_at);
+
+        // Not batches
+        response.body = ResponseBody::Json(serde_json::json!([1, 2]));
+        assert!(response.split_batch().is_none());
+        response.body = ResponseBody::Json(serde_json::json!({ "id": 1 }));
+        assert!(response.split_batch().is_none());
+        assert!(Response::new(200, "OK".to_string()).split_batch().is_none());
+    }
+
+    #[test]
+    fn test_split_multipart_batch() {
+        let mut response = Response::new(200, "OK".to_string());
+        response.headers.push(ResponseHeader::new(
+            "Content-Type".to_string(),
+            "multipart/mixed; boundary=\"batch_abc\"".to_string(),
+        ));
+        response.body = ResponseBody::Text(
+            "preamble\r\n--batch_abc\r\nContent-Type: application/http\r\n\r\n\
+             HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{\"ok\":true}\r\n\
+             --batch_abc\r\nContent-Type: application/http\r\n\r\n\
+             HTTP/1.1 404 Not Found\r\n\r\n\r\n\
+             --batch_abc--\r\n"
+                .to_string(),
+        );
+
+        let parts = response.split_batch().unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].status_code, 200);
+        assert_eq!(parts[0].json().unwrap(), serde_json::json!({ "ok": true }));
+        assert_eq!(parts[1].status_code, 404);
+        assert_eq!(parts[1].status_text, "Not Found");
+        assert!(parts[1].body.is_empty());
+    }
+
+    #[test]
+    fn test_vary_headers() {
+        let mut response = Response::new(200, "OK".to_string());
+        assert!(response.vary_headers().is_empty());
+
+        response.headers.push(ResponseHeader::new("Vary".to_string(), "Accept, Accept-Encoding".to_string()));
+        response.headers.push(ResponseHeader::new("vary".to_string(), "Origin".to_string()));
+        assert_eq!(response.vary_headers(), vec!["Accept", "Accept-Encoding", "Origin"]);
+    }
+
+    #[test]
+    fn test_pretty_and_minified_body() {
+        let mut response = Response::new(200, "OK".to_string());
+        response.body = ResponseBody::Text("{ \"b\": [1, 2.50, {}], \"a\" : \"x, y: {z}\" }".to_string());
+        assert_eq!(
+            response.pretty_body(),
+            "{\n  \"b\": [\n    1,\n    2.50,\n    {}\n  ],\n  \"a\": \"x, y: {z}\"\n}"
+        );
+        assert_eq!(response.minified_body(), "{\"b\":[1,2.50,{}],\"a\":\"x, y: {z}\"}");
+
+        response.body = ResponseBody::Json(serde_json::json!({"id": 1}));
+        assert_eq!(response.pretty_body(), "{\n  \"id\": 1\n}");
+        assert_eq!(response.minified_body(), "{\"id\":1}");
+
+        // Non-JSON content types and unparseable text pass through
+        response.headers.push(ResponseHeader::new("Content-Type".to_string(), "text/html".to_string()));
+        response.body = ResponseBody::Text("[ 1 ]".to_string());
+        assert_eq!(response.pretty_body(), "[ 1 ]");
+        response.headers.clear();
+        response.body = ResponseBody::Text("{ not json".to_string());
+        assert_eq!(response.minified_body(), "{ not json");
+    }
+
     #[test]
     fn test_format_duration() {
         assert_eq!(format_duration(100), "100ms");
@@ -399,4 +1684,131 @@ mod tests {
         assert_eq!(response.failed_tests().len(), 1);
         assert!(!response.all_tests_passed());
     }
+
+    #[test]
+    fn test_is_xml() {
+        let mut response = Response::new(200, "OK".to_string());
+        response.body = ResponseBody::Text("<user id=\"1\"/>".to_string());
+        assert!(response.is_xml());
+
+        response.headers.push(ResponseHeader::new("Content-Type".to_string(), "text/html".to_string()));
+        assert!(!response.is_xml());
+
+        response.body = ResponseBody::Json(serde_json::json!({"id": 1}));
+        assert!(!response.is_xml());
+    }
+
+    #[cfg(feature = "xml")]
+    #[test]
+    fn test_xml_body() {
+        let mut response = Response::new(200, "OK".to_string());
+        response.body = ResponseBody::Text(
+            r#"<?xml version="1.0"?>
+            <soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+              <soap:Body>
+                <GetUserResponse>
+                  <user id="42" active="true">
+                    <name> Ada </name>
+                    <role>admin</role>
+                  </user>
+                </GetUserResponse>
+              </soap:Body>
+            </soap:Envelope>"#
+                .to_string(),
+        );
+
+        let root = response.xml().unwrap();
+        assert_eq!(root.name, "Envelope");
+        let user = root.child("Body").and_then(|b| b.child("GetUserResponse")).and_then(|r| r.child("user")).unwrap();
+        assert_eq!(user.attribute("id"), Some("42"));
+        assert_eq!(user.children.len(), 2);
+
+        assert_eq!(response.xml_path("Envelope/Body/GetUserResponse/user/name").unwrap().as_deref(), Some("Ada"));
+        assert_eq!(response.xml_path("/Envelope/Body/GetUserResponse/user/@active").unwrap().as_deref(), Some("true"));
+        assert_eq!(response.xml_path("Envelope/Body/Fault").unwrap(), None);
+        assert_eq!(response.xml_path("Envelope/Body/GetUserResponse/user/@active/name").unwrap(), None);
+        assert_eq!(response.xml_path("Body").unwrap(), None);
+
+        response.body = ResponseBody::Text("<open>".to_string());
+        assert!(response.xml().unwrap_err().starts_with("Failed to parse XML"));
+        response.body = ResponseBody::Empty;
+        assert!(response.xml_path("a").is_err());
+    }
+
+    #[cfg(feature = "protobuf")]
+    #[test]
+    fn test_decode_protobuf() {
+        use prost_reflect::prost_types::{
+            field_descriptor_proto::{Label, Type},
+            DescriptorProto, FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet,
+        };
+
+        let field = |name: &str, number: i32, kind: Type| FieldDescriptorProto {
+            name: Some(name.to_string()),
+            number: Some(number),
+            label: Some(Label::Optional as i32),
+            r#type: Some(kind as i32),
+            json_name: None,
+            ..Default::default()
+        };
+        let file = FileDescriptorProto {
+            name: Some("user.proto".to_string()),
+            package: Some("api.v1".to_string()),
+            syntax: Some("proto3".to_string()),
+            message_type: vec![DescriptorProto {
+                name: Some("User".to_string()),
+                field: vec![field("id", 1, Type::Int64), field("display_name", 2, Type::String)],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let pool = prost_reflect::DescriptorPool::from_file_descriptor_set(FileDescriptorSet { file: vec![file] }).unwrap();
+        let descriptor = pool.get_file_by_name("user.proto").unwrap();
+
+        // id = 42, display_name = "Ada"
+        let mut response = Response::new(200, "OK".to_string());
+        response.body = ResponseBody::Binary(vec![0x08, 42, 0x12, 3, b'A', b'd', b'a']);
+        let expected = serde_json::json!({"id": "42", "displayName": "Ada"});
+        assert_eq!(response.decode_protobuf(&descriptor, "api.v1.User").unwrap(), expected);
+        assert_eq!(response.decode_protobuf(&descriptor, "User").unwrap(), expected);
+
+        let err = response.decode_protobuf(&descriptor, "Order").unwrap_err();
+        assert_eq!(err, "Message type 'Order' not found in user.proto");
+
+        // display_name isn't valid UTF-8
+        response.body = ResponseBody::Binary(vec![0x12, 1, 0xff]);
+        let err = response.decode_protobuf(&descriptor, "User").unwrap_err();
+        assert!(err.starts_with("Failed to decode User at field 'display_name': "), "{}", err);
+
+        // id's varint is cut off
+        response.body = ResponseBody::Binary(vec![0x12, 1, b'A', 0x08, 0x80]);
+        let err = response.decode_protobuf(&descriptor, "User").unwrap_err();
+        assert!(err.starts_with("Failed to decode User at field 'id': "), "{}", err);
+
+        response.body = ResponseBody::Text("{}".to_string());
+        assert!(response.decode_protobuf(&descriptor, "User").is_err());
+    }
+
+    #[test]
+    fn test_assertion_builders() {
+        let fast = TestResult::assert_duration_under("Fast".to_string(), 120, 500);
+        assert!(fast.passed);
+        assert_eq!(fast.error_message, None);
+
+        let slow = TestResult::assert_duration_under("Fast".to_string(), 1500, 500);
+        assert!(!slow.passed);
+        assert_eq!(slow.error_message.as_deref(), Some("Response took 1.5s, budget is under 500ms"));
+        assert!(!TestResult::assert_duration_under("Fast".to_string(), 500, 500).passed);
+
+        assert!(TestResult::assert_status("OK".to_string(), 200, 200).passed);
+        let status = TestResult::assert_status("OK".to_string(), 404, 200);
+        assert_eq!(status.error_message.as_deref(), Some("Expected status 200, got 404"));
+
+        let mut response = Response::new(200, "OK".to_string());
+        response.headers.push(ResponseHeader::new("Content-Type".to_string(), "application/json".to_string()));
+        assert!(TestResult::assert_header_present("JSON".to_string(), &response, "content-type").passed);
+        let missing = TestResult::assert_header_present("ETag".to_string(), &response, "ETag");
+        assert_eq!(missing.name, "ETag");
+        assert_eq!(missing.error_message.as_deref(), Some("Expected header 'ETag' in response"));
+    }
 }
@@ -0,0 +1,131 @@
+//! Collection runner contract
+//!
+//! The runner drives requests over iteration data; these types carry the
+//! state it shares with model-level code, including cooperative cancellation.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::environment::VariableResolver;
+use crate::request::{Request, RequestBody};
+
+/// Cooperative cancellation flag shared between the runner and its work
+///
+/// Clones share the same flag; once cancelled it stays cancelled.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask all holders of this token to stop
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// State for a single collection run
+#[derive(Debug, Clone, Default)]
+pub struct RunContext {
+    /// Cancelled when the user stops the run
+    pub cancel: CancellationToken,
+}
+
+impl RunContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check whether the run should stop; long loops call this between items
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+}
+
+/// Expand a request into one copy per row of iteration data
+///
+/// `{{key}}` placeholders for keys in the row are substituted in the URL,
+/// headers, query params and body; anything else is left for environment
+/// resolution at send time. Stops early, returning the rows expanded so far,
+/// once the context is cancelled.
+pub fn expand_request(request: &Request, data: &[HashMap<String, String>], ctx: &RunContext) -> Vec<Request> {
+    let mut expanded = Vec::with_capacity(data.len());
+
+    for row in data {
+        if ctx.is_cancelled() {
+            break;
+        }
+        expanded.push(substitute_row(request, row));
+    }
+
+    expanded
+}
+
+fn substitute_row(request: &Request, row: &HashMap<String, String>) -> Request {
+    let resolver = VariableResolver::new().with_environment(row.clone());
+    let mut request = request.clone();
+
+    request.url = crate::request::Url::new(resolver.resolve(&request.url.raw));
+    for header in &mut request.headers {
+        header.key = resolver.resolve(&header.key);
+        header.value = resolver.resolve(&header.value);
+    }
+    for param in &mut request.query_params {
+        param.key = resolver.resolve(&param.key);
+        param.value = resolver.resolve(&param.value);
+    }
+    match &mut request.body {
+        RequestBody::Json { raw } | RequestBody::Raw { raw, .. } => *raw = resolver.resolve(raw),
+        RequestBody::FormData { formdata: fields } | RequestBody::UrlEncoded { urlencoded: fields } => {
+            for field in fields {
+                field.value = resolver.resolve(&field.value);
+            }
+        }
+        RequestBody::None | RequestBody::Binary => {}
+    }
+
+    request
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::HttpMethod;
+
+    fn rows(count: usize) -> Vec<HashMap<String, String>> {
+        (0..count)
+            .map(|i| HashMap::from([("user_id".to_string(), i.to_string())]))
+            .collect()
+    }
+
+    #[test]
+    fn test_expand_request() {
+        let request = Request::new("User".to_string(), HttpMethod::GET, "{{base_url}}/users/{{user_id}}".to_string())
+            .with_header("X-User".to_string(), "{{user_id}}".to_string());
+
+        let expanded = expand_request(&request, &rows(3), &RunContext::new());
+        assert_eq!(expanded.len(), 3);
+        assert_eq!(expanded[2].url.raw, "{{base_url}}/users/2");
+        assert_eq!(expanded[2].headers[0].value, "2");
+    }
+
+    #[test]
+    fn test_expand_request_stops_when_cancelled() {
+        let request = Request::new("User".to_string(), HttpMethod::GET, "/users/{{user_id}}".to_string());
+        let ctx = RunContext::new();
+        let runner_handle = ctx.cancel.clone();
+
+        runner_handle.cancel();
+        assert!(ctx.is_cancelled());
+        assert!(expand_request(&request, &rows(1000), &ctx).is_empty());
+    }
+}
@@ -7,7 +7,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use std::collections::HashMap;
 
-use crate::{Id, Timestamp, new_id, now};
+use crate::{Id, RemoteId, Timestamp, new_id, now};
 
 /// Synchronization mode
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -73,6 +73,20 @@ pub struct SyncConfig {
 
     /// Conflict resolution strategy
     pub conflict_strategy: ConflictStrategy,
+
+    /// How long deleted-item tombstones are kept, in milliseconds
+    ///
+    /// Must exceed the longest expected offline period of any device,
+    /// otherwise a stale copy could resurrect a deleted item.
+    #[serde(default = "default_tombstone_ttl")]
+    pub tombstone_ttl: i64,
+}
+
+/// Default tombstone retention (30 days)
+pub const DEFAULT_TOMBSTONE_TTL_MS: i64 = 30 * 24 * 60 * 60 * 1000;
+
+fn default_tombstone_ttl() -> i64 {
+    DEFAULT_TOMBSTONE_TTL_MS
 }
 
 impl Default for SyncConfig {
@@ -85,6 +99,7 @@ impl Default for SyncConfig {
             last_sync: None,
             auto_sync_interval: 0,
             conflict_strategy: ConflictStrategy::LastWriteWins,
+            tombstone_ttl: DEFAULT_TOMBSTONE_TTL_MS,
         }
     }
 }
@@ -105,6 +120,7 @@ impl SyncConfig {
             last_sync: None,
             auto_sync_interval: 300, // 5 minutes
             conflict_strategy: ConflictStrategy::LastWriteWins,
+            tombstone_ttl: DEFAULT_TOMBSTONE_TTL_MS,
         }
     }
 
@@ -156,6 +172,11 @@ pub struct ConflictInfo {
     pub conflict_id: Id,
     pub item_type: SyncItemType,
     pub item_id: Id,
+
+    /// The item's id on the server, when it differs from `item_id`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_id: Option<RemoteId>,
+
     pub item_name: String,
     pub local_version: i64,
     pub remote_version: i64,
@@ -164,6 +185,59 @@ pub struct ConflictInfo {
     pub created_at: Timestamp,
 }
 
+impl ConflictInfo {
+    /// Pick a side automatically using `strategy`
+    ///
+    /// `LastWriteWins` compares the `updated_at` timestamps embedded in the two
+    /// values. Returns `None` for `Manual`, which needs a user decision.
+    pub fn resolve(&self, strategy: ConflictStrategy) -> Option<ConflictChoice> {
+        let updated_at = |value: &serde_json::Value| value.get("updated_at").and_then(|v| v.as_i64());
+        self.resolve_with_timestamps(strategy, updated_at(&self.local_value), updated_at(&self.remote_value))
+    }
+
+    /// Like [`ConflictInfo::resolve`], with explicit last-modified timestamps
+    ///
+    /// A missing timestamp loses. On a tie the higher version wins, and the
+    /// remote side wins if versions are equal too, so every device picks the
+    /// same value.
+    pub fn resolve_with_timestamps(
+        &self,
+        strategy: ConflictStrategy,
+        local_modified: Option<Timestamp>,
+        remote_modified: Option<Timestamp>,
+    ) -> Option<ConflictChoice> {
+        match strategy {
+            ConflictStrategy::LocalWins => Some(ConflictChoice::Local),
+            ConflictStrategy::RemoteWins => Some(ConflictChoice::Remote),
+            ConflictStrategy::Manual => None,
+            ConflictStrategy::LastWriteWins => {
+                let local = (local_modified, self.local_version);
+                let remote = (remote_modified, self.remote_version);
+                if local > remote {
+                    Some(ConflictChoice::Local)
+                } else {
+                    Some(ConflictChoice::Remote)
+                }
+            }
+        }
+    }
+}
+
+/// Resolve every conflict that `strategy` can decide on its own
+///
+/// Conflicts needing manual resolution are left out of the result.
+pub fn resolve_all(conflicts: &[ConflictInfo], strategy: ConflictStrategy) -> Vec<ConflictResolution> {
+    conflicts
+        .iter()
+        .filter_map(|conflict| {
+            conflict.resolve(strategy).map(|resolution| ConflictResolution {
+                conflict_id: conflict.conflict_id,
+                resolution,
+            })
+        })
+        .collect()
+}
+
 /// Types of items that can be synced
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SyncItemType {
@@ -184,6 +258,20 @@ impl SyncItemType {
     }
 }
 
+impl std::str::FromStr for SyncItemType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "collection" => Ok(SyncItemType::Collection),
+            "folder" => Ok(SyncItemType::Folder),
+            "request" => Ok(SyncItemType::Request),
+            "environment" => Ok(SyncItemType::Environment),
+            _ => Err(format!("Invalid sync item type: {}", s)),
+        }
+    }
+}
+
 /// A sync change operation
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SyncChange {
@@ -240,6 +328,42 @@ impl SyncChange {
     pub fn mark_synced(&mut self) {
         self.synced = true;
     }
+
+    /// Blank secret variable values in the change payload before it is pushed
+    pub fn without_secrets(mut self) -> Self {
+        strip_secrets_for_sync(&mut self.data, self.item_type);
+        self
+    }
+}
+
+/// Blank the values of secret variables in a serialized item
+///
+/// Only the payload is modified; the local item keeps its values. Environments
+/// keep variables under `values`, collections under `variables`. Other item
+/// types carry no variables and are left unchanged.
+pub fn strip_secrets_for_sync(value: &mut serde_json::Value, item_type: SyncItemType) {
+    let (field, type_field) = match item_type {
+        SyncItemType::Environment => ("values", "variable_type"),
+        SyncItemType::Collection => ("variables", "type"),
+        SyncItemType::Folder | SyncItemType::Request => return,
+    };
+
+    let Some(variables) = value.get_mut(field).and_then(|v| v.as_array_mut()) else {
+        return;
+    };
+
+    for variable in variables {
+        let is_secret = variable.get(type_field).and_then(|t| t.as_str()) == Some("secret");
+        if !is_secret {
+            continue;
+        }
+        if let Some(obj) = variable.as_object_mut() {
+            obj.insert("value".to_string(), serde_json::Value::String(String::new()));
+            if obj.get("initial_value").is_some_and(|v| !v.is_null()) {
+                obj.insert("initial_value".to_string(), serde_json::Value::String(String::new()));
+            }
+        }
+    }
 }
 
 /// Sync operation type
@@ -260,6 +384,19 @@ impl SyncOperation {
     }
 }
 
+impl std::str::FromStr for SyncOperation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "create" => Ok(SyncOperation::Create),
+            "update" => Ok(SyncOperation::Update),
+            "delete" => Ok(SyncOperation::Delete),
+            _ => Err(format!("Invalid sync operation: {}", s)),
+        }
+    }
+}
+
 /// Result of a sync operation
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SyncResult {
@@ -343,11 +480,63 @@ impl SyncSession {
     }
 }
 
+/// Record of a deleted item, kept so the delete reaches every device
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tombstone {
+    pub item_type: SyncItemType,
+    pub item_id: Id,
+
+    /// Version of the delete
+    pub version: i64,
+
+    pub deleted_at: Timestamp,
+}
+
+impl Tombstone {
+    /// Tombstone for a delete change (None for other operations)
+    pub fn from_change(change: &SyncChange) -> Option<Self> {
+        (change.operation == SyncOperation::Delete).then_some(Self {
+            item_type: change.item_type,
+            item_id: change.item_id,
+            version: change.version,
+            deleted_at: change.timestamp,
+        })
+    }
+
+    /// Check if the tombstone has outlived `ttl` milliseconds
+    pub fn is_expired(&self, now: Timestamp, ttl: i64) -> bool {
+        now - self.deleted_at > ttl
+    }
+
+    /// Check if `change` is an older write to the deleted item
+    ///
+    /// Such a change must be dropped, or it would resurrect the item. A
+    /// create or update with a newer version than the delete still applies.
+    pub fn supersedes(&self, change: &SyncChange) -> bool {
+        change.item_type == self.item_type
+            && change.item_id == self.item_id
+            && change.operation != SyncOperation::Delete
+            && change.version <= self.version
+    }
+}
+
+/// Drop pulled changes that would resurrect a deleted item
+pub fn reconcile_with_tombstones(changes: Vec<SyncChange>, tombstones: &[Tombstone]) -> Vec<SyncChange> {
+    changes
+        .into_iter()
+        .filter(|change| !tombstones.iter().any(|t| t.supersedes(change)))
+        .collect()
+}
+
 /// Pending changes queue for offline-first sync
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct PendingChanges {
     pub changes: Vec<SyncChange>,
     pub max_size: usize,
+
+    /// Deleted items, kept after their delete change is drained
+    #[serde(default)]
+    pub tombstones: Vec<Tombstone>,
 }
 
 impl PendingChanges {
@@ -355,6 +544,7 @@ impl PendingChanges {
         Self {
             changes: Vec::with_capacity(max_size),
             max_size,
+            tombstones: Vec::new(),
         }
     }
 
@@ -369,10 +559,26 @@ impl PendingChanges {
             return Err(SyncError::QueueFull);
         }
 
+        if let Some(tombstone) = Tombstone::from_change(&change) {
+            self.tombstones.retain(|t| !(t.item_id == tombstone.item_id && t.item_type == tombstone.item_type));
+            self.tombstones.push(tombstone);
+        }
+
         self.changes.push(change);
         Ok(())
     }
 
+    /// Drop pulled changes that would resurrect an item deleted locally
+    pub fn reconcile(&self, remote: Vec<SyncChange>) -> Vec<SyncChange> {
+        reconcile_with_tombstones(remote, &self.tombstones)
+    }
+
+    /// Forget tombstones older than `ttl` milliseconds
+    pub fn purge_expired_tombstones(&mut self, ttl: i64) {
+        let now = now();
+        self.tombstones.retain(|t| !t.is_expired(now, ttl));
+    }
+
     pub fn drain(&mut self) -> Vec<SyncChange> {
         std::mem::take(&mut self.changes)
     }
@@ -427,7 +633,126 @@ pub enum SyncError {
     InvalidData(String),
 }
 
-/// Cloud sync provider (placeholder for future implementation)
+impl SyncError {
+    /// Check if the operation may succeed if retried
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            SyncError::ConnectionFailed(_) | SyncError::NetworkError(_) | SyncError::ServerError(_)
+        )
+    }
+}
+
+/// Exponential backoff settings for retrying sync operations
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first
+    pub max_attempts: u32,
+
+    /// Delay before the first retry
+    pub base_delay_ms: u64,
+
+    /// Upper bound for any single delay
+    pub max_delay_ms: u64,
+
+    /// Use full jitter (a random delay between zero and the backoff)
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay_ms: 500,
+            max_delay_ms: 30_000,
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy that never retries
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Delay before retry number `retry` (0 for the first retry)
+    pub fn delay(&self, retry: u32) -> std::time::Duration {
+        let backoff = self
+            .base_delay_ms
+            .saturating_mul(1u64.checked_shl(retry).unwrap_or(u64::MAX))
+            .min(self.max_delay_ms);
+
+        let delay = if self.jitter {
+            (backoff as f64 * random_fraction()) as u64
+        } else {
+            backoff
+        };
+
+        std::time::Duration::from_millis(delay)
+    }
+}
+
+/// Uniform random number in `[0, 1)`, taken from the random bits of a v4 UUID
+fn random_fraction() -> f64 {
+    const BITS: u32 = 53;
+    let (_, low) = Uuid::new_v4().as_u64_pair();
+    (low & ((1 << BITS) - 1)) as f64 / (1u64 << BITS) as f64
+}
+
+/// Run `op`, retrying transient errors with exponential backoff
+///
+/// Blocks the current thread between attempts. Returns the last error once
+/// attempts are exhausted; non-transient errors are returned immediately.
+pub fn retry_with_backoff<T>(
+    policy: &RetryPolicy,
+    mut op: impl FnMut() -> Result<T, SyncError>,
+) -> Result<T, SyncError> {
+    let mut retry = 0;
+    loop {
+        match op() {
+            Err(e) if e.is_transient() && retry + 1 < policy.max_attempts => {
+                std::thread::sleep(policy.delay(retry));
+                retry += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Async variant of [`retry_with_backoff`]
+///
+/// `sleep` is the runtime's timer, e.g. `tokio::time::sleep`.
+pub async fn retry_with_backoff_async<T, F, Fut, S, SFut>(
+    policy: &RetryPolicy,
+    mut op: F,
+    sleep: S,
+) -> Result<T, SyncError>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, SyncError>>,
+    S: Fn(std::time::Duration) -> SFut,
+    SFut: std::future::Future<Output = ()>,
+{
+    let mut retry = 0;
+    loop {
+        match op().await {
+            Err(e) if e.is_transient() && retry + 1 < policy.max_attempts => {
+                sleep(policy.delay(retry)).await;
+                retry += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Cloud sync provider
+///
+/// Calls are blocking. Network-backed providers implement
+/// [`AsyncSyncProvider`] instead.
 pub trait SyncProvider: Send + Sync {
     /// Authenticate with the sync server
     fn authenticate(&self, api_key: &str) -> Result<bool, SyncError>;
@@ -440,6 +765,30 @@ pub trait SyncProvider: Send + Sync {
 
     /// Resolve conflicts on server
     fn resolve_conflicts(&self, resolutions: Vec<ConflictResolution>) -> Result<(), SyncError>;
+
+    /// Push local changes, retrying transient failures per `policy`
+    fn push_changes_with_retry(&self, changes: Vec<SyncChange>, policy: &RetryPolicy) -> Result<SyncResult, SyncError> {
+        retry_with_backoff(policy, || self.push_changes(changes.clone()))
+    }
+}
+
+/// Async cloud sync provider
+///
+/// Preferred over [`SyncProvider`] for network-backed providers, whose calls
+/// would otherwise block the calling thread for the full round trip.
+#[async_trait::async_trait]
+pub trait AsyncSyncProvider: Send + Sync {
+    /// Authenticate with the sync server
+    async fn authenticate(&self, api_key: &str) -> Result<bool, SyncError>;
+
+    /// Push local changes to server
+    async fn push_changes(&self, changes: Vec<SyncChange>) -> Result<SyncResult, SyncError>;
+
+    /// Pull remote changes from server
+    async fn pull_changes(&self, since: Option<Timestamp>) -> Result<Vec<SyncChange>, SyncError>;
+
+    /// Resolve conflicts on server
+    async fn resolve_conflicts(&self, resolutions: Vec<ConflictResolution>) -> Result<(), SyncError>;
 }
 
 /// Conflict resolution choice
@@ -456,6 +805,91 @@ pub enum ConflictChoice {
     Merged { value: serde_json::Value },
 }
 
+/// Result of a three-way JSON merge
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeOutcome {
+    /// Merged value; conflicting fields keep the local value
+    pub merged: serde_json::Value,
+
+    /// Fields changed on both sides to different values
+    pub conflicts: Vec<FieldConflict>,
+}
+
+impl MergeOutcome {
+    /// Check if the merge needs no manual decisions
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+
+    /// Turn a clean merge into a `ConflictChoice::Merged`
+    pub fn into_choice(self) -> Option<ConflictChoice> {
+        self.is_clean().then_some(ConflictChoice::Merged { value: self.merged })
+    }
+}
+
+/// A single field changed differently on both sides
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldConflict {
+    /// Dotted path to the field (empty for the root value)
+    pub path: String,
+
+    /// Local value (None if deleted locally)
+    pub local: Option<serde_json::Value>,
+
+    /// Remote value (None if deleted remotely)
+    pub remote: Option<serde_json::Value>,
+}
+
+/// Field-level three-way merge of JSON objects
+///
+/// Changes made on only one side since `base` are taken as-is. Objects are
+/// merged key by key; any other value (including arrays) changed on both
+/// sides to different values is reported as a conflict.
+pub fn merge_json(base: &serde_json::Value, local: &serde_json::Value, remote: &serde_json::Value) -> MergeOutcome {
+    let mut conflicts = Vec::new();
+    let merged = merge_value(Some(base), Some(local), Some(remote), "", &mut conflicts)
+        .unwrap_or(serde_json::Value::Null);
+
+    MergeOutcome { merged, conflicts }
+}
+
+fn merge_value(
+    base: Option<&serde_json::Value>,
+    local: Option<&serde_json::Value>,
+    remote: Option<&serde_json::Value>,
+    path: &str,
+    conflicts: &mut Vec<FieldConflict>,
+) -> Option<serde_json::Value> {
+    if local == remote || remote == base {
+        return local.cloned();
+    }
+    if local == base {
+        return remote.cloned();
+    }
+
+    if let (Some(serde_json::Value::Object(l)), Some(serde_json::Value::Object(r))) = (local, remote) {
+        let empty = serde_json::Map::new();
+        let b = base.and_then(|b| b.as_object()).unwrap_or(&empty);
+
+        let mut merged = serde_json::Map::new();
+        let keys = l.keys().chain(r.keys().filter(|k| !l.contains_key(*k)));
+        for key in keys {
+            let field_path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+            if let Some(value) = merge_value(b.get(key), l.get(key), r.get(key), &field_path, conflicts) {
+                merged.insert(key.clone(), value);
+            }
+        }
+        return Some(serde_json::Value::Object(merged));
+    }
+
+    conflicts.push(FieldConflict {
+        path: path.to_string(),
+        local: local.cloned(),
+        remote: remote.cloned(),
+    });
+    local.cloned()
+}
+
 /// Local sync provider for offline mode
 pub struct LocalSyncProvider;
 
@@ -481,6 +915,147 @@ impl SyncProvider for LocalSyncProvider {
     }
 }
 
+#[async_trait::async_trait]
+impl AsyncSyncProvider for LocalSyncProvider {
+    async fn authenticate(&self, api_key: &str) -> Result<bool, SyncError> {
+        SyncProvider::authenticate(self, api_key)
+    }
+
+    async fn push_changes(&self, changes: Vec<SyncChange>) -> Result<SyncResult, SyncError> {
+        SyncProvider::push_changes(self, changes)
+    }
+
+    async fn pull_changes(&self, since: Option<Timestamp>) -> Result<Vec<SyncChange>, SyncError> {
+        SyncProvider::pull_changes(self, since)
+    }
+
+    async fn resolve_conflicts(&self, resolutions: Vec<ConflictResolution>) -> Result<(), SyncError> {
+        SyncProvider::resolve_conflicts(self, resolutions)
+    }
+}
+
+/// Map an HTTP error status from the sync server to a `SyncError`
+///
+/// Returns `None` for success statuses. A 409 body of the form
+/// `{"item_type": "...", "item_id": "..."}` names the conflicting item.
+pub fn sync_error_for_status(status: u16, body: &str) -> Option<SyncError> {
+    match status {
+        200..=299 => None,
+        401 => Some(SyncError::AuthenticationFailed),
+        409 => {
+            let value: serde_json::Value = serde_json::from_str(body).unwrap_or_default();
+            let field = |name: &str| {
+                value.get(name)
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("unknown")
+                    .to_string()
+            };
+            Some(SyncError::Conflict {
+                item_type: field("item_type"),
+                item_id: field("item_id"),
+            })
+        }
+        _ => Some(SyncError::ServerError(format!("HTTP {}: {}", status, body))),
+    }
+}
+
+/// Sync provider backed by the Postboy cloud REST API
+///
+/// Endpoints, relative to the base URL:
+/// - `GET /auth` - validate the API key
+/// - `POST /changes` - push local changes, returns a [`SyncResult`]
+/// - `GET /changes?since=` - pull remote changes
+/// - `POST /conflicts/resolve` - submit conflict resolutions
+#[cfg(feature = "cloud")]
+pub struct HttpSyncProvider {
+    base_url: String,
+    api_key: String,
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "cloud")]
+impl HttpSyncProvider {
+    pub fn new(base_url: impl Into<String>, api_key: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            api_key: api_key.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Create a provider from an online sync config
+    pub fn from_config(config: &SyncConfig) -> Result<Self, SyncError> {
+        match (&config.server_url, &config.api_key) {
+            (Some(url), Some(key)) => Ok(Self::new(url.clone(), key.clone())),
+            _ => Err(SyncError::NotConfigured),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Send a request and check the response status
+    async fn send(&self, request: reqwest::RequestBuilder, api_key: &str) -> Result<reqwest::Response, SyncError> {
+        let response = request
+            .bearer_auth(api_key)
+            .send()
+            .await
+            .map_err(|e| SyncError::NetworkError(e.to_string()))?;
+
+        let status = response.status().as_u16();
+        if (200..300).contains(&status) {
+            return Ok(response);
+        }
+
+        let body = response.text().await.unwrap_or_default();
+        Err(sync_error_for_status(status, &body)
+            .unwrap_or_else(|| SyncError::ServerError(format!("HTTP {}", status))))
+    }
+
+    async fn json<T: serde::de::DeserializeOwned>(response: reqwest::Response) -> Result<T, SyncError> {
+        response
+            .json()
+            .await
+            .map_err(|e| SyncError::InvalidData(e.to_string()))
+    }
+}
+
+#[cfg(feature = "cloud")]
+#[async_trait::async_trait]
+impl AsyncSyncProvider for HttpSyncProvider {
+    async fn authenticate(&self, api_key: &str) -> Result<bool, SyncError> {
+        self.send(self.client.get(self.url("/auth")), api_key).await?;
+        Ok(true)
+    }
+
+    async fn push_changes(&self, changes: Vec<SyncChange>) -> Result<SyncResult, SyncError> {
+        let response = self
+            .send(self.client.post(self.url("/changes")).json(&changes), &self.api_key)
+            .await?;
+        Self::json(response).await
+    }
+
+    async fn pull_changes(&self, since: Option<Timestamp>) -> Result<Vec<SyncChange>, SyncError> {
+        let mut request = self.client.get(self.url("/changes"));
+        if let Some(since) = since {
+            request = request.query(&[("since", since)]);
+        }
+
+        let response = self.send(request, &self.api_key).await?;
+        Self::json(response).await
+    }
+
+    async fn resolve_conflicts(&self, resolutions: Vec<ConflictResolution>) -> Result<(), SyncError> {
+        self.send(
+            self.client.post(self.url("/conflicts/resolve")).json(&resolutions),
+            &self.api_key,
+        )
+        .await?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -504,6 +1079,164 @@ mod tests {
         assert_eq!(config.server_url, Some("https://api.postboy.app".to_string()));
     }
 
+    #[test]
+    fn test_sync_error_for_status() {
+        assert_eq!(sync_error_for_status(200, ""), None);
+        assert_eq!(sync_error_for_status(401, ""), Some(SyncError::AuthenticationFailed));
+        assert_eq!(
+            sync_error_for_status(409, r#"{"item_type":"request","item_id":"abc"}"#),
+            Some(SyncError::Conflict { item_type: "request".to_string(), item_id: "abc".to_string() })
+        );
+        assert!(matches!(sync_error_for_status(500, "boom"), Some(SyncError::ServerError(msg)) if msg.contains("boom")));
+    }
+
+    fn conflict(local_updated: i64, remote_updated: i64) -> ConflictInfo {
+        ConflictInfo {
+            conflict_id: new_id(),
+            item_type: SyncItemType::Request,
+            item_id: new_id(),
+            remote_id: None,
+            item_name: "Users".to_string(),
+            local_version: 2,
+            remote_version: 2,
+            local_value: serde_json::json!({"name": "local", "updated_at": local_updated}),
+            remote_value: serde_json::json!({"name": "remote", "updated_at": remote_updated}),
+            created_at: now(),
+        }
+    }
+
+    #[test]
+    fn test_last_write_wins() {
+        let strategy = ConflictStrategy::LastWriteWins;
+
+        assert_eq!(conflict(200, 100).resolve(strategy), Some(ConflictChoice::Local));
+        assert_eq!(conflict(100, 200).resolve(strategy), Some(ConflictChoice::Remote));
+
+        // Tie: higher version wins, then remote
+        let mut tied = conflict(100, 100);
+        assert_eq!(tied.resolve(strategy), Some(ConflictChoice::Remote));
+        tied.local_version = 3;
+        assert_eq!(tied.resolve(strategy), Some(ConflictChoice::Local));
+
+        // Explicit timestamps override the embedded ones
+        assert_eq!(
+            conflict(100, 200).resolve_with_timestamps(strategy, Some(300), Some(200)),
+            Some(ConflictChoice::Local)
+        );
+    }
+
+    #[test]
+    fn test_resolve_all() {
+        let conflicts = vec![conflict(200, 100), conflict(100, 200)];
+
+        let resolutions = resolve_all(&conflicts, ConflictStrategy::LastWriteWins);
+        assert_eq!(resolutions.len(), 2);
+        assert_eq!(resolutions[0].conflict_id, conflicts[0].conflict_id);
+        assert_eq!(resolutions[0].resolution, ConflictChoice::Local);
+        assert_eq!(resolutions[1].resolution, ConflictChoice::Remote);
+
+        assert!(resolve_all(&conflicts, ConflictStrategy::Manual).is_empty());
+        assert!(resolve_all(&conflicts, ConflictStrategy::LocalWins)
+            .iter()
+            .all(|r| r.resolution == ConflictChoice::Local));
+    }
+
+    #[test]
+    fn test_merge_json() {
+        let base = serde_json::json!({
+            "name": "Users",
+            "url": "https://example.com/users",
+            "auth": {"type": "bearer", "token": "a"},
+            "tags": ["a"],
+        });
+        let local = serde_json::json!({
+            "name": "List users",
+            "url": "https://example.com/users",
+            "auth": {"type": "bearer", "token": "b"},
+            "tags": ["a", "local"],
+        });
+        let remote = serde_json::json!({
+            "name": "Users",
+            "url": "https://api.example.com/users",
+            "auth": {"type": "bearer", "token": "c"},
+            "tags": ["a", "remote"],
+            "description": "Added remotely",
+        });
+
+        let outcome = merge_json(&base, &local, &remote);
+        assert_eq!(outcome.merged["name"], "List users");
+        assert_eq!(outcome.merged["url"], "https://api.example.com/users");
+        assert_eq!(outcome.merged["description"], "Added remotely");
+
+        let paths: Vec<_> = outcome.conflicts.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, vec!["auth.token", "tags"]);
+        assert_eq!(outcome.conflicts[0].remote, Some(serde_json::json!("c")));
+        assert!(outcome.clone().into_choice().is_none());
+
+        // Without overlapping edits the merge is clean
+        let remote = serde_json::json!({"name": "Users", "url": "https://api.example.com/users"});
+        let local = serde_json::json!({"name": "List users", "url": "https://example.com/users"});
+        let base = serde_json::json!({"name": "Users", "url": "https://example.com/users"});
+        let outcome = merge_json(&base, &local, &remote);
+        assert_eq!(
+            outcome.into_choice(),
+            Some(ConflictChoice::Merged {
+                value: serde_json::json!({"name": "List users", "url": "https://api.example.com/users"})
+            })
+        );
+    }
+
+    #[test]
+    fn test_retry_policy_delay() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay_ms: 100,
+            max_delay_ms: 1_000,
+            jitter: false,
+        };
+        let delays: Vec<_> = (0..6).map(|r| policy.delay(r).as_millis()).collect();
+        assert_eq!(delays, vec![100, 200, 400, 800, 1_000, 1_000]);
+        assert_eq!(policy.delay(200).as_millis(), 1_000);
+
+        let jittered = RetryPolicy { jitter: true, ..policy };
+        assert!((0..20).all(|r| jittered.delay(r).as_millis() <= 1_000));
+    }
+
+    #[test]
+    fn test_retry_with_backoff() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            base_delay_ms: 0,
+            max_delay_ms: 0,
+            jitter: false,
+        };
+
+        // Transient errors are retried until success
+        let mut attempts = 0;
+        let result = retry_with_backoff(&policy, || {
+            attempts += 1;
+            if attempts < 3 { Err(SyncError::NetworkError("reset".to_string())) } else { Ok(attempts) }
+        });
+        assert_eq!(result, Ok(3));
+
+        // Authentication failures are not
+        let mut attempts = 0;
+        let result: Result<(), _> = retry_with_backoff(&policy, || {
+            attempts += 1;
+            Err(SyncError::AuthenticationFailed)
+        });
+        assert_eq!(result, Err(SyncError::AuthenticationFailed));
+        assert_eq!(attempts, 1);
+
+        // The last error is returned once attempts run out
+        let mut attempts = 0;
+        let result: Result<(), _> = retry_with_backoff(&policy, || {
+            attempts += 1;
+            Err(SyncError::ServerError(format!("attempt {}", attempts)))
+        });
+        assert_eq!(result, Err(SyncError::ServerError("attempt 3".to_string())));
+    }
+
     #[test]
     fn test_pending_changes() {
         let mut pending = PendingChanges::new(10);
@@ -523,6 +1256,31 @@ mod tests {
         assert!(pending.is_empty());
     }
 
+    #[test]
+    fn test_tombstone_blocks_stale_remote_update() {
+        let mut pending = PendingChanges::new(10);
+        let id = new_id();
+
+        // Deleted locally at version 3, then the queue is pushed and drained
+        pending.push(SyncChange::delete(SyncItemType::Request, id, 3)).unwrap();
+        pending.drain();
+        assert_eq!(pending.tombstones.len(), 1);
+
+        let stale = SyncChange::update(SyncItemType::Request, id, 2, serde_json::json!({"v": 2}));
+        let newer = SyncChange::update(SyncItemType::Request, id, 4, serde_json::json!({"v": 4}));
+        let unrelated = SyncChange::create(SyncItemType::Request, new_id(), serde_json::json!({}));
+
+        let applied = pending.reconcile(vec![stale, newer.clone(), unrelated.clone()]);
+        assert_eq!(applied, vec![newer, unrelated]);
+
+        // Tombstones outlive a short TTL only until they expire
+        pending.purge_expired_tombstones(DEFAULT_TOMBSTONE_TTL_MS);
+        assert_eq!(pending.tombstones.len(), 1);
+        pending.tombstones[0].deleted_at -= 10;
+        pending.purge_expired_tombstones(5);
+        assert!(pending.tombstones.is_empty());
+    }
+
     #[test]
     fn test_pending_changes_dedup() {
         let mut pending = PendingChanges::new(10);
@@ -548,6 +1306,31 @@ mod tests {
         assert_eq!(pending.changes[0].version, 2);
     }
 
+    #[test]
+    fn test_strip_secrets_for_sync() {
+        use crate::environment::{Environment, Variable};
+
+        let env = Environment::new("Prod".to_string()).with_values(vec![
+            Variable::new("base_url".to_string(), "https://api.example.com".to_string()),
+            Variable::secret("api_key".to_string(), "s3cr3t".to_string()),
+        ]);
+
+        let change = SyncChange::create(
+            SyncItemType::Environment,
+            env.id,
+            serde_json::to_value(&env).unwrap(),
+        )
+        .without_secrets();
+
+        let values = change.data["values"].as_array().unwrap();
+        assert_eq!(values[0]["value"], "https://api.example.com");
+        assert_eq!(values[1]["value"], "");
+        assert_eq!(values[1]["initial_value"], "");
+
+        // Local copy is untouched
+        assert_eq!(env.get("api_key"), Some("s3cr3t".to_string()));
+    }
+
     #[test]
     fn test_sync_session() {
         let mut session = SyncSession::new();
@@ -572,4 +1355,25 @@ mod tests {
         assert_eq!(DeviceType::Mobile.as_str(), "mobile");
         assert_eq!(DeviceType::Web.as_str(), "web");
     }
+
+    #[test]
+    fn test_string_remote_id_round_trip() {
+        let mut info = conflict(100, 200);
+        info.remote_id = Some(RemoteId::from("req_8f2a"));
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(json["remote_id"], "req_8f2a");
+        assert_eq!(serde_json::from_value::<ConflictInfo>(json).unwrap(), info);
+
+        let mut collection = crate::Collection::new("API".to_string());
+        collection.mark_synced("col_42", 3);
+        let json = serde_json::to_string(&collection.sync_state).unwrap();
+        let state: crate::SyncState = serde_json::from_str(&json).unwrap();
+        assert_eq!(state.remote_id, Some(RemoteId::String("col_42".to_string())));
+
+        // Integer ids and UUIDs stored before remote ids were generalized
+        let uuid = new_id();
+        assert_eq!(serde_json::from_value::<RemoteId>(serde_json::json!(uuid)).unwrap(), RemoteId::Uuid(uuid));
+        assert_eq!(serde_json::from_value::<RemoteId>(serde_json::json!(7)).unwrap(), RemoteId::Int(7));
+        assert_eq!(RemoteId::Int(7).to_string(), "7");
+    }
 }
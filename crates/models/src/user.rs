@@ -128,6 +128,17 @@ impl Default for UserPlan {
     }
 }
 
+/// Limit value meaning "no limit"
+const UNLIMITED: u32 = u32::MAX;
+
+/// A quota limit that usage can exceed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaLimit {
+    Collections,
+    Storage,
+}
+
 /// User quota limits
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct UserQuota {
@@ -208,6 +219,49 @@ impl UserQuota {
             UserPlan::Enterprise => Self::enterprise(),
         }
     }
+
+    /// Collections that can still be created (None when unlimited)
+    pub fn remaining_collections(&self) -> Option<u32> {
+        remaining(self.max_collections, self.collections_count)
+    }
+
+    /// Storage left in MB (None when unlimited)
+    pub fn storage_remaining_mb(&self) -> Option<u32> {
+        remaining(self.max_storage_mb, self.storage_used_mb)
+    }
+
+    /// Count a newly created collection
+    ///
+    /// Returns false, leaving the count unchanged, when the limit is reached.
+    pub fn increment_collections(&mut self) -> bool {
+        if self.remaining_collections() == Some(0) {
+            return false;
+        }
+        self.collections_count = self.collections_count.saturating_add(1);
+        true
+    }
+
+    /// Count a deleted collection
+    pub fn decrement_collections(&mut self) {
+        self.collections_count = self.collections_count.saturating_sub(1);
+    }
+
+    /// Limits current usage exceeds, e.g. after a downgrade; empty when
+    /// within quota. Unlimited limits are never exceeded.
+    pub fn is_over_quota(&self) -> Vec<QuotaLimit> {
+        let mut exceeded = Vec::new();
+        if self.max_collections != UNLIMITED && self.collections_count > self.max_collections {
+            exceeded.push(QuotaLimit::Collections);
+        }
+        if self.max_storage_mb != UNLIMITED && self.storage_used_mb > self.max_storage_mb {
+            exceeded.push(QuotaLimit::Storage);
+        }
+        exceeded
+    }
+}
+
+fn remaining(limit: u32, used: u32) -> Option<u32> {
+    (limit != UNLIMITED).then(|| limit.saturating_sub(used))
 }
 
 /// Device information (for multi-device sync in the future)
@@ -322,6 +376,31 @@ impl Session {
     pub fn is_valid(&self) -> bool {
         !self.is_expired()
     }
+
+    /// Check if the session expires within `window_ms` from now (or
+    /// already has), i.e. it's time to refresh
+    pub fn expires_within(&self, window_ms: i64) -> bool {
+        now().saturating_add(window_ms) >= self.expires_at
+    }
+
+    /// Replace the tokens after a refresh and extend the session by `ttl_ms`
+    ///
+    /// `new_refresh` is `None` when the server doesn't rotate refresh tokens,
+    /// keeping the current one.
+    pub fn refresh(&mut self, new_access: String, new_refresh: Option<String>, ttl_ms: i64) {
+        let now = now();
+        self.access_token = Some(new_access);
+        if let Some(refresh_token) = new_refresh {
+            self.refresh_token = Some(refresh_token);
+        }
+        self.expires_at = now.saturating_add(ttl_ms);
+        self.last_used_at = now;
+    }
+
+    /// Record that the session was just used
+    pub fn touch(&mut self) {
+        self.last_used_at = now();
+    }
 }
 
 impl Identifiable for Session {
@@ -340,6 +419,11 @@ pub struct UserSettings {
     pub check_updates: bool,
     pub default_request_timeout_secs: u32,
     pub follow_redirects: bool,
+
+    /// Most redirects to follow before giving up
+    #[serde(default = "default_max_redirects")]
+    pub max_redirects: u32,
+
     pub validate_ssl: bool,
 
     /// Editor settings
@@ -350,6 +434,71 @@ pub struct UserSettings {
 
     /// Cloud sync settings (for future use)
     pub cloud_sync: CloudSyncSettings,
+
+    /// Action name to key combo, e.g. `send_request` -> `Mod+Enter`, where
+    /// `Mod` is Cmd on macOS and Ctrl elsewhere
+    #[serde(default = "default_keybindings")]
+    pub keybindings: HashMap<String, String>,
+}
+
+fn default_max_redirects() -> u32 {
+    10
+}
+
+/// Built-in keybindings, used when settings don't specify any
+pub fn default_keybindings() -> HashMap<String, String> {
+    [
+        ("send_request", "Mod+Enter"),
+        ("new_request", "Mod+N"),
+        ("save", "Mod+S"),
+        ("switch_environment", "Mod+E"),
+    ]
+    .into_iter()
+    .map(|(action, combo)| (action.to_string(), combo.to_string()))
+    .collect()
+}
+
+/// Canonical form of a key combo: lowercase, modifiers sorted, key last
+fn normalize_key_combo(combo: &str) -> String {
+    let mut parts: Vec<String> = combo
+        .split('+')
+        .map(|part| part.trim().to_lowercase())
+        .filter(|part| !part.is_empty())
+        .collect();
+    let key = parts.pop().unwrap_or_default();
+    parts.sort();
+    parts.dedup();
+    parts.push(key);
+    parts.join("+")
+}
+
+impl UserSettings {
+    /// Check that no key combo is bound to more than one action
+    ///
+    /// Combos are compared ignoring case and modifier order. Each error names
+    /// the combo and the actions sharing it.
+    pub fn validate_keybindings(&self) -> Result<(), Vec<String>> {
+        let mut by_combo: HashMap<String, Vec<&str>> = HashMap::new();
+        for (action, combo) in &self.keybindings {
+            by_combo.entry(normalize_key_combo(combo)).or_default().push(action);
+        }
+
+        let mut errors: Vec<String> = by_combo
+            .into_values()
+            .filter(|actions| actions.len() > 1)
+            .map(|mut actions| {
+                actions.sort();
+                format!(
+                    "'{}' is bound to more than one action: {}",
+                    self.keybindings[actions[0]],
+                    actions.join(", ")
+                )
+            })
+            .collect();
+        errors.sort();
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
 }
 
 impl Default for UserSettings {
@@ -362,10 +511,12 @@ impl Default for UserSettings {
             check_updates: true,
             default_request_timeout_secs: 30,
             follow_redirects: true,
+            max_redirects: default_max_redirects(),
             validate_ssl: true,
             editor: EditorSettings::default(),
             proxy: None,
             cloud_sync: CloudSyncSettings::default(),
+            keybindings: default_keybindings(),
         }
     }
 }
@@ -417,6 +568,66 @@ pub struct ProxySettings {
     pub host: String,
     pub port: u16,
     pub auth: Option<ProxyAuth>,
+
+    /// Hosts that skip the proxy: exact hosts (`api.example.com`), domain
+    /// suffixes (`.internal`, also matching `internal` itself), CIDR ranges
+    /// (`10.0.0.0/8`) or `*` for everything
+    #[serde(default)]
+    pub bypass: Vec<String>,
+
+    /// Send loopback hosts (`localhost`, `127.0.0.0/8`, `::1`) through the
+    /// proxy instead of bypassing it
+    #[serde(default)]
+    pub proxy_loopback: bool,
+}
+
+impl ProxySettings {
+    /// Whether requests to `host` should go direct rather than via the proxy
+    ///
+    /// `host` is a bare hostname or IP address; IPv6 may be bracketed.
+    pub fn should_bypass(&self, host: &str) -> bool {
+        let host = host.trim().trim_start_matches('[').trim_end_matches(']').trim_end_matches('.').to_ascii_lowercase();
+        let ip: Option<std::net::IpAddr> = host.parse().ok();
+
+        let loopback = host == "localhost" || host.ends_with(".localhost") || ip.is_some_and(|ip| ip.is_loopback());
+        if loopback && !self.proxy_loopback {
+            return true;
+        }
+
+        self.bypass.iter().any(|entry| {
+            let entry = entry.trim().trim_end_matches('.').to_ascii_lowercase();
+            if entry == "*" {
+                return true;
+            }
+            if let Some((network, prefix)) = entry.split_once('/') {
+                return match (ip, network.parse(), prefix.parse()) {
+                    (Some(ip), Ok(network), Ok(prefix)) => cidr_contains(network, prefix, ip),
+                    _ => false,
+                };
+            }
+            match entry.strip_prefix("*.").or_else(|| entry.strip_prefix('.')) {
+                Some(domain) => host == domain || host.strip_suffix(domain).is_some_and(|rest| rest.ends_with('.')),
+                None => host == entry.trim_start_matches('[').trim_end_matches(']'),
+            }
+        })
+    }
+}
+
+/// Whether `ip` is in `network/prefix`; mismatched address families never match
+fn cidr_contains(network: std::net::IpAddr, prefix: u32, ip: std::net::IpAddr) -> bool {
+    use std::net::IpAddr;
+
+    match (network, ip) {
+        (IpAddr::V4(network), IpAddr::V4(ip)) if prefix <= 32 => {
+            let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+            u32::from(network) & mask == u32::from(ip) & mask
+        }
+        (IpAddr::V6(network), IpAddr::V6(ip)) if prefix <= 128 => {
+            let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+            u128::from(network) & mask == u128::from(ip) & mask
+        }
+        _ => false,
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -546,6 +757,41 @@ mod tests {
         assert_eq!(enterprise_quota.max_collections, u32::MAX);
     }
 
+    #[test]
+    fn test_user_quota_usage() {
+        let mut quota = UserQuota::free();
+        assert_eq!(quota.remaining_collections(), Some(10));
+        assert_eq!(quota.storage_remaining_mb(), Some(100));
+
+        for _ in 0..10 {
+            assert!(quota.increment_collections());
+        }
+        assert!(!quota.increment_collections());
+        assert_eq!(quota.collections_count, 10);
+        assert_eq!(quota.remaining_collections(), Some(0));
+        assert!(quota.is_over_quota().is_empty());
+
+        quota.decrement_collections();
+        assert_eq!(quota.remaining_collections(), Some(1));
+
+        // Downgrading from Pro leaves usage above the Free limits
+        let mut downgraded = UserQuota::pro();
+        downgraded.collections_count = 50;
+        downgraded.storage_used_mb = 500;
+        downgraded.max_collections = quota.max_collections;
+        downgraded.max_storage_mb = quota.max_storage_mb;
+        assert_eq!(downgraded.is_over_quota(), vec![QuotaLimit::Collections, QuotaLimit::Storage]);
+        assert_eq!(downgraded.remaining_collections(), Some(0));
+
+        let mut enterprise = UserQuota::enterprise();
+        enterprise.collections_count = u32::MAX;
+        enterprise.storage_used_mb = u32::MAX;
+        assert_eq!(enterprise.remaining_collections(), None);
+        assert_eq!(enterprise.storage_remaining_mb(), None);
+        assert!(enterprise.increment_collections());
+        assert!(enterprise.is_over_quota().is_empty());
+    }
+
     #[test]
     fn test_can_create_collection() {
         let user = User::new("test@example.com".to_string(), "Test".to_string());
@@ -569,6 +815,55 @@ mod tests {
         assert!(session.is_expired());
     }
 
+    #[test]
+    fn test_keybindings() {
+        let mut settings = UserSettings::default();
+        assert_eq!(settings.keybindings["send_request"], "Mod+Enter");
+        assert_eq!(settings.validate_keybindings(), Ok(()));
+
+        settings.keybindings.insert("save".to_string(), "Shift+Mod+S".to_string());
+        settings.keybindings.insert("save_as".to_string(), "mod + shift + s".to_string());
+        settings.keybindings.insert("new_request".to_string(), "Mod+Enter".to_string());
+        let errors = settings.validate_keybindings().unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].ends_with("new_request, send_request"));
+        assert!(errors[1].ends_with("save, save_as"));
+    }
+
+    #[test]
+    fn test_settings_without_keybindings_deserialize() {
+        let mut value = serde_json::to_value(UserSettings::default()).unwrap();
+        value.as_object_mut().unwrap().remove("keybindings");
+        let settings: UserSettings = serde_json::from_value(value).unwrap();
+        assert_eq!(settings.keybindings, default_keybindings());
+    }
+
+    #[test]
+    fn test_session_refresh() {
+        let mut session = Session::new(new_id()).with_tokens("access-1".to_string(), "refresh-1".to_string());
+        session.expires_at = now() + 60_000;
+        assert!(session.expires_within(5 * 60_000));
+        assert!(!session.expires_within(1000));
+
+        let before = now();
+        session.refresh("access-2".to_string(), None, 3_600_000);
+        assert_eq!(session.access_token.as_deref(), Some("access-2"));
+        assert_eq!(session.refresh_token.as_deref(), Some("refresh-1"));
+        assert!(session.expires_at >= before + 3_600_000);
+        assert!(session.last_used_at >= before);
+        assert!(!session.expires_within(5 * 60_000));
+
+        session.refresh("access-3".to_string(), Some("refresh-2".to_string()), 3_600_000);
+        assert_eq!(session.refresh_token.as_deref(), Some("refresh-2"));
+
+        session.last_used_at = 0;
+        session.touch();
+        assert!(session.last_used_at >= before);
+
+        session.expires_at = now() - 1000;
+        assert!(session.expires_within(0));
+    }
+
     #[test]
     fn test_device_creation() {
         let device = Device::new(
@@ -597,4 +892,56 @@ mod tests {
         assert!(settings.auto_save);
         assert!(settings.validate_ssl);
     }
+
+    fn proxy(bypass: &[&str]) -> ProxySettings {
+        ProxySettings {
+            enabled: true,
+            protocol: ProxyProtocol::Http,
+            host: "proxy.corp.example".to_string(),
+            port: 3128,
+            auth: None,
+            bypass: bypass.iter().map(|b| b.to_string()).collect(),
+            proxy_loopback: false,
+        }
+    }
+
+    #[test]
+    fn test_proxy_bypass() {
+        let settings = proxy(&["intranet.example.com", ".internal", "10.0.0.0/8", "fd00::/8"]);
+
+        assert!(settings.should_bypass("intranet.example.com"));
+        assert!(settings.should_bypass("INTRANET.example.com."));
+        assert!(!settings.should_bypass("www.intranet.example.com"));
+        assert!(settings.should_bypass("git.internal"));
+        assert!(settings.should_bypass("internal"));
+        assert!(!settings.should_bypass("notinternal"));
+        assert!(settings.should_bypass("10.1.2.3"));
+        assert!(!settings.should_bypass("11.1.2.3"));
+        assert!(settings.should_bypass("[fd12::1]"));
+        assert!(!settings.should_bypass("example.com"));
+
+        assert!(settings.should_bypass("localhost"));
+        assert!(settings.should_bypass("127.0.0.1"));
+        let mut through_proxy = settings.clone();
+        through_proxy.proxy_loopback = true;
+        assert!(!through_proxy.should_bypass("localhost"));
+        assert!(!through_proxy.should_bypass("127.0.0.1"));
+
+        assert!(proxy(&["*"]).should_bypass("example.com"));
+        assert!(proxy(&["0.0.0.0/0"]).should_bypass("8.8.8.8"));
+    }
+
+    #[test]
+    fn test_proxy_settings_without_bypass_deserialize() {
+        let settings: ProxySettings = serde_json::from_value(serde_json::json!({
+            "enabled": true,
+            "protocol": "http",
+            "host": "proxy",
+            "port": 8080,
+            "auth": null
+        }))
+        .unwrap();
+        assert!(settings.bypass.is_empty());
+        assert!(!settings.proxy_loopback);
+    }
 }
@@ -0,0 +1,215 @@
+//! Pre-send request validation
+//!
+//! Purely model-level checks so the UI can flag problems without a network
+//! round-trip. Issues are advisory; nothing here blocks a send.
+
+use serde::{Deserialize, Serialize};
+
+use crate::environment::VariableResolver;
+use crate::request::{HttpMethod, Request, RequestBody, Url};
+
+/// How many nested `{{var}}` levels are resolved before giving up
+const MAX_RESOLVE_DEPTH: usize = 5;
+
+/// How serious a validation issue is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Severity {
+    /// Probably a mistake, but the request can still be sent
+    Warning,
+    /// The request will not work as written
+    Error,
+}
+
+/// A single problem found in a request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+        }
+    }
+}
+
+impl Request {
+    /// Check the request for common mistakes before sending
+    ///
+    /// Flags a body on GET/HEAD, a body that doesn't match the `Content-Type`
+    /// header, duplicate enabled headers, and an unparseable URL. A URL that
+    /// only fails to parse because of `{{variables}}` is a warning; use
+    /// [`Request::validate_in_env`] to check it after resolution.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        if let Err(e) = Url::parse(self.url.raw.clone()) {
+            if VariableResolver::has_unresolved(&self.url.raw) {
+                issues.push(ValidationIssue::warning(format!(
+                    "URL is only valid once its variables are resolved: {}",
+                    e
+                )));
+            } else {
+                issues.push(ValidationIssue::error(format!("URL '{}' is not valid: {}", self.url.raw, e)));
+            }
+        }
+
+        issues.extend(self.validate_headers_and_body());
+        issues
+    }
+
+    /// Like [`Request::validate`], but checks the URL as it would be sent
+    ///
+    /// Catches environment-specific breakage such as an environment missing
+    /// `base_url`: leftover `{{var}}` placeholders, a resolved URL that isn't
+    /// absolute, or one without a host.
+    pub fn validate_in_env(&self, resolver: &VariableResolver) -> Vec<ValidationIssue> {
+        let resolved = resolver.resolve_recursive(&self.url.raw, MAX_RESOLVE_DEPTH);
+
+        let mut issues = if VariableResolver::has_unresolved(&resolved) {
+            vec![ValidationIssue::error(format!(
+                "URL has unresolved variables: {}",
+                resolved
+            ))]
+        } else {
+            match Url::parse(resolved.clone()) {
+                Err(e) => vec![ValidationIssue::error(format!(
+                    "Resolved URL '{}' is not a valid absolute URL: {}",
+                    resolved, e
+                ))],
+                Ok(url) if url.host.as_deref().is_none_or(str::is_empty) => {
+                    vec![ValidationIssue::error(format!("Resolved URL '{}' has no host", resolved))]
+                }
+                Ok(_) => Vec::new(),
+            }
+        };
+
+        issues.extend(self.validate_headers_and_body());
+        issues
+    }
+
+    fn validate_headers_and_body(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        let headers = self.enabled_headers();
+
+        if matches!(self.method, HttpMethod::GET | HttpMethod::HEAD) && self.has_body() {
+            issues.push(ValidationIssue::warning(format!(
+                "{} requests usually don't have a body; many servers ignore it",
+                self.method.as_str()
+            )));
+        }
+
+        let content_type = headers
+            .iter()
+            .find(|h| h.key.eq_ignore_ascii_case("content-type"))
+            .map(|h| h.value.split(';').next().unwrap_or("").trim().to_ascii_lowercase());
+        if let Some(content_type) = content_type {
+            let expected = match &self.body {
+                RequestBody::Json { .. } => Some("application/json"),
+                RequestBody::UrlEncoded { .. } => Some("application/x-www-form-urlencoded"),
+                RequestBody::FormData { .. } => Some("multipart/form-data"),
+                _ => None,
+            };
+            let matches = match expected {
+                Some("application/json") => content_type == "application/json" || content_type.ends_with("+json"),
+                Some(expected) => content_type == expected,
+                None => true,
+            };
+            if !matches {
+                issues.push(ValidationIssue::warning(format!(
+                    "Content-Type '{}' doesn't match the {} body; expected '{}'",
+                    content_type,
+                    self.body.mode().as_str(),
+                    expected.unwrap_or_default()
+                )));
+            }
+        }
+
+        let mut seen: Vec<String> = Vec::new();
+        let mut reported: Vec<String> = Vec::new();
+        for header in &headers {
+            let name = header.key.to_ascii_lowercase();
+            if seen.contains(&name) {
+                if !reported.contains(&name) {
+                    issues.push(ValidationIssue::warning(format!(
+                        "Header '{}' is set more than once",
+                        header.key
+                    )));
+                    reported.push(name);
+                }
+            } else {
+                seen.push(name);
+            }
+        }
+
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn request(url: &str) -> Request {
+        Request::new("Users".to_string(), HttpMethod::GET, url.to_string())
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(request("https://api.example.com/users").validate().is_empty());
+
+        let mut get_with_body = request("https://api.example.com/users")
+            .with_header("Content-Type".to_string(), "text/plain".to_string())
+            .with_header("accept".to_string(), "*/*".to_string())
+            .with_header("Accept".to_string(), "application/json".to_string());
+        get_with_body.body = RequestBody::Json { raw: "{}".to_string() };
+        let issues = get_with_body.validate();
+        assert_eq!(issues.len(), 3);
+        assert!(issues.iter().all(|i| i.severity == Severity::Warning));
+        assert!(issues[0].message.starts_with("GET requests"));
+        assert!(issues[1].message.contains("application/json"));
+        assert!(issues[2].message.contains("more than once"));
+
+        let invalid = request("not a url").validate();
+        assert_eq!(invalid.len(), 1);
+        assert_eq!(invalid[0].severity, Severity::Error);
+
+        let templated = request("{{base_url}}/users").validate();
+        assert_eq!(templated.len(), 1);
+        assert_eq!(templated[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_validate_in_env() {
+        let resolver = VariableResolver::new().with_environment(HashMap::from([
+            ("base_url".to_string(), "https://api.example.com".to_string()),
+            ("relative".to_string(), "/api".to_string()),
+        ]));
+
+        assert!(request("{{base_url}}/users").validate_in_env(&resolver).is_empty());
+
+        let missing = request("{{base_url}}/users").validate_in_env(&VariableResolver::new());
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].severity, Severity::Error);
+        assert!(missing[0].message.contains("{{base_url}}"));
+
+        let relative = request("{{relative}}/users").validate_in_env(&resolver);
+        assert_eq!(relative.len(), 1);
+        assert!(relative[0].message.contains("not a valid absolute URL"));
+
+        let no_host = request("file:///etc/hosts").validate_in_env(&resolver);
+        assert_eq!(no_host.len(), 1);
+        assert!(no_host[0].message.contains("no host"));
+    }
+}
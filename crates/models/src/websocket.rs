@@ -0,0 +1,268 @@
+//! WebSocket request models
+//!
+//! A saved WebSocket request is a connection target plus messages the user
+//! keeps around to send; the frames exchanged while connected are recorded
+//! in a [`WebSocketLog`].
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use crate::request::Header;
+use crate::{Id, Identifiable, Temporal, Timestamp, new_id, now};
+
+/// A saved WebSocket connection
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebSocketRequest {
+    pub id: Id,
+    pub name: String,
+    pub description: Option<String>,
+
+    /// `ws://` or `wss://` URL (may contain variables)
+    pub url: String,
+
+    /// Headers sent with the opening handshake
+    #[serde(default)]
+    pub headers: Vec<Header>,
+
+    /// Subprotocols offered in `Sec-WebSocket-Protocol`, in preference order
+    #[serde(default)]
+    pub subprotocols: Vec<String>,
+
+    /// Saved outgoing messages
+    #[serde(default)]
+    pub messages: Vec<WebSocketMessage>,
+
+    pub collection_id: Option<Id>,
+    pub folder_id: Option<Id>,
+
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+}
+
+impl WebSocketRequest {
+    pub fn new(name: String, url: String) -> Self {
+        let now = now();
+        Self {
+            id: new_id(),
+            name,
+            description: None,
+            url,
+            headers: Vec::new(),
+            subprotocols: Vec::new(),
+            messages: Vec::new(),
+            collection_id: None,
+            folder_id: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn with_header(mut self, key: String, value: String) -> Self {
+        self.headers.push(Header::new(key, value));
+        self
+    }
+
+    pub fn with_subprotocol(mut self, subprotocol: String) -> Self {
+        self.subprotocols.push(subprotocol);
+        self
+    }
+
+    pub fn with_collection(mut self, collection_id: Id) -> Self {
+        self.collection_id = Some(collection_id);
+        self
+    }
+
+    /// Save a message for sending later
+    pub fn add_message(&mut self, message: WebSocketMessage) {
+        self.messages.push(message);
+        self.updated_at = now();
+    }
+
+    /// Check the URL uses `ws://` or `wss://`
+    ///
+    /// A URL starting with a variable can't be checked until it's resolved
+    /// and is accepted.
+    pub fn validate_url(&self) -> Result<(), String> {
+        let url = self.url.trim();
+        if url.starts_with("{{") {
+            return Ok(());
+        }
+        match url.split_once("://") {
+            Some((scheme, rest)) if matches!(scheme.to_ascii_lowercase().as_str(), "ws" | "wss") && !rest.is_empty() => {
+                Ok(())
+            }
+            _ => Err(format!("WebSocket URL must start with ws:// or wss://: {}", url)),
+        }
+    }
+
+    /// Whether the connection is over TLS
+    pub fn is_secure(&self) -> bool {
+        self.url.trim().to_ascii_lowercase().starts_with("wss://")
+    }
+}
+
+impl Temporal for WebSocketRequest {
+    fn created_at(&self) -> Timestamp {
+        self.created_at
+    }
+
+    fn updated_at(&self) -> Timestamp {
+        self.updated_at
+    }
+}
+
+impl Identifiable for WebSocketRequest {
+    fn id(&self) -> Id {
+        self.id
+    }
+}
+
+/// A saved outgoing message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebSocketMessage {
+    pub id: Id,
+    pub name: String,
+    pub payload: WebSocketPayload,
+}
+
+impl WebSocketMessage {
+    pub fn new(name: String, payload: WebSocketPayload) -> Self {
+        Self {
+            id: new_id(),
+            name,
+            payload,
+        }
+    }
+}
+
+/// Contents of a WebSocket data frame
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum WebSocketPayload {
+    Text { text: String },
+    /// Binary data, base64-encoded so it serializes compactly
+    Binary { base64: String },
+}
+
+impl WebSocketPayload {
+    pub fn text(text: String) -> Self {
+        Self::Text { text }
+    }
+
+    pub fn binary(data: &[u8]) -> Self {
+        Self::Binary {
+            base64: base64::engine::general_purpose::STANDARD.encode(data),
+        }
+    }
+
+    /// The payload as it goes on the wire; invalid base64 gives no bytes
+    pub fn bytes(&self) -> Vec<u8> {
+        match self {
+            WebSocketPayload::Text { text } => text.as_bytes().to_vec(),
+            WebSocketPayload::Binary { base64 } => base64::engine::general_purpose::STANDARD
+                .decode(base64)
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            WebSocketPayload::Text { text } => text.len(),
+            WebSocketPayload::Binary { .. } => self.bytes().len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Which way a frame went
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameDirection {
+    Sent,
+    Received,
+}
+
+/// A frame exchanged on a connection
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebSocketFrame {
+    pub direction: FrameDirection,
+    pub payload: WebSocketPayload,
+    pub timestamp: Timestamp,
+}
+
+/// Frames exchanged during one connection, oldest first
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebSocketLog {
+    pub request_id: Option<Id>,
+    #[serde(default)]
+    pub frames: Vec<WebSocketFrame>,
+}
+
+impl WebSocketLog {
+    pub fn new(request_id: Id) -> Self {
+        Self {
+            request_id: Some(request_id),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Record a frame as happening now
+    pub fn record(&mut self, direction: FrameDirection, payload: WebSocketPayload) {
+        self.frames.push(WebSocketFrame {
+            direction,
+            payload,
+            timestamp: now(),
+        });
+    }
+
+    /// Frames that went one way
+    pub fn frames_in(&self, direction: FrameDirection) -> impl Iterator<Item = &WebSocketFrame> {
+        self.frames.iter().filter(move |frame| frame.direction == direction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_websocket_request() {
+        let mut request = WebSocketRequest::new("Chat".to_string(), "wss://chat.example.com/socket".to_string())
+            .with_header("Authorization".to_string(), "Bearer abc".to_string())
+            .with_subprotocol("chat.v2".to_string());
+        request.add_message(WebSocketMessage::new("Hello".to_string(), WebSocketPayload::text("hi".to_string())));
+        request.add_message(WebSocketMessage::new("Ping".to_string(), WebSocketPayload::binary(&[0, 159, 255])));
+
+        assert!(request.validate_url().is_ok());
+        assert!(request.is_secure());
+        assert_eq!(request.messages[1].payload.bytes(), vec![0, 159, 255]);
+        assert_eq!(request.messages[1].payload.len(), 3);
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["messages"][0]["payload"], serde_json::json!({"type": "text", "text": "hi"}));
+        let restored: WebSocketRequest = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, request);
+
+        request.url = "https://chat.example.com".to_string();
+        assert!(request.validate_url().is_err());
+        request.url = "{{ws_base}}/socket".to_string();
+        assert!(request.validate_url().is_ok());
+    }
+
+    #[test]
+    fn test_websocket_log() {
+        let request = WebSocketRequest::new("Chat".to_string(), "ws://localhost:8080".to_string());
+        let mut log = WebSocketLog::new(request.id);
+        log.record(FrameDirection::Sent, WebSocketPayload::text("ping".to_string()));
+        log.record(FrameDirection::Received, WebSocketPayload::text("pong".to_string()));
+
+        assert_eq!(log.frames.len(), 2);
+        let received: Vec<_> = log.frames_in(FrameDirection::Received).collect();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].payload, WebSocketPayload::text("pong".to_string()));
+        assert!(log.frames[0].timestamp <= log.frames[1].timestamp);
+    }
+}
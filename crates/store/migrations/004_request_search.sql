@@ -0,0 +1,39 @@
+-- Migration: 004_request_search.sql
+-- Description: Full-text search over request name, URL and description
+-- Replaces the requests_fts table from 001, whose `url` column did not match
+-- any column of the external content table.
+
+DROP TRIGGER IF EXISTS requests_fts_insert;
+DROP TRIGGER IF EXISTS requests_fts_delete;
+DROP TRIGGER IF EXISTS requests_fts_update;
+DROP TABLE IF EXISTS requests_fts;
+
+CREATE VIRTUAL TABLE IF NOT EXISTS requests_fts USING fts5(
+    name,
+    url_raw,
+    description,
+    content='requests',
+    content_rowid='rowid'
+);
+
+CREATE TRIGGER IF NOT EXISTS requests_fts_insert AFTER INSERT ON requests BEGIN
+    INSERT INTO requests_fts(rowid, name, url_raw, description)
+    VALUES (new.rowid, new.name, new.url_raw, new.description);
+END;
+
+CREATE TRIGGER IF NOT EXISTS requests_fts_delete AFTER DELETE ON requests BEGIN
+    INSERT INTO requests_fts(requests_fts, rowid, name, url_raw, description)
+    VALUES ('delete', old.rowid, old.name, old.url_raw, old.description);
+END;
+
+CREATE TRIGGER IF NOT EXISTS requests_fts_update AFTER UPDATE OF name, url_raw, description ON requests BEGIN
+    INSERT INTO requests_fts(requests_fts, rowid, name, url_raw, description)
+    VALUES ('delete', old.rowid, old.name, old.url_raw, old.description);
+    INSERT INTO requests_fts(rowid, name, url_raw, description)
+    VALUES (new.rowid, new.name, new.url_raw, new.description);
+END;
+
+-- Index rows that existed before this migration
+INSERT INTO requests_fts(requests_fts) VALUES ('rebuild');
+
+INSERT OR IGNORE INTO schema_version (version, applied_at) VALUES (4, strftime('%s', 'now') * 1000);
@@ -0,0 +1,18 @@
+-- Migration: 005_soft_delete.sql
+-- Description: Soft-delete (trash) support for collections, requests and environments
+-- Rows with a non-NULL deleted_at are in the trash and hidden from normal queries.
+
+ALTER TABLE collections ADD COLUMN deleted_at INTEGER;
+ALTER TABLE requests ADD COLUMN deleted_at INTEGER;
+ALTER TABLE environments ADD COLUMN deleted_at INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_collections_deleted
+    ON collections(deleted_at) WHERE deleted_at IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_requests_deleted
+    ON requests(deleted_at) WHERE deleted_at IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_environments_deleted
+    ON environments(deleted_at) WHERE deleted_at IS NOT NULL;
+
+INSERT OR IGNORE INTO schema_version (version, applied_at) VALUES (5, strftime('%s', 'now') * 1000);
@@ -0,0 +1,15 @@
+-- Migration: 006_recently_opened.sql
+-- Description: Track when collections, requests and other items were last opened
+
+CREATE TABLE IF NOT EXISTS recently_opened (
+    item_type TEXT NOT NULL CHECK(item_type IN ('collection', 'folder', 'request', 'environment')),
+    item_id TEXT NOT NULL,
+    last_opened_at INTEGER NOT NULL,
+
+    PRIMARY KEY (item_type, item_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_recently_opened_at
+    ON recently_opened(last_opened_at DESC);
+
+INSERT OR IGNORE INTO schema_version (version, applied_at) VALUES (6, strftime('%s', 'now') * 1000);
@@ -0,0 +1,15 @@
+-- Migration: 007_sync_queue_dedup.sql
+-- Description: At most one unsynced change per item in sync_changes
+-- Mirrors the in-memory PendingChanges queue, where a newer change for the
+-- same item replaces the older one.
+
+DELETE FROM sync_changes
+WHERE synced = 0
+AND rowid NOT IN (
+    SELECT MAX(rowid) FROM sync_changes WHERE synced = 0 GROUP BY item_type, item_id
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_sync_changes_pending_item
+    ON sync_changes(item_type, item_id) WHERE synced = 0;
+
+INSERT OR IGNORE INTO schema_version (version, applied_at) VALUES (7, strftime('%s', 'now') * 1000);
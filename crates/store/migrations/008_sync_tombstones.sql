@@ -0,0 +1,18 @@
+-- Migration: 008_sync_tombstones.sql
+-- Description: Tombstones for deleted items
+-- Kept after the delete change is synced so older writes from other devices
+-- can't resurrect the item, and purged once older than the sync tombstone TTL.
+
+CREATE TABLE IF NOT EXISTS sync_tombstones (
+    item_type TEXT NOT NULL CHECK(item_type IN ('collection', 'folder', 'request', 'environment')),
+    item_id TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    deleted_at INTEGER NOT NULL,
+
+    PRIMARY KEY (item_type, item_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_sync_tombstones_deleted
+    ON sync_tombstones(deleted_at);
+
+INSERT OR IGNORE INTO schema_version (version, applied_at) VALUES (8, strftime('%s', 'now') * 1000);
@@ -0,0 +1,8 @@
+-- Migration: 009_collection_tags.sql
+-- Description: Tags on collections
+-- Stored as a JSON array of lowercase strings so they can be filtered with
+-- json_each without a join table.
+
+ALTER TABLE collections ADD COLUMN tags TEXT NOT NULL DEFAULT '[]';
+
+INSERT OR IGNORE INTO schema_version (version, applied_at) VALUES (9, strftime('%s', 'now') * 1000);
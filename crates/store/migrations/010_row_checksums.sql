@@ -0,0 +1,8 @@
+-- Migration: 010_row_checksums.sql
+-- Description: CRC32 checksums over the JSON columns of collections and requests
+-- NULL for rows written before this migration; those are not verified.
+
+ALTER TABLE collections ADD COLUMN checksum INTEGER;
+ALTER TABLE requests ADD COLUMN checksum INTEGER;
+
+INSERT OR IGNORE INTO schema_version (version, applied_at) VALUES (10, strftime('%s', 'now') * 1000);
@@ -0,0 +1,146 @@
+-- Migration: 011_custom_methods.sql
+-- Description: Allow TRACE, CONNECT and custom methods such as PURGE on requests
+-- SQLite can't drop a CHECK constraint in place, so the table is rebuilt.
+-- Dropping the old table nulls request_history.request_id through its
+-- ON DELETE SET NULL foreign key, so those links are saved and restored.
+-- The helper views from 001 reference requests and are recreated unchanged.
+
+CREATE TABLE requests_new (
+    id TEXT PRIMARY KEY NOT NULL,
+    collection_id TEXT REFERENCES collections(id) ON DELETE SET NULL,
+    folder_id TEXT REFERENCES folders(id) ON DELETE SET NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+
+    -- HTTP method, uppercase; any token is allowed for custom methods
+    method TEXT NOT NULL CHECK(method <> '' AND method = upper(method)),
+
+    url_raw TEXT NOT NULL,
+    url_protocol TEXT,
+    url_host TEXT,
+    url_path TEXT,
+
+    headers TEXT NOT NULL DEFAULT '[]',
+    query_params TEXT NOT NULL DEFAULT '[]',
+    body TEXT NOT NULL DEFAULT '{}',
+    auth TEXT,
+
+    script TEXT NOT NULL DEFAULT '{}',
+
+    ui_state TEXT NOT NULL DEFAULT '{}',
+
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+
+    fts_name TEXT GENERATED ALWAYS AS (name) STORED,
+    fts_url TEXT GENERATED ALWAYS AS (url_raw) STORED,
+
+    sync_state TEXT DEFAULT '{"status":"not_synced","version":null,"remote_id":null,"last_synced_at":null}',
+    deleted_at INTEGER,
+    checksum INTEGER
+);
+
+-- Keep rowids so the external-content FTS index stays aligned
+INSERT INTO requests_new (
+    rowid, id, collection_id, folder_id, name, description, method,
+    url_raw, url_protocol, url_host, url_path,
+    headers, query_params, body, auth, script, ui_state,
+    created_at, updated_at, sync_state, deleted_at, checksum
+)
+SELECT
+    rowid, id, collection_id, folder_id, name, description, method,
+    url_raw, url_protocol, url_host, url_path,
+    headers, query_params, body, auth, script, ui_state,
+    created_at, updated_at, sync_state, deleted_at, checksum
+FROM requests;
+
+CREATE TEMP TABLE history_request_links AS
+    SELECT id, request_id FROM request_history WHERE request_id IS NOT NULL;
+
+DROP VIEW IF EXISTS v_collections_with_stats;
+DROP VIEW IF EXISTS v_recent_history;
+
+DROP TABLE requests;
+ALTER TABLE requests_new RENAME TO requests;
+
+UPDATE request_history
+SET request_id = (SELECT request_id FROM history_request_links WHERE history_request_links.id = request_history.id)
+WHERE id IN (SELECT id FROM history_request_links);
+
+DROP TABLE history_request_links;
+
+CREATE INDEX IF NOT EXISTS idx_requests_collection
+    ON requests(collection_id);
+
+CREATE INDEX IF NOT EXISTS idx_requests_folder
+    ON requests(folder_id);
+
+CREATE INDEX IF NOT EXISTS idx_requests_method
+    ON requests(method);
+
+CREATE INDEX IF NOT EXISTS idx_requests_updated
+    ON requests(updated_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_requests_deleted
+    ON requests(deleted_at) WHERE deleted_at IS NOT NULL;
+
+CREATE TRIGGER IF NOT EXISTS requests_updated_at
+    AFTER UPDATE ON requests
+BEGIN
+    UPDATE requests SET updated_at = strftime('%s', 'now') * 1000
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS requests_fts_insert AFTER INSERT ON requests BEGIN
+    INSERT INTO requests_fts(rowid, name, url_raw, description)
+    VALUES (new.rowid, new.name, new.url_raw, new.description);
+END;
+
+CREATE TRIGGER IF NOT EXISTS requests_fts_delete AFTER DELETE ON requests BEGIN
+    INSERT INTO requests_fts(requests_fts, rowid, name, url_raw, description)
+    VALUES ('delete', old.rowid, old.name, old.url_raw, old.description);
+END;
+
+CREATE TRIGGER IF NOT EXISTS requests_fts_update AFTER UPDATE OF name, url_raw, description ON requests BEGIN
+    INSERT INTO requests_fts(requests_fts, rowid, name, url_raw, description)
+    VALUES ('delete', old.rowid, old.name, old.url_raw, old.description);
+    INSERT INTO requests_fts(rowid, name, url_raw, description)
+    VALUES (new.rowid, new.name, new.url_raw, new.description);
+END;
+
+INSERT INTO requests_fts(requests_fts) VALUES ('rebuild');
+
+CREATE VIEW IF NOT EXISTS v_collections_with_stats AS
+SELECT
+    c.id,
+    c.name,
+    c.description,
+    c.info,
+    c.auth,
+    c.sync_state,
+    c.created_at,
+    c.updated_at,
+    COUNT(DISTINCT r.id) as request_count,
+    COUNT(DISTINCT CASE WHEN r.folder_id IS NULL THEN r.id END) as root_request_count
+FROM collections c
+LEFT JOIN requests r ON r.collection_id = c.id
+GROUP BY c.id;
+
+CREATE VIEW IF NOT EXISTS v_recent_history AS
+SELECT
+    rh.id,
+    rh.request_id,
+    r.name as request_name,
+    rh.method,
+    rh.url,
+    rh.status_code,
+    rh.status_text,
+    rh.duration_ms,
+    rh.timestamp,
+    rh.test_results
+FROM request_history rh
+LEFT JOIN requests r ON rh.request_id = r.id
+ORDER BY rh.timestamp DESC
+LIMIT 100;
+
+INSERT OR IGNORE INTO schema_version (version, applied_at) VALUES (11, strftime('%s', 'now') * 1000);
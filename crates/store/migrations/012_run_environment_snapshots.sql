@@ -0,0 +1,14 @@
+-- Migration: 012_run_environment_snapshots.sql
+-- Description: Resolved variables in effect for each recorded send
+-- One row per history entry; removed together with the entry.
+
+CREATE TABLE IF NOT EXISTS run_environment_snapshots (
+    history_id TEXT PRIMARY KEY NOT NULL REFERENCES request_history(id) ON DELETE CASCADE,
+
+    -- JSON object of variable name to resolved value
+    variables TEXT NOT NULL DEFAULT '{}',
+
+    created_at INTEGER NOT NULL
+);
+
+INSERT OR IGNORE INTO schema_version (version, applied_at) VALUES (12, strftime('%s', 'now') * 1000);
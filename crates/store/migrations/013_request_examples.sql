@@ -0,0 +1,8 @@
+-- Migration: 013_request_examples.sql
+-- Description: Saved request/response examples on requests
+-- JSON array of RequestExample; covered by the row checksum, which
+-- run_migrations recomputes for rows written before this migration.
+
+ALTER TABLE requests ADD COLUMN examples TEXT NOT NULL DEFAULT '[]';
+
+INSERT OR IGNORE INTO schema_version (version, applied_at) VALUES (13, strftime('%s', 'now') * 1000);
@@ -0,0 +1,9 @@
+-- Migration: 014_request_settings.sql
+-- Description: Per-request timeout and redirect overrides
+-- NULL means the request follows the user's settings.
+
+ALTER TABLE requests ADD COLUMN timeout_secs INTEGER;
+ALTER TABLE requests ADD COLUMN follow_redirects INTEGER;
+ALTER TABLE requests ADD COLUMN max_redirects INTEGER;
+
+INSERT OR IGNORE INTO schema_version (version, applied_at) VALUES (14, strftime('%s', 'now') * 1000);
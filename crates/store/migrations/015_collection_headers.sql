@@ -0,0 +1,8 @@
+-- Migration: 015_collection_headers.sql
+-- Description: Headers sent with every request in a collection
+-- JSON array of Header; covered by the row checksum, which
+-- run_migrations recomputes for rows written before this migration.
+
+ALTER TABLE collections ADD COLUMN headers TEXT NOT NULL DEFAULT '[]';
+
+INSERT OR IGNORE INTO schema_version (version, applied_at) VALUES (15, strftime('%s', 'now') * 1000);
@@ -0,0 +1,7 @@
+-- Migration: 016_folder_auth.sql
+-- Description: Folder-level auth inherited by the requests inside
+-- JSON AuthConfig; NULL inherits from the parent folder or collection.
+
+ALTER TABLE folders ADD COLUMN auth TEXT;
+
+INSERT OR IGNORE INTO schema_version (version, applied_at) VALUES (16, strftime('%s', 'now') * 1000);
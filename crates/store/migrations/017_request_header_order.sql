@@ -0,0 +1,7 @@
+-- Migration: 017_request_header_order.sql
+-- Description: Whether a request keeps its headers exactly as listed
+-- 0 lets header normalization canonicalize names like `content-type`.
+
+ALTER TABLE requests ADD COLUMN preserve_header_order INTEGER NOT NULL DEFAULT 0;
+
+INSERT OR IGNORE INTO schema_version (version, applied_at) VALUES (17, strftime('%s', 'now') * 1000);
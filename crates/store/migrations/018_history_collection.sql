@@ -0,0 +1,11 @@
+-- Migration: 018_history_collection.sql
+-- Description: Collection a history entry was sent from
+-- Kept apart from request_id so sends of unsaved or since-deleted requests
+-- still go when their collection is deleted.
+
+ALTER TABLE request_history ADD COLUMN collection_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_history_collection
+    ON request_history(collection_id);
+
+INSERT OR IGNORE INTO schema_version (version, applied_at) VALUES (18, strftime('%s', 'now') * 1000);
@@ -0,0 +1,143 @@
+//! Checksums over serialized JSON columns
+//!
+//! Collections and requests store a CRC32 of their JSON columns, written on
+//! save and compared on load when `StoreConfig::verify_checksums` is set, so
+//! silent corruption surfaces as an error instead of a half-parsed record.
+
+use sqlx::{Row, sqlite::SqliteRow};
+
+use crate::{Database, StoreError, StoreResult};
+
+/// JSON columns covered by the `collections.checksum` column
+pub(crate) const COLLECTION_COLUMNS: &[&str] = &["info", "auth", "tags", "sync_state", "ui_state", "headers"];
+
+/// JSON columns covered by the `requests.checksum` column
+pub(crate) const REQUEST_COLUMNS: &[&str] = &["headers", "query_params", "body", "auth", "script", "ui_state", "examples"];
+
+/// Compute the checksum of a row's JSON columns, in column order
+///
+/// NULL and empty values hash differently so a dropped value is detected.
+pub(crate) fn checksum(values: &[Option<&str>]) -> i64 {
+    let mut hasher = crc32fast::Hasher::new();
+    for value in values {
+        match value {
+            Some(value) => {
+                hasher.update(&[1]);
+                hasher.update(value.as_bytes());
+            }
+            None => hasher.update(&[0]),
+        }
+        hasher.update(&[0x1f]);
+    }
+    hasher.finalize() as i64
+}
+
+/// Checksum of `columns` as currently stored in `row`
+pub(crate) fn row_checksum(row: &SqliteRow, columns: &[&str]) -> StoreResult<i64> {
+    let values = columns
+        .iter()
+        .map(|column| row.try_get::<Option<String>, _>(*column))
+        .collect::<Result<Vec<_>, _>>()?;
+    let values: Vec<Option<&str>> = values.iter().map(Option::as_deref).collect();
+    Ok(checksum(&values))
+}
+
+impl Database {
+    /// Check a row against its stored checksum
+    ///
+    /// Rows written before checksums existed have a NULL checksum and are
+    /// accepted as-is.
+    pub(crate) fn verified<'r>(&self, row: &'r SqliteRow, columns: &[&str]) -> StoreResult<&'r SqliteRow> {
+        if !self.verify_checksums() {
+            return Ok(row);
+        }
+
+        let Some(stored) = row.try_get::<Option<i64>, _>("checksum")? else {
+            return Ok(row);
+        };
+
+        if row_checksum(row, columns)? != stored {
+            return Err(StoreError::InvalidData("checksum mismatch".into()));
+        }
+        Ok(row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StoreConfig, open_store, test_db};
+    use models::{Collection, HttpMethod, Request};
+
+    #[test]
+    fn test_checksum_distinguishes_null_and_empty() {
+        assert_eq!(checksum(&[Some("{}"), None]), checksum(&[Some("{}"), None]));
+        assert_ne!(checksum(&[Some("{}"), None]), checksum(&[Some("{}"), Some("")]));
+        assert_ne!(checksum(&[Some("ab"), Some("c")]), checksum(&[Some("a"), Some("bc")]));
+    }
+
+    #[tokio::test]
+    async fn test_tampered_row_fails_verification() {
+        let db = test_db().await;
+        let collection = Collection::new("API".to_string());
+        let request = Request::new("Users".to_string(), HttpMethod::GET, "https://example.com/users".to_string());
+        db.save_collection(&collection).await.unwrap();
+        db.save_request(&request).await.unwrap();
+
+        assert!(db.get_collection(collection.id).await.unwrap().is_some());
+        assert!(db.get_request(request.id).await.unwrap().is_some());
+
+        sqlx::query("UPDATE collections SET info = '{\"schema\":\"tampered\"}' WHERE id = ?")
+            .bind(collection.id.to_string())
+            .execute(db.pool())
+            .await
+            .unwrap();
+        sqlx::query("UPDATE requests SET headers = '[{\"key\":\"X-Tampered\",\"value\":\"1\"}]' WHERE id = ?")
+            .bind(request.id.to_string())
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        let err = db.get_collection(collection.id).await.unwrap_err();
+        assert!(matches!(err, StoreError::InvalidData(ref msg) if msg == "checksum mismatch"));
+        assert!(db.list_collections_paged(10, 0).await.is_err());
+        assert!(matches!(db.get_request(request.id).await, Err(StoreError::InvalidData(_))));
+    }
+
+    #[tokio::test]
+    async fn test_verification_can_be_disabled() {
+        let config = StoreConfig {
+            db_path: ":memory:".to_string(),
+            max_connections: 1,
+            enable_wal: false,
+            ..Default::default()
+        }
+        .with_checksum_verification(false);
+        let db = open_store(config).await.unwrap();
+        let collection = Collection::new("API".to_string());
+        db.save_collection(&collection).await.unwrap();
+
+        sqlx::query("UPDATE collections SET ui_state = '{}' WHERE id = ?")
+            .bind(collection.id.to_string())
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        assert!(db.get_collection(collection.id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_rows_without_checksum_are_accepted() {
+        let db = test_db().await;
+        let collection = Collection::new("API".to_string());
+        db.save_collection(&collection).await.unwrap();
+
+        sqlx::query("UPDATE collections SET checksum = NULL, info = '{}' WHERE id = ?")
+            .bind(collection.id.to_string())
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        assert!(db.get_collection(collection.id).await.unwrap().is_some());
+    }
+}
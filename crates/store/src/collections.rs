@@ -0,0 +1,750 @@
+//! Collection persistence operations
+
+use sqlx::{Row, SqliteConnection, sqlite::SqliteRow};
+
+use crate::checksum::{COLLECTION_COLUMNS, checksum};
+use crate::events::{ChangeEvent, ChangeOp, EntityType, returned_events, upsert_op};
+use crate::requests::write_request;
+use crate::sync::write_change;
+use crate::{Database, Page, StoreError, StoreResult, Transaction, json_column, record_rows};
+use models::{Collection, Folder, Id, Request, SyncChange, SyncItemType, normalize_tag, now};
+
+impl Database {
+    /// List collections one page at a time, ordered by creation time
+    #[tracing::instrument(skip(self), fields(rows = tracing::field::Empty))]
+    pub async fn list_collections_paged(&self, limit: u32, offset: u32) -> StoreResult<Page<Collection>> {
+        let total_count = self.count_collections().await?;
+
+        let items = sqlx::query(
+            "SELECT * FROM collections WHERE deleted_at IS NULL
+            ORDER BY created_at, id LIMIT ? OFFSET ?"
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(self.pool())
+        .await?
+        .iter()
+        .map(|row| self.verified(row, COLLECTION_COLUMNS).and_then(collection_from_row))
+        .collect::<StoreResult<Vec<_>>>()?;
+
+        record_rows(items.len());
+        Ok(Page::new(items, total_count, limit, offset))
+    }
+
+    /// Count all collections not in the trash
+    pub async fn count_collections(&self) -> StoreResult<u64> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM collections WHERE deleted_at IS NULL")
+            .fetch_one(self.pool())
+            .await?;
+
+        Ok(count as u64)
+    }
+
+    /// List collections carrying a tag (case-insensitive), ordered by name
+    #[tracing::instrument(skip(self), fields(rows = tracing::field::Empty))]
+    pub async fn list_collections_by_tag(&self, tag: &str) -> StoreResult<Vec<Collection>> {
+        let collections = sqlx::query(
+            "SELECT * FROM collections
+            WHERE deleted_at IS NULL
+              AND EXISTS (SELECT 1 FROM json_each(collections.tags) WHERE json_each.value = ?)
+            ORDER BY name, id"
+        )
+        .bind(normalize_tag(tag))
+        .fetch_all(self.pool())
+        .await?
+        .iter()
+        .map(|row| self.verified(row, COLLECTION_COLUMNS).and_then(collection_from_row))
+        .collect::<StoreResult<Vec<_>>>()?;
+
+        record_rows(collections.len());
+        Ok(collections)
+    }
+
+    /// Approximate storage used by each collection, largest first
+    ///
+    /// Sums `LENGTH()` of the text and JSON columns of the collection, its
+    /// folders and its requests. Collections in the trash are not included.
+    #[tracing::instrument(skip(self), fields(rows = tracing::field::Empty))]
+    pub async fn storage_by_collection(&self) -> StoreResult<Vec<(Id, i64)>> {
+        let rows = sqlx::query(
+            "SELECT c.id AS id,
+                LENGTH(c.name) + IFNULL(LENGTH(c.description), 0) + LENGTH(c.info)
+                + IFNULL(LENGTH(c.auth), 0) + LENGTH(c.sync_state) + LENGTH(c.ui_state)
+                + IFNULL((
+                    SELECT SUM(LENGTH(f.name) + IFNULL(LENGTH(f.description), 0) + LENGTH(f.ui_state))
+                    FROM folders f WHERE f.collection_id = c.id
+                ), 0)
+                + IFNULL((
+                    SELECT SUM(
+                        LENGTH(r.name) + IFNULL(LENGTH(r.description), 0) + LENGTH(r.url_raw)
+                        + LENGTH(r.headers) + LENGTH(r.query_params) + LENGTH(r.body)
+                        + IFNULL(LENGTH(r.auth), 0) + LENGTH(r.script) + LENGTH(r.ui_state)
+                    )
+                    FROM requests r WHERE r.collection_id = c.id AND r.deleted_at IS NULL
+                ), 0) AS size
+            FROM collections c
+            WHERE c.deleted_at IS NULL
+            ORDER BY size DESC, c.id"
+        )
+        .fetch_all(self.pool())
+        .await?;
+
+        record_rows(rows.len());
+        rows.iter()
+            .map(|row| {
+                let id: String = row.try_get("id")?;
+                Ok((parse_id(&id)?, row.try_get("size")?))
+            })
+            .collect()
+    }
+
+    /// Get a collection by id, ignoring collections in the trash
+    #[tracing::instrument(skip(self), fields(id = %id))]
+    pub async fn get_collection(&self, id: Id) -> StoreResult<Option<Collection>> {
+        sqlx::query("SELECT * FROM collections WHERE id = ? AND deleted_at IS NULL")
+            .bind(id.to_string())
+            .fetch_optional(self.pool())
+            .await?
+            .as_ref()
+            .map(|row| self.verified(row, COLLECTION_COLUMNS).and_then(collection_from_row))
+            .transpose()
+    }
+
+    /// Get a folder by id
+    ///
+    /// Child folders and request IDs are not loaded; `children` and
+    /// `requests` are left empty.
+    #[tracing::instrument(skip(self), fields(id = %id))]
+    pub async fn get_folder(&self, id: Id) -> StoreResult<Option<Folder>> {
+        sqlx::query("SELECT * FROM folders WHERE id = ?")
+            .bind(id.to_string())
+            .fetch_optional(self.pool())
+            .await?
+            .as_ref()
+            .map(folder_from_row)
+            .transpose()
+    }
+
+    /// Insert or update a collection
+    ///
+    /// Folders, root request IDs and variables are stored separately and are
+    /// not written here.
+    #[tracing::instrument(skip_all, fields(id = %collection.id))]
+    pub async fn save_collection(&self, collection: &Collection) -> StoreResult<()> {
+        self.ensure_writable()?;
+        let mut tx = self.pool().begin().await?;
+        let op = upsert_op(&mut tx, EntityType::Collection, collection.id).await?;
+        write_collection(&mut tx, collection).await?;
+        tx.commit().await?;
+        self.emit([ChangeEvent::new(EntityType::Collection, op, collection.id)]);
+        Ok(())
+    }
+
+    /// Save a collection with all of its nested folders and the given
+    /// requests in one transaction
+    ///
+    /// Requests without a collection are assigned to this one; a request that
+    /// belongs to another collection is rejected. If any row fails to write,
+    /// nothing is saved.
+    #[tracing::instrument(skip_all, fields(id = %collection.id, rows = tracing::field::Empty))]
+    pub async fn save_collection_tree(&self, collection: &Collection, requests: &[Request]) -> StoreResult<()> {
+        self.ensure_writable()?;
+        let mut tx = Transaction::new(self.pool().begin().await?);
+
+        let mut events = Vec::with_capacity(1 + requests.len());
+        match write_collection_tree(tx.as_mut(), collection, requests, &mut events).await {
+            Ok(rows) => {
+                tx.commit().await?;
+                record_rows(rows);
+                self.emit(events);
+                Ok(())
+            }
+            Err(e) => {
+                tx.rollback().await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Permanently delete a collection with its folders, requests, their
+    /// history and any pending sync changes, in one transaction
+    ///
+    /// Pending changes are replaced by delete changes (and tombstones) for
+    /// the collection, its folders and its requests, so the deletion syncs.
+    /// Works on trashed and live collections alike; prefer
+    /// [`Database::delete_collection`] when the user may want to undo. A
+    /// missing collection is not an error and returns all zeros.
+    #[tracing::instrument(skip(self), fields(id = %id, rows = tracing::field::Empty))]
+    pub async fn delete_collection_cascade(&self, id: Id) -> StoreResult<DeleteSummary> {
+        self.ensure_writable()?;
+        let mut tx = self.pool().begin().await?;
+        let mut removed = [0; CASCADE_DELETES.len()];
+
+        let Some(row) = sqlx::query("SELECT * FROM collections WHERE id = ?")
+            .bind(id.to_string())
+            .fetch_optional(&mut *tx)
+            .await?
+        else {
+            return Ok(DeleteSummary::default());
+        };
+        let version = collection_from_row(&row)?.current_version() + 1;
+        // Subfolders go through the parent_id cascade and aren't in rows_affected
+        let folder_ids: Vec<String> = sqlx::query_scalar("SELECT id FROM folders WHERE collection_id = ?")
+            .bind(id.to_string())
+            .fetch_all(&mut *tx)
+            .await?;
+        let request_ids: Vec<String> = sqlx::query_scalar("SELECT id FROM requests WHERE collection_id = ?")
+            .bind(id.to_string())
+            .fetch_all(&mut *tx)
+            .await?;
+        let request_events = returned_events(EntityType::Request, ChangeOp::Delete, &request_ids)?;
+
+        for (count, statement) in removed.iter_mut().zip(CASCADE_DELETES) {
+            *count = sqlx::query(statement)
+                .bind(id.to_string())
+                .execute(&mut *tx)
+                .await?
+                .rows_affected() as usize;
+        }
+
+        let deleted = std::iter::once((SyncItemType::Collection, id.to_string()))
+            .chain(folder_ids.iter().map(|folder| (SyncItemType::Folder, folder.clone())))
+            .chain(request_ids.iter().map(|request| (SyncItemType::Request, request.clone())));
+        for (item_type, item_id) in deleted {
+            write_change(&mut tx, &SyncChange::delete(item_type, parse_id(&item_id)?, version)).await?;
+        }
+
+        tx.commit().await?;
+        let [history, sync_changes, _recent, requests, _, collections] = removed;
+        let summary = DeleteSummary { collections, folders: folder_ids.len(), requests, history, sync_changes };
+        record_rows(collections + summary.folders + requests + history + sync_changes);
+        if collections > 0 {
+            self.emit(std::iter::once(ChangeEvent::new(EntityType::Collection, ChangeOp::Delete, id)).chain(request_events));
+        }
+        Ok(summary)
+    }
+
+    /// Move a collection and its requests to the trash
+    ///
+    /// The requests share the collection's `deleted_at`, so restoring the
+    /// collection brings back exactly the requests trashed with it.
+    #[tracing::instrument(skip(self), fields(id = %id, rows = tracing::field::Empty))]
+    pub async fn delete_collection(&self, id: Id) -> StoreResult<()> {
+        self.ensure_writable()?;
+        let deleted_at = now();
+        let mut tx = self.pool().begin().await?;
+
+        let result = sqlx::query(
+            "UPDATE collections SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL"
+        )
+        .bind(deleted_at)
+        .bind(id.to_string())
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(StoreError::NotFound(format!("Collection {}", id)));
+        }
+
+        let request_ids: Vec<String> = sqlx::query_scalar(
+            "UPDATE requests SET deleted_at = ? WHERE collection_id = ? AND deleted_at IS NULL RETURNING id"
+        )
+        .bind(deleted_at)
+        .bind(id.to_string())
+        .fetch_all(&mut *tx)
+        .await?;
+        let mut events = vec![ChangeEvent::new(EntityType::Collection, ChangeOp::Delete, id)];
+        events.extend(returned_events(EntityType::Request, ChangeOp::Delete, &request_ids)?);
+
+        tx.commit().await?;
+        record_rows(events.len());
+        self.emit(events);
+        Ok(())
+    }
+}
+
+/// Delete statements for [`Database::delete_collection_cascade`], children
+/// first; each binds the collection id as `?1`
+const CASCADE_DELETES: [&str; 6] = [
+    "DELETE FROM request_history WHERE collection_id = ?1
+        OR request_id IN (SELECT id FROM requests WHERE collection_id = ?1)",
+    "DELETE FROM sync_changes WHERE (item_type = 'collection' AND item_id = ?1)
+        OR (item_type = 'folder' AND item_id IN (SELECT id FROM folders WHERE collection_id = ?1))
+        OR (item_type = 'request' AND item_id IN (SELECT id FROM requests WHERE collection_id = ?1))",
+    "DELETE FROM recently_opened WHERE (item_type = 'collection' AND item_id = ?1)
+        OR (item_type = 'request' AND item_id IN (SELECT id FROM requests WHERE collection_id = ?1))",
+    "DELETE FROM requests WHERE collection_id = ?1",
+    "DELETE FROM folders WHERE collection_id = ?1",
+    "DELETE FROM collections WHERE id = ?1",
+];
+
+/// Upsert a `collections` row, leaving `deleted_at` untouched
+pub(crate) async fn write_collection(conn: &mut SqliteConnection, collection: &Collection) -> StoreResult<()> {
+    let info = to_json(&collection.info)?;
+    let auth = collection.auth.as_ref().map(to_json).transpose()?;
+    let sync_state = to_json(&collection.sync_state)?;
+    let ui_state = to_json(&collection.ui_state)?;
+    let tags = to_json(&collection.tags)?;
+    let headers = to_json(&collection.headers)?;
+    let checksum = checksum(&[
+        Some(&info), auth.as_deref(), Some(&tags), Some(&sync_state), Some(&ui_state), Some(&headers),
+    ]);
+
+    sqlx::query(
+        "INSERT INTO collections
+        (id, name, description, info, auth, tags, headers, sync_state, ui_state, checksum, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(id) DO UPDATE SET
+            name = excluded.name,
+            description = excluded.description,
+            info = excluded.info,
+            auth = excluded.auth,
+            tags = excluded.tags,
+            headers = excluded.headers,
+            sync_state = excluded.sync_state,
+            ui_state = excluded.ui_state,
+            checksum = excluded.checksum,
+            updated_at = excluded.updated_at"
+    )
+    .bind(collection.id.to_string())
+    .bind(&collection.name)
+    .bind(&collection.description)
+    .bind(&info)
+    .bind(&auth)
+    .bind(&tags)
+    .bind(&headers)
+    .bind(&sync_state)
+    .bind(&ui_state)
+    .bind(checksum)
+    .bind(collection.created_at)
+    .bind(collection.updated_at)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Rows removed by [`Database::delete_collection_cascade`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct DeleteSummary {
+    pub collections: usize,
+    pub folders: usize,
+    pub requests: usize,
+    pub history: usize,
+    pub sync_changes: usize,
+}
+
+/// Write a collection, its folders (parents before children) and requests;
+/// returns the number of rows written and pushes a change event for the
+/// collection and each request to `events`
+async fn write_collection_tree(
+    conn: &mut SqliteConnection,
+    collection: &Collection,
+    requests: &[Request],
+    events: &mut Vec<ChangeEvent>,
+) -> StoreResult<usize> {
+    let op = upsert_op(conn, EntityType::Collection, collection.id).await?;
+    write_collection(conn, collection).await?;
+    events.push(ChangeEvent::new(EntityType::Collection, op, collection.id));
+
+    let mut pending: Vec<(Option<Id>, &Folder)> = collection.folders.iter().rev().map(|f| (None, f)).collect();
+    let mut folders = 0;
+    while let Some((parent_id, folder)) = pending.pop() {
+        write_folder(conn, collection.id, parent_id, folder).await?;
+        pending.extend(folder.children.iter().rev().map(|child| (Some(folder.id), child)));
+        folders += 1;
+    }
+
+    for request in requests {
+        let mut request = request.clone();
+        match request.collection_id {
+            Some(id) if id != collection.id => {
+                return Err(StoreError::InvalidData(format!(
+                    "Request {} belongs to collection {}, not {}",
+                    request.id, id, collection.id
+                )));
+            }
+            _ => request.collection_id = Some(collection.id),
+        }
+        let op = upsert_op(conn, EntityType::Request, request.id).await?;
+        write_request(conn, &request).await?;
+        events.push(ChangeEvent::new(EntityType::Request, op, request.id));
+    }
+
+    Ok(1 + folders + requests.len())
+}
+
+/// Upsert a `folders` row under `parent_id` (None for the collection root)
+pub(crate) async fn write_folder(
+    conn: &mut SqliteConnection,
+    collection_id: Id,
+    parent_id: Option<Id>,
+    folder: &Folder,
+) -> StoreResult<()> {
+    sqlx::query(
+        "INSERT INTO folders (id, collection_id, parent_id, name, description, auth, ui_state, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(id) DO UPDATE SET
+            collection_id = excluded.collection_id,
+            parent_id = excluded.parent_id,
+            name = excluded.name,
+            description = excluded.description,
+            auth = excluded.auth,
+            ui_state = excluded.ui_state,
+            updated_at = excluded.updated_at"
+    )
+    .bind(folder.id.to_string())
+    .bind(collection_id.to_string())
+    .bind(parent_id.map(|id| id.to_string()))
+    .bind(&folder.name)
+    .bind(&folder.description)
+    .bind(folder.auth.as_ref().map(to_json).transpose()?)
+    .bind(to_json(&folder.ui_state)?)
+    .bind(folder.created_at)
+    .bind(folder.updated_at)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Build a `Folder` from a `folders` row, without children or request IDs
+pub(crate) fn folder_from_row(row: &SqliteRow) -> StoreResult<Folder> {
+    let id: String = row.try_get("id")?;
+    let parent_id: Option<String> = row.try_get("parent_id")?;
+    let auth: Option<String> = row.try_get("auth")?;
+
+    let mut folder = Folder::new(row.try_get("name")?);
+    folder.id = parse_id(&id)?;
+    folder.description = row.try_get("description")?;
+    folder.parent_id = parent_id.as_deref().map(parse_id).transpose()?;
+    folder.auth = auth.and_then(|a| serde_json::from_str(&a).ok());
+    folder.ui_state = json_column(&row.try_get::<String, _>("ui_state")?);
+    folder.created_at = row.try_get("created_at")?;
+    folder.updated_at = row.try_get("updated_at")?;
+    Ok(folder)
+}
+
+/// Build a `Collection` from a `collections` row
+///
+/// Folders, root request IDs and variables live in their own tables and are
+/// left empty here.
+pub(crate) fn collection_from_row(row: &SqliteRow) -> StoreResult<Collection> {
+    let id: String = row.try_get("id")?;
+    let auth: Option<String> = row.try_get("auth")?;
+
+    let mut collection = Collection::new(row.try_get("name")?);
+    collection.id = parse_id(&id)?;
+    collection.description = row.try_get("description")?;
+    collection.info = json_column(&row.try_get::<String, _>("info")?);
+    collection.auth = auth.and_then(|a| serde_json::from_str(&a).ok());
+    collection.tags = json_column(&row.try_get::<String, _>("tags")?);
+    collection.headers = json_column(&row.try_get::<String, _>("headers")?);
+    collection.sync_state = json_column(&row.try_get::<String, _>("sync_state")?);
+    collection.ui_state = json_column(&row.try_get::<String, _>("ui_state")?);
+    collection.created_at = row.try_get("created_at")?;
+    collection.updated_at = row.try_get("updated_at")?;
+
+    Ok(collection)
+}
+
+/// Serialize a value for a JSON column
+pub(crate) fn to_json<T: serde::Serialize + ?Sized>(value: &T) -> StoreResult<String> {
+    serde_json::to_string(value).map_err(|e| StoreError::Serialization(e.to_string()))
+}
+
+/// Parse a TEXT id column
+pub(crate) fn parse_id(id: &str) -> StoreResult<Id> {
+    id.parse()
+        .map_err(|_| StoreError::InvalidData(format!("Invalid id: {}", id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_db;
+    use crate::test_support::insert_collection;
+    use models::new_id;
+
+    #[tokio::test]
+    async fn test_list_collections_paged() {
+        let db = test_db().await;
+        let base = now();
+        let first = insert_collection(&db, "First", base).await;
+        let second = insert_collection(&db, "Second", base + 1).await;
+        let third = insert_collection(&db, "Third", base + 2).await;
+
+        assert_eq!(db.count_collections().await.unwrap(), 3);
+
+        let page = db.list_collections_paged(2, 0).await.unwrap();
+        assert_eq!(page.total_count, 3);
+        assert_eq!(page.items.iter().map(|c| c.id).collect::<Vec<_>>(), vec![first, second]);
+        assert!(page.has_more());
+
+        let page = db.list_collections_paged(2, 2).await.unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].id, third);
+        assert_eq!(page.items[0].name, "Third");
+        assert!(!page.has_more());
+    }
+
+    #[tokio::test]
+    async fn test_storage_by_collection() {
+        let db = test_db().await;
+        let small = insert_collection(&db, "Small", now()).await;
+        let large = insert_collection(&db, "Large", now()).await;
+
+        sqlx::query(
+            "INSERT INTO requests (id, collection_id, name, method, url_raw, body, created_at, updated_at)
+            VALUES (?, ?, 'Upload', 'POST', 'https://example.com', ?, ?, ?)"
+        )
+        .bind(new_id().to_string())
+        .bind(large.to_string())
+        .bind("x".repeat(5_000))
+        .bind(now())
+        .bind(now())
+        .execute(db.pool())
+        .await
+        .unwrap();
+
+        let sizes = db.storage_by_collection().await.unwrap();
+        assert_eq!(sizes.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![large, small]);
+        assert!(sizes[0].1 > sizes[1].1 + 5_000);
+    }
+
+    #[tokio::test]
+    async fn test_save_collection_round_trip() {
+        let db = test_db().await;
+        let mut collection = Collection::new("API".to_string());
+        collection.description = Some("Public API".to_string());
+        db.save_collection(&collection).await.unwrap();
+
+        collection.name = "Renamed".to_string();
+        collection.bump_version();
+        db.save_collection(&collection).await.unwrap();
+
+        let loaded = db.get_collection(collection.id).await.unwrap().unwrap();
+        assert_eq!(loaded.name, "Renamed");
+        assert_eq!(loaded.description.as_deref(), Some("Public API"));
+        assert_eq!(loaded.sync_state, collection.sync_state);
+        assert_eq!(db.count_collections().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_collection_headers_round_trip() {
+        let db = test_db().await;
+        let mut collection = Collection::new("API".to_string())
+            .with_header("X-Api-Version".to_string(), "2".to_string());
+        db.save_collection(&collection).await.unwrap();
+        assert_eq!(db.get_collection(collection.id).await.unwrap().unwrap().headers, collection.headers);
+
+        let export = db.export_json().await.unwrap();
+        let target = test_db().await;
+        target.import_json(&export).await.unwrap();
+        assert_eq!(target.get_collection(collection.id).await.unwrap().unwrap().headers, collection.headers);
+
+        collection.headers.clear();
+        db.save_collection(&collection).await.unwrap();
+        assert!(db.get_collection(collection.id).await.unwrap().unwrap().headers.is_empty());
+    }
+
+    /// Subscriber that remembers the names of entered spans
+    #[derive(Default)]
+    struct SpanRecorder {
+        spans: std::sync::Mutex<Vec<&'static str>>,
+        entered: std::sync::Arc<std::sync::Mutex<Vec<&'static str>>>,
+    }
+
+    impl tracing::Subscriber for SpanRecorder {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            let mut spans = self.spans.lock().unwrap();
+            spans.push(attrs.metadata().name());
+            tracing::span::Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn event(&self, _: &tracing::Event<'_>) {}
+
+        fn enter(&self, span: &tracing::span::Id) {
+            let name = self.spans.lock().unwrap()[span.into_u64() as usize - 1];
+            self.entered.lock().unwrap().push(name);
+        }
+
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    #[tokio::test]
+    async fn test_save_collection_enters_span() {
+        let db = test_db().await;
+        let recorder = SpanRecorder::default();
+        let entered = recorder.entered.clone();
+        let _guard = tracing::subscriber::set_default(recorder);
+
+        db.save_collection(&Collection::new("API".to_string())).await.unwrap();
+
+        assert!(entered.lock().unwrap().contains(&"save_collection"));
+    }
+
+    #[tokio::test]
+    async fn test_delete_collection_hides_it() {
+        let db = test_db().await;
+        let kept = insert_collection(&db, "Kept", now()).await;
+        let deleted = insert_collection(&db, "Deleted", now()).await;
+
+        db.delete_collection(deleted).await.unwrap();
+
+        assert!(db.get_collection(deleted).await.unwrap().is_none());
+        assert!(db.get_collection(kept).await.unwrap().is_some());
+        assert_eq!(db.count_collections().await.unwrap(), 1);
+        assert_eq!(db.list_collections_paged(10, 0).await.unwrap().items.len(), 1);
+
+        // Already in the trash
+        assert!(matches!(db.delete_collection(deleted).await, Err(StoreError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_list_collections_by_tag() {
+        let db = test_db().await;
+        let mut billing = Collection::new("Billing".to_string());
+        billing.add_tag("payments");
+        let mut invoices = Collection::new("Invoices".to_string());
+        invoices.add_tag("Payments");
+        invoices.add_tag("internal");
+        let mut trashed = Collection::new("Old".to_string());
+        trashed.add_tag("payments");
+        for collection in [&billing, &invoices, &trashed] {
+            db.save_collection(collection).await.unwrap();
+        }
+        db.save_collection(&Collection::new("Untagged".to_string())).await.unwrap();
+        db.delete_collection(trashed.id).await.unwrap();
+
+        let tagged = db.list_collections_by_tag("PAYMENTS").await.unwrap();
+        assert_eq!(tagged.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec!["Billing", "Invoices"]);
+        assert_eq!(tagged[1].tags, vec!["payments", "internal"]);
+        assert!(db.list_collections_by_tag("missing").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_save_collection_tree() {
+        let db = test_db().await;
+        let mut collection = Collection::new("Shop".to_string());
+        let mut users = Folder::new("Users".to_string());
+        let admin = Folder::new("Admin".to_string());
+        let admin_id = admin.id;
+        users.add_child(admin);
+        let users_id = users.id;
+        collection.add_folder(users);
+
+        let mut request = Request::new("List admins".to_string(), models::HttpMethod::GET, "https://example.com/admins".to_string());
+        request.folder_id = Some(admin_id);
+        db.save_collection_tree(&collection, &[request.clone()]).await.unwrap();
+
+        let parent: Option<String> = sqlx::query_scalar("SELECT parent_id FROM folders WHERE id = ?")
+            .bind(admin_id.to_string())
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(parent, Some(users_id.to_string()));
+        let saved = db.get_request(request.id).await.unwrap().unwrap();
+        assert_eq!((saved.collection_id, saved.folder_id), (Some(collection.id), Some(admin_id)));
+
+        // A request in a folder that doesn't exist fails the foreign key and
+        // rolls back everything written before it
+        let other = Collection::new("Other".to_string());
+        let mut orphan = Request::new("Orphan".to_string(), models::HttpMethod::GET, "https://example.com".to_string());
+        orphan.folder_id = Some(new_id());
+        let valid = Request::new("Valid".to_string(), models::HttpMethod::GET, "https://example.com".to_string());
+        assert!(db.save_collection_tree(&other, &[valid.clone(), orphan]).await.is_err());
+        assert!(db.get_collection(other.id).await.unwrap().is_none());
+        assert!(db.get_request(valid.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_folder_auth_round_trip() {
+        let db = test_db().await;
+        let mut collection = Collection::new("Shop".to_string());
+        let mut users = Folder::new("Users".to_string());
+        users.auth = Some(models::AuthConfig::Bearer { token: "{{token}}".to_string() });
+        let plain = Folder::new("Plain".to_string());
+        let (users_id, plain_id) = (users.id, plain.id);
+        collection.add_folder(users.clone());
+        collection.add_folder(plain);
+        db.save_collection_tree(&collection, &[]).await.unwrap();
+
+        let loaded = db.get_folder(users_id).await.unwrap().unwrap();
+        assert_eq!(loaded.name, "Users");
+        assert_eq!(loaded.auth, users.auth);
+        assert_eq!(db.get_folder(plain_id).await.unwrap().unwrap().auth, None);
+        assert!(db.get_folder(new_id()).await.unwrap().is_none());
+
+        // Export and import carry it too
+        let export = db.export_json().await.unwrap();
+        let target = test_db().await;
+        target.import_json(&export).await.unwrap();
+        assert_eq!(target.get_folder(users_id).await.unwrap().unwrap().auth, users.auth);
+    }
+
+    #[tokio::test]
+    async fn test_delete_collection_cascade() {
+        let db = test_db().await;
+        let mut collection = Collection::new("Shop".to_string());
+        let mut users = Folder::new("Users".to_string());
+        users.add_child(Folder::new("Admin".to_string()));
+        collection.add_folder(users);
+        let request = Request::new("Users".to_string(), models::HttpMethod::GET, "https://example.com/users".to_string());
+        db.save_collection_tree(&collection, std::slice::from_ref(&request)).await.unwrap();
+        let kept = Collection::new("Kept".to_string());
+        db.save_collection(&kept).await.unwrap();
+
+        let mut saved = request.clone();
+        saved.collection_id = Some(collection.id);
+        let response = models::Response::new(200, "OK".to_string());
+        db.record_history(crate::history::HistoryEntry::from_exchange(&saved, &response)).await.unwrap();
+        // Sent from the collection but never saved
+        let mut unsaved = Request::new("Draft".to_string(), models::HttpMethod::GET, "https://example.com".to_string());
+        unsaved.collection_id = Some(collection.id);
+        db.record_history(crate::history::HistoryEntry::from_exchange(&unsaved, &response).without_request())
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO sync_changes (id, item_type, item_id, operation, version, data, created_at)
+            VALUES (?, 'request', ?, 'create', 1, '{}', ?)"
+        )
+        .bind(new_id().to_string())
+        .bind(request.id.to_string())
+        .bind(now())
+        .execute(db.pool())
+        .await
+        .unwrap();
+
+        let summary = db.delete_collection_cascade(collection.id).await.unwrap();
+        assert_eq!(
+            summary,
+            DeleteSummary { collections: 1, folders: 2, requests: 1, history: 2, sync_changes: 1 }
+        );
+        assert!(db.get_request(request.id).await.unwrap().is_none());
+        assert!(db.get_collection(kept.id).await.unwrap().is_some());
+
+        // Deletes are queued for sync, with tombstones so pulls don't resurrect them
+        let mut tombstones: Vec<SyncItemType> = db.tombstones().await.unwrap().iter().map(|t| t.item_type).collect();
+        tombstones.sort_by_key(|t| t.as_str());
+        assert_eq!(
+            tombstones,
+            vec![SyncItemType::Collection, SyncItemType::Folder, SyncItemType::Folder, SyncItemType::Request]
+        );
+        let pending: Vec<(String, String)> = sqlx::query_as("SELECT item_id, operation FROM sync_changes WHERE item_type = 'request'")
+            .fetch_all(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(pending, vec![(request.id.to_string(), "delete".to_string())]);
+
+        assert_eq!(db.delete_collection_cascade(collection.id).await.unwrap(), DeleteSummary::default());
+    }
+}
@@ -0,0 +1,234 @@
+//! Applying sync conflict resolutions
+//!
+//! Conflicts are recorded in `sync_conflicts` by the sync engine. Resolving
+//! one writes the chosen value back to the item's table and queues it to be
+//! pushed with a version above both sides. Resolving a request or folder
+//! also bumps the version of the collection holding it.
+
+use sqlx::SqliteConnection;
+
+use crate::collections::{collection_from_row, parse_id, to_json, write_collection};
+use crate::environments::write_environment;
+use crate::events::{ChangeEvent, ChangeOp, EntityType};
+use crate::requests::write_request;
+use crate::sync::write_change;
+use crate::{Database, StoreError, StoreResult};
+use models::{
+    Collection, ConflictChoice, ConflictInfo, Environment, Folder, Id, Request, SyncChange, SyncItemType, now,
+};
+
+impl Database {
+    /// Write the chosen side of a conflict back to the store
+    ///
+    /// The item is saved with version `max(local, remote) + 1`, an update is
+    /// queued in `sync_changes`, and the conflict is marked resolved. For a
+    /// request or folder, the collection holding it gets a new version and is
+    /// marked pending.
+    #[tracing::instrument(skip_all, fields(id = %conflict.item_id, item_type = conflict.item_type.as_str()))]
+    pub async fn apply_resolution(&self, conflict: &ConflictInfo, choice: &ConflictChoice) -> StoreResult<()> {
+        self.ensure_writable()?;
+        let (value, resolution) = match choice {
+            ConflictChoice::Local => (conflict.local_value.clone(), "local"),
+            ConflictChoice::Remote => (conflict.remote_value.clone(), "remote"),
+            ConflictChoice::Merged { value } => (value.clone(), "merged"),
+        };
+        let version = conflict.local_version.max(conflict.remote_version) + 1;
+        let invalid = |e: serde_json::Error| StoreError::Deserialization(e.to_string());
+
+        let mut tx = self.pool().begin().await?;
+
+        let mut parent = None;
+        match conflict.item_type {
+            SyncItemType::Collection => {
+                let mut collection: Collection = serde_json::from_value(value.clone()).map_err(invalid)?;
+                collection.id = conflict.item_id;
+                collection.sync_state.version = Some(version - 1);
+                collection.bump_version();
+                write_collection(&mut tx, &collection).await?;
+            }
+            SyncItemType::Request => {
+                let mut request: Request = serde_json::from_value(value.clone()).map_err(invalid)?;
+                request.id = conflict.item_id;
+                write_request(&mut tx, &request).await?;
+                if let Some(collection_id) = request.collection_id {
+                    parent = bump_collection_version(&mut tx, collection_id).await?;
+                }
+            }
+            SyncItemType::Environment => {
+                let mut environment: Environment = serde_json::from_value(value.clone()).map_err(invalid)?;
+                environment.id = conflict.item_id;
+                write_environment(&mut tx, &environment).await?;
+
+                sqlx::query(
+                    "UPDATE environments SET sync_state = json_set(sync_state, '$.version', ?) WHERE id = ?"
+                )
+                .bind(version)
+                .bind(conflict.item_id.to_string())
+                .execute(&mut *tx)
+                .await?;
+            }
+            SyncItemType::Folder => {
+                // Folder values don't carry their collection, so only existing rows can be updated
+                let folder: Folder = serde_json::from_value(value.clone()).map_err(invalid)?;
+                let ui_state = to_json(&folder.ui_state)?;
+
+                let result = sqlx::query(
+                    "UPDATE folders SET name = ?, description = ?, parent_id = ?, ui_state = ?, updated_at = ?
+                    WHERE id = ?"
+                )
+                .bind(&folder.name)
+                .bind(&folder.description)
+                .bind(folder.parent_id.map(|id| id.to_string()))
+                .bind(&ui_state)
+                .bind(now())
+                .bind(conflict.item_id.to_string())
+                .execute(&mut *tx)
+                .await?;
+
+                if result.rows_affected() == 0 {
+                    return Err(StoreError::NotFound(format!("Folder {}", conflict.item_id)));
+                }
+
+                let collection_id: String = sqlx::query_scalar("SELECT collection_id FROM folders WHERE id = ?")
+                    .bind(conflict.item_id.to_string())
+                    .fetch_one(&mut *tx)
+                    .await?;
+                parent = bump_collection_version(&mut tx, parse_id(&collection_id)?).await?;
+            }
+        }
+
+        let change = SyncChange::update(conflict.item_type, conflict.item_id, version, value);
+        write_change(&mut tx, &change).await?;
+
+        sqlx::query(
+            "UPDATE sync_conflicts SET resolved = 1, resolution = ?, resolved_at = ? WHERE conflict_id = ?"
+        )
+        .bind(resolution)
+        .bind(now())
+        .bind(conflict.conflict_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        let entity = match conflict.item_type {
+            SyncItemType::Collection => Some(EntityType::Collection),
+            SyncItemType::Request => Some(EntityType::Request),
+            SyncItemType::Environment => Some(EntityType::Environment),
+            SyncItemType::Folder => None,
+        };
+        self.emit(
+            entity
+                .map(|entity| ChangeEvent::new(entity, ChangeOp::Update, conflict.item_id))
+                .into_iter()
+                .chain(parent.map(|id| ChangeEvent::new(EntityType::Collection, ChangeOp::Update, id))),
+        );
+        Ok(())
+    }
+}
+
+/// Bump the sync version of collection `id`, if it exists; returns its id
+async fn bump_collection_version(conn: &mut SqliteConnection, id: Id) -> StoreResult<Option<Id>> {
+    let row = sqlx::query("SELECT * FROM collections WHERE id = ?")
+        .bind(id.to_string())
+        .fetch_optional(&mut *conn)
+        .await?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let mut collection = collection_from_row(&row)?;
+    collection.bump_version();
+    write_collection(conn, &collection).await?;
+    Ok(Some(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_db;
+    use models::{HttpMethod, new_id};
+
+    fn conflict_for(local: &Request, remote: &Request) -> ConflictInfo {
+        ConflictInfo {
+            conflict_id: new_id(),
+            item_type: SyncItemType::Request,
+            item_id: local.id,
+            remote_id: None,
+            item_name: local.name.clone(),
+            local_version: 3,
+            remote_version: 4,
+            local_value: serde_json::to_value(local).unwrap(),
+            remote_value: serde_json::to_value(remote).unwrap(),
+            created_at: now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_apply_remote_resolution_overwrites_local() {
+        let db = test_db().await;
+        let local = Request::new("Users".to_string(), HttpMethod::GET, "https://local.example.com/users".to_string());
+        db.save_request(&local).await.unwrap();
+
+        let mut remote = local.clone();
+        remote.name = "All users".to_string();
+        remote.url = models::Url::new("https://remote.example.com/users".to_string());
+
+        let conflict = conflict_for(&local, &remote);
+        sqlx::query(
+            "INSERT INTO sync_conflicts (conflict_id, item_type, item_id, local_version, remote_version, local_value, remote_value, created_at)
+            VALUES (?, 'request', ?, 3, 4, '{}', '{}', ?)"
+        )
+        .bind(conflict.conflict_id.to_string())
+        .bind(local.id.to_string())
+        .bind(now())
+        .execute(db.pool())
+        .await
+        .unwrap();
+
+        db.apply_resolution(&conflict, &ConflictChoice::Remote).await.unwrap();
+
+        let saved = db.get_request(local.id).await.unwrap().unwrap();
+        assert_eq!(saved.name, "All users");
+        assert_eq!(saved.url.raw, "https://remote.example.com/users");
+
+        let (resolved, resolution): (i64, String) = sqlx::query_as(
+            "SELECT resolved, resolution FROM sync_conflicts WHERE conflict_id = ?"
+        )
+        .bind(conflict.conflict_id.to_string())
+        .fetch_one(db.pool())
+        .await
+        .unwrap();
+        assert_eq!((resolved, resolution.as_str()), (1, "remote"));
+
+        let version: i64 = sqlx::query_scalar("SELECT version FROM sync_changes WHERE item_id = ?")
+            .bind(local.id.to_string())
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(version, 5);
+    }
+
+    #[tokio::test]
+    async fn test_resolving_request_and_folder_bumps_collection_version() {
+        let db = test_db().await;
+        let mut collection = Collection::new("API".to_string());
+        let folder = Folder::new("Users".to_string());
+        collection.add_folder(folder.clone());
+        let mut local = Request::new("Users".to_string(), HttpMethod::GET, "https://example.com/users".to_string());
+        local.collection_id = Some(collection.id);
+        db.save_collection_tree(&collection, &[local.clone()]).await.unwrap();
+
+        let sync_state = |collection: Collection| (collection.sync_state.version, collection.sync_state.status);
+
+        db.apply_resolution(&conflict_for(&local, &local), &ConflictChoice::Local).await.unwrap();
+        let saved = db.get_collection(collection.id).await.unwrap().unwrap();
+        assert_eq!(sync_state(saved), (Some(1), models::collection::SyncStatus::Pending));
+
+        let mut conflict = conflict_for(&local, &local);
+        conflict.item_type = SyncItemType::Folder;
+        conflict.item_id = folder.id;
+        conflict.local_value = serde_json::to_value(&folder).unwrap();
+        db.apply_resolution(&conflict, &ConflictChoice::Local).await.unwrap();
+        let saved = db.get_collection(collection.id).await.unwrap().unwrap();
+        assert_eq!(sync_state(saved), (Some(2), models::collection::SyncStatus::Pending));
+    }
+}
@@ -3,17 +3,24 @@
 //! Provides a clean API over raw SQL operations for all CRUD operations.
 //! Designed for offline-first with future cloud sync compatibility.
 
-use sqlx::{SqlitePool, sqlite::Sqlite};
-use std::sync::Arc;
+use sqlx::{Row, SqliteConnection, SqlitePool, sqlite::{Sqlite, SqliteRow}};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
 use anyhow::Result;
 
+use crate::checksum::checksum;
+use crate::events::{ChangeEvent, ChangeListener, ChangeOp, EntityType};
 use crate::{StoreError, StoreResult};
-use models::{Id, Timestamp, new_id, now};
+use models::{Id, new_id, normalize_tag, now};
 
 /// Main database interface for Postboy
 #[derive(Clone)]
 pub struct Database {
     pool: Arc<SqlitePool>,
+    verify_checksums: bool,
+    read_only: bool,
+    /// Shared by clones, so a listener sees writes made through any of them
+    pub(crate) listeners: Arc<RwLock<Vec<ChangeListener>>>,
 }
 
 impl Database {
@@ -21,14 +28,53 @@ impl Database {
     pub fn new(pool: SqlitePool) -> Self {
         Self {
             pool: Arc::new(pool),
+            verify_checksums: true,
+            read_only: false,
+            listeners: Arc::new(RwLock::new(Vec::new())),
         }
     }
 
+    /// Enable or disable checksum verification when loading rows
+    pub fn with_checksum_verification(mut self, enable: bool) -> Self {
+        self.verify_checksums = enable;
+        self
+    }
+
+    /// Reject write methods up front, for a pool opened read-only
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
     /// Get reference to the connection pool
     pub fn pool(&self) -> &SqlitePool {
         &self.pool
     }
 
+    /// Whether loaded rows are checked against their stored checksum
+    pub fn verify_checksums(&self) -> bool {
+        self.verify_checksums
+    }
+
+    /// Whether write methods are rejected
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Fail fast in write methods when the store is read-only
+    pub(crate) fn ensure_writable(&self) -> StoreResult<()> {
+        if self.read_only {
+            return Err(StoreError::InvalidData("database opened read-only".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Highest schema version recorded in the database, `None` if it was
+    /// never migrated
+    pub async fn schema_version(&self) -> StoreResult<Option<i64>> {
+        crate::migrations::stored_schema_version(self.pool()).await
+    }
+
     /// Begin a new transaction
     pub async fn begin(&self) -> Result<sqlx::Transaction<'_, Sqlite>> {
         self.pool
@@ -47,41 +93,41 @@ impl Database {
 
     /// Get database statistics
     pub async fn stats(&self) -> Result<DbStats> {
-        let collections_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM collections")
+        let collections_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM collections WHERE deleted_at IS NULL")
             .fetch_one(self.pool())
             .await
-            .map_err(|e| StoreError::Database(e))?;
+            .map_err(StoreError::Database)?;
 
-        let requests_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM requests")
+        let requests_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM requests WHERE deleted_at IS NULL")
             .fetch_one(self.pool())
             .await
-            .map_err(|e| StoreError::Database(e))?;
+            .map_err(StoreError::Database)?;
 
-        let environments_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM environments")
+        let environments_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM environments WHERE deleted_at IS NULL")
             .fetch_one(self.pool())
             .await
-            .map_err(|e| StoreError::Database(e))?;
+            .map_err(StoreError::Database)?;
 
         let history_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM request_history")
             .fetch_one(self.pool())
             .await
-            .map_err(|e| StoreError::Database(e))?;
+            .map_err(StoreError::Database)?;
 
         let pending_sync: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sync_changes WHERE synced = 0")
             .fetch_one(self.pool())
             .await
-            .map_err(|e| StoreError::Database(e))?;
+            .map_err(StoreError::Database)?;
 
         // Get database file size
         let page_count: i64 = sqlx::query_scalar("PRAGMA page_count")
             .fetch_one(self.pool())
             .await
-            .map_err(|e| StoreError::Database(e))?;
+            .map_err(StoreError::Database)?;
 
         let page_size: i64 = sqlx::query_scalar("PRAGMA page_size")
             .fetch_one(self.pool())
             .await
-            .map_err(|e| StoreError::Database(e))?;
+            .map_err(StoreError::Database)?;
 
         let db_size_bytes = page_count * page_size;
 
@@ -97,6 +143,7 @@ impl Database {
 
     /// Vacuum the database to reclaim space
     pub async fn vacuum(&self) -> Result<()> {
+        self.ensure_writable()?;
         sqlx::query("VACUUM")
             .execute(self.pool())
             .await
@@ -105,6 +152,7 @@ impl Database {
 
     /// Analyze the database to update statistics
     pub async fn analyze(&self) -> Result<()> {
+        self.ensure_writable()?;
         sqlx::query("ANALYZE")
             .execute(self.pool())
             .await
@@ -113,140 +161,274 @@ impl Database {
 
     /// Export all data as JSON (for backup/migration)
     pub async fn export_json(&self) -> Result<serde_json::Value> {
+        self.export_json_with(&ExportOptions::default()).await
+    }
+
+    /// Export all data as JSON, as set up by `options`
+    #[tracing::instrument(skip(self), fields(rows = tracing::field::Empty))]
+    pub async fn export_json_with(&self, options: &ExportOptions) -> Result<serde_json::Value> {
         let collections: Vec<serde_json::Value> = sqlx::query(
-            "SELECT * FROM collections ORDER BY created_at"
+            "SELECT * FROM collections WHERE deleted_at IS NULL ORDER BY created_at"
         )
         .fetch_all(self.pool())
         .await
-        .map_err(|e| StoreError::Database(e))?
-        .into_iter()
-        .map(|row| {
-            let id: String = row.get("id");
-            let name: String = row.get("name");
-            let description: Option<String> = row.get("description");
-            let info: String = row.get("info");
-            let auth: Option<String> = row.get("auth");
-            let sync_state: String = row.get("sync_state");
-            let ui_state: String = row.get("ui_state");
-            let created_at: i64 = row.get("created_at");
-            let updated_at: i64 = row.get("updated_at");
-
-            serde_json::json!({
-                "id": id,
-                "name": name,
-                "description": description,
-                "info": serde_json::from_str::<serde_json::Value>(&info).unwrap_or_default(),
-                "auth": auth.and_then(|a| serde_json::from_str(&a).ok()),
-                "sync_state": serde_json::from_str::<serde_json::Value>(&sync_state).unwrap_or_default(),
-                "ui_state": serde_json::from_str::<serde_json::Value>(&ui_state).unwrap_or_default(),
-                "created_at": created_at,
-                "updated_at": updated_at,
-            })
-        })
+        .map_err(StoreError::Database)?
+        .iter()
+        .map(collection_export_json)
         .collect();
 
-        let requests: Vec<serde_json::Value> = sqlx::query(
-            "SELECT * FROM requests ORDER BY created_at"
+        let folders: Vec<serde_json::Value> = sqlx::query(
+            "SELECT * FROM folders
+            WHERE collection_id IN (SELECT id FROM collections WHERE deleted_at IS NULL)
+            ORDER BY created_at"
         )
         .fetch_all(self.pool())
         .await
-        .map_err(|e| StoreError::Database(e))?
-        .into_iter()
-        .map(|row| {
-            let id: String = row.get("id");
-            let collection_id: Option<String> = row.get("collection_id");
-            let folder_id: Option<String> = row.get("folder_id");
-            let name: String = row.get("name");
-            let description: Option<String> = row.get("description");
-            let method: String = row.get("method");
-            let url_raw: String = row.get("url_raw");
-            let headers: String = row.get("headers");
-            let query_params: String = row.get("query_params");
-            let body: String = row.get("body");
-            let auth: Option<String> = row.get("auth");
-            let script: String = row.get("script");
-            let ui_state: String = row.get("ui_state");
-            let created_at: i64 = row.get("created_at");
-            let updated_at: i64 = row.get("updated_at");
-
-            serde_json::json!({
-                "id": id,
-                "collection_id": collection_id,
-                "folder_id": folder_id,
-                "name": name,
-                "description": description,
-                "method": method,
-                "url": {"raw": url_raw},
-                "headers": serde_json::from_str::<Vec<serde_json::Value>>(&headers).unwrap_or_default(),
-                "query_params": serde_json::from_str::<Vec<serde_json::Value>>(&query_params).unwrap_or_default(),
-                "body": serde_json::from_str::<serde_json::Value>(&body).unwrap_or_default(),
-                "auth": auth.and_then(|a| serde_json::from_str(&a).ok()),
-                "script": serde_json::from_str::<serde_json::Value>(&script).unwrap_or_default(),
-                "ui_state": serde_json::from_str::<serde_json::Value>(&ui_state).unwrap_or_default(),
-                "created_at": created_at,
-                "updated_at": updated_at,
-            })
-        })
+        .map_err(StoreError::Database)?
+        .iter()
+        .map(folder_export_json)
         .collect();
 
+        let requests: Vec<serde_json::Value> = sqlx::query(
+            "SELECT * FROM requests WHERE deleted_at IS NULL ORDER BY created_at"
+        )
+        .fetch_all(self.pool())
+        .await
+        .map_err(StoreError::Database)?
+        .iter()
+        .map(|row| request_export(row, options))
+        .collect::<StoreResult<_>>()?;
+
         let environments: Vec<serde_json::Value> = sqlx::query(
-            "SELECT * FROM environments ORDER BY created_at"
+            "SELECT * FROM environments WHERE deleted_at IS NULL ORDER BY created_at"
         )
         .fetch_all(self.pool())
         .await
-        .map_err(|e| StoreError::Database(e))?
-        .into_iter()
-        .map(|row| {
-            let id: String = row.get("id");
-            let name: String = row.get("name");
-            let variables: String = row.get("variables");
-            let is_active: bool = row.get("is_active");
-            let sync_state: String = row.get("sync_state");
-            let created_at: i64 = row.get("created_at");
-            let updated_at: i64 = row.get("updated_at");
-
-            serde_json::json!({
-                "id": id,
-                "name": name,
-                "variables": serde_json::from_str::<Vec<serde_json::Value>>(&variables).unwrap_or_default(),
-                "is_active": is_active,
-                "sync_state": serde_json::from_str::<serde_json::Value>(&sync_state).unwrap_or_default(),
-                "created_at": created_at,
-                "updated_at": updated_at,
-            })
-        })
+        .map_err(StoreError::Database)?
+        .iter()
+        .map(environment_export_json)
         .collect();
 
-        let globals: serde_json::Value = sqlx::query("SELECT * FROM globals")
-            .fetch_one(self.pool())
-            .await
-            .map_err(|e| StoreError::Database(e))
-            .and_then(|row| {
-                let variables: String = row.get("variables");
-                serde_json::from_str::<serde_json::Value>(&variables)
-                    .map_err(|e| StoreError::Deserialization(e.to_string()))
-            })?;
+        let globals = self.globals_export_json().await?;
+
+        crate::record_rows(collections.len() + folders.len() + requests.len() + environments.len());
 
         Ok(serde_json::json!({
             "version": 1,
             "exported_at": now(),
             "collections": collections,
+            "folders": folders,
             "requests": requests,
             "environments": environments,
             "globals": globals,
         }))
     }
 
-    /// Import data from JSON export
+    /// Export one collection, keeping only the parts selected by `filter`
+    ///
+    /// The result has the same shape as [`Database::export_json`] and can be
+    /// imported on its own. A folder filter keeps the requests in the listed
+    /// folders and their subfolders, plus the folders on the path to them. A
+    /// tag filter keeps all or nothing, since only collections are tagged.
+    #[tracing::instrument(skip(self), fields(rows = tracing::field::Empty))]
+    pub async fn export_collection_subset(&self, collection_id: Id, filter: ExportFilter) -> StoreResult<serde_json::Value> {
+        let collection = sqlx::query("SELECT * FROM collections WHERE id = ? AND deleted_at IS NULL")
+            .bind(collection_id.to_string())
+            .fetch_optional(self.pool())
+            .await?
+            .ok_or_else(|| StoreError::NotFound(format!("collection {}", collection_id)))?;
+        let folder_rows = sqlx::query("SELECT * FROM folders WHERE collection_id = ? ORDER BY created_at")
+            .bind(collection_id.to_string())
+            .fetch_all(self.pool())
+            .await?;
+        let request_rows = sqlx::query(
+            "SELECT * FROM requests WHERE collection_id = ? AND deleted_at IS NULL ORDER BY created_at"
+        )
+        .bind(collection_id.to_string())
+        .fetch_all(self.pool())
+        .await?;
+
+        let parents = folder_rows
+            .iter()
+            .map(|row| Ok((row.try_get("id")?, row.try_get("parent_id")?)))
+            .collect::<StoreResult<Vec<(String, Option<String>)>>>()?;
+
+        let (folder_ids, request_folders): (HashSet<String>, Option<HashSet<String>>) = match &filter {
+            ExportFilter::Folders(ids) => {
+                let selected: Vec<String> = ids.iter().map(Id::to_string).collect();
+                if let Some(unknown) = selected.iter().find(|id| !parents.iter().any(|(f, _)| f == *id)) {
+                    return Err(StoreError::NotFound(format!("folder {} in collection {}", unknown, collection_id)));
+                }
+                let contents = with_descendants(&parents, &selected);
+                (with_ancestors(&parents, &contents), Some(contents))
+            }
+            ExportFilter::CollectionTags(tags) => {
+                let collection_tags: Vec<String> = crate::json_column(&collection.try_get::<String, _>("tags")?);
+                if tags.iter().any(|tag| collection_tags.contains(&normalize_tag(tag))) {
+                    (parents.iter().map(|(id, _)| id.clone()).collect(), None)
+                } else {
+                    (HashSet::new(), Some(HashSet::new()))
+                }
+            }
+        };
+
+        let folders: Vec<serde_json::Value> = folder_rows
+            .iter()
+            .filter(|row| folder_ids.contains(&row.get::<String, _>("id")))
+            .map(folder_export_json)
+            .collect();
+        let requests: Vec<serde_json::Value> = request_rows
+            .iter()
+            .filter(|row| match &request_folders {
+                None => true,
+                Some(selected) => row
+                    .get::<Option<String>, _>("folder_id")
+                    .is_some_and(|id| selected.contains(&id)),
+            })
+            .map(request_export_json)
+            .collect();
+
+        crate::record_rows(1 + folders.len() + requests.len());
+        Ok(serde_json::json!({
+            "version": 1,
+            "exported_at": now(),
+            "collections": [collection_export_json(&collection)],
+            "folders": folders,
+            "requests": requests,
+            "environments": [],
+        }))
+    }
+
+    /// Export the given collections with their folders and requests
+    ///
+    /// The result has the same shape as [`Database::export_json`]. Only
+    /// environments defining a variable the exported requests use as
+    /// `{{name}}` are included, and globals only if `include_globals` is set.
+    /// Collections in the trash count as missing.
+    pub async fn export_collections(&self, ids: &[Id], include_globals: bool) -> StoreResult<serde_json::Value> {
+        self.export_collections_with(ids, include_globals, &ExportOptions::default()).await
+    }
+
+    /// [`Database::export_collections`], as set up by `options`
+    #[tracing::instrument(skip(self), fields(rows = tracing::field::Empty))]
+    pub async fn export_collections_with(
+        &self,
+        ids: &[Id],
+        include_globals: bool,
+        options: &ExportOptions,
+    ) -> StoreResult<serde_json::Value> {
+        let ids: Vec<String> = ids.iter().map(Id::to_string).collect();
+        let collection_rows = self
+            .fetch_by_ids("SELECT * FROM collections WHERE id IN ({ids}) AND deleted_at IS NULL ORDER BY created_at", &ids)
+            .await?;
+        if let Some(missing) = ids.iter().find(|id| !collection_rows.iter().any(|row| row.get::<String, _>("id") == **id)) {
+            return Err(StoreError::NotFound(format!("collection {}", missing)));
+        }
+        let folder_rows = self
+            .fetch_by_ids("SELECT * FROM folders WHERE collection_id IN ({ids}) ORDER BY created_at", &ids)
+            .await?;
+        let request_rows = self
+            .fetch_by_ids(
+                "SELECT * FROM requests WHERE collection_id IN ({ids}) AND deleted_at IS NULL ORDER BY created_at",
+                &ids,
+            )
+            .await?;
+
+        let collections: Vec<serde_json::Value> = collection_rows.iter().map(collection_export_json).collect();
+        let folders: Vec<serde_json::Value> = folder_rows.iter().map(folder_export_json).collect();
+        let requests: Vec<serde_json::Value> =
+            request_rows.iter().map(|row| request_export(row, options)).collect::<StoreResult<_>>()?;
+
+        let mut used = HashSet::new();
+        for value in collections.iter().chain(&requests) {
+            template_variables(&value.to_string(), &mut used);
+        }
+        let environments: Vec<serde_json::Value> =
+            sqlx::query("SELECT * FROM environments WHERE deleted_at IS NULL ORDER BY created_at")
+                .fetch_all(self.pool())
+                .await?
+                .iter()
+                .map(environment_export_json)
+                .filter(|env| {
+                    env["variables"]
+                        .as_array()
+                        .is_some_and(|vars| vars.iter().any(|v| v["key"].as_str().is_some_and(|key| used.contains(key))))
+                })
+                .collect();
+
+        crate::record_rows(collections.len() + folders.len() + requests.len() + environments.len());
+        let mut export = serde_json::json!({
+            "version": 1,
+            "exported_at": now(),
+            "collections": collections,
+            "folders": folders,
+            "requests": requests,
+            "environments": environments,
+        });
+        if include_globals {
+            export["globals"] = self.globals_export_json().await?;
+        }
+        Ok(export)
+    }
+
+    /// Run `sql` with `{ids}` expanded to one placeholder per id
+    async fn fetch_by_ids(&self, sql: &str, ids: &[String]) -> StoreResult<Vec<SqliteRow>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let sql = sql.replace("{ids}", &vec!["?"; ids.len()].join(", "));
+        let mut query = sqlx::query(&sql);
+        for id in ids {
+            query = query.bind(id);
+        }
+        Ok(query.fetch_all(self.pool()).await?)
+    }
+
+    /// Global variables as stored, for exports
+    async fn globals_export_json(&self) -> StoreResult<serde_json::Value> {
+        let variables: String = sqlx::query_scalar("SELECT variables FROM globals")
+            .fetch_one(self.pool())
+            .await?;
+        serde_json::from_str(&variables).map_err(|e| StoreError::Deserialization(e.to_string()))
+    }
+
+    /// Import data from JSON export, replacing rows with the same id
     pub async fn import_json(&self, data: &serde_json::Value) -> Result<ImportResult> {
+        self.import_json_with(data, &ImportOptions::default()).await
+    }
+
+    /// Import data from JSON export, handling existing ids per `options`
+    ///
+    /// Ids are matched against every row, trashed or not. When a collection
+    /// or folder is renamed, imported folders and requests inside it follow
+    /// it to the new id. Only collections, environments and requests outside
+    /// a renamed collection get the " (Imported)" suffix. With `Skip` or
+    /// `Rename`, imported globals only add keys that don't exist yet.
+    ///
+    /// Emits a change event for each imported collection, request and
+    /// environment once the import commits.
+    #[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
+    pub async fn import_json_with(&self, data: &serde_json::Value, options: &ImportOptions) -> Result<ImportResult> {
+        self.ensure_writable()?;
+        let mode = options.on_conflict;
         let mut result = ImportResult::default();
+        let mut events = Vec::new();
 
         let mut tx = self.begin().await?;
 
         // Import globals first
         if let Some(globals) = data.get("globals") {
-            let variables_json = serde_json::to_string(globals)
-                .map_err(|e| StoreError::Serialization(e.to_string()))?;
+            let variables_json = match mode {
+                ConflictMode::Replace => serde_json::to_string(globals)
+                    .map_err(|e| StoreError::Serialization(e.to_string()))?,
+                ConflictMode::Skip | ConflictMode::Rename => {
+                    let existing: Option<String> = sqlx::query_scalar("SELECT variables FROM globals")
+                        .fetch_optional(&mut *tx)
+                        .await
+                        .map_err(StoreError::Database)?;
+                    merge_globals(existing.as_deref().unwrap_or("[]"), globals)?
+                }
+            };
 
             sqlx::query(
                 "UPDATE globals SET variables = ?, updated_at = ?"
@@ -255,7 +437,7 @@ impl Database {
             .bind(now())
             .execute(&mut *tx)
             .await
-            .map_err(|e| StoreError::Database(e))?;
+            .map_err(StoreError::Database)?;
 
             result.globals_imported = 1;
         }
@@ -264,9 +446,21 @@ impl Database {
         if let Some(envs) = data.get("environments").and_then(|v| v.as_array()) {
             for env in envs {
                 let id = env.get("id").and_then(|v| v.as_str())
+                    .map(String::from)
                     .unwrap_or_else(|| new_id().to_string());
                 let name = env.get("name").and_then(|v| v.as_str())
                     .ok_or_else(|| StoreError::InvalidData("Environment name missing".into()))?;
+                let (id, name) = match import_action(&mut tx, "environments", id, mode).await? {
+                    ImportAction::Skip => {
+                        result.skipped += 1;
+                        continue;
+                    }
+                    ImportAction::Write { id, renamed, op } => {
+                        result.renamed += renamed as usize;
+                        events.extend(import_event(EntityType::Environment, op, &id));
+                        (id, imported_name(name, renamed))
+                    }
+                };
                 let variables = serde_json::to_string(env.get("variables").unwrap_or(&serde_json::json!([])))
                     .map_err(|e| StoreError::Serialization(e.to_string()))?;
 
@@ -275,25 +469,43 @@ impl Database {
                     VALUES (?, ?, ?, 0, '{}', ?, ?)"
                 )
                 .bind(&id)
-                .bind(name)
+                .bind(&name)
                 .bind(&variables)
                 .bind(now())
                 .bind(now())
                 .execute(&mut *tx)
                 .await
-                .map_err(|e| StoreError::Database(e))?;
+                .map_err(StoreError::Database)?;
 
                 result.environments_imported += 1;
             }
         }
 
-        // Import collections
+        // Import collections, remembering renamed ids for their contents
+        let mut new_ids: HashMap<String, String> = HashMap::new();
+        let mut renamed_collections: HashSet<String> = HashSet::new();
         if let Some(collections) = data.get("collections").and_then(|v| v.as_array()) {
             for collection in collections {
                 let id = collection.get("id").and_then(|v| v.as_str())
+                    .map(String::from)
                     .unwrap_or_else(|| new_id().to_string());
                 let name = collection.get("name").and_then(|v| v.as_str())
                     .ok_or_else(|| StoreError::InvalidData("Collection name missing".into()))?;
+                let (id, name) = match import_action(&mut tx, "collections", id.clone(), mode).await? {
+                    ImportAction::Skip => {
+                        result.skipped += 1;
+                        continue;
+                    }
+                    ImportAction::Write { id: new, renamed, op } => {
+                        events.extend(import_event(EntityType::Collection, op, &new));
+                        if renamed {
+                            result.renamed += 1;
+                            renamed_collections.insert(id.clone());
+                            new_ids.insert(id, new.clone());
+                        }
+                        (new, imported_name(name, renamed))
+                    }
+                };
                 let description = collection.get("description").and_then(|v| v.as_str());
                 let info = serde_json::to_string(
                     collection.get("info").unwrap_or(&serde_json::json!({}))
@@ -305,39 +517,140 @@ impl Database {
                     collection.get("ui_state").unwrap_or(&serde_json::json!({}))
                 ).map_err(|e| StoreError::Serialization(e.to_string()))?;
                 let auth = collection.get("auth")
-                    .and_then(|v| v.as_str())
-                    .and_then(|s| serde_json::to_string(&serde_json::json!(s)).ok());
+                    .filter(|v| !v.is_null())
+                    .map(serde_json::to_string)
+                    .transpose()
+                    .map_err(|e| StoreError::Serialization(e.to_string()))?;
+                // Exports from before tags existed have no "tags" key
+                let tags = serde_json::to_string(
+                    collection.get("tags").filter(|v| v.is_array()).unwrap_or(&serde_json::json!([]))
+                ).map_err(|e| StoreError::Serialization(e.to_string()))?;
+                let headers = serde_json::to_string(
+                    collection.get("headers").filter(|v| v.is_array()).unwrap_or(&serde_json::json!([]))
+                ).map_err(|e| StoreError::Serialization(e.to_string()))?;
+                let checksum = checksum(&[
+                    Some(&info), auth.as_deref(), Some(&tags), Some(&sync_state), Some(&ui_state), Some(&headers),
+                ]);
 
                 sqlx::query(
-                    "INSERT OR REPLACE INTO collections (id, name, description, info, auth, sync_state, ui_state, created_at, updated_at)
-                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"
+                    "INSERT OR REPLACE INTO collections (id, name, description, info, auth, tags, headers, sync_state, ui_state, checksum, created_at, updated_at)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
                 )
                 .bind(&id)
-                .bind(name)
+                .bind(&name)
                 .bind(description)
                 .bind(&info)
                 .bind(&auth)
+                .bind(&tags)
+                .bind(&headers)
                 .bind(&sync_state)
                 .bind(&ui_state)
+                .bind(checksum)
                 .bind(now())
                 .bind(now())
                 .execute(&mut *tx)
                 .await
-                .map_err(|e| StoreError::Database(e))?;
+                .map_err(StoreError::Database)?;
 
                 result.collections_imported += 1;
             }
         }
 
+        // Import folders (after collections so collection_id resolves, before requests
+        // so folder_id resolves). Nested folders may be listed before their parent,
+        // so foreign key checks are deferred until commit.
+        if let Some(folders) = data.get("folders").and_then(|v| v.as_array()) {
+            sqlx::query("PRAGMA defer_foreign_keys = ON")
+                .execute(&mut *tx)
+                .await
+                .map_err(StoreError::Database)?;
+
+            // Decide every folder first so children listed before a renamed
+            // parent still follow it
+            let mut actions = Vec::with_capacity(folders.len());
+            for folder in folders {
+                let id = folder.get("id").and_then(|v| v.as_str())
+                    .map(String::from)
+                    .unwrap_or_else(|| new_id().to_string());
+                let action = import_action(&mut tx, "folders", id.clone(), mode).await?;
+                if let ImportAction::Write { id: new, renamed: true, .. } = &action {
+                    new_ids.insert(id, new.clone());
+                }
+                actions.push(action);
+            }
+
+            for (folder, action) in folders.iter().zip(actions) {
+                let (id, renamed) = match action {
+                    ImportAction::Skip => {
+                        result.skipped += 1;
+                        continue;
+                    }
+                    ImportAction::Write { id, renamed, .. } => (id, renamed),
+                };
+                let collection_id = folder.get("collection_id").and_then(|v| v.as_str())
+                    .ok_or_else(|| StoreError::InvalidData("Folder collection_id missing".into()))?;
+                let parent_id = folder.get("parent_id").and_then(|v| v.as_str()).map(|id| remapped(&new_ids, id));
+                let name = folder.get("name").and_then(|v| v.as_str())
+                    .ok_or_else(|| StoreError::InvalidData("Folder name missing".into()))?;
+                let name = imported_name(name, renamed && !renamed_collections.contains(collection_id));
+                result.renamed += renamed as usize;
+                let collection_id = remapped(&new_ids, collection_id);
+                let description = folder.get("description").and_then(|v| v.as_str());
+                let auth = folder.get("auth")
+                    .filter(|v| !v.is_null())
+                    .map(serde_json::to_string)
+                    .transpose()
+                    .map_err(|e| StoreError::Serialization(e.to_string()))?;
+                let ui_state = serde_json::to_string(
+                    folder.get("ui_state").unwrap_or(&serde_json::json!({}))
+                ).map_err(|e| StoreError::Serialization(e.to_string()))?;
+                let created_at = folder.get("created_at").and_then(|v| v.as_i64()).unwrap_or_else(now);
+                let updated_at = folder.get("updated_at").and_then(|v| v.as_i64()).unwrap_or(created_at);
+
+                sqlx::query(
+                    "INSERT OR REPLACE INTO folders (id, collection_id, parent_id, name, description, auth, ui_state, created_at, updated_at)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"
+                )
+                .bind(&id)
+                .bind(collection_id)
+                .bind(parent_id)
+                .bind(&name)
+                .bind(description)
+                .bind(&auth)
+                .bind(&ui_state)
+                .bind(created_at)
+                .bind(updated_at)
+                .execute(&mut *tx)
+                .await
+                .map_err(StoreError::Database)?;
+
+                result.folders_imported += 1;
+            }
+        }
+
         // Import requests
         if let Some(requests) = data.get("requests").and_then(|v| v.as_array()) {
             for request in requests {
                 let id = request.get("id").and_then(|v| v.as_str())
+                    .map(String::from)
                     .unwrap_or_else(|| new_id().to_string());
                 let collection_id = request.get("collection_id").and_then(|v| v.as_str());
-                let folder_id = request.get("folder_id").and_then(|v| v.as_str());
+                let folder_id = request.get("folder_id").and_then(|v| v.as_str()).map(|id| remapped(&new_ids, id));
                 let name = request.get("name").and_then(|v| v.as_str())
                     .ok_or_else(|| StoreError::InvalidData("Request name missing".into()))?;
+                let (id, name) = match import_action(&mut tx, "requests", id, mode).await? {
+                    ImportAction::Skip => {
+                        result.skipped += 1;
+                        continue;
+                    }
+                    ImportAction::Write { id, renamed, op } => {
+                        result.renamed += renamed as usize;
+                        events.extend(import_event(EntityType::Request, op, &id));
+                        let in_renamed = collection_id.is_some_and(|c| renamed_collections.contains(c));
+                        (id, imported_name(name, renamed && !in_renamed))
+                    }
+                };
+                let collection_id = collection_id.map(|id| remapped(&new_ids, id));
                 let method = request.get("method").and_then(|v| v.as_str())
                     .ok_or_else(|| StoreError::InvalidData("Request method missing".into()))?;
                 let url = request.get("url")
@@ -360,19 +673,33 @@ impl Database {
                 let ui_state = serde_json::to_string(
                     request.get("ui_state").unwrap_or(&serde_json::json!({}))
                 ).map_err(|e| StoreError::Serialization(e.to_string()))?;
+                let examples = serde_json::to_string(
+                    request.get("examples").filter(|v| v.is_array()).unwrap_or(&serde_json::json!([]))
+                ).map_err(|e| StoreError::Serialization(e.to_string()))?;
                 let auth = request.get("auth")
-                    .and_then(|v| v.as_str())
-                    .and_then(|s| serde_json::to_string(&serde_json::json!(s)).ok());
+                    .filter(|v| !v.is_null())
+                    .map(serde_json::to_string)
+                    .transpose()
+                    .map_err(|e| StoreError::Serialization(e.to_string()))?;
+                let timeout_secs = request.get("timeout_secs").and_then(|v| v.as_i64());
+                let follow_redirects = request.get("follow_redirects").and_then(|v| v.as_bool());
+                let max_redirects = request.get("max_redirects").and_then(|v| v.as_i64());
+                let preserve_header_order = request.get("preserve_header_order").and_then(|v| v.as_bool()).unwrap_or(false);
+                let checksum = checksum(&[
+                    Some(&headers), Some(&query_params), Some(&body), auth.as_deref(), Some(&script), Some(&ui_state),
+                    Some(&examples),
+                ]);
 
                 sqlx::query(
                     "INSERT OR REPLACE INTO requests
-                    (id, collection_id, folder_id, name, method, url_raw, headers, query_params, body, auth, script, ui_state, created_at, updated_at)
-                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+                    (id, collection_id, folder_id, name, method, url_raw, headers, query_params, body, auth, script, ui_state, examples,
+                     timeout_secs, follow_redirects, max_redirects, preserve_header_order, checksum, created_at, updated_at)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
                 )
                 .bind(&id)
                 .bind(collection_id)
                 .bind(folder_id)
-                .bind(name)
+                .bind(&name)
                 .bind(method)
                 .bind(url)
                 .bind(&headers)
@@ -381,21 +708,290 @@ impl Database {
                 .bind(&auth)
                 .bind(&script)
                 .bind(&ui_state)
+                .bind(&examples)
+                .bind(timeout_secs)
+                .bind(follow_redirects)
+                .bind(max_redirects)
+                .bind(preserve_header_order)
+                .bind(checksum)
                 .bind(now())
                 .bind(now())
                 .execute(&mut *tx)
                 .await
-                .map_err(|e| StoreError::Database(e))?;
+                .map_err(StoreError::Database)?;
 
                 result.requests_imported += 1;
             }
         }
 
         tx.commit().await?;
+        crate::record_rows(
+            result.collections_imported
+                + result.folders_imported
+                + result.requests_imported
+                + result.environments_imported,
+        );
+        self.emit(events);
         Ok(result)
     }
 }
 
+/// Suffix for imported items renamed to avoid an existing id
+const IMPORTED_SUFFIX: &str = " (Imported)";
+
+/// How [`Database::import_json_with`] writes one imported row
+enum ImportAction {
+    /// Write under `id`, a fresh one if `renamed`; `op` says whether that
+    /// inserts a row or replaces an existing one
+    Write { id: String, renamed: bool, op: ChangeOp },
+    Skip,
+}
+
+/// Decide how to import a row given whether `id` already exists in `table`
+async fn import_action(conn: &mut SqliteConnection, table: &str, id: String, mode: ConflictMode) -> StoreResult<ImportAction> {
+    let exists: bool = sqlx::query_scalar(&format!("SELECT EXISTS(SELECT 1 FROM {} WHERE id = ?)", table))
+        .bind(&id)
+        .fetch_one(conn)
+        .await?;
+    Ok(match (exists, mode) {
+        (false, _) => ImportAction::Write { id, renamed: false, op: ChangeOp::Insert },
+        (true, ConflictMode::Replace) => ImportAction::Write { id, renamed: false, op: ChangeOp::Update },
+        (true, ConflictMode::Skip) => ImportAction::Skip,
+        (true, ConflictMode::Rename) => ImportAction::Write { id: new_id().to_string(), renamed: true, op: ChangeOp::Insert },
+    })
+}
+
+/// Change event for an imported row
+///
+/// Rows whose id isn't a UUID can't be loaded back, so nobody listens for them.
+fn import_event(entity: EntityType, op: ChangeOp, id: &str) -> Option<ChangeEvent> {
+    id.parse().ok().map(|id| ChangeEvent::new(entity, op, id))
+}
+
+fn imported_name(name: &str, suffixed: bool) -> String {
+    if suffixed {
+        format!("{}{}", name, IMPORTED_SUFFIX)
+    } else {
+        name.to_string()
+    }
+}
+
+/// The id a renamed collection or folder was imported under, else `id`
+fn remapped<'a>(new_ids: &'a HashMap<String, String>, id: &'a str) -> &'a str {
+    new_ids.get(id).map_or(id, String::as_str)
+}
+
+/// Add imported global variables whose keys aren't set yet
+fn merge_globals(existing: &str, imported: &serde_json::Value) -> StoreResult<String> {
+    let imported: Vec<models::environment::Variable> = serde_json::from_value(imported.clone())
+        .map_err(|e| StoreError::Deserialization(e.to_string()))?;
+    let mut globals = models::Globals::new().with_values(crate::json_column(existing));
+    globals.merge(&models::Globals::new().with_values(imported), models::MergeStrategy::KeepExisting);
+    serde_json::to_string(&globals.values).map_err(|e| StoreError::Serialization(e.to_string()))
+}
+
+/// Export form of a `collections` row
+fn collection_export_json(row: &SqliteRow) -> serde_json::Value {
+    let id: String = row.get("id");
+    let name: String = row.get("name");
+    let description: Option<String> = row.get("description");
+    let info: String = row.get("info");
+    let auth: Option<String> = row.get("auth");
+    let tags: String = row.get("tags");
+    let headers: String = row.get("headers");
+    let sync_state: String = row.get("sync_state");
+    let ui_state: String = row.get("ui_state");
+    let created_at: i64 = row.get("created_at");
+    let updated_at: i64 = row.get("updated_at");
+
+    serde_json::json!({
+        "id": id,
+        "name": name,
+        "description": description,
+        "info": serde_json::from_str::<serde_json::Value>(&info).unwrap_or_default(),
+        "auth": auth.and_then(|a| serde_json::from_str::<serde_json::Value>(&a).ok()),
+        "tags": serde_json::from_str::<serde_json::Value>(&tags).unwrap_or_default(),
+        "headers": serde_json::from_str::<Vec<serde_json::Value>>(&headers).unwrap_or_default(),
+        "sync_state": serde_json::from_str::<serde_json::Value>(&sync_state).unwrap_or_default(),
+        "ui_state": serde_json::from_str::<serde_json::Value>(&ui_state).unwrap_or_default(),
+        "created_at": created_at,
+        "updated_at": updated_at,
+    })
+}
+
+/// Export form of an `environments` row
+fn environment_export_json(row: &SqliteRow) -> serde_json::Value {
+    let id: String = row.get("id");
+    let name: String = row.get("name");
+    let variables: String = row.get("variables");
+    let is_active: bool = row.get("is_active");
+    let sync_state: String = row.get("sync_state");
+    let created_at: i64 = row.get("created_at");
+    let updated_at: i64 = row.get("updated_at");
+
+    serde_json::json!({
+        "id": id,
+        "name": name,
+        "variables": serde_json::from_str::<Vec<serde_json::Value>>(&variables).unwrap_or_default(),
+        "is_active": is_active,
+        "sync_state": serde_json::from_str::<serde_json::Value>(&sync_state).unwrap_or_default(),
+        "created_at": created_at,
+        "updated_at": updated_at,
+    })
+}
+
+/// Add the names of `{{name}}` placeholders in `text` to `names`
+fn template_variables(text: &str, names: &mut HashSet<String>) {
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find("}}") else {
+            break;
+        };
+        let name = rest[..end].trim();
+        if !name.is_empty() {
+            names.insert(name.to_string());
+        }
+        rest = &rest[end + 2..];
+    }
+}
+
+/// Export form of a `folders` row
+fn folder_export_json(row: &SqliteRow) -> serde_json::Value {
+    let id: String = row.get("id");
+    let collection_id: String = row.get("collection_id");
+    let parent_id: Option<String> = row.get("parent_id");
+    let name: String = row.get("name");
+    let description: Option<String> = row.get("description");
+    let auth: Option<String> = row.get("auth");
+    let ui_state: String = row.get("ui_state");
+    let created_at: i64 = row.get("created_at");
+    let updated_at: i64 = row.get("updated_at");
+
+    serde_json::json!({
+        "id": id,
+        "collection_id": collection_id,
+        "parent_id": parent_id,
+        "name": name,
+        "description": description,
+        "auth": auth.and_then(|a| serde_json::from_str::<serde_json::Value>(&a).ok()),
+        "ui_state": serde_json::from_str::<serde_json::Value>(&ui_state).unwrap_or_default(),
+        "created_at": created_at,
+        "updated_at": updated_at,
+    })
+}
+
+/// Export form of a `requests` row
+fn request_export_json(row: &SqliteRow) -> serde_json::Value {
+    let id: String = row.get("id");
+    let collection_id: Option<String> = row.get("collection_id");
+    let folder_id: Option<String> = row.get("folder_id");
+    let name: String = row.get("name");
+    let description: Option<String> = row.get("description");
+    let method: String = row.get("method");
+    let url_raw: String = row.get("url_raw");
+    let headers: String = row.get("headers");
+    let query_params: String = row.get("query_params");
+    let body: String = row.get("body");
+    let auth: Option<String> = row.get("auth");
+    let script: String = row.get("script");
+    let ui_state: String = row.get("ui_state");
+    let examples: String = row.get("examples");
+    let timeout_secs: Option<i64> = row.get("timeout_secs");
+    let follow_redirects: Option<bool> = row.get("follow_redirects");
+    let max_redirects: Option<i64> = row.get("max_redirects");
+    let preserve_header_order: bool = row.get("preserve_header_order");
+    let created_at: i64 = row.get("created_at");
+    let updated_at: i64 = row.get("updated_at");
+
+    serde_json::json!({
+        "id": id,
+        "collection_id": collection_id,
+        "folder_id": folder_id,
+        "name": name,
+        "description": description,
+        "method": method,
+        "url": {"raw": url_raw},
+        "headers": serde_json::from_str::<Vec<serde_json::Value>>(&headers).unwrap_or_default(),
+        "query_params": serde_json::from_str::<Vec<serde_json::Value>>(&query_params).unwrap_or_default(),
+        "body": serde_json::from_str::<serde_json::Value>(&body).unwrap_or_default(),
+        "auth": auth.and_then(|a| serde_json::from_str::<serde_json::Value>(&a).ok()),
+        "script": serde_json::from_str::<serde_json::Value>(&script).unwrap_or_default(),
+        "ui_state": serde_json::from_str::<serde_json::Value>(&ui_state).unwrap_or_default(),
+        "examples": serde_json::from_str::<Vec<serde_json::Value>>(&examples).unwrap_or_default(),
+        "timeout_secs": timeout_secs,
+        "follow_redirects": follow_redirects,
+        "max_redirects": max_redirects,
+        "preserve_header_order": preserve_header_order,
+        "created_at": created_at,
+        "updated_at": updated_at,
+    })
+}
+
+/// Export form of a `requests` row, masked by [`Request::redacted`] if
+/// `options` ask for it
+///
+/// [`Request::redacted`]: models::Request::redacted
+fn request_export(row: &SqliteRow, options: &ExportOptions) -> StoreResult<serde_json::Value> {
+    let mut export = request_export_json(row);
+    if options.redact {
+        let to_value = |value: serde_json::Result<serde_json::Value>| {
+            value.map_err(|e| StoreError::Serialization(e.to_string()))
+        };
+        let request = crate::requests::request_from_row(row)?.redacted(&[]);
+        export["url"] = serde_json::json!({"raw": request.url.raw});
+        export["headers"] = to_value(serde_json::to_value(&request.headers))?;
+        export["query_params"] = to_value(serde_json::to_value(&request.query_params))?;
+        export["body"] = to_value(serde_json::to_value(&request.body))?;
+        export["auth"] = to_value(serde_json::to_value(&request.auth))?;
+    }
+    Ok(export)
+}
+
+/// Folder ids in `selected` plus all of their subfolders
+fn with_descendants(parents: &[(String, Option<String>)], selected: &[String]) -> HashSet<String> {
+    let mut ids: HashSet<String> = selected.iter().cloned().collect();
+    loop {
+        let before = ids.len();
+        for (id, parent) in parents {
+            if parent.as_ref().is_some_and(|p| ids.contains(p)) {
+                ids.insert(id.clone());
+            }
+        }
+        if ids.len() == before {
+            return ids;
+        }
+    }
+}
+
+/// Folder ids in `selected` plus every folder on the path up to the root
+fn with_ancestors(parents: &[(String, Option<String>)], selected: &HashSet<String>) -> HashSet<String> {
+    let mut ids = selected.clone();
+    for id in selected {
+        let mut current = id;
+        while let Some((_, Some(parent))) = parents.iter().find(|(f, _)| f == current) {
+            if !ids.insert(parent.clone()) {
+                break;
+            }
+            current = parent;
+        }
+    }
+    ids
+}
+
+/// Which parts of a collection [`Database::export_collection_subset`] keeps
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportFilter {
+    /// Requests in these folders (and their subfolders)
+    Folders(Vec<Id>),
+    /// The whole collection if it carries any of these tags, otherwise an
+    /// empty collection
+    ///
+    /// Tags are matched against the collection only; requests and folders
+    /// have no tags of their own.
+    CollectionTags(Vec<String>),
+}
+
 /// Database statistics
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct DbStats {
@@ -407,19 +1003,304 @@ pub struct DbStats {
     pub db_size_bytes: i64,
 }
 
+/// What [`Database::import_json_with`] does with an item whose id exists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictMode {
+    /// Overwrite the existing item
+    #[default]
+    Replace,
+    /// Keep the existing item and drop the imported one
+    Skip,
+    /// Import under a new id, marking the name with " (Imported)"
+    Rename,
+}
+
+/// Options for [`Database::export_json_with`] and
+/// [`Database::export_collections_with`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportOptions {
+    /// Mask secrets in requests with [`Request::redacted`], for sharing an
+    /// export rather than backing up
+    ///
+    /// [`Request::redacted`]: models::Request::redacted
+    pub redact: bool,
+}
+
+/// Options for [`Database::import_json_with`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportOptions {
+    pub on_conflict: ConflictMode,
+}
+
+impl ImportOptions {
+    pub fn new(on_conflict: ConflictMode) -> Self {
+        Self { on_conflict }
+    }
+}
+
 /// Result of an import operation
 #[derive(Debug, Clone, Default, serde::Serialize)]
 pub struct ImportResult {
     pub collections_imported: usize,
+    pub folders_imported: usize,
     pub requests_imported: usize,
     pub environments_imported: usize,
     pub globals_imported: usize,
+    /// Items left out because their id already existed
+    pub skipped: usize,
+    /// Items imported under a new id (included in the counts above)
+    pub renamed: usize,
     pub errors: Vec<String>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_db;
+    use crate::test_support::{insert_collection, insert_folder, insert_request, request};
+
+    #[tokio::test]
+    async fn test_folders_round_trip() {
+        let source = test_db().await;
+        let collection_id = insert_collection(&source, "API", now()).await;
+        let parent_id = insert_folder(&source, collection_id, None, "Users").await;
+        let child_id = insert_folder(&source, collection_id, Some(parent_id), "Admin").await;
+
+        let export = source.export_json().await.unwrap();
+        assert_eq!(export["folders"].as_array().unwrap().len(), 2);
+
+        let target = test_db().await;
+        let result = target.import_json(&export).await.unwrap();
+        assert_eq!(result.folders_imported, 2);
+
+        let parent: Option<String> = sqlx::query_scalar("SELECT parent_id FROM folders WHERE id = ?")
+            .bind(child_id.to_string())
+            .fetch_one(target.pool())
+            .await
+            .unwrap();
+        assert_eq!(parent, Some(parent_id.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_export_collection_subset_by_folder() {
+        let db = test_db().await;
+        let collection_id = insert_collection(&db, "API", now()).await;
+        let root_id = insert_folder(&db, collection_id, None, "v1").await;
+        let users_id = insert_folder(&db, collection_id, Some(root_id), "Users").await;
+        let admin_id = insert_folder(&db, collection_id, Some(users_id), "Admin").await;
+        let orders_id = insert_folder(&db, collection_id, Some(root_id), "Orders").await;
+        insert_request(&db, &request("Health").with_collection(collection_id)).await;
+        insert_request(&db, &request("List users").with_collection(collection_id).with_folder(users_id)).await;
+        insert_request(&db, &request("Ban user").with_collection(collection_id).with_folder(admin_id)).await;
+        insert_request(&db, &request("List orders").with_collection(collection_id).with_folder(orders_id)).await;
+
+        let export = db
+            .export_collection_subset(collection_id, ExportFilter::Folders(vec![users_id]))
+            .await
+            .unwrap();
+
+        let names = |key: &str| -> Vec<String> {
+            let mut names: Vec<String> = export[key]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|v| v["name"].as_str().unwrap().to_string())
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names("requests"), vec!["Ban user", "List users"]);
+        assert_eq!(names("folders"), vec!["Admin", "Users", "v1"]);
+        assert_eq!(export["collections"].as_array().unwrap().len(), 1);
+
+        // The subset imports on its own
+        let target = test_db().await;
+        let result = target.import_json(&export).await.unwrap();
+        assert_eq!(result.requests_imported, 2);
+
+        assert!(db.export_collection_subset(collection_id, ExportFilter::Folders(vec![new_id()])).await.is_err());
+        let untagged = db
+            .export_collection_subset(collection_id, ExportFilter::CollectionTags(vec!["public".to_string()]))
+            .await
+            .unwrap();
+        assert!(untagged["requests"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_import_conflict_modes() {
+        let db = test_db().await;
+        let collection_id = insert_collection(&db, "API", now()).await;
+        let folder_id = insert_folder(&db, collection_id, None, "Users").await;
+        insert_request(&db, &request("List users").with_collection(collection_id).with_folder(folder_id)).await;
+        let collection = collection_id.to_string();
+        let export = db.export_json().await.unwrap();
+
+        let skip = db.import_json_with(&export, &ImportOptions::new(ConflictMode::Skip)).await.unwrap();
+        assert_eq!((skip.collections_imported, skip.folders_imported, skip.requests_imported), (0, 0, 0));
+        assert_eq!(skip.skipped, 3);
+
+        let rename = db.import_json_with(&export, &ImportOptions::new(ConflictMode::Rename)).await.unwrap();
+        assert_eq!((rename.collections_imported, rename.folders_imported, rename.requests_imported), (1, 1, 1));
+        assert_eq!(rename.renamed, 3);
+        let copy: (String, String) = sqlx::query_as("SELECT id, name FROM collections WHERE id != ?")
+            .bind(&collection)
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(copy.1, "API (Imported)");
+        // The copied folder and request live in the copy, without a suffix of their own
+        let (folder_name, folder_id): (String, String) =
+            sqlx::query_as("SELECT name, id FROM folders WHERE collection_id = ?")
+                .bind(&copy.0)
+                .fetch_one(db.pool())
+                .await
+                .unwrap();
+        assert_eq!(folder_name, "Users");
+        let request: (String, String) = sqlx::query_as("SELECT name, folder_id FROM requests WHERE collection_id = ?")
+            .bind(&copy.0)
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(request, ("List users".to_string(), folder_id));
+
+        let replace = db.import_json(&export).await.unwrap();
+        assert_eq!((replace.collections_imported, replace.skipped, replace.renamed), (1, 0, 0));
+        assert_eq!(db.stats().await.unwrap().collections_count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_export_redacted() {
+        let db = test_db().await;
+        let collection = models::Collection::new("API".to_string());
+        db.save_collection(&collection).await.unwrap();
+        let mut request = models::Request::new(
+            "Me".to_string(),
+            models::HttpMethod::GET,
+            "https://example.com/me?api_key=k3y".to_string(),
+        )
+        .with_header("Authorization".to_string(), "Bearer s3cret".to_string());
+        request.collection_id = Some(collection.id);
+        db.save_request(&request).await.unwrap();
+
+        let redact = ExportOptions { redact: true };
+        for export in [
+            db.export_json_with(&redact).await.unwrap(),
+            db.export_collections_with(&[collection.id], false, &redact).await.unwrap(),
+        ] {
+            let exported = &export["requests"][0];
+            assert_eq!(exported["url"]["raw"], format!("https://example.com/me?api_key={}", models::REDACTED));
+            assert_eq!(exported["headers"][0]["value"], models::REDACTED);
+            assert!(!export.to_string().contains("s3cret"));
+        }
+        assert!(db.export_json().await.unwrap().to_string().contains("s3cret"));
+    }
+
+    #[tokio::test]
+    async fn test_export_collections() {
+        let db = test_db().await;
+        let shared = insert_collection(&db, "Shared", now()).await;
+        let private = insert_collection(&db, "Private", now()).await;
+        insert_folder(&db, shared, None, "Users").await;
+        insert_request(&db, &request("Private request").with_collection(private)).await;
+        let list_users = models::Request::new("List users".to_string(), models::HttpMethod::GET, "{{base_url}}/users".to_string());
+        insert_request(&db, &list_users.with_collection(shared)).await;
+        for (name, key) in [("Staging", "base_url"), ("Other", "token")] {
+            sqlx::query(
+                "INSERT INTO environments (id, name, variables, is_active, sync_state, created_at, updated_at)
+                VALUES (?, ?, ?, 0, '{}', 0, 0)"
+            )
+            .bind(new_id().to_string())
+            .bind(name)
+            .bind(serde_json::json!([{"key": key, "value": "x", "enabled": true}]).to_string())
+            .execute(db.pool())
+            .await
+            .unwrap();
+        }
+
+        let export = db.export_collections(&[shared], false).await.unwrap();
+        let names = |key: &str| -> Vec<String> {
+            export[key].as_array().unwrap().iter().map(|v| v["name"].as_str().unwrap().to_string()).collect()
+        };
+        assert_eq!(names("collections"), vec!["Shared"]);
+        assert_eq!(names("folders"), vec!["Users"]);
+        assert_eq!(names("requests"), vec!["List users"]);
+        assert_eq!(names("environments"), vec!["Staging"]);
+        assert!(export.get("globals").is_none());
+
+        let with_globals = db.export_collections(&[shared, private], true).await.unwrap();
+        assert_eq!(with_globals["collections"].as_array().unwrap().len(), 2);
+        assert!(with_globals["globals"].is_array());
+        assert!(matches!(db.export_collections(&[new_id()], false).await, Err(StoreError::NotFound(_))));
+
+        // The export imports on its own
+        let target = test_db().await;
+        let result = target.import_json(&export).await.unwrap();
+        assert_eq!((result.collections_imported, result.requests_imported), (1, 1));
+    }
+
+    #[test]
+    fn test_template_variables() {
+        let mut names = HashSet::new();
+        template_variables("{{base_url}}/users/{{ id }}?q={{}}&x={{open", &mut names);
+        assert_eq!(names, HashSet::from(["base_url".to_string(), "id".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn test_auth_round_trip() {
+        use models::AuthConfig;
+
+        let auth = AuthConfig::Bearer { token: "secret-token".to_string() };
+        let auth_json = serde_json::to_string(&auth).unwrap();
+
+        let source = test_db().await;
+        let collection_id = new_id().to_string();
+        let request_id = new_id().to_string();
+
+        sqlx::query(
+            "INSERT INTO collections (id, name, info, auth, sync_state, ui_state, created_at, updated_at)
+            VALUES (?, 'API', '{}', ?, '{}', '{}', ?, ?)"
+        )
+        .bind(&collection_id)
+        .bind(&auth_json)
+        .bind(now())
+        .bind(now())
+        .execute(source.pool())
+        .await
+        .unwrap();
+
+        sqlx::query(
+            "INSERT INTO requests (id, collection_id, name, method, url_raw, auth, created_at, updated_at)
+            VALUES (?, ?, 'Get', 'GET', 'https://example.com', ?, ?, ?)"
+        )
+        .bind(&request_id)
+        .bind(&collection_id)
+        .bind(&auth_json)
+        .bind(now())
+        .bind(now())
+        .execute(source.pool())
+        .await
+        .unwrap();
+
+        let export = source.export_json().await.unwrap();
+
+        let target = test_db().await;
+        target.import_json(&export).await.unwrap();
+
+        let collection_auth: Option<String> = sqlx::query_scalar("SELECT auth FROM collections WHERE id = ?")
+            .bind(&collection_id)
+            .fetch_one(target.pool())
+            .await
+            .unwrap();
+        let request_auth: Option<String> = sqlx::query_scalar("SELECT auth FROM requests WHERE id = ?")
+            .bind(&request_id)
+            .fetch_one(target.pool())
+            .await
+            .unwrap();
+
+        assert_eq!(serde_json::from_str::<AuthConfig>(&collection_auth.unwrap()).unwrap(), auth);
+        assert_eq!(serde_json::from_str::<AuthConfig>(&request_auth.unwrap()).unwrap(), auth);
+    }
 
     #[tokio::test]
     async fn test_database_ping() {
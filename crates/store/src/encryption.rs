@@ -0,0 +1,104 @@
+//! Whole-database encryption
+//!
+//! Encryption is provided by SQLCipher and is only available with the
+//! `sqlcipher` feature, which builds a bundled SQLCipher in place of plain
+//! SQLite. SQLCipher needs OpenSSL's libcrypto at build and run time
+//! (`libssl-dev` on Debian/Ubuntu, `openssl` from Homebrew on macOS with
+//! `OPENSSL_DIR` pointing at it). The default build links plain SQLite, and
+//! opening a store with an `encryption_key` then fails with
+//! [`StoreError::Encryption`].
+
+use crate::StoreError;
+#[cfg(feature = "sqlcipher")]
+use crate::{Database, StoreResult};
+
+/// SQLite's result code for a file that isn't a database, which is also what
+/// SQLCipher reports when the key is wrong
+const SQLITE_NOTADB: &str = "26";
+
+/// Quote a passphrase for use as a `PRAGMA key` / `PRAGMA rekey` value
+pub(crate) fn key_pragma_value(key: &str) -> String {
+    format!("'{}'", key.replace('\'', "''"))
+}
+
+/// Turn "file is not a database" into a clear wrong-key error
+pub(crate) fn map_key_error(error: sqlx::Error) -> StoreError {
+    match &error {
+        sqlx::Error::Database(db) if db.code().as_deref() == Some(SQLITE_NOTADB) => StoreError::Encryption(
+            "Wrong encryption key, or the database is not encrypted".to_string(),
+        ),
+        _ => StoreError::Database(error),
+    }
+}
+
+#[cfg(feature = "sqlcipher")]
+impl Database {
+    /// Change the encryption key from `old` to `new`
+    ///
+    /// `old` is checked on a separate connection before rekeying, so a wrong
+    /// key leaves the database untouched. Connections already in the pool
+    /// keep the old key; reopen the store with the new key afterwards.
+    pub async fn rekey(&self, old: &str, new: &str) -> StoreResult<()> {
+        self.ensure_writable()?;
+        use sqlx::{ConnectOptions, Connection};
+
+        let options = self.pool().connect_options().as_ref().clone().pragma("key", key_pragma_value(old));
+        let mut conn = options.connect().await.map_err(map_key_error)?;
+
+        sqlx::query("SELECT count(*) FROM sqlite_master")
+            .execute(&mut conn)
+            .await
+            .map_err(map_key_error)?;
+        sqlx::query(&format!("PRAGMA rekey = {}", key_pragma_value(new)))
+            .execute(&mut conn)
+            .await?;
+
+        conn.close().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_pragma_value() {
+        assert_eq!(key_pragma_value("secret"), "'secret'");
+        assert_eq!(key_pragma_value("it's"), "'it''s'");
+    }
+
+    #[cfg(not(feature = "sqlcipher"))]
+    #[tokio::test]
+    async fn test_encryption_key_requires_feature() {
+        let config = crate::StoreConfig::default()
+            .with_db_path(":memory:")
+            .with_encryption_key("secret");
+        let err = crate::open_store(config).await.err().unwrap();
+        assert!(matches!(err.downcast_ref::<StoreError>(), Some(StoreError::Encryption(_))));
+    }
+
+    #[cfg(feature = "sqlcipher")]
+    #[tokio::test]
+    async fn test_encrypted_store_and_rekey() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.db").to_string_lossy().to_string();
+        // open_store doesn't create missing files; SQLCipher treats an empty one as new
+        std::fs::File::create(&path).unwrap();
+        let config = |key: &str| {
+            crate::StoreConfig::default()
+                .with_db_path(path.clone())
+                .with_wal(false)
+                .with_encryption_key(key)
+        };
+
+        let db = crate::open_store(config("first")).await.unwrap();
+        assert!(matches!(db.rekey("wrong", "second").await, Err(StoreError::Encryption(_))));
+        db.rekey("first", "second").await.unwrap();
+        db.pool().close().await;
+
+        let err = crate::open_store(config("first")).await.err().unwrap();
+        assert!(matches!(err.downcast_ref::<StoreError>(), Some(StoreError::Encryption(_))));
+        crate::open_store(config("second")).await.unwrap();
+    }
+}
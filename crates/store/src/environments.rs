@@ -0,0 +1,148 @@
+//! Environment persistence operations
+
+use sqlx::{Row, SqliteConnection, sqlite::SqliteRow};
+
+use crate::collections::{parse_id, to_json};
+use crate::events::{ChangeEvent, ChangeOp, EntityType, upsert_op};
+use crate::{Database, StoreError, StoreResult, json_column, record_rows};
+use models::{Environment, Id, now};
+
+impl Database {
+    /// List all environments not in the trash, ordered by name
+    #[tracing::instrument(skip(self), fields(rows = tracing::field::Empty))]
+    pub async fn list_environments(&self) -> StoreResult<Vec<Environment>> {
+        let rows = sqlx::query("SELECT * FROM environments WHERE deleted_at IS NULL ORDER BY name")
+            .fetch_all(self.pool())
+            .await?;
+
+        record_rows(rows.len());
+        rows.iter().map(environment_from_row).collect()
+    }
+
+    /// Get an environment by id, ignoring environments in the trash
+    #[tracing::instrument(skip(self), fields(id = %id))]
+    pub async fn get_environment(&self, id: Id) -> StoreResult<Option<Environment>> {
+        sqlx::query("SELECT * FROM environments WHERE id = ? AND deleted_at IS NULL")
+            .bind(id.to_string())
+            .fetch_optional(self.pool())
+            .await?
+            .as_ref()
+            .map(environment_from_row)
+            .transpose()
+    }
+
+    /// Insert or update an environment
+    #[tracing::instrument(skip_all, fields(id = %environment.id))]
+    pub async fn save_environment(&self, environment: &Environment) -> StoreResult<()> {
+        self.ensure_writable()?;
+        let mut tx = self.pool().begin().await?;
+        let op = upsert_op(&mut tx, EntityType::Environment, environment.id).await?;
+        write_environment(&mut tx, environment).await?;
+        tx.commit().await?;
+        self.emit([ChangeEvent::new(EntityType::Environment, op, environment.id)]);
+        Ok(())
+    }
+
+    /// Move an environment to the trash
+    ///
+    /// A trashed environment is never active, so it is deactivated as well.
+    #[tracing::instrument(skip(self), fields(id = %id))]
+    pub async fn delete_environment(&self, id: Id) -> StoreResult<()> {
+        self.ensure_writable()?;
+        let result = sqlx::query(
+            "UPDATE environments SET deleted_at = ?, is_active = 0
+            WHERE id = ? AND deleted_at IS NULL"
+        )
+        .bind(now())
+        .bind(id.to_string())
+        .execute(self.pool())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(StoreError::NotFound(format!("Environment {}", id)));
+        }
+
+        self.emit([ChangeEvent::new(EntityType::Environment, ChangeOp::Delete, id)]);
+        Ok(())
+    }
+}
+
+/// Upsert an `environments` row, leaving `sync_state` and `deleted_at` untouched
+pub(crate) async fn write_environment(conn: &mut SqliteConnection, environment: &Environment) -> StoreResult<()> {
+    let variables = to_json(&environment.values)?;
+
+    sqlx::query(
+        "INSERT INTO environments (id, name, variables, is_active, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        ON CONFLICT(id) DO UPDATE SET
+            name = excluded.name,
+            variables = excluded.variables,
+            is_active = excluded.is_active,
+            updated_at = excluded.updated_at"
+    )
+    .bind(environment.id.to_string())
+    .bind(&environment.name)
+    .bind(&variables)
+    .bind(environment.is_active)
+    .bind(environment.created_at)
+    .bind(environment.updated_at)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Build an `Environment` from an `environments` row
+pub(crate) fn environment_from_row(row: &SqliteRow) -> StoreResult<Environment> {
+    let id: String = row.try_get("id")?;
+    let is_active: i64 = row.try_get("is_active")?;
+
+    let mut environment = Environment::new(row.try_get("name")?);
+    environment.id = parse_id(&id)?;
+    environment.values = json_column(&row.try_get::<String, _>("variables")?);
+    environment.is_active = is_active != 0;
+    environment.created_at = row.try_get("created_at")?;
+    environment.updated_at = row.try_get("updated_at")?;
+
+    Ok(environment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_db;
+    use models::new_id;
+
+    async fn insert_environment(db: &Database, name: &str, is_active: bool) -> Id {
+        let id = new_id();
+        sqlx::query(
+            "INSERT INTO environments (id, name, variables, is_active, created_at, updated_at)
+            VALUES (?, ?, '[{\"key\":\"host\",\"value\":\"localhost\"}]', ?, ?, ?)"
+        )
+        .bind(id.to_string())
+        .bind(name)
+        .bind(is_active)
+        .bind(now())
+        .bind(now())
+        .execute(db.pool())
+        .await
+        .unwrap();
+        id
+    }
+
+    #[tokio::test]
+    async fn test_list_and_delete_environments() {
+        let db = test_db().await;
+        let dev = insert_environment(&db, "Dev", true).await;
+        let prod = insert_environment(&db, "Prod", false).await;
+
+        let environments = db.list_environments().await.unwrap();
+        assert_eq!(environments.iter().map(|e| e.id).collect::<Vec<_>>(), vec![dev, prod]);
+        assert!(environments[0].is_active);
+        assert_eq!(environments[0].values[0].key, "host");
+
+        db.delete_environment(dev).await.unwrap();
+        assert!(db.get_environment(dev).await.unwrap().is_none());
+        assert_eq!(db.list_environments().await.unwrap().len(), 1);
+    }
+}
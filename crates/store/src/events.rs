@@ -0,0 +1,263 @@
+//! Change notifications for collections, requests and environments
+//!
+//! Listeners registered with [`Database::on_change`] are called after each
+//! write commits, so a UI can invalidate exactly what changed instead of
+//! reloading everything. Operations that touch many rows (importing,
+//! emptying the trash, deleting or restoring a collection) emit one event per
+//! affected collection, request and environment.
+
+use std::sync::Arc;
+
+use sqlx::SqliteConnection;
+
+use crate::collections::parse_id;
+use crate::{Database, StoreResult};
+use models::Id;
+
+/// Kind of entity a [`ChangeEvent`] is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityType {
+    Collection,
+    Request,
+    Environment,
+}
+
+impl EntityType {
+    /// Table backing this entity type
+    fn table(&self) -> &'static str {
+        match self {
+            EntityType::Collection => "collections",
+            EntityType::Request => "requests",
+            EntityType::Environment => "environments",
+        }
+    }
+}
+
+/// What happened to the entity
+///
+/// Moving an item to the trash is a `Delete`; restoring it is an `Insert`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeOp {
+    Insert,
+    Update,
+    Delete,
+}
+
+/// A committed change to one entity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct ChangeEvent {
+    pub entity: EntityType,
+    pub op: ChangeOp,
+    pub id: Id,
+}
+
+impl ChangeEvent {
+    pub fn new(entity: EntityType, op: ChangeOp, id: Id) -> Self {
+        Self { entity, op, id }
+    }
+}
+
+/// Callback registered with [`Database::on_change`]
+pub type ChangeListener = Arc<dyn Fn(ChangeEvent) + Send + Sync>;
+
+impl Database {
+    /// Call `listener` after every committed change
+    ///
+    /// Listeners run synchronously on the writing task, in registration
+    /// order, so they should be quick (e.g. send on a channel).
+    pub fn on_change(&self, listener: ChangeListener) {
+        self.listeners
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(listener);
+    }
+
+    /// Notify listeners of committed changes
+    pub(crate) fn emit(&self, events: impl IntoIterator<Item = ChangeEvent>) {
+        // Clone the list so a listener can register another without deadlocking
+        let listeners = self
+            .listeners
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        if listeners.is_empty() {
+            return;
+        }
+        for event in events {
+            for listener in &listeners {
+                listener(event);
+            }
+        }
+    }
+}
+
+/// Whether upserting `id` will insert or update its row
+///
+/// Call this on the connection or transaction that does the write, so the
+/// answer can't go stale before the row is written.
+pub(crate) async fn upsert_op(conn: &mut SqliteConnection, entity: EntityType, id: Id) -> StoreResult<ChangeOp> {
+    let exists: bool = sqlx::query_scalar(&format!("SELECT EXISTS(SELECT 1 FROM {} WHERE id = ?)", entity.table()))
+        .bind(id.to_string())
+        .fetch_one(conn)
+        .await?;
+    Ok(if exists { ChangeOp::Update } else { ChangeOp::Insert })
+}
+
+/// One event per id returned by a `RETURNING id` statement
+pub(crate) fn returned_events(entity: EntityType, op: ChangeOp, ids: &[String]) -> StoreResult<Vec<ChangeEvent>> {
+    ids.iter()
+        .map(|id| Ok(ChangeEvent::new(entity, op, parse_id(id)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_db;
+    use models::{Collection, Environment, HttpMethod, Request};
+    use std::sync::Mutex;
+
+    fn recorder(db: &Database) -> Arc<Mutex<Vec<ChangeEvent>>> {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        db.on_change(Arc::new(move |event| sink.lock().unwrap().push(event)));
+        events
+    }
+
+    #[tokio::test]
+    async fn test_change_events() {
+        let db = test_db().await;
+        let events = recorder(&db);
+
+        let mut collection = Collection::new("API".to_string());
+        db.save_collection(&collection).await.unwrap();
+        collection.description = Some("Public API".to_string());
+        db.save_collection(&collection).await.unwrap();
+
+        let request = Request::new("Get".to_string(), HttpMethod::GET, "https://example.com".to_string());
+        db.save_request(&request).await.unwrap();
+        db.delete_request(request.id).await.unwrap();
+        db.restore(request.id).await.unwrap();
+
+        let environment = Environment::new("Dev".to_string());
+        db.save_environment(&environment).await.unwrap();
+        db.delete_environment(environment.id).await.unwrap();
+
+        // Failed writes don't notify
+        assert!(db.delete_request(models::new_id()).await.is_err());
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ChangeEvent::new(EntityType::Collection, ChangeOp::Insert, collection.id),
+                ChangeEvent::new(EntityType::Collection, ChangeOp::Update, collection.id),
+                ChangeEvent::new(EntityType::Request, ChangeOp::Insert, request.id),
+                ChangeEvent::new(EntityType::Request, ChangeOp::Delete, request.id),
+                ChangeEvent::new(EntityType::Request, ChangeOp::Insert, request.id),
+                ChangeEvent::new(EntityType::Environment, ChangeOp::Insert, environment.id),
+                ChangeEvent::new(EntityType::Environment, ChangeOp::Delete, environment.id),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_collection_events_cover_its_requests() {
+        let db = test_db().await;
+        let collection = Collection::new("API".to_string());
+        let mut request = Request::new("Get".to_string(), HttpMethod::GET, "https://example.com".to_string());
+        request.collection_id = Some(collection.id);
+        db.save_collection_tree(&collection, &[request.clone()]).await.unwrap();
+        let events = recorder(&db);
+
+        db.delete_collection(collection.id).await.unwrap();
+        db.restore(collection.id).await.unwrap();
+        db.delete_collection(collection.id).await.unwrap();
+        db.purge(collection.id).await.unwrap();
+
+        let event = |entity, op, id| ChangeEvent::new(entity, op, id);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                event(EntityType::Collection, ChangeOp::Delete, collection.id),
+                event(EntityType::Request, ChangeOp::Delete, request.id),
+                event(EntityType::Collection, ChangeOp::Insert, collection.id),
+                event(EntityType::Request, ChangeOp::Insert, request.id),
+                event(EntityType::Collection, ChangeOp::Delete, collection.id),
+                event(EntityType::Request, ChangeOp::Delete, request.id),
+                event(EntityType::Collection, ChangeOp::Delete, collection.id),
+                event(EntityType::Request, ChangeOp::Delete, request.id),
+            ]
+        );
+
+        events.lock().unwrap().clear();
+        db.save_collection_tree(&collection, &[request.clone()]).await.unwrap();
+        events.lock().unwrap().clear();
+        db.delete_collection_cascade(collection.id).await.unwrap();
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                event(EntityType::Collection, ChangeOp::Delete, collection.id),
+                event(EntityType::Request, ChangeOp::Delete, request.id),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bulk_operations_emit_events() {
+        let db = test_db().await;
+        let collection = Collection::new("API".to_string());
+        let request = Request::new("Get".to_string(), HttpMethod::GET, "https://example.com".to_string());
+        let environment = Environment::new("Dev".to_string());
+        db.save_collection(&collection).await.unwrap();
+        let events = recorder(&db);
+
+        db.import_json(&serde_json::json!({
+            "collections": [{ "id": collection.id.to_string(), "name": "API" }],
+            "requests": [{
+                "id": request.id.to_string(),
+                "name": "Get",
+                "method": "GET",
+                "url": { "raw": "https://example.com" },
+            }],
+            "environments": [{ "id": environment.id.to_string(), "name": "Dev" }],
+        }))
+        .await
+        .unwrap();
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ChangeEvent::new(EntityType::Environment, ChangeOp::Insert, environment.id),
+                ChangeEvent::new(EntityType::Collection, ChangeOp::Update, collection.id),
+                ChangeEvent::new(EntityType::Request, ChangeOp::Insert, request.id),
+            ]
+        );
+
+        events.lock().unwrap().clear();
+        db.delete_request(request.id).await.unwrap();
+        db.delete_environment(environment.id).await.unwrap();
+        events.lock().unwrap().clear();
+        assert_eq!(db.empty_trash(models::now() + 1).await.unwrap(), 2);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ChangeEvent::new(EntityType::Request, ChangeOp::Delete, request.id),
+                ChangeEvent::new(EntityType::Environment, ChangeOp::Delete, environment.id),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_listeners_are_shared_between_clones() {
+        let db = test_db().await;
+        let clone = db.clone();
+        let events = recorder(&db);
+
+        let collection = Collection::new("API".to_string());
+        clone.save_collection(&collection).await.unwrap();
+        clone.delete_collection(collection.id).await.unwrap();
+
+        assert_eq!(events.lock().unwrap().len(), 2);
+    }
+}
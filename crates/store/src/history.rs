@@ -0,0 +1,459 @@
+//! Request history persistence
+//!
+//! Every send is recorded with a snapshot of the request as it was sent and a
+//! summary of the response, so the "recent sends" view survives restarts.
+
+use std::collections::HashMap;
+
+use sqlx::{Row, sqlite::SqliteRow};
+
+use crate::collections::{parse_id, to_json};
+use crate::{Database, StoreError, StoreResult, json_column, record_rows};
+use models::{
+    Header, HttpMethod, Id, Request, Response, ResponseError, ResponseHeader, TestResult,
+    Timestamp, new_id, now,
+};
+
+/// Maximum number of characters of the request body kept in history
+const BODY_PREVIEW_CHARS: usize = 1024;
+
+/// A single recorded send
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct HistoryEntry {
+    pub id: Id,
+
+    /// Saved request this send came from (None for ad-hoc sends)
+    pub request_id: Option<Id>,
+
+    /// Collection the request belonged to when it was sent
+    pub collection_id: Option<Id>,
+
+    /// Request snapshot at time of execution
+    pub method: HttpMethod,
+    pub url: String,
+    pub headers: Vec<Header>,
+    pub body_preview: Option<String>,
+
+    /// Response summary (None if the request never got a response)
+    pub status_code: Option<u16>,
+    pub status_text: Option<String>,
+    pub response_headers: Vec<ResponseHeader>,
+    pub response_size: Option<u64>,
+    pub duration_ms: Option<u64>,
+
+    pub test_results: Vec<TestResult>,
+    pub errors: Vec<ResponseError>,
+
+    /// When the response was received
+    pub timestamp: Timestamp,
+}
+
+impl HistoryEntry {
+    /// Capture a request/response exchange
+    pub fn from_exchange(request: &Request, response: &Response) -> Self {
+        let body_preview = request
+            .body
+            .get_raw()
+            .map(|raw| raw.chars().take(BODY_PREVIEW_CHARS).collect());
+
+        Self {
+            id: new_id(),
+            request_id: Some(request.id),
+            collection_id: request.collection_id,
+            method: request.method.clone(),
+            url: request.url.raw.clone(),
+            headers: request.headers.clone(),
+            body_preview,
+            status_code: Some(response.status_code),
+            status_text: Some(response.status_text.clone()),
+            response_headers: response.headers.clone(),
+            response_size: Some(response.size),
+            duration_ms: Some(response.duration_ms),
+            test_results: response.test_results.clone(),
+            errors: response.errors.clone(),
+            timestamp: response.received_at,
+        }
+    }
+
+    /// Mark this entry as an ad-hoc send not tied to a saved request
+    pub fn without_request(mut self) -> Self {
+        self.request_id = None;
+        self
+    }
+}
+
+impl Database {
+    /// Record a send in the history
+    #[tracing::instrument(skip_all, fields(id = %entry.id))]
+    pub async fn record_history(&self, entry: HistoryEntry) -> StoreResult<()> {
+        self.ensure_writable()?;
+        let headers = serde_json::to_string(&entry.headers)
+            .map_err(|e| StoreError::Serialization(e.to_string()))?;
+        let response_headers = serde_json::to_string(&entry.response_headers)
+            .map_err(|e| StoreError::Serialization(e.to_string()))?;
+        let test_results = serde_json::to_string(&entry.test_results)
+            .map_err(|e| StoreError::Serialization(e.to_string()))?;
+        let errors = serde_json::to_string(&entry.errors)
+            .map_err(|e| StoreError::Serialization(e.to_string()))?;
+
+        sqlx::query(
+            "INSERT INTO request_history
+            (id, request_id, collection_id, method, url, headers, body_preview, status_code, status_text,
+             response_headers, response_body_size, duration_ms, timestamp, test_results, errors)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(entry.id.to_string())
+        .bind(entry.request_id.map(|id| id.to_string()))
+        .bind(entry.collection_id.map(|id| id.to_string()))
+        .bind(entry.method.as_str())
+        .bind(&entry.url)
+        .bind(&headers)
+        .bind(&entry.body_preview)
+        .bind(entry.status_code)
+        .bind(&entry.status_text)
+        .bind(&response_headers)
+        .bind(entry.response_size.map(|s| s as i64))
+        .bind(entry.duration_ms.map(|d| d as i64))
+        .bind(entry.timestamp)
+        .bind(&test_results)
+        .bind(&errors)
+        .execute(self.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Snapshot the resolved variables in effect for a recorded send
+    ///
+    /// Replaces any earlier snapshot for the entry. The snapshot is deleted
+    /// with the history entry when history is pruned.
+    #[tracing::instrument(skip(self, variables), fields(id = %history_id))]
+    pub async fn record_run_environment(&self, history_id: Id, variables: &HashMap<String, String>) -> StoreResult<()> {
+        self.ensure_writable()?;
+        sqlx::query(
+            "INSERT OR REPLACE INTO run_environment_snapshots (history_id, variables, created_at)
+            VALUES (?, ?, ?)"
+        )
+        .bind(history_id.to_string())
+        .bind(to_json(variables)?)
+        .bind(now())
+        .execute(self.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Variables that were in effect for a recorded send
+    ///
+    /// Returns `NotFound` for entries recorded without a snapshot.
+    #[tracing::instrument(skip(self), fields(id = %history_id))]
+    pub async fn run_environment(&self, history_id: Id) -> StoreResult<HashMap<String, String>> {
+        let variables: String = sqlx::query_scalar(
+            "SELECT variables FROM run_environment_snapshots WHERE history_id = ?"
+        )
+        .bind(history_id.to_string())
+        .fetch_optional(self.pool())
+        .await?
+        .ok_or_else(|| StoreError::NotFound(format!("Environment snapshot for history entry {}", history_id)))?;
+
+        serde_json::from_str(&variables).map_err(|e| StoreError::Deserialization(e.to_string()))
+    }
+
+    /// List history entries, newest first
+    ///
+    /// With a `request_id`, only sends of that request are returned.
+    #[tracing::instrument(skip(self), fields(rows = tracing::field::Empty))]
+    pub async fn list_history(&self, request_id: Option<Id>, limit: u32) -> StoreResult<Vec<HistoryEntry>> {
+        let rows = match request_id {
+            Some(id) => {
+                sqlx::query(
+                    "SELECT * FROM request_history WHERE request_id = ?
+                    ORDER BY timestamp DESC, rowid DESC LIMIT ?"
+                )
+                .bind(id.to_string())
+                .bind(limit)
+                .fetch_all(self.pool())
+                .await?
+            }
+            None => {
+                sqlx::query(
+                    "SELECT * FROM request_history ORDER BY timestamp DESC, rowid DESC LIMIT ?"
+                )
+                .bind(limit)
+                .fetch_all(self.pool())
+                .await?
+            }
+        };
+
+        record_rows(rows.len());
+        rows.iter().map(history_from_row).collect()
+    }
+
+    /// Response time statistics across all recorded sends of a request
+    ///
+    /// Sends without a duration (failed before a response) are left out.
+    /// Returns empty stats when the request has no history.
+    #[tracing::instrument(skip(self), fields(rows = tracing::field::Empty))]
+    pub async fn timing_stats(&self, request_id: Id) -> StoreResult<TimingStats> {
+        let durations: Vec<i64> = sqlx::query_scalar(
+            "SELECT duration_ms FROM request_history
+            WHERE request_id = ? AND duration_ms IS NOT NULL
+            ORDER BY duration_ms"
+        )
+        .bind(request_id.to_string())
+        .fetch_all(self.pool())
+        .await?;
+
+        record_rows(durations.len());
+        let durations: Vec<u64> = durations.into_iter().map(|d| d.max(0) as u64).collect();
+        Ok(TimingStats::from_sorted(&durations))
+    }
+
+    /// Delete all but the `keep_per_request` most recent entries for each
+    /// request, plus any entry older than `older_than`
+    ///
+    /// Ad-hoc sends (no saved request) are pruned together as one group.
+    /// Returns the number of entries deleted.
+    #[tracing::instrument(skip(self), fields(rows = tracing::field::Empty))]
+    pub async fn prune_history(&self, keep_per_request: usize, older_than: Option<Timestamp>) -> StoreResult<usize> {
+        self.ensure_writable()?;
+        let result = sqlx::query(
+            "DELETE FROM request_history WHERE id IN (
+                SELECT id FROM (
+                    SELECT id, timestamp, ROW_NUMBER() OVER (
+                        PARTITION BY request_id ORDER BY timestamp DESC, rowid DESC
+                    ) AS position
+                    FROM request_history
+                )
+                WHERE position > ?1 OR (?2 IS NOT NULL AND timestamp < ?2)
+            )"
+        )
+        .bind(keep_per_request as i64)
+        .bind(older_than)
+        .execute(self.pool())
+        .await?;
+
+        record_rows(result.rows_affected() as usize);
+        Ok(result.rows_affected() as usize)
+    }
+}
+
+/// Response time statistics for one request, see [`Database::timing_stats`]
+///
+/// All fields are zero when there is no history.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TimingStats {
+    /// Number of sends with a recorded duration
+    pub count: usize,
+    pub min_ms: u64,
+    pub max_ms: u64,
+    pub mean_ms: f64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub p99_ms: u64,
+}
+
+impl TimingStats {
+    /// Stats over `durations`, which must be sorted ascending
+    fn from_sorted(durations: &[u64]) -> Self {
+        let (Some(&min_ms), Some(&max_ms)) = (durations.first(), durations.last()) else {
+            return Self::default();
+        };
+        // Nearest-rank: the smallest value with at least p% of values at or below it
+        let percentile = |p: usize| durations[(p * durations.len()).div_ceil(100).max(1) - 1];
+
+        Self {
+            count: durations.len(),
+            min_ms,
+            max_ms,
+            mean_ms: durations.iter().sum::<u64>() as f64 / durations.len() as f64,
+            p50_ms: percentile(50),
+            p95_ms: percentile(95),
+            p99_ms: percentile(99),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+}
+
+fn history_from_row(row: &SqliteRow) -> StoreResult<HistoryEntry> {
+    let id: String = row.try_get("id")?;
+    let request_id: Option<String> = row.try_get("request_id")?;
+    let collection_id: Option<String> = row.try_get("collection_id")?;
+    let method: String = row.try_get("method")?;
+    let headers: Option<String> = row.try_get("headers")?;
+    let response_headers: Option<String> = row.try_get("response_headers")?;
+    let status_code: Option<i64> = row.try_get("status_code")?;
+    let response_size: Option<i64> = row.try_get("response_body_size")?;
+    let duration_ms: Option<i64> = row.try_get("duration_ms")?;
+
+    Ok(HistoryEntry {
+        id: parse_id(&id)?,
+        request_id: request_id.as_deref().map(parse_id).transpose()?,
+        collection_id: collection_id.as_deref().map(parse_id).transpose()?,
+        method: method.parse().map_err(StoreError::InvalidData)?,
+        url: row.try_get("url")?,
+        headers: headers.as_deref().map(json_column).unwrap_or_default(),
+        body_preview: row.try_get("body_preview")?,
+        status_code: status_code.map(|c| c as u16),
+        status_text: row.try_get("status_text")?,
+        response_headers: response_headers.as_deref().map(json_column).unwrap_or_default(),
+        response_size: response_size.map(|s| s as u64),
+        duration_ms: duration_ms.map(|d| d as u64),
+        test_results: json_column(&row.try_get::<String, _>("test_results")?),
+        errors: json_column(&row.try_get::<String, _>("errors")?),
+        timestamp: row.try_get("timestamp")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_db;
+    use crate::test_support::insert_request;
+
+    fn send(request: &Request, status: u16, received_at: Timestamp) -> HistoryEntry {
+        let mut response = Response::new(status, "OK".to_string());
+        response.received_at = received_at;
+        response.duration_ms = 42;
+        HistoryEntry::from_exchange(request, &response)
+    }
+
+    #[tokio::test]
+    async fn test_record_and_list_history() {
+        let db = test_db().await;
+        let request = Request::new("Users".to_string(), HttpMethod::GET, "https://example.com/users".to_string());
+        let other = Request::new("Orders".to_string(), HttpMethod::POST, "https://example.com/orders".to_string());
+        insert_request(&db, &request).await;
+        insert_request(&db, &other).await;
+
+        let base = now();
+        db.record_history(send(&request, 200, base)).await.unwrap();
+        db.record_history(send(&request, 500, base + 10)).await.unwrap();
+        db.record_history(send(&other, 201, base + 5)).await.unwrap();
+
+        let all = db.list_history(None, 10).await.unwrap();
+        assert_eq!(all.iter().map(|e| e.status_code).collect::<Vec<_>>(), vec![Some(500), Some(201), Some(200)]);
+
+        let for_request = db.list_history(Some(request.id), 10).await.unwrap();
+        assert_eq!(for_request.len(), 2);
+        assert_eq!(for_request[0].status_code, Some(500));
+        assert_eq!(for_request[0].duration_ms, Some(42));
+        assert_eq!(for_request[0].url, "https://example.com/users");
+    }
+
+    #[tokio::test]
+    async fn test_prune_history_keeps_recent_per_request() {
+        let db = test_db().await;
+        let request = Request::new("Users".to_string(), HttpMethod::GET, "https://example.com/users".to_string());
+        let other = Request::new("Orders".to_string(), HttpMethod::GET, "https://example.com/orders".to_string());
+        insert_request(&db, &request).await;
+        insert_request(&db, &other).await;
+
+        let base = now();
+        for i in 0..3 {
+            db.record_history(send(&request, 200 + i, base + i as i64)).await.unwrap();
+        }
+        db.record_history(send(&other, 200, base)).await.unwrap();
+
+        assert_eq!(db.prune_history(1, None).await.unwrap(), 2);
+
+        let remaining = db.list_history(Some(request.id), 10).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].status_code, Some(202));
+        assert_eq!(db.list_history(Some(other.id), 10).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_prune_history_with_cutoff() {
+        let db = test_db().await;
+        let request = Request::new("Users".to_string(), HttpMethod::GET, "https://example.com/users".to_string());
+        let other = Request::new("Orders".to_string(), HttpMethod::GET, "https://example.com/orders".to_string());
+        insert_request(&db, &request).await;
+        insert_request(&db, &other).await;
+
+        let base = now();
+        for i in 0..4 {
+            db.record_history(send(&request, 200 + i, base + i as i64 * 1000)).await.unwrap();
+        }
+        db.record_history(send(&other, 200, base)).await.unwrap();
+        db.record_history(send(&other, 201, base + 3000)).await.unwrap();
+
+        assert_eq!(db.prune_history(2, None).await.unwrap(), 2);
+        assert_eq!(db.list_history(Some(request.id), 10).await.unwrap().len(), 2);
+
+        // The cutoff applies on top of the per-request limit
+        assert_eq!(db.prune_history(2, Some(base + 3000)).await.unwrap(), 2);
+
+        let remaining = db.list_history(Some(request.id), 10).await.unwrap();
+        assert_eq!(remaining.iter().map(|e| e.status_code).collect::<Vec<_>>(), vec![Some(203)]);
+        let remaining = db.list_history(Some(other.id), 10).await.unwrap();
+        assert_eq!(remaining.iter().map(|e| e.status_code).collect::<Vec<_>>(), vec![Some(201)]);
+    }
+
+    #[tokio::test]
+    async fn test_run_environment_snapshot() {
+        let db = test_db().await;
+        let request = Request::new("Users".to_string(), HttpMethod::GET, "{{base_url}}/users".to_string());
+        insert_request(&db, &request).await;
+        let entry = send(&request, 200, now());
+        let history_id = entry.id;
+        db.record_history(entry).await.unwrap();
+
+        assert!(matches!(db.run_environment(history_id).await, Err(StoreError::NotFound(_))));
+
+        let variables = HashMap::from([
+            ("base_url".to_string(), "https://staging.example.com".to_string()),
+            ("token".to_string(), "abc".to_string()),
+        ]);
+        db.record_run_environment(history_id, &variables).await.unwrap();
+        assert_eq!(db.run_environment(history_id).await.unwrap(), variables);
+
+        // Pruning the entry drops its snapshot
+        db.prune_history(0, None).await.unwrap();
+        assert!(matches!(db.run_environment(history_id).await, Err(StoreError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_timing_stats() {
+        let db = test_db().await;
+        let request = Request::new("Users".to_string(), HttpMethod::GET, "https://example.com/users".to_string());
+        insert_request(&db, &request).await;
+
+        assert!(db.timing_stats(request.id).await.unwrap().is_empty());
+
+        let base = now();
+        for (i, duration) in [30, 10, 20, 100, 40].into_iter().enumerate() {
+            let mut entry = send(&request, 200, base + i as i64);
+            entry.duration_ms = Some(duration);
+            db.record_history(entry).await.unwrap();
+        }
+        let mut failed = send(&request, 200, base + 10);
+        failed.duration_ms = None;
+        db.record_history(failed).await.unwrap();
+
+        let stats = db.timing_stats(request.id).await.unwrap();
+        assert_eq!(
+            stats,
+            TimingStats {
+                count: 5,
+                min_ms: 10,
+                max_ms: 100,
+                mean_ms: 40.0,
+                p50_ms: 30,
+                p95_ms: 100,
+                p99_ms: 100,
+            }
+        );
+    }
+
+    #[test]
+    fn test_timing_stats_nearest_rank() {
+        let durations: Vec<u64> = (1..=100).collect();
+        let stats = TimingStats::from_sorted(&durations);
+        assert_eq!((stats.p50_ms, stats.p95_ms, stats.p99_ms), (50, 95, 99));
+        assert_eq!(TimingStats::from_sorted(&[7]).p99_ms, 7);
+    }
+}
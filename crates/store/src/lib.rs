@@ -7,15 +7,28 @@ pub mod database;
 pub mod collections;
 pub mod requests;
 pub mod environments;
+pub mod history;
+pub mod trash;
+pub mod conflicts;
+pub mod recent;
+pub mod sync;
+pub mod checksum;
 pub mod settings;
 pub mod migrations;
+pub mod encryption;
+pub mod search;
+pub mod events;
+#[cfg(test)]
+mod test_support;
 
 use sqlx::{SqlitePool, sqlite::SqliteConnectOptions, sqlite::SqlitePoolOptions};
 use std::path::Path;
 use std::str::FromStr;
-use anyhow::Result;
+use std::time::Duration;
+use anyhow::{Context, Result};
 
 pub use database::Database;
+pub use events::{ChangeEvent, ChangeListener, ChangeOp, EntityType};
 
 /// Re-export commonly used types
 pub use models::{Id, Timestamp, new_id, now};
@@ -34,6 +47,21 @@ pub struct StoreConfig {
 
     /// Enable foreign key constraints
     pub enable_foreign_keys: bool,
+
+    /// Check collection and request rows against their stored checksum on load
+    pub verify_checksums: bool,
+
+    /// SQLCipher passphrase; requires the `sqlcipher` feature
+    pub encryption_key: Option<String>,
+
+    /// Open the file read-only and skip migrations; write methods fail
+    pub read_only: bool,
+
+    /// Attempts at opening the pool when the file is busy or locked (at least one is made)
+    pub connect_retries: u32,
+
+    /// How long each attempt waits for a connection
+    pub connect_timeout: Duration,
 }
 
 impl Default for StoreConfig {
@@ -43,6 +71,11 @@ impl Default for StoreConfig {
             max_connections: 5,
             enable_wal: true,
             enable_foreign_keys: true,
+            verify_checksums: true,
+            encryption_key: None,
+            read_only: false,
+            connect_retries: 3,
+            connect_timeout: Duration::from_secs(5),
         }
     }
 }
@@ -65,23 +98,64 @@ impl StoreConfig {
         self.enable_wal = enable;
         self
     }
+
+    /// Enable or disable checksum verification on load
+    pub fn with_checksum_verification(mut self, enable: bool) -> Self {
+        self.verify_checksums = enable;
+        self
+    }
+
+    /// Encrypt the database with the given passphrase (see [`encryption`])
+    pub fn with_encryption_key(mut self, key: impl Into<String>) -> Self {
+        self.encryption_key = Some(key.into());
+        self
+    }
+
+    /// Set how many times opening the pool is attempted while the file is locked
+    pub fn with_connect_retries(mut self, retries: u32) -> Self {
+        self.connect_retries = retries;
+        self
+    }
+
+    /// Set how long each connection attempt may take
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Open the database read-only, e.g. to view a backup or shared archive
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
 }
 
 /// Initialize and open the database
 pub async fn open_store(config: StoreConfig) -> Result<Database> {
     let db_path = &config.db_path;
 
-    // Ensure parent directory exists
+    // Ensure parent directory exists (a read-only store must already exist)
     if let Some(parent) = Path::new(db_path).parent() {
-        if !parent.as_os_str().is_empty() {
+        if !parent.as_os_str().is_empty() && !config.read_only {
             tokio::fs::create_dir_all(parent).await?;
         }
     }
 
     // Configure connection options
-    let mut options = SqliteConnectOptions::from_str(db_path)?;
+    let mut options = SqliteConnectOptions::from_str(db_path)?.read_only(config.read_only);
+
+    if let Some(key) = &config.encryption_key {
+        if !cfg!(feature = "sqlcipher") {
+            return Err(StoreError::Encryption(
+                "An encryption key was given but the store was built without the sqlcipher feature".to_string(),
+            )
+            .into());
+        }
+        options = options.pragma("key", encryption::key_pragma_value(key));
+    }
 
-    if config.enable_wal {
+    // Switching the journal mode writes to the file
+    if config.enable_wal && !config.read_only {
         options = options.pragma("journal_mode", "WAL");
         options = options.pragma("synchronous", "NORMAL");
     }
@@ -94,16 +168,61 @@ pub async fn open_store(config: StoreConfig) -> Result<Database> {
     options = options.pragma("cache_size", "-64000"); // 64MB cache
     options = options.pragma("temp_store", "memory");
 
-    // Create connection pool
-    let pool = SqlitePoolOptions::new()
-        .max_connections(config.max_connections)
-        .connect_with(options)
-        .await?;
+    // Create connection pool, retrying while another process holds the file
+    let attempts = config.connect_retries.max(1);
+    let mut attempt = 1;
+    let pool = loop {
+        let result = SqlitePoolOptions::new()
+            .max_connections(config.max_connections)
+            .acquire_timeout(config.connect_timeout)
+            .connect_with(options.clone())
+            .await;
+        match result {
+            Ok(pool) => break pool,
+            Err(e) if attempt < attempts && is_retryable_connect_error(&e) => {
+                tracing::debug!(attempt, error = %e, "database busy, retrying");
+                tokio::time::sleep(CONNECT_RETRY_DELAY * attempt).await;
+                attempt += 1;
+            }
+            Err(e) => {
+                return Err(encryption::map_key_error(e))
+                    .with_context(|| format!("Could not open {} after {} attempt(s)", db_path, attempt));
+            }
+        }
+    };
+
+    if config.encryption_key.is_some() {
+        sqlx::query("SELECT count(*) FROM sqlite_master")
+            .execute(&pool)
+            .await
+            .map_err(encryption::map_key_error)?;
+    }
 
-    // Run migrations
-    migrations::run_migrations(&pool).await?;
+    // Run migrations, unless the file was written by a newer build
+    if !config.read_only {
+        migrations::check_schema_compatible(&pool).await?;
+        migrations::run_migrations(&pool).await?;
+    }
 
-    Ok(Database::new(pool))
+    Ok(Database::new(pool)
+        .with_checksum_verification(config.verify_checksums)
+        .with_read_only(config.read_only))
+}
+
+/// Pause before the first retry in [`open_store`]; later retries wait longer
+const CONNECT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Whether opening the pool may succeed if tried again
+fn is_retryable_connect_error(error: &sqlx::Error) -> bool {
+    match error {
+        sqlx::Error::PoolTimedOut => true,
+        // SQLITE_BUSY and SQLITE_LOCKED, including extended codes
+        sqlx::Error::Database(e) => {
+            let primary = e.code().and_then(|code| code.parse::<i32>().ok()).map(|code| code & 0xff);
+            matches!(primary, Some(5 | 6)) || e.message().contains("database is locked")
+        }
+        _ => false,
+    }
 }
 
 /// Result type alias for store operations
@@ -132,6 +251,51 @@ pub enum StoreError {
 
     #[error("Migration error: {0}")]
     Migration(String),
+
+    #[error("Encryption error: {0}")]
+    Encryption(String),
+}
+
+/// A single page of rows from a paginated listing
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Page<T> {
+    /// Rows in this page
+    pub items: Vec<T>,
+
+    /// Total number of rows across all pages
+    pub total_count: u64,
+
+    /// Requested page size
+    pub limit: u32,
+
+    /// Offset of the first row in this page
+    pub offset: u32,
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>, total_count: u64, limit: u32, offset: u32) -> Self {
+        Self {
+            items,
+            total_count,
+            limit,
+            offset,
+        }
+    }
+
+    /// Check if more rows exist after this page
+    pub fn has_more(&self) -> bool {
+        (self.offset as u64 + self.items.len() as u64) < self.total_count
+    }
+}
+
+/// Record the number of rows touched on the current `tracing` span
+pub(crate) fn record_rows(rows: usize) {
+    tracing::Span::current().record("rows", rows);
+}
+
+/// Parse a JSON column, falling back to the default for empty or legacy values
+pub(crate) fn json_column<T: serde::de::DeserializeOwned + Default>(raw: &str) -> T {
+    serde_json::from_str(raw).unwrap_or_default()
 }
 
 /// Transaction wrapper for atomic operations
@@ -146,13 +310,13 @@ impl<'a> Transaction<'a> {
     }
 
     /// Commit the transaction
-    pub async fn commit(self) -> Result<()> {
+    pub async fn commit(self) -> StoreResult<()> {
         self.inner.commit().await?;
         Ok(())
     }
 
     /// Rollback the transaction
-    pub async fn rollback(self) -> Result<()> {
+    pub async fn rollback(self) -> StoreResult<()> {
         self.inner.rollback().await?;
         Ok(())
     }
@@ -163,6 +327,20 @@ impl<'a> Transaction<'a> {
     }
 }
 
+/// Open a fresh in-memory store for tests
+///
+/// A single connection keeps every query on the same in-memory database.
+#[cfg(test)]
+pub(crate) async fn test_db() -> Database {
+    let config = StoreConfig {
+        db_path: ":memory:".to_string(),
+        max_connections: 1,
+        enable_wal: false,
+        ..Default::default()
+    };
+    open_store(config).await.unwrap()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,9 +353,53 @@ mod tests {
         };
 
         let db = open_store(config).await.unwrap();
+        assert_eq!(db.schema_version().await.unwrap(), Some(migrations::latest_schema_version()));
         drop(db);
     }
 
+    #[tokio::test]
+    async fn test_open_store_read_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.db").to_string_lossy().to_string();
+        std::fs::File::create(&path).unwrap();
+
+        let collection = models::Collection::new("Shared".to_string());
+        let db = open_store(StoreConfig::default().with_db_path(path.clone()).with_wal(false)).await.unwrap();
+        db.save_collection(&collection).await.unwrap();
+        db.pool().close().await;
+
+        let db = open_store(StoreConfig::default().with_db_path(path).with_read_only(true)).await.unwrap();
+        assert!(db.is_read_only());
+        assert_eq!(db.get_collection(collection.id).await.unwrap().unwrap().name, "Shared");
+        let err = db.save_collection(&collection).await.unwrap_err();
+        assert!(matches!(&err, StoreError::InvalidData(msg) if msg == "database opened read-only"));
+        assert!(db.delete_collection(collection.id).await.is_err());
+        assert!(db.analyze().await.is_err());
+        // Writes that bypass the store methods are refused by SQLite
+        assert!(sqlx::query("DELETE FROM collections").execute(db.pool()).await.is_err());
+        db.pool().close().await;
+    }
+
+    #[test]
+    fn test_is_retryable_connect_error() {
+        assert!(is_retryable_connect_error(&sqlx::Error::PoolTimedOut));
+        assert!(!is_retryable_connect_error(&sqlx::Error::RowNotFound));
+    }
+
+    #[tokio::test]
+    async fn test_open_store_reports_attempts() {
+        let path = std::env::temp_dir().join(format!("postboy-missing-{}.db", new_id()));
+        let config = StoreConfig::default()
+            .with_db_path(path.to_string_lossy())
+            .with_read_only(true)
+            .with_connect_retries(0);
+
+        // A missing file isn't retryable, so it fails on the first attempt
+        let err = open_store(config).await.err().unwrap();
+        assert!(err.to_string().ends_with("after 1 attempt(s)"), "{}", err);
+        assert!(err.downcast_ref::<StoreError>().is_some());
+    }
+
     #[tokio::test]
     async fn test_open_store_with_wal() {
         let config = StoreConfig {
@@ -1,21 +1,72 @@
 //! Database migration runner
 
-use sqlx::{Pool, Sqlite, SqlitePool, migrate::MigrateDatabase};
+use sqlx::{Row, Sqlite, SqlitePool, migrate::{MigrateDatabase, Migrator}};
 use std::path::Path;
 use anyhow::Result;
 
+use crate::checksum::{COLLECTION_COLUMNS, REQUEST_COLUMNS, row_checksum};
+use crate::{StoreError, StoreResult};
+
+/// Migrations in the migrations/ directory, embedded at compile time
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Migrations that widen a table's row checksum: version, table, and the
+/// checksum columns before and after that migration
+///
+/// Rows written before the migration carry a checksum over the old columns.
+/// After it runs, rows whose stored checksum still matches the old columns
+/// get one over the current columns, so they keep verifying.
+const CHECKSUM_UPGRADES: &[(i64, &str, &[&str], &[&str])] = &[
+    (13, "requests", &["headers", "query_params", "body", "auth", "script", "ui_state"], REQUEST_COLUMNS),
+    (15, "collections", &["info", "auth", "tags", "sync_state", "ui_state"], COLLECTION_COLUMNS),
+];
+
 /// Run all database migrations
 pub async fn run_migrations(pool: &SqlitePool) -> Result<()> {
-    // sqlx::migrate! will look for migrations in the migrations/ directory
-    sqlx::migrate!("./migrations")
+    let before = stored_schema_version(pool).await?;
+    MIGRATOR
         .run(pool)
         .await
         .with_context(|| "Failed to run database migrations")?;
 
+    if let Some(before) = before {
+        for (version, table, old_columns, columns) in CHECKSUM_UPGRADES {
+            if before < *version {
+                upgrade_checksums(pool, table, old_columns, columns)
+                    .await
+                    .with_context(|| format!("Failed to recompute {} checksums", table))?;
+            }
+        }
+    }
+
     tracing::info!("Database migrations completed successfully");
     Ok(())
 }
 
+/// Move `table` rows that verify against `old_columns` to a checksum over `columns`
+///
+/// Rows that don't verify keep their stored checksum, so corruption from
+/// before the upgrade is still reported on load.
+async fn upgrade_checksums(pool: &SqlitePool, table: &str, old_columns: &[&str], columns: &[&str]) -> StoreResult<()> {
+    let mut tx = pool.begin().await?;
+    let rows = sqlx::query(&format!("SELECT * FROM {} WHERE checksum IS NOT NULL", table))
+        .fetch_all(&mut *tx)
+        .await?;
+    for row in &rows {
+        let stored: i64 = row.try_get("checksum")?;
+        if row_checksum(row, old_columns)? != stored {
+            continue;
+        }
+        sqlx::query(&format!("UPDATE {} SET checksum = ? WHERE id = ?", table))
+            .bind(row_checksum(row, columns)?)
+            .bind(row.try_get::<String, _>("id")?)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
 /// Create a new database file and run migrations
 pub async fn create_database(db_path: impl AsRef<Path>) -> Result<SqlitePool> {
     let path = db_path.as_ref();
@@ -39,14 +90,45 @@ pub async fn create_database(db_path: impl AsRef<Path>) -> Result<SqlitePool> {
 }
 
 /// Get the current schema version from the database
+///
+/// `None` for a database that has never been migrated.
 pub async fn get_schema_version(pool: &SqlitePool) -> Result<Option<i64>> {
-    let result: Option<(i64,)> = sqlx::query_as(
-        "SELECT version FROM schema_version ORDER BY version DESC LIMIT 1"
+    Ok(stored_schema_version(pool).await?)
+}
+
+pub(crate) async fn stored_schema_version(pool: &SqlitePool) -> StoreResult<Option<i64>> {
+    let has_table: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'schema_version')"
     )
-    .fetch_optional(pool)
+    .fetch_one(pool)
     .await?;
+    if !has_table {
+        return Ok(None);
+    }
+
+    let version: Option<i64> = sqlx::query_scalar("SELECT MAX(version) FROM schema_version")
+        .fetch_one(pool)
+        .await?;
+    Ok(version)
+}
+
+/// Highest schema version this build knows how to migrate to
+pub fn latest_schema_version() -> i64 {
+    MIGRATOR.iter().map(|m| m.version).max().unwrap_or(0)
+}
 
-    Ok(result.map(|(v,)| v))
+/// Refuse databases written by a newer version of the app
+///
+/// Running older migrations over a newer schema could lose data.
+pub(crate) async fn check_schema_compatible(pool: &SqlitePool) -> StoreResult<()> {
+    match stored_schema_version(pool).await? {
+        Some(version) if version > latest_schema_version() => Err(StoreError::Migration(format!(
+            "database is from a newer version (schema {}, this build supports up to {})",
+            version,
+            latest_schema_version()
+        ))),
+        _ => Ok(()),
+    }
 }
 
 #[cfg(test)]
@@ -65,5 +147,65 @@ mod tests {
         let version = get_schema_version(&pool).await.unwrap();
         assert!(version.is_some());
         assert!(version.unwrap() >= 1);
+        assert_eq!(version, Some(latest_schema_version()));
+    }
+
+    #[tokio::test]
+    async fn test_check_schema_compatible() {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        assert_eq!(get_schema_version(&pool).await.unwrap(), None);
+        assert!(check_schema_compatible(&pool).await.is_ok());
+
+        run_migrations(&pool).await.unwrap();
+        assert!(check_schema_compatible(&pool).await.is_ok());
+
+        sqlx::query("INSERT INTO schema_version (version, applied_at) VALUES (?, 0)")
+            .bind(latest_schema_version() + 1)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let err = check_schema_compatible(&pool).await.unwrap_err();
+        assert!(matches!(err, StoreError::Migration(msg) if msg.starts_with("database is from a newer version")));
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_recomputes_widened_checksums() {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        let v12 = Migrator {
+            migrations: MIGRATOR.iter().filter(|m| m.version <= 12).cloned().collect::<Vec<_>>().into(),
+            ignore_missing: false,
+            locking: true,
+        };
+        v12.run(&pool).await.unwrap();
+
+        let old = crate::checksum::checksum(&[Some("{}"), None, Some("[]"), Some("{}"), Some("{}")]);
+        sqlx::query("INSERT INTO collections (id, name, checksum, created_at, updated_at) VALUES ('c1', 'API', ?, 0, 0), ('c2', 'Bad', 1, 0, 0)")
+            .bind(old)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let old = crate::checksum::checksum(&[Some("[]"), Some("[]"), Some("{}"), None, Some("{}"), Some("{}")]);
+        sqlx::query("INSERT INTO requests (id, name, method, url_raw, checksum, created_at, updated_at) VALUES ('r1', 'Users', 'GET', '/', ?, 0, 0)")
+            .bind(old)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        run_migrations(&pool).await.unwrap();
+
+        async fn checksums(pool: &SqlitePool, table: &str, id: &str, columns: &[&str]) -> (i64, i64) {
+            let row = sqlx::query(&format!("SELECT * FROM {} WHERE id = ?", table))
+                .bind(id)
+                .fetch_one(pool)
+                .await
+                .unwrap();
+            (row.get("checksum"), row_checksum(&row, columns).unwrap())
+        }
+        let (stored, current) = checksums(&pool, "collections", "c1", COLLECTION_COLUMNS).await;
+        assert_eq!(stored, current);
+        let (stored, current) = checksums(&pool, "requests", "r1", REQUEST_COLUMNS).await;
+        assert_eq!(stored, current);
+        // A row that was already corrupt still is
+        assert_eq!(checksums(&pool, "collections", "c2", COLLECTION_COLUMNS).await.0, 1);
     }
 }
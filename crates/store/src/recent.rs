@@ -0,0 +1,75 @@
+//! Recently opened items for "continue where you left off"
+
+use sqlx::Row;
+
+use crate::collections::parse_id;
+use crate::{Database, StoreError, StoreResult, record_rows};
+use models::{Id, SyncItemType, now};
+
+impl Database {
+    /// Remember that an item was just opened
+    #[tracing::instrument(skip(self), fields(id = %id))]
+    pub async fn record_opened(&self, item_type: SyncItemType, id: Id) -> StoreResult<()> {
+        self.ensure_writable()?;
+        // REPLACE gives the row a new rowid, which breaks ties within a millisecond
+        sqlx::query(
+            "INSERT OR REPLACE INTO recently_opened (item_type, item_id, last_opened_at) VALUES (?, ?, ?)"
+        )
+        .bind(item_type.as_str())
+        .bind(id.to_string())
+        .bind(now())
+        .execute(self.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Most recently opened items across all types, newest first
+    #[tracing::instrument(skip(self), fields(rows = tracing::field::Empty))]
+    pub async fn last_opened(&self, limit: u32) -> StoreResult<Vec<(SyncItemType, Id)>> {
+        let rows = sqlx::query(
+            "SELECT item_type, item_id FROM recently_opened
+            ORDER BY last_opened_at DESC, rowid DESC LIMIT ?"
+        )
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await?;
+
+        record_rows(rows.len());
+        rows.iter()
+            .map(|row| {
+                let item_type: String = row.try_get("item_type")?;
+                let id: String = row.try_get("item_id")?;
+                Ok((item_type.parse().map_err(StoreError::InvalidData)?, parse_id(&id)?))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_db;
+    use models::new_id;
+
+    #[tokio::test]
+    async fn test_last_opened_newest_first() {
+        let db = test_db().await;
+        let a = new_id();
+        let b = new_id();
+
+        db.record_opened(SyncItemType::Collection, a).await.unwrap();
+        db.record_opened(SyncItemType::Request, b).await.unwrap();
+
+        assert_eq!(
+            db.last_opened(10).await.unwrap(),
+            vec![(SyncItemType::Request, b), (SyncItemType::Collection, a)]
+        );
+
+        // Reopening moves an item back to the top without duplicating it
+        db.record_opened(SyncItemType::Collection, a).await.unwrap();
+        let recent = db.last_opened(10).await.unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0], (SyncItemType::Collection, a));
+    }
+}
@@ -0,0 +1,773 @@
+//! Request persistence operations
+
+use sqlx::{Row, SqliteConnection, sqlite::SqliteRow};
+
+use crate::checksum::{REQUEST_COLUMNS, checksum};
+use crate::collections::{parse_id, to_json};
+use crate::events::{ChangeEvent, ChangeOp, EntityType, upsert_op};
+use crate::{Database, Page, StoreError, StoreResult, json_column, record_rows};
+use models::{AuthConfig, Header, HttpMethod, Id, Param, Request, RequestBody, ScriptConfig, Url, now};
+
+impl Database {
+    /// List requests one page at a time, ordered by creation time
+    #[tracing::instrument(skip(self), fields(rows = tracing::field::Empty))]
+    pub async fn list_requests_paged(&self, limit: u32, offset: u32) -> StoreResult<Page<Request>> {
+        let total_count = self.count_requests().await?;
+
+        let items = sqlx::query(
+            "SELECT * FROM requests WHERE deleted_at IS NULL
+            ORDER BY created_at, id LIMIT ? OFFSET ?"
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(self.pool())
+        .await?
+        .iter()
+        .map(|row| self.verified(row, REQUEST_COLUMNS).and_then(request_from_row))
+        .collect::<StoreResult<Vec<_>>>()?;
+
+        record_rows(items.len());
+        Ok(Page::new(items, total_count, limit, offset))
+    }
+
+    /// Count all requests not in the trash
+    pub async fn count_requests(&self) -> StoreResult<u64> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM requests WHERE deleted_at IS NULL")
+            .fetch_one(self.pool())
+            .await?;
+
+        Ok(count as u64)
+    }
+
+    /// Get a request by id, ignoring requests in the trash
+    #[tracing::instrument(skip(self), fields(id = %id))]
+    pub async fn get_request(&self, id: Id) -> StoreResult<Option<Request>> {
+        sqlx::query("SELECT * FROM requests WHERE id = ? AND deleted_at IS NULL")
+            .bind(id.to_string())
+            .fetch_optional(self.pool())
+            .await?
+            .as_ref()
+            .map(|row| self.verified(row, REQUEST_COLUMNS).and_then(request_from_row))
+            .transpose()
+    }
+
+    /// Insert or update a request
+    #[tracing::instrument(skip_all, fields(id = %request.id))]
+    pub async fn save_request(&self, request: &Request) -> StoreResult<()> {
+        self.ensure_writable()?;
+        let mut tx = self.pool().begin().await?;
+        let op = upsert_op(&mut tx, EntityType::Request, request.id).await?;
+        write_request(&mut tx, request).await?;
+        tx.commit().await?;
+        self.emit([ChangeEvent::new(EntityType::Request, op, request.id)]);
+        Ok(())
+    }
+
+    /// Update only the columns set in `update`
+    ///
+    /// Cheaper than [`Database::save_request`] for small edits such as toggling
+    /// a header. The row checksum is recomputed when a JSON column changes.
+    /// A missing request is `NotFound` even when `update` is empty.
+    #[tracing::instrument(skip(self, update), fields(id = %id))]
+    pub async fn patch_request(&self, id: Id, update: RequestUpdate) -> StoreResult<()> {
+        self.ensure_writable()?;
+        let assignments = update.into_columns()?;
+
+        let mut tx = self.pool().begin().await?;
+        let row = sqlx::query("SELECT * FROM requests WHERE id = ? AND deleted_at IS NULL")
+            .bind(id.to_string())
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| StoreError::NotFound(format!("Request {}", id)))?;
+        if assignments.is_empty() {
+            return Ok(());
+        }
+        let row = self.verified(&row, REQUEST_COLUMNS)?;
+
+        let mut query = sqlx::QueryBuilder::<sqlx::Sqlite>::new("UPDATE requests SET ");
+        let mut columns = query.separated(", ");
+        for (column, value) in &assignments {
+            columns.push(format!("{} = ", column)).push_bind_unseparated(value.clone());
+        }
+
+        if assignments.iter().any(|(column, _)| REQUEST_COLUMNS.contains(column)) {
+            let mut values = Vec::with_capacity(REQUEST_COLUMNS.len());
+            for column in REQUEST_COLUMNS {
+                let value = match assignments.iter().find(|(c, _)| c == column) {
+                    Some((_, value)) => value.clone(),
+                    None => row.try_get::<Option<String>, _>(*column)?,
+                };
+                values.push(value);
+            }
+            let values: Vec<Option<&str>> = values.iter().map(Option::as_deref).collect();
+            columns.push("checksum = ").push_bind_unseparated(checksum(&values));
+        }
+        columns.push("updated_at = ").push_bind_unseparated(now());
+        query.push(" WHERE id = ").push_bind(id.to_string());
+
+        query.build().execute(&mut *tx).await?;
+        tx.commit().await?;
+        self.emit([ChangeEvent::new(EntityType::Request, ChangeOp::Update, id)]);
+        Ok(())
+    }
+
+    /// Move a request to the trash
+    #[tracing::instrument(skip(self), fields(id = %id))]
+    pub async fn delete_request(&self, id: Id) -> StoreResult<()> {
+        self.ensure_writable()?;
+        let result = sqlx::query(
+            "UPDATE requests SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL"
+        )
+        .bind(now())
+        .bind(id.to_string())
+        .execute(self.pool())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(StoreError::NotFound(format!("Request {}", id)));
+        }
+
+        self.emit([ChangeEvent::new(EntityType::Request, ChangeOp::Delete, id)]);
+        Ok(())
+    }
+
+    /// Insert many new requests in one transaction
+    ///
+    /// Rows go in as multi-row INSERTs, chunked to stay under SQLite's bound
+    /// parameter limit, which is much faster than one [`Database::save_request`]
+    /// per request for imports. Unlike `save_request` this never updates: an
+    /// id that already exists fails the whole batch and nothing is written.
+    /// Returns the number of rows inserted.
+    #[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
+    pub async fn insert_requests_bulk(&self, requests: &[Request]) -> StoreResult<usize> {
+        self.ensure_writable()?;
+        if requests.is_empty() {
+            return Ok(0);
+        }
+
+        let rows = requests.iter().map(RequestRow::new).collect::<StoreResult<Vec<_>>>()?;
+        let mut tx = self.pool().begin().await?;
+        let mut inserted = 0;
+
+        for chunk in rows.chunks(BULK_INSERT_ROWS) {
+            let mut query = sqlx::QueryBuilder::<sqlx::Sqlite>::new(
+                "INSERT INTO requests
+                (id, collection_id, folder_id, name, description, method, url_raw, url_protocol, url_host, url_path,
+                 headers, query_params, body, auth, script, ui_state, examples, timeout_secs, follow_redirects,
+                 max_redirects, preserve_header_order, checksum, created_at, updated_at) ",
+            );
+            query.push_values(chunk, |mut values, row| {
+                let request = row.request;
+                values
+                    .push_bind(request.id.to_string())
+                    .push_bind(request.collection_id.map(|id| id.to_string()))
+                    .push_bind(request.folder_id.map(|id| id.to_string()))
+                    .push_bind(&request.name)
+                    .push_bind(&request.description)
+                    .push_bind(request.method.as_str())
+                    .push_bind(&request.url.raw)
+                    .push_bind(&request.url.protocol)
+                    .push_bind(&request.url.host)
+                    .push_bind(&request.url.path)
+                    .push_bind(&row.headers)
+                    .push_bind(&row.query_params)
+                    .push_bind(&row.body)
+                    .push_bind(&row.auth)
+                    .push_bind(&row.script)
+                    .push_bind(&row.ui_state)
+                    .push_bind(&row.examples)
+                    .push_bind(request.timeout_secs)
+                    .push_bind(request.follow_redirects)
+                    .push_bind(request.max_redirects)
+                    .push_bind(request.preserve_header_order)
+                    .push_bind(row.checksum)
+                    .push_bind(request.created_at)
+                    .push_bind(request.updated_at);
+            });
+            inserted += query.build().execute(&mut *tx).await?.rows_affected() as usize;
+        }
+
+        tx.commit().await?;
+        record_rows(inserted);
+        self.emit(requests.iter().map(|r| ChangeEvent::new(EntityType::Request, ChangeOp::Insert, r.id)));
+        Ok(inserted)
+    }
+}
+
+/// Columns bound per row by [`Database::insert_requests_bulk`]
+const REQUEST_INSERT_COLUMNS: usize = 24;
+
+/// Rows per bulk INSERT, keeping under the 999 bound parameters older
+/// SQLite builds allow
+const BULK_INSERT_ROWS: usize = 999 / REQUEST_INSERT_COLUMNS;
+
+/// A request with its JSON columns serialized for writing
+struct RequestRow<'a> {
+    request: &'a Request,
+    headers: String,
+    query_params: String,
+    body: String,
+    auth: Option<String>,
+    script: String,
+    ui_state: String,
+    examples: String,
+    checksum: i64,
+}
+
+impl<'a> RequestRow<'a> {
+    fn new(request: &'a Request) -> StoreResult<Self> {
+        let headers = to_json(&request.headers)?;
+        let query_params = to_json(&request.query_params)?;
+        let body = to_json(&request.body)?;
+        let auth = request.auth.as_ref().map(to_json).transpose()?;
+        let script = to_json(&request.script)?;
+        let ui_state = to_json(&request.ui_state)?;
+        let examples = to_json(&request.examples)?;
+        let checksum = checksum(&[
+            Some(&headers), Some(&query_params), Some(&body), auth.as_deref(), Some(&script), Some(&ui_state),
+            Some(&examples),
+        ]);
+        Ok(Self { request, headers, query_params, body, auth, script, ui_state, examples, checksum })
+    }
+}
+
+/// A partial update for [`Database::patch_request`]
+///
+/// Only fields that were set are written.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestUpdate {
+    name: Option<String>,
+    description: Option<Option<String>>,
+    method: Option<HttpMethod>,
+    url: Option<String>,
+    headers: Option<Vec<Header>>,
+    query_params: Option<Vec<Param>>,
+    body: Option<RequestBody>,
+    auth: Option<Option<AuthConfig>>,
+    script: Option<ScriptConfig>,
+    folder_id: Option<Option<Id>>,
+}
+
+impl RequestUpdate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn with_description(mut self, description: Option<String>) -> Self {
+        self.description = Some(description);
+        self
+    }
+
+    pub fn with_method(mut self, method: HttpMethod) -> Self {
+        self.method = Some(method);
+        self
+    }
+
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    pub fn with_headers(mut self, headers: Vec<Header>) -> Self {
+        self.headers = Some(headers);
+        self
+    }
+
+    pub fn with_query_params(mut self, query_params: Vec<Param>) -> Self {
+        self.query_params = Some(query_params);
+        self
+    }
+
+    pub fn with_body(mut self, body: RequestBody) -> Self {
+        self.body = Some(body);
+        self
+    }
+
+    pub fn with_auth(mut self, auth: Option<AuthConfig>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    pub fn with_script(mut self, script: ScriptConfig) -> Self {
+        self.script = Some(script);
+        self
+    }
+
+    /// Move the request to another folder (None for the collection root)
+    pub fn with_folder(mut self, folder_id: Option<Id>) -> Self {
+        self.folder_id = Some(folder_id);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Column assignments for the fields that were set
+    fn into_columns(self) -> StoreResult<Vec<(&'static str, Option<String>)>> {
+        let mut columns = Vec::new();
+        if let Some(name) = self.name {
+            columns.push(("name", Some(name)));
+        }
+        if let Some(description) = self.description {
+            columns.push(("description", description));
+        }
+        if let Some(method) = self.method {
+            columns.push(("method", Some(method.to_string())));
+        }
+        if let Some(raw) = self.url {
+            let url = Url::parse(raw.clone()).unwrap_or_else(|_| Url::new(raw));
+            columns.push(("url_raw", Some(url.raw)));
+            columns.push(("url_protocol", url.protocol));
+            columns.push(("url_host", url.host));
+            columns.push(("url_path", url.path));
+        }
+        if let Some(headers) = self.headers {
+            columns.push(("headers", Some(to_json(&headers)?)));
+        }
+        if let Some(query_params) = self.query_params {
+            columns.push(("query_params", Some(to_json(&query_params)?)));
+        }
+        if let Some(body) = self.body {
+            columns.push(("body", Some(to_json(&body)?)));
+        }
+        if let Some(auth) = self.auth {
+            columns.push(("auth", auth.as_ref().map(to_json).transpose()?));
+        }
+        if let Some(script) = self.script {
+            columns.push(("script", Some(to_json(&script)?)));
+        }
+        if let Some(folder_id) = self.folder_id {
+            columns.push(("folder_id", folder_id.map(|id| id.to_string())));
+        }
+        Ok(columns)
+    }
+}
+
+/// A request matched by full-text search
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct RequestSearchHit {
+    pub request_id: Id,
+
+    /// Matching text with the hit wrapped in `[` `]`
+    pub snippet: String,
+
+    /// BM25 rank (lower is a better match)
+    pub rank: f64,
+}
+
+impl Database {
+    /// Search requests by name, URL and description
+    ///
+    /// Supports prefix terms (`user*`) and quoted phrases (`"get user"`); other
+    /// punctuation is treated literally rather than as FTS5 syntax.
+    #[tracing::instrument(skip(self), fields(rows = tracing::field::Empty))]
+    pub async fn search_requests(&self, query: &str, limit: u32) -> StoreResult<Vec<RequestSearchHit>> {
+        let match_query = fts_match_query(query);
+        if match_query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query(
+            "SELECT r.id AS id,
+                snippet(requests_fts, -1, '[', ']', '…', 10) AS snippet,
+                bm25(requests_fts) AS rank
+            FROM requests_fts
+            JOIN requests r ON r.rowid = requests_fts.rowid
+            WHERE requests_fts MATCH ? AND r.deleted_at IS NULL
+            ORDER BY rank
+            LIMIT ?"
+        )
+        .bind(&match_query)
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await?;
+
+        record_rows(rows.len());
+        rows.iter()
+            .map(|row| {
+                let id: String = row.try_get("id")?;
+                Ok(RequestSearchHit {
+                    request_id: parse_id(&id)?,
+                    snippet: row.try_get("snippet")?,
+                    rank: row.try_get("rank")?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Turn free-form user input into a safe FTS5 MATCH expression
+///
+/// Each term is quoted so punctuation (`.`, `/`, `-`) can't produce syntax
+/// errors. A trailing `*` keeps prefix matching and `"..."` keeps phrases.
+pub(crate) fn fts_match_query(input: &str) -> String {
+    let mut terms = Vec::new();
+    let mut rest = input.trim();
+
+    while !rest.is_empty() {
+        if let Some(stripped) = rest.strip_prefix('"') {
+            let end = stripped.find('"').unwrap_or(stripped.len());
+            let phrase = stripped[..end].trim();
+            if !phrase.is_empty() {
+                terms.push(format!("\"{}\"", phrase));
+            }
+            rest = stripped.get(end + 1..).unwrap_or("").trim_start();
+            continue;
+        }
+
+        let end = rest.find(|c: char| c.is_whitespace() || c == '"').unwrap_or(rest.len());
+        let word = &rest[..end];
+        rest = rest[end..].trim_start();
+
+        let (word, prefix) = match word.strip_suffix('*') {
+            Some(w) => (w, true),
+            None => (word, false),
+        };
+        if word.is_empty() {
+            continue;
+        }
+        terms.push(format!("\"{}\"{}", word, if prefix { "*" } else { "" }));
+    }
+
+    terms.join(" ")
+}
+
+/// Upsert a `requests` row, leaving `deleted_at` untouched
+pub(crate) async fn write_request(conn: &mut SqliteConnection, request: &Request) -> StoreResult<()> {
+    let row = RequestRow::new(request)?;
+
+    sqlx::query(
+        "INSERT INTO requests
+        (id, collection_id, folder_id, name, description, method, url_raw, url_protocol, url_host, url_path,
+         headers, query_params, body, auth, script, ui_state, examples, timeout_secs, follow_redirects,
+         max_redirects, preserve_header_order, checksum, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(id) DO UPDATE SET
+            collection_id = excluded.collection_id,
+            folder_id = excluded.folder_id,
+            name = excluded.name,
+            description = excluded.description,
+            method = excluded.method,
+            url_raw = excluded.url_raw,
+            url_protocol = excluded.url_protocol,
+            url_host = excluded.url_host,
+            url_path = excluded.url_path,
+            headers = excluded.headers,
+            query_params = excluded.query_params,
+            body = excluded.body,
+            auth = excluded.auth,
+            script = excluded.script,
+            ui_state = excluded.ui_state,
+            examples = excluded.examples,
+            timeout_secs = excluded.timeout_secs,
+            follow_redirects = excluded.follow_redirects,
+            max_redirects = excluded.max_redirects,
+            preserve_header_order = excluded.preserve_header_order,
+            checksum = excluded.checksum,
+            updated_at = excluded.updated_at"
+    )
+    .bind(request.id.to_string())
+    .bind(request.collection_id.map(|id| id.to_string()))
+    .bind(request.folder_id.map(|id| id.to_string()))
+    .bind(&request.name)
+    .bind(&request.description)
+    .bind(request.method.as_str())
+    .bind(&request.url.raw)
+    .bind(&request.url.protocol)
+    .bind(&request.url.host)
+    .bind(&request.url.path)
+    .bind(&row.headers)
+    .bind(&row.query_params)
+    .bind(&row.body)
+    .bind(&row.auth)
+    .bind(&row.script)
+    .bind(&row.ui_state)
+    .bind(&row.examples)
+    .bind(request.timeout_secs)
+    .bind(request.follow_redirects)
+    .bind(request.max_redirects)
+    .bind(request.preserve_header_order)
+    .bind(row.checksum)
+    .bind(request.created_at)
+    .bind(request.updated_at)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Build a `Request` from a `requests` row
+pub(crate) fn request_from_row(row: &SqliteRow) -> StoreResult<Request> {
+    let id: String = row.try_get("id")?;
+    let method: String = row.try_get("method")?;
+    let collection_id: Option<String> = row.try_get("collection_id")?;
+    let folder_id: Option<String> = row.try_get("folder_id")?;
+    let body: String = row.try_get("body")?;
+    let auth: Option<String> = row.try_get("auth")?;
+
+    let method: HttpMethod = method.parse().map_err(StoreError::InvalidData)?;
+
+    let mut request = Request::new(row.try_get("name")?, method, String::new());
+    request.id = parse_id(&id)?;
+    request.description = row.try_get("description")?;
+    request.url = Url::new(row.try_get("url_raw")?);
+    request.headers = json_column(&row.try_get::<String, _>("headers")?);
+    request.query_params = json_column(&row.try_get::<String, _>("query_params")?);
+    request.body = serde_json::from_str(&body).unwrap_or(RequestBody::None);
+    request.auth = auth.and_then(|a| serde_json::from_str(&a).ok());
+    request.script = json_column(&row.try_get::<String, _>("script")?);
+    request.collection_id = collection_id.as_deref().map(parse_id).transpose()?;
+    request.folder_id = folder_id.as_deref().map(parse_id).transpose()?;
+    request.ui_state = json_column(&row.try_get::<String, _>("ui_state")?);
+    request.examples = json_column(&row.try_get::<String, _>("examples")?);
+    request.timeout_secs = row.try_get("timeout_secs")?;
+    request.follow_redirects = row.try_get("follow_redirects")?;
+    request.max_redirects = row.try_get("max_redirects")?;
+    request.preserve_header_order = row.try_get("preserve_header_order")?;
+    request.created_at = row.try_get("created_at")?;
+    request.updated_at = row.try_get("updated_at")?;
+
+    Ok(request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_db;
+    use crate::test_support::{insert_request, request};
+    use models::new_id;
+
+    #[tokio::test]
+    async fn test_insert_requests_bulk() {
+        let db = test_db().await;
+        // Spans several chunks with a partial one at the end
+        let requests: Vec<Request> = (0..BULK_INSERT_ROWS * 2 + 7)
+            .map(|i| Request::new(format!("Request {}", i), HttpMethod::POST, format!("https://example.com/{}", i))
+                .with_header("Accept".to_string(), "application/json".to_string()))
+            .collect();
+
+        assert_eq!(db.insert_requests_bulk(&requests).await.unwrap(), requests.len());
+        assert_eq!(db.count_requests().await.unwrap(), requests.len() as u64);
+        let last = requests.last().unwrap();
+        let loaded = db.get_request(last.id).await.unwrap().unwrap();
+        assert_eq!(loaded.name, last.name);
+        assert_eq!(loaded.headers, last.headers);
+
+        // A duplicate id rolls back the whole batch
+        let fresh = Request::new("Fresh".to_string(), HttpMethod::GET, "https://example.com".to_string());
+        assert!(db.insert_requests_bulk(&[fresh.clone(), last.clone()]).await.is_err());
+        assert!(db.get_request(fresh.id).await.unwrap().is_none());
+        assert_eq!(db.insert_requests_bulk(&[]).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_request_examples_round_trip() {
+        let db = test_db().await;
+        let mut request = Request::new("Get user".to_string(), HttpMethod::GET, "https://example.com/users/1".to_string());
+        let mut response = models::Response::new(200, "OK".to_string());
+        response.body = models::ResponseBody::Text("{\"id\": 1}".to_string());
+        request.add_example(models::RequestExample::new("Found".to_string(), &request.clone(), response));
+
+        db.save_request(&request).await.unwrap();
+        let loaded = db.get_request(request.id).await.unwrap().unwrap();
+        assert_eq!(loaded.examples, request.examples);
+
+        db.insert_requests_bulk(&[Request { id: new_id(), ..request.clone() }]).await.unwrap();
+        let bulk = db.list_requests_paged(10, 0).await.unwrap();
+        assert!(bulk.items.iter().all(|r| r.examples.len() == 1));
+
+        let export = db.export_json().await.unwrap();
+        let target = test_db().await;
+        target.import_json(&export).await.unwrap();
+        assert_eq!(target.get_request(request.id).await.unwrap().unwrap().examples, request.examples);
+    }
+
+    #[tokio::test]
+    async fn test_request_settings_round_trip() {
+        let db = test_db().await;
+        let mut request = Request::new("Slow".to_string(), HttpMethod::GET, "https://example.com/slow".to_string());
+        db.save_request(&request).await.unwrap();
+        let loaded = db.get_request(request.id).await.unwrap().unwrap();
+        assert_eq!((loaded.timeout_secs, loaded.follow_redirects, loaded.max_redirects), (None, None, None));
+
+        request.timeout_secs = Some(300);
+        request.follow_redirects = Some(false);
+        request.max_redirects = Some(2);
+        db.save_request(&request).await.unwrap();
+        let loaded = db.get_request(request.id).await.unwrap().unwrap();
+        assert_eq!((loaded.timeout_secs, loaded.follow_redirects, loaded.max_redirects), (Some(300), Some(false), Some(2)));
+
+        let bulk = Request { id: new_id(), ..request.clone() };
+        db.insert_requests_bulk(std::slice::from_ref(&bulk)).await.unwrap();
+        assert_eq!(db.get_request(bulk.id).await.unwrap().unwrap().max_redirects, Some(2));
+
+        let export = db.export_json().await.unwrap();
+        let target = test_db().await;
+        target.import_json(&export).await.unwrap();
+        let imported = target.get_request(request.id).await.unwrap().unwrap();
+        assert_eq!((imported.timeout_secs, imported.follow_redirects, imported.max_redirects), (Some(300), Some(false), Some(2)));
+    }
+
+    #[tokio::test]
+    async fn test_preserve_header_order_round_trip() {
+        let db = test_db().await;
+        let mut request = Request::new("Signed".to_string(), HttpMethod::GET, "https://example.com".to_string());
+        db.save_request(&request).await.unwrap();
+        assert!(!db.get_request(request.id).await.unwrap().unwrap().preserve_header_order);
+
+        request.preserve_header_order = true;
+        db.save_request(&request).await.unwrap();
+        assert!(db.get_request(request.id).await.unwrap().unwrap().preserve_header_order);
+
+        let bulk = Request { id: new_id(), ..request.clone() };
+        db.insert_requests_bulk(std::slice::from_ref(&bulk)).await.unwrap();
+        assert!(db.get_request(bulk.id).await.unwrap().unwrap().preserve_header_order);
+
+        let export = db.export_json().await.unwrap();
+        let target = test_db().await;
+        target.import_json(&export).await.unwrap();
+        assert!(target.get_request(request.id).await.unwrap().unwrap().preserve_header_order);
+    }
+
+    #[test]
+    fn test_fts_match_query() {
+        assert_eq!(fts_match_query("user*"), "\"user\"*");
+        assert_eq!(fts_match_query("\"get user\" api.example.com"), "\"get user\" \"api.example.com\"");
+        assert_eq!(fts_match_query("   "), "");
+        assert_eq!(fts_match_query("a\"b \"x* y\" z*"), "\"a\" \"b\" \"x\"* \"y\" \"z*\"");
+    }
+
+    #[tokio::test]
+    async fn test_search_requests() {
+        let db = test_db().await;
+        let users = insert_request(&db, &request("List users")).await;
+        let orders = insert_request(&db, &request("Get orders")).await;
+
+        sqlx::query("UPDATE requests SET url_raw = 'https://api.example.com/orders', description = 'Fetch order history' WHERE id = ?")
+            .bind(orders.to_string())
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        let hits = db.search_requests("use*", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].request_id, users);
+        assert!(hits[0].snippet.contains("[users]"));
+
+        let hits = db.search_requests("\"order history\"", 10).await.unwrap();
+        assert_eq!(hits.iter().map(|h| h.request_id).collect::<Vec<_>>(), vec![orders]);
+
+        // The update trigger re-indexes the new URL
+        let hits = db.search_requests("example.com/orders", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(db.search_requests("nothing", 10).await.unwrap().is_empty());
+        assert!(db.search_requests("a\"b \"x* y\" z*", 10).await.unwrap().is_empty());
+
+        // Trashed requests drop out of search
+        db.delete_request(orders).await.unwrap();
+        assert!(db.search_requests("example.com/orders", 10).await.unwrap().is_empty());
+        assert!(db.get_request(orders).await.unwrap().is_none());
+        assert!(db.get_request(users).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_save_request_round_trip() {
+        let db = test_db().await;
+        let mut request = Request::new("Create user".to_string(), HttpMethod::POST, "https://example.com/users".to_string())
+            .with_header("Content-Type".to_string(), "application/json".to_string());
+        request.body = RequestBody::json(r#"{"name":"a"}"#.to_string());
+        db.save_request(&request).await.unwrap();
+
+        request.url = Url::new("https://example.com/v2/users".to_string());
+        db.save_request(&request).await.unwrap();
+
+        let loaded = db.get_request(request.id).await.unwrap().unwrap();
+        assert_eq!(loaded.url.raw, "https://example.com/v2/users");
+        assert_eq!(loaded.headers, request.headers);
+        assert_eq!(loaded.body, request.body);
+        assert_eq!(db.count_requests().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_save_request_with_custom_method() {
+        let db = test_db().await;
+        let method: HttpMethod = "purge".parse().unwrap();
+        let request = Request::new("Purge".to_string(), method, "https://cdn.example.com/a".to_string());
+        db.save_request(&request).await.unwrap();
+
+        let loaded = db.get_request(request.id).await.unwrap().unwrap();
+        assert_eq!(loaded.method, HttpMethod::Custom("PURGE".to_string()));
+        assert_eq!(db.search_requests("purge", 10).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_patch_request_only_touches_given_columns() {
+        let db = test_db().await;
+        let mut request = Request::new("Create user".to_string(), HttpMethod::POST, "https://example.com/users".to_string())
+            .with_header("Content-Type".to_string(), "application/json".to_string());
+        request.body = RequestBody::json(r#"{"name":"a"}"#.to_string());
+        db.save_request(&request).await.unwrap();
+
+        let columns = "SELECT method, url_raw, headers, body, checksum FROM requests WHERE id = ?";
+        let before = sqlx::query(columns).bind(request.id.to_string()).fetch_one(db.pool()).await.unwrap();
+
+        db.patch_request(request.id, RequestUpdate::new().with_name("Create account")).await.unwrap();
+
+        let after = sqlx::query(columns).bind(request.id.to_string()).fetch_one(db.pool()).await.unwrap();
+        for column in ["method", "url_raw", "headers", "body"] {
+            assert_eq!(after.get::<String, _>(column), before.get::<String, _>(column));
+        }
+        assert_eq!(after.get::<i64, _>("checksum"), before.get::<i64, _>("checksum"));
+        assert_eq!(db.get_request(request.id).await.unwrap().unwrap().name, "Create account");
+
+        // JSON columns keep the checksum valid
+        let mut headers = request.headers.clone();
+        headers[0].enabled = false;
+        db.patch_request(request.id, RequestUpdate::new().with_headers(headers.clone())).await.unwrap();
+        let loaded = db.get_request(request.id).await.unwrap().unwrap();
+        assert_eq!(loaded.headers, headers);
+        assert_eq!(loaded.body, request.body);
+
+        assert!(db.patch_request(new_id(), RequestUpdate::new().with_name("x")).await.is_err());
+        db.patch_request(request.id, RequestUpdate::new()).await.unwrap();
+        assert!(matches!(
+            db.patch_request(new_id(), RequestUpdate::new()).await,
+            Err(StoreError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_list_requests_paged() {
+        let db = test_db().await;
+        let base = now();
+
+        // Same created_at: ordering falls back to id
+        let mut same_time = [
+            insert_request(&db, &Request { created_at: base, ..request("A") }).await,
+            insert_request(&db, &Request { created_at: base, ..request("B") }).await,
+        ];
+        same_time.sort_by_key(|id| id.to_string());
+        let later = insert_request(&db, &Request { created_at: base + 1, ..request("C") }).await;
+
+        assert_eq!(db.count_requests().await.unwrap(), 3);
+
+        let page = db.list_requests_paged(10, 0).await.unwrap();
+        assert_eq!(page.total_count, 3);
+        assert_eq!(
+            page.items.iter().map(|r| r.id).collect::<Vec<_>>(),
+            vec![same_time[0], same_time[1], later]
+        );
+        assert_eq!(page.items[2].url.raw, "https://example.com");
+
+        let page = db.list_requests_paged(1, 1).await.unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].id, same_time[1]);
+        assert!(page.has_more());
+    }
+}
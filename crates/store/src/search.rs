@@ -0,0 +1,266 @@
+//! Cross-entity search
+//!
+//! Backs the command palette: one query over collections, folders, requests,
+//! environments and history, returning hits of a single shape.
+
+use std::cmp::Reverse;
+
+use sqlx::Row;
+
+use crate::collections::parse_id;
+use crate::{Database, StoreResult, record_rows};
+use models::{Id, SyncItemType, Timestamp};
+
+/// Characters of context kept on each side of the match in a snippet
+const SNIPPET_CONTEXT_CHARS: usize = 30;
+
+/// Kind of entity a search hit points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchHitKind {
+    Collection,
+    Folder,
+    Request,
+    Environment,
+    History,
+}
+
+impl From<SyncItemType> for SearchHitKind {
+    fn from(item_type: SyncItemType) -> Self {
+        match item_type {
+            SyncItemType::Collection => SearchHitKind::Collection,
+            SyncItemType::Folder => SearchHitKind::Folder,
+            SyncItemType::Request => SearchHitKind::Request,
+            SyncItemType::Environment => SearchHitKind::Environment,
+        }
+    }
+}
+
+/// A single result of [`Database::global_search`]
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SearchHit {
+    pub kind: SearchHitKind,
+    pub id: Id,
+
+    /// Display name (`METHOD url` for history entries)
+    pub title: String,
+
+    /// Text around the match with the hit wrapped in `[` `]`
+    pub snippet: String,
+
+    /// Last change (or send time for history)
+    pub updated_at: Timestamp,
+}
+
+/// Per-kind query returning `id`, `title`, `detail` and `updated_at`, with
+/// `?1` the LIKE pattern and `?2` the limit
+fn kind_query(kind: SearchHitKind) -> &'static str {
+    match kind {
+        SearchHitKind::Collection => {
+            "SELECT id, name AS title, COALESCE(description, '') AS detail, updated_at
+            FROM collections
+            WHERE deleted_at IS NULL AND (name LIKE ?1 ESCAPE '\\' OR description LIKE ?1 ESCAPE '\\')
+            ORDER BY updated_at DESC LIMIT ?2"
+        }
+        SearchHitKind::Folder => {
+            "SELECT f.id AS id, f.name AS title, COALESCE(f.description, '') AS detail, f.updated_at AS updated_at
+            FROM folders f
+            JOIN collections c ON c.id = f.collection_id
+            WHERE c.deleted_at IS NULL AND (f.name LIKE ?1 ESCAPE '\\' OR f.description LIKE ?1 ESCAPE '\\')
+            ORDER BY f.updated_at DESC LIMIT ?2"
+        }
+        SearchHitKind::Request => {
+            "SELECT id, name AS title, url_raw || ' ' || COALESCE(description, '') AS detail, updated_at
+            FROM requests
+            WHERE deleted_at IS NULL
+                AND (name LIKE ?1 ESCAPE '\\' OR url_raw LIKE ?1 ESCAPE '\\' OR description LIKE ?1 ESCAPE '\\')
+            ORDER BY updated_at DESC LIMIT ?2"
+        }
+        // Only variable names are searched; values may hold secrets
+        SearchHitKind::Environment => {
+            "SELECT id, name AS title,
+                COALESCE((SELECT group_concat(json_extract(v.value, '$.key'), ' ') FROM json_each(variables) v), '') AS detail,
+                updated_at
+            FROM environments
+            WHERE deleted_at IS NULL AND (name LIKE ?1 ESCAPE '\\' OR EXISTS (
+                SELECT 1 FROM json_each(variables) v WHERE json_extract(v.value, '$.key') LIKE ?1 ESCAPE '\\'
+            ))
+            ORDER BY updated_at DESC LIMIT ?2"
+        }
+        SearchHitKind::History => {
+            "SELECT id, method || ' ' || url AS title, url AS detail, timestamp AS updated_at
+            FROM request_history
+            WHERE url LIKE ?1 ESCAPE '\\'
+            ORDER BY timestamp DESC LIMIT ?2"
+        }
+    }
+}
+
+impl Database {
+    /// Search the selected entity types for `query`
+    ///
+    /// Matching is a case-insensitive substring match on names, plus URLs and
+    /// descriptions where the entity has them. History has no sync type and
+    /// is searched whenever `SyncItemType::Request` is selected; an empty
+    /// `kinds` searches everything. Hits whose title matches rank above hits
+    /// matching elsewhere (exact, then prefix, then substring), newest first
+    /// within each tier.
+    #[tracing::instrument(skip(self), fields(rows = tracing::field::Empty))]
+    pub async fn global_search(&self, query: &str, kinds: &[SyncItemType], limit: u32) -> StoreResult<Vec<SearchHit>> {
+        let query = query.trim();
+        if query.is_empty() || limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let kinds = if kinds.is_empty() {
+            &[SyncItemType::Collection, SyncItemType::Folder, SyncItemType::Request, SyncItemType::Environment][..]
+        } else {
+            kinds
+        };
+        let mut selected: Vec<SearchHitKind> = Vec::new();
+        for &kind in kinds {
+            if !selected.contains(&kind.into()) {
+                selected.push(kind.into());
+            }
+        }
+        if selected.contains(&SearchHitKind::Request) {
+            selected.push(SearchHitKind::History);
+        }
+
+        let pattern = format!("%{}%", escape_like(query));
+        let mut hits = Vec::new();
+        for kind in selected {
+            let rows = sqlx::query(kind_query(kind))
+                .bind(&pattern)
+                .bind(limit)
+                .fetch_all(self.pool())
+                .await?;
+
+            for row in rows {
+                let id: String = row.try_get("id")?;
+                let title: String = row.try_get("title")?;
+                let detail: String = row.try_get("detail")?;
+                let snippet = snippet(&title, query).or_else(|| snippet(&detail, query)).unwrap_or_default();
+                hits.push((
+                    title_relevance(&title, query),
+                    SearchHit {
+                        kind,
+                        id: parse_id(&id)?,
+                        title,
+                        snippet,
+                        updated_at: row.try_get("updated_at")?,
+                    },
+                ));
+            }
+        }
+
+        hits.sort_by_key(|(relevance, hit)| (*relevance, Reverse(hit.updated_at)));
+        hits.truncate(limit as usize);
+        record_rows(hits.len());
+        Ok(hits.into_iter().map(|(_, hit)| hit).collect())
+    }
+}
+
+/// 0 for an exact title match, 1 for a prefix, 2 for a substring, 3 if the
+/// match was somewhere other than the title
+fn title_relevance(title: &str, query: &str) -> u8 {
+    let (title, query) = (title.to_lowercase(), query.to_lowercase());
+    if title == query {
+        0
+    } else if title.starts_with(&query) {
+        1
+    } else if title.contains(&query) {
+        2
+    } else {
+        3
+    }
+}
+
+/// The match in `text` wrapped in `[` `]` with some context either side
+fn snippet(text: &str, query: &str) -> Option<String> {
+    let (lower, query) = (text.to_lowercase(), query.to_lowercase());
+    let start = lower.find(&query)?;
+    let end = start + query.len();
+    // Lowercasing can change byte lengths outside ASCII; give up on context
+    // rather than slice mid-character
+    if lower.len() != text.len() || !text.is_char_boundary(start) || !text.is_char_boundary(end) {
+        return Some(text.to_string());
+    }
+
+    let from = text[..start]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CONTEXT_CHARS - 1)
+        .map_or(0, |(i, _)| i);
+    let to = text[end..]
+        .char_indices()
+        .nth(SNIPPET_CONTEXT_CHARS)
+        .map_or(text.len(), |(i, _)| end + i);
+
+    Some(format!(
+        "{}{}[{}]{}{}",
+        if from > 0 { "…" } else { "" },
+        &text[from..start],
+        &text[start..end],
+        &text[end..to],
+        if to < text.len() { "…" } else { "" },
+    ))
+}
+
+/// Escape `%`, `_` and `\` for a LIKE pattern using `ESCAPE '\'`
+fn escape_like(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_db;
+    use models::{Environment, HttpMethod, Request};
+
+    #[tokio::test]
+    async fn test_global_search_across_kinds() {
+        let db = test_db().await;
+        let request = Request::new("Billing invoices".to_string(), HttpMethod::GET, "https://example.com/invoices".to_string());
+        db.save_request(&request).await.unwrap();
+        let mut environment = Environment::new("Staging".to_string());
+        environment.add_variable("billing_token".to_string(), "secret".to_string());
+        db.save_environment(&environment).await.unwrap();
+        let unrelated = Request::new("Users".to_string(), HttpMethod::GET, "https://example.com/users".to_string());
+        db.save_request(&unrelated).await.unwrap();
+
+        let hits = db.global_search("billing", &[], 10).await.unwrap();
+        assert_eq!(hits.len(), 2);
+        // The request matches by title, the environment only by a variable name
+        assert_eq!((hits[0].kind, hits[0].id), (SearchHitKind::Request, request.id));
+        assert_eq!(hits[0].snippet, "[Billing] invoices");
+        assert_eq!((hits[1].kind, hits[1].id), (SearchHitKind::Environment, environment.id));
+        assert_eq!(hits[1].snippet, "[billing]_token");
+
+        let hits = db.global_search("billing", &[SyncItemType::Environment], 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].kind, SearchHitKind::Environment);
+
+        // The secret value is never searched
+        assert!(db.global_search("secret", &[], 10).await.unwrap().is_empty());
+        assert!(db.global_search("100%", &[], 10).await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_snippet() {
+        assert_eq!(snippet("Get users", "USER").unwrap(), "Get [user]s");
+        let long = format!("{}needle{}", "a".repeat(40), "b".repeat(40));
+        assert_eq!(
+            snippet(&long, "needle").unwrap(),
+            format!("…{}[needle]{}…", "a".repeat(30), "b".repeat(30))
+        );
+        assert_eq!(snippet("Get users", "orders"), None);
+    }
+}
@@ -0,0 +1,234 @@
+//! Persistent queue of local changes waiting to be synced
+//!
+//! Backs `PendingChanges` with the `sync_changes` table so offline edits
+//! survive a restart. Deletes also leave a tombstone in `sync_tombstones`.
+
+use sqlx::{Row, SqliteConnection, sqlite::SqliteRow};
+
+use crate::collections::{parse_id, to_json};
+use crate::{Database, StoreError, StoreResult, json_column, record_rows};
+use models::{Id, PendingChanges, SyncChange, Tombstone, now, reconcile_with_tombstones};
+
+impl Database {
+    /// Queue a change, replacing any unsynced change for the same item
+    #[tracing::instrument(skip_all, fields(id = %change.item_id))]
+    pub async fn enqueue_change(&self, change: &SyncChange) -> StoreResult<()> {
+        self.ensure_writable()?;
+        let mut conn = self.pool().acquire().await?;
+        write_change(&mut conn, change).await
+    }
+
+    /// Unsynced changes, oldest first
+    #[tracing::instrument(skip(self), fields(rows = tracing::field::Empty))]
+    pub async fn pending_changes(&self, limit: u32) -> StoreResult<Vec<SyncChange>> {
+        let rows = sqlx::query(
+            "SELECT * FROM sync_changes WHERE synced = 0 ORDER BY created_at, rowid LIMIT ?"
+        )
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await?;
+
+        record_rows(rows.len());
+        rows.iter().map(change_from_row).collect()
+    }
+
+    /// Mark changes as pushed to the server
+    ///
+    /// Returns the number of changes updated.
+    #[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
+    pub async fn mark_changes_synced(&self, change_ids: &[Id]) -> StoreResult<usize> {
+        self.ensure_writable()?;
+        if change_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let placeholders = vec!["?"; change_ids.len()].join(", ");
+        let sql = format!("UPDATE sync_changes SET synced = 1 WHERE id IN ({})", placeholders);
+
+        let mut query = sqlx::query(&sql);
+        for id in change_ids {
+            query = query.bind(id.to_string());
+        }
+        let updated = query.execute(self.pool()).await?.rows_affected() as usize;
+
+        record_rows(updated);
+        Ok(updated)
+    }
+
+    /// Rebuild the in-memory queue from the table, e.g. on startup
+    ///
+    /// Changes beyond `max_size` stay in the table and are picked up once the
+    /// queue has drained.
+    pub async fn load_pending_changes(&self, max_size: usize) -> StoreResult<PendingChanges> {
+        let mut pending = PendingChanges::new(max_size);
+        for change in self.pending_changes(max_size as u32).await? {
+            pending.push(change).map_err(|e| StoreError::InvalidData(e.to_string()))?;
+        }
+        pending.tombstones = self.tombstones().await?;
+        Ok(pending)
+    }
+
+    /// All retained tombstones
+    pub async fn tombstones(&self) -> StoreResult<Vec<Tombstone>> {
+        let rows = sqlx::query("SELECT * FROM sync_tombstones ORDER BY deleted_at")
+            .fetch_all(self.pool())
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                let item_type: String = row.try_get("item_type")?;
+                let item_id: String = row.try_get("item_id")?;
+                Ok(Tombstone {
+                    item_type: item_type.parse().map_err(StoreError::InvalidData)?,
+                    item_id: parse_id(&item_id)?,
+                    version: row.try_get("version")?,
+                    deleted_at: row.try_get("deleted_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Delete tombstones older than `ttl` milliseconds
+    ///
+    /// Returns the number of tombstones deleted.
+    #[tracing::instrument(skip(self), fields(rows = tracing::field::Empty))]
+    pub async fn purge_tombstones(&self, ttl: i64) -> StoreResult<usize> {
+        self.ensure_writable()?;
+        let result = sqlx::query("DELETE FROM sync_tombstones WHERE deleted_at < ?")
+            .bind(now() - ttl)
+            .execute(self.pool())
+            .await?;
+
+        record_rows(result.rows_affected() as usize);
+        Ok(result.rows_affected() as usize)
+    }
+
+    /// Drop pulled changes that would resurrect a deleted item
+    pub async fn reconcile_pulled(&self, changes: Vec<SyncChange>) -> StoreResult<Vec<SyncChange>> {
+        let tombstones = self.tombstones().await?;
+        Ok(reconcile_with_tombstones(changes, &tombstones))
+    }
+}
+
+/// Upsert an unsynced change keyed on its item
+pub(crate) async fn write_change(conn: &mut SqliteConnection, change: &SyncChange) -> StoreResult<()> {
+    sqlx::query(
+        "INSERT INTO sync_changes (id, item_type, item_id, operation, version, data, synced, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(item_type, item_id) WHERE synced = 0 DO UPDATE SET
+            id = excluded.id,
+            operation = excluded.operation,
+            version = excluded.version,
+            data = excluded.data,
+            error = NULL,
+            created_at = excluded.created_at"
+    )
+    .bind(change.change_id.to_string())
+    .bind(change.item_type.as_str())
+    .bind(change.item_id.to_string())
+    .bind(change.operation.as_str())
+    .bind(change.version)
+    .bind(to_json(&change.data)?)
+    .bind(change.synced)
+    .bind(change.timestamp)
+    .execute(&mut *conn)
+    .await?;
+
+    if let Some(tombstone) = Tombstone::from_change(change) {
+        sqlx::query(
+            "INSERT OR REPLACE INTO sync_tombstones (item_type, item_id, version, deleted_at)
+            VALUES (?, ?, ?, ?)"
+        )
+        .bind(tombstone.item_type.as_str())
+        .bind(tombstone.item_id.to_string())
+        .bind(tombstone.version)
+        .bind(tombstone.deleted_at)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+fn change_from_row(row: &SqliteRow) -> StoreResult<SyncChange> {
+    let id: String = row.try_get("id")?;
+    let item_type: String = row.try_get("item_type")?;
+    let item_id: String = row.try_get("item_id")?;
+    let operation: String = row.try_get("operation")?;
+
+    Ok(SyncChange {
+        change_id: parse_id(&id)?,
+        item_type: item_type.parse().map_err(StoreError::InvalidData)?,
+        item_id: parse_id(&item_id)?,
+        operation: operation.parse().map_err(StoreError::InvalidData)?,
+        version: row.try_get("version")?,
+        data: json_column(&row.try_get::<String, _>("data")?),
+        timestamp: row.try_get("created_at")?,
+        synced: row.try_get("synced")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_db;
+    use models::{SyncItemType, SyncOperation, new_id};
+
+    #[tokio::test]
+    async fn test_enqueue_dedups_per_item() {
+        let db = test_db().await;
+        let item = new_id();
+        let other = new_id();
+
+        db.enqueue_change(&SyncChange::create(SyncItemType::Request, item, serde_json::json!({"v": 1}))).await.unwrap();
+        db.enqueue_change(&SyncChange::create(SyncItemType::Collection, other, serde_json::json!({}))).await.unwrap();
+        db.enqueue_change(&SyncChange::update(SyncItemType::Request, item, 2, serde_json::json!({"v": 2}))).await.unwrap();
+
+        let pending = db.pending_changes(10).await.unwrap();
+        assert_eq!(pending.len(), 2);
+        let latest = pending.iter().find(|c| c.item_id == item).unwrap();
+        assert_eq!(latest.operation, SyncOperation::Update);
+        assert_eq!(latest.version, 2);
+        assert_eq!(latest.data, serde_json::json!({"v": 2}));
+
+        // The queue survives a "restart"
+        let queue = db.load_pending_changes(100).await.unwrap();
+        assert_eq!(queue.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_tombstone_survives_sync() {
+        let db = test_db().await;
+        let item = new_id();
+        let delete = SyncChange::delete(SyncItemType::Request, item, 3);
+        db.enqueue_change(&delete).await.unwrap();
+        db.mark_changes_synced(&[delete.change_id]).await.unwrap();
+
+        // A stale update from another device arrives after the delete was pushed
+        let stale = SyncChange::update(SyncItemType::Request, item, 2, serde_json::json!({"v": 2}));
+        assert!(db.reconcile_pulled(vec![stale]).await.unwrap().is_empty());
+
+        let queue = db.load_pending_changes(10).await.unwrap();
+        assert!(queue.is_empty());
+        assert_eq!(queue.tombstones.len(), 1);
+
+        assert_eq!(db.purge_tombstones(models::DEFAULT_TOMBSTONE_TTL_MS).await.unwrap(), 0);
+        assert_eq!(db.purge_tombstones(-1).await.unwrap(), 1);
+        assert!(db.tombstones().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mark_changes_synced() {
+        let db = test_db().await;
+        let item = new_id();
+        let change = SyncChange::create(SyncItemType::Request, item, serde_json::json!({}));
+        db.enqueue_change(&change).await.unwrap();
+
+        assert_eq!(db.mark_changes_synced(&[change.change_id]).await.unwrap(), 1);
+        assert!(db.pending_changes(10).await.unwrap().is_empty());
+
+        // A synced change no longer blocks a new one for the same item
+        db.enqueue_change(&SyncChange::update(SyncItemType::Request, item, 2, serde_json::json!({}))).await.unwrap();
+        assert_eq!(db.pending_changes(10).await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,69 @@
+//! Row fixtures shared by the store's unit tests
+//!
+//! Rows are written with plain SQL rather than through `Database`, so a test
+//! of one operation doesn't depend on the save path of another.
+
+use models::{HttpMethod, Id, Request, Timestamp, new_id};
+
+use crate::Database;
+
+/// Insert a collection with empty JSON columns
+pub(crate) async fn insert_collection(db: &Database, name: &str, created_at: Timestamp) -> Id {
+    let id = new_id();
+    sqlx::query(
+        "INSERT INTO collections (id, name, info, sync_state, ui_state, created_at, updated_at)
+        VALUES (?, ?, '{}', '{}', '{}', ?, ?)"
+    )
+    .bind(id.to_string())
+    .bind(name)
+    .bind(created_at)
+    .bind(created_at)
+    .execute(db.pool())
+    .await
+    .unwrap();
+    id
+}
+
+/// Insert a folder under `parent_id`, or at the top of the collection
+pub(crate) async fn insert_folder(db: &Database, collection_id: Id, parent_id: Option<Id>, name: &str) -> Id {
+    let id = new_id();
+    sqlx::query(
+        "INSERT INTO folders (id, collection_id, parent_id, name, ui_state, created_at, updated_at)
+        VALUES (?, ?, ?, ?, '{}', ?, ?)"
+    )
+    .bind(id.to_string())
+    .bind(collection_id.to_string())
+    .bind(parent_id.map(|id| id.to_string()))
+    .bind(name)
+    .bind(models::now())
+    .bind(models::now())
+    .execute(db.pool())
+    .await
+    .unwrap();
+    id
+}
+
+/// A `GET https://example.com` request to pass to [`insert_request`]
+pub(crate) fn request(name: &str) -> Request {
+    Request::new(name.to_string(), HttpMethod::GET, "https://example.com".to_string())
+}
+
+/// Insert `request`'s id, location, name, method, URL and timestamps
+pub(crate) async fn insert_request(db: &Database, request: &Request) -> Id {
+    sqlx::query(
+        "INSERT INTO requests (id, collection_id, folder_id, name, method, url_raw, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+    )
+    .bind(request.id.to_string())
+    .bind(request.collection_id.map(|id| id.to_string()))
+    .bind(request.folder_id.map(|id| id.to_string()))
+    .bind(&request.name)
+    .bind(request.method.as_str())
+    .bind(&request.url.raw)
+    .bind(request.created_at)
+    .bind(request.updated_at)
+    .execute(db.pool())
+    .await
+    .unwrap();
+    request.id
+}
@@ -0,0 +1,273 @@
+//! Trash for soft-deleted collections, requests and environments
+//!
+//! The regular delete methods only set `deleted_at`; rows stay in the trash
+//! until they are restored, purged or the trash is emptied.
+
+use sqlx::Row;
+
+use crate::collections::parse_id;
+use crate::events::{ChangeEvent, ChangeOp, EntityType, returned_events};
+use crate::{Database, StoreError, StoreResult, record_rows};
+use models::{Id, Timestamp};
+
+/// Kind of item in the trash
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashItemType {
+    Collection,
+    Request,
+    Environment,
+}
+
+impl TrashItemType {
+    /// Entity type used in change events
+    fn entity(&self) -> EntityType {
+        match self {
+            TrashItemType::Collection => EntityType::Collection,
+            TrashItemType::Request => EntityType::Request,
+            TrashItemType::Environment => EntityType::Environment,
+        }
+    }
+
+    /// Table backing this item type
+    fn table(&self) -> &'static str {
+        match self {
+            TrashItemType::Collection => "collections",
+            TrashItemType::Request => "requests",
+            TrashItemType::Environment => "environments",
+        }
+    }
+}
+
+impl std::str::FromStr for TrashItemType {
+    type Err = StoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "collection" => Ok(TrashItemType::Collection),
+            "request" => Ok(TrashItemType::Request),
+            "environment" => Ok(TrashItemType::Environment),
+            _ => Err(StoreError::InvalidData(format!("Unknown trash item type: {}", s))),
+        }
+    }
+}
+
+/// A soft-deleted item
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct TrashItem {
+    pub id: Id,
+    pub item_type: TrashItemType,
+    pub name: String,
+    pub deleted_at: Timestamp,
+}
+
+impl Database {
+    /// List everything in the trash, most recently deleted first
+    ///
+    /// Requests trashed together with their collection are not listed
+    /// separately; they come back when the collection is restored.
+    #[tracing::instrument(skip(self), fields(rows = tracing::field::Empty))]
+    pub async fn list_trash(&self) -> StoreResult<Vec<TrashItem>> {
+        let rows = sqlx::query(
+            "SELECT id, 'collection' AS item_type, name, deleted_at
+                FROM collections WHERE deleted_at IS NOT NULL
+            UNION ALL
+            SELECT r.id, 'request', r.name, r.deleted_at
+                FROM requests r
+                LEFT JOIN collections c ON c.id = r.collection_id
+                WHERE r.deleted_at IS NOT NULL
+                AND (c.deleted_at IS NULL OR c.deleted_at != r.deleted_at)
+            UNION ALL
+            SELECT id, 'environment', name, deleted_at
+                FROM environments WHERE deleted_at IS NOT NULL
+            ORDER BY deleted_at DESC"
+        )
+        .fetch_all(self.pool())
+        .await?;
+
+        record_rows(rows.len());
+        rows.iter()
+            .map(|row| {
+                let id: String = row.try_get("id")?;
+                let item_type: String = row.try_get("item_type")?;
+                Ok(TrashItem {
+                    id: parse_id(&id)?,
+                    item_type: item_type.parse()?,
+                    name: row.try_get("name")?,
+                    deleted_at: row.try_get("deleted_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Restore an item from the trash
+    ///
+    /// Restoring a collection also restores the requests trashed with it.
+    #[tracing::instrument(skip(self), fields(id = %id))]
+    pub async fn restore(&self, id: Id) -> StoreResult<()> {
+        self.ensure_writable()?;
+        let item_type = self.trashed_item_type(id).await?;
+        let mut tx = self.pool().begin().await?;
+
+        let mut request_ids: Vec<String> = Vec::new();
+        if item_type == TrashItemType::Collection {
+            request_ids = sqlx::query_scalar(
+                "UPDATE requests SET deleted_at = NULL
+                WHERE collection_id = ?
+                AND deleted_at = (SELECT deleted_at FROM collections WHERE id = ?)
+                RETURNING id"
+            )
+            .bind(id.to_string())
+            .bind(id.to_string())
+            .fetch_all(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query(&format!("UPDATE {} SET deleted_at = NULL WHERE id = ?", item_type.table()))
+            .bind(id.to_string())
+            .execute(&mut *tx)
+            .await?;
+
+        let mut events = vec![ChangeEvent::new(item_type.entity(), ChangeOp::Insert, id)];
+        events.extend(returned_events(EntityType::Request, ChangeOp::Insert, &request_ids)?);
+
+        tx.commit().await?;
+        self.emit(events);
+        Ok(())
+    }
+
+    /// Permanently delete an item from the trash
+    ///
+    /// Purging a collection also purges the requests trashed with it.
+    #[tracing::instrument(skip(self), fields(id = %id))]
+    pub async fn purge(&self, id: Id) -> StoreResult<()> {
+        self.ensure_writable()?;
+        let item_type = self.trashed_item_type(id).await?;
+        let mut tx = self.pool().begin().await?;
+
+        let mut request_ids: Vec<String> = Vec::new();
+        if item_type == TrashItemType::Collection {
+            request_ids = sqlx::query_scalar(
+                "DELETE FROM requests
+                WHERE collection_id = ?
+                AND deleted_at = (SELECT deleted_at FROM collections WHERE id = ?)
+                RETURNING id"
+            )
+            .bind(id.to_string())
+            .bind(id.to_string())
+            .fetch_all(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query(&format!("DELETE FROM {} WHERE id = ?", item_type.table()))
+            .bind(id.to_string())
+            .execute(&mut *tx)
+            .await?;
+
+        let mut events = vec![ChangeEvent::new(item_type.entity(), ChangeOp::Delete, id)];
+        events.extend(returned_events(EntityType::Request, ChangeOp::Delete, &request_ids)?);
+
+        tx.commit().await?;
+        self.emit(events);
+        Ok(())
+    }
+
+    /// Permanently delete everything trashed before `older_than`
+    ///
+    /// Returns the number of rows deleted.
+    #[tracing::instrument(skip(self), fields(rows = tracing::field::Empty))]
+    pub async fn empty_trash(&self, older_than: Timestamp) -> StoreResult<usize> {
+        self.ensure_writable()?;
+        let mut tx = self.pool().begin().await?;
+        let mut events = Vec::new();
+
+        // Requests first so none are left pointing at a purged collection
+        for item_type in [TrashItemType::Request, TrashItemType::Collection, TrashItemType::Environment] {
+            let ids: Vec<String> = sqlx::query_scalar(&format!(
+                "DELETE FROM {} WHERE deleted_at IS NOT NULL AND deleted_at < ? RETURNING id",
+                item_type.table()
+            ))
+            .bind(older_than)
+            .fetch_all(&mut *tx)
+            .await?;
+            events.extend(returned_events(item_type.entity(), ChangeOp::Delete, &ids)?);
+        }
+
+        tx.commit().await?;
+        let deleted = events.len();
+        record_rows(deleted);
+        self.emit(events);
+        Ok(deleted)
+    }
+
+    /// Find which table holds a trashed item
+    async fn trashed_item_type(&self, id: Id) -> StoreResult<TrashItemType> {
+        for item_type in [TrashItemType::Collection, TrashItemType::Request, TrashItemType::Environment] {
+            let found: Option<i64> = sqlx::query_scalar(&format!(
+                "SELECT 1 FROM {} WHERE id = ? AND deleted_at IS NOT NULL",
+                item_type.table()
+            ))
+            .bind(id.to_string())
+            .fetch_optional(self.pool())
+            .await?;
+
+            if found.is_some() {
+                return Ok(item_type);
+            }
+        }
+
+        Err(StoreError::NotFound(format!("Trashed item {}", id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_db;
+    use crate::test_support::{insert_collection, insert_request, request};
+    use models::now;
+
+    #[tokio::test]
+    async fn test_delete_and_restore_collection() {
+        let db = test_db().await;
+        let collection = insert_collection(&db, "API", now()).await;
+        let request = insert_request(&db, &request("Users").with_collection(collection)).await;
+
+        db.delete_collection(collection).await.unwrap();
+        assert!(db.get_request(request).await.unwrap().is_none());
+
+        // Only the collection is listed; its request rides along
+        let trash = db.list_trash().await.unwrap();
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].id, collection);
+        assert_eq!(trash[0].item_type, TrashItemType::Collection);
+
+        db.restore(collection).await.unwrap();
+        assert!(db.get_collection(collection).await.unwrap().is_some());
+        assert!(db.get_request(request).await.unwrap().is_some());
+        assert!(db.list_trash().await.unwrap().is_empty());
+
+        assert!(matches!(db.restore(collection).await, Err(StoreError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_purge_and_empty_trash() {
+        let db = test_db().await;
+        let collection = insert_collection(&db, "API", now()).await;
+        let loose = insert_request(&db, &request("Loose")).await;
+        let other = insert_request(&db, &request("Other")).await;
+
+        db.delete_request(loose).await.unwrap();
+        db.purge(loose).await.unwrap();
+        assert!(db.list_trash().await.unwrap().is_empty());
+        assert_eq!(db.count_requests().await.unwrap(), 1);
+
+        db.delete_request(other).await.unwrap();
+        db.delete_collection(collection).await.unwrap();
+
+        // Nothing is older than the start of time
+        assert_eq!(db.empty_trash(0).await.unwrap(), 0);
+        assert_eq!(db.empty_trash(now() + 1).await.unwrap(), 2);
+        assert!(db.list_trash().await.unwrap().is_empty());
+    }
+}